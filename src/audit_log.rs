@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use std::{
+    env,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    process::Command,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// The log file for the current invocation, and when the invocation started; set by `AuditLog::open()`.
+static LOG_FILE: OnceLock<(Mutex<File>, Instant)> = OnceLock::new();
+
+/// An opt-in log of what **git-util** did, written as JSON lines.
+///
+/// Each invocation appends an `invocation` event with its arguments, a `command` event for every process it runs
+/// (with the command line, exit code, and duration), and an `exit` event with its exit code and total duration.
+pub struct AuditLog();
+
+impl AuditLog {
+    /// Append events for the current invocation to the file at `path`, creating it if it doesn't exist.
+    pub fn open(path: &Path) -> Result<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+
+        // ignore the error if the log file was already opened
+        let _ = LOG_FILE.set((Mutex::new(file), Instant::now()));
+
+        let args: Vec<String> = env::args().collect();
+        let cwd = env::current_dir().unwrap_or_default();
+
+        AuditLog::write(
+            "invocation",
+            &[
+                ("pid", std::process::id().to_string()),
+                ("args", json_array(&args)),
+                ("cwd", json_string(&cwd.to_string_lossy())),
+            ],
+        );
+
+        Ok(())
+    }
+
+    /// Whether events are being logged.
+    pub fn is_enabled() -> bool {
+        LOG_FILE.get().is_some()
+    }
+
+    /// Log that `command` exited with `exit_code` (`None` if it couldn't be run or was killed) after `duration`.
+    pub fn command(command: &Command, exit_code: Option<i32>, duration: Duration) {
+        if !AuditLog::is_enabled() {
+            return;
+        }
+
+        let command_line = std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        AuditLog::write(
+            "command",
+            &[
+                ("command", json_array(&command_line)),
+                ("exit_code", json_number(exit_code)),
+                ("duration_ms", duration.as_millis().to_string()),
+            ],
+        );
+    }
+
+    /// Log that the invocation is exiting with `exit_code`, and with `error` if it failed.
+    pub fn exit(exit_code: i32, error: Option<&str>) {
+        let Some((_, start)) = LOG_FILE.get() else {
+            return;
+        };
+
+        let mut fields = vec![
+            ("exit_code", exit_code.to_string()),
+            ("duration_ms", start.elapsed().as_millis().to_string()),
+        ];
+        if let Some(error) = error {
+            fields.push(("error", json_string(error)));
+        }
+
+        AuditLog::write("exit", &fields);
+    }
+
+    /// Append a single JSON object for `event` with `fields`, whose values must already be JSON-encoded.
+    fn write(event: &str, fields: &[(&str, String)]) {
+        let Some((file, _)) = LOG_FILE.get() else {
+            return;
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        let mut line = format!(
+            "{{\"timestamp_ms\":{timestamp},\"event\":{}",
+            json_string(event)
+        );
+        for (key, value) in fields {
+            line.push_str(&format!(",{}:{}", json_string(key), value));
+        }
+        line.push_str("}\n");
+
+        // logging is best-effort; a failure to write shouldn't fail the command being logged
+        if let Ok(mut file) = file.lock() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+/// `value` as a JSON string literal, quoted and escaped.
+pub fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+fn json_array(values: &[String]) -> String {
+    format!(
+        "[{}]",
+        values
+            .iter()
+            .map(|value| json_string(value))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+fn json_number(value: Option<i32>) -> String {
+    value.map_or_else(|| "null".to_string(), |value| value.to_string())
+}
@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use log::debug;
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+};
+
+/// A file cache under `$XDG_CACHE_HOME/git-util/` (or `$HOME/.cache/git-util/`).
+///
+/// Each entry is stored with a `signature` describing the inputs it was generated from; an entry is only returned if
+/// it was stored with the same signature.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Cache();
+
+impl Cache {
+    /// The contents cached for `key`, if they were stored with `signature`.
+    pub fn get(key: &str, signature: &str) -> Option<Vec<u8>> {
+        let contents = fs::read(Cache::path(key)?).ok()?;
+        let newline = contents.iter().position(|byte| *byte == b'\n')?;
+
+        if &contents[..newline] == signature.as_bytes() {
+            debug!("cache hit for {key}");
+            Some(contents[newline + 1..].to_vec())
+        } else {
+            debug!("cache entry for {key} is stale");
+            None
+        }
+    }
+
+    /// Cache `contents` for `key`, along with `signature`.
+    pub fn put(key: &str, signature: &str, contents: &[u8]) -> Result<()> {
+        let Some(path) = Cache::path(key) else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+        }
+
+        let mut entry = signature.replace('\n', " ").into_bytes();
+        entry.push(b'\n');
+        entry.extend_from_slice(contents);
+
+        fs::write(&path, entry)
+            .with_context(|| format!("Failed to write cache file {}", path.display()))
+    }
+
+    /// A signature built from the modification times of `files`, for detecting when any of them change.
+    pub fn signature(files: &[PathBuf]) -> String {
+        files
+            .iter()
+            .map(
+                |file| match fs::metadata(file).and_then(|metadata| metadata.modified()) {
+                    Ok(modified) => format!("{modified:?}"),
+                    Err(_) => "missing".to_string(),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    /// The cache directory, or `None` if neither `$XDG_CACHE_HOME` nor `$HOME` is set.
+    pub fn dir() -> Option<PathBuf> {
+        let cache_home = match env::var_os("XDG_CACHE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+        };
+
+        Some(cache_home.join("git-util"))
+    }
+
+    /// The cache file for `key`, or `None` if there's no cache directory.
+    fn path(key: &str) -> Option<PathBuf> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        Some(Cache::dir()?.join(format!("{:016x}", hasher.finish())))
+    }
+}
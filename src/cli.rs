@@ -1,5 +1,10 @@
 use self::subcommands::Subcommands;
-use crate::git::{Git, GitResult, DRY_RUN, PRINT_COMMANDS};
+use crate::{
+    audit_log::AuditLog,
+    git::{Git, GitResult, CONFIG_OVERRIDES, DRY_RUN, PRINT_COMMANDS, QUIET, TIMINGS},
+    output::OutputTarget,
+    settings::Settings,
+};
 use clap::{
     arg,
     builder::{styling::AnsiColor, Styles},
@@ -8,8 +13,9 @@ use clap::{
     Args, CommandFactory, Parser,
 };
 use log::{info, LevelFilter};
-use std::sync::atomic::Ordering;
+use std::{path::PathBuf, sync::atomic::Ordering};
 
+mod completions;
 mod subcommands;
 
 const STYLES: Styles = Styles::styled()
@@ -34,7 +40,7 @@ pub struct Cli {
     pub subcommand: Option<Subcommands>,
 }
 
-#[derive(Args, Debug, Clone, Copy)]
+#[derive(Args, Debug, Clone)]
 pub struct CliOptions {
     /// Set verbosity; adding multiple times increases the verbosity level (>=4, i.e. `-vvvv`, sets maximum verbosity).
     #[arg(
@@ -61,6 +67,27 @@ pub struct CliOptions {
     /// Print the `std::process::Command`s that will be executed, but do not run
     #[arg(long, short = 'd')]
     pub dry_run: bool,
+
+    /// Suppress informational output, and pass `--quiet` to the `git` commands that support it
+    #[arg(long, short = 'q')]
+    pub quiet: bool,
+
+    /// Pass a config override (as `git -c KEY=VALUE`) to every `git` command; can be given multiple times
+    #[arg(short = 'c', value_name = "KEY=VALUE", value_parser = parse_config_override)]
+    pub config: Vec<String>,
+
+    /// Report how long each spawned process and internal phase took, on `stderr`
+    #[arg(long)]
+    pub timings: bool,
+
+    /// Append a JSON lines record of the invocation, the commands it runs, and their exit codes and durations to a file
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Write the output to a file instead of `stdout`; only supported by subcommands that print a report (e.g. `l`,
+    /// `files`, and `find`)
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
 }
 
 #[derive(Args, Debug, Clone, Copy)]
@@ -74,11 +101,75 @@ pub struct GitConfigOpts {
     pub show_origin: bool,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct LogDateRange {
+    /// Only list commits after this date, e.g. `yesterday`, `2w` (2 weeks ago), or `2024-01-01`
+    #[arg(long, value_name = "DATE")]
+    pub since: Option<String>,
+
+    /// Only list commits before this date, e.g. `yesterday`, `3d` (3 days ago), or `2024-01-01`
+    #[arg(long, value_name = "DATE")]
+    pub until: Option<String>,
+}
+
+#[derive(Args, Debug, Clone, Copy)]
+pub struct RenameDetection {
+    /// Show renamed files as `OLD -> NEW`, optionally only if they're at least PERCENT similar (git's default is 50)
+    #[arg(long, short = 'M', value_name = "PERCENT", num_args = 0..=1, require_equals = true,
+          value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub find_renames: Option<Option<u8>>,
+
+    /// Also detect files that were copied from a file changed in the same commit
+    #[arg(long, short = 'C')]
+    pub find_copies: bool,
+}
+
 impl Cli {
-    pub fn run_subcommand(&self) -> GitResult {
+    /// Run the subcommand (named `subcommand_name`), or pass the arguments through to `git`.
+    pub fn run_subcommand(&self, subcommand_name: Option<&str>) -> GitResult {
         // global flags
         PRINT_COMMANDS.store(self.options.print_command, Ordering::Relaxed);
         DRY_RUN.store(self.options.dry_run, Ordering::Relaxed);
+        QUIET.store(self.options.quiet, Ordering::Relaxed);
+        TIMINGS.store(self.options.timings, Ordering::Relaxed);
+        // set before the settings are loaded, so that they can be overridden too
+        let _ = CONFIG_OVERRIDES.set(self.options.config.clone());
+
+        if let Some(path) = &self.options.output {
+            match &self.subcommand {
+                Some(subcommand) if subcommand.supports_output_file() => {
+                    OutputTarget::redirect_to(path)?
+                }
+                _ => Cli::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        format!(
+                            "--output is only supported by subcommands that print a report, e.g. {}",
+                            "l, files, and find"
+                        ),
+                    )
+                    .exit(),
+            }
+        }
+
+        // the arguments passed through to `git` run as they would with plain `git`, so the settings aren't loaded for
+        // them; that way a broken setting (or the `git config` call to load them) can't get in the way
+        if self.fallback.is_none() {
+            Settings::init()?;
+        }
+
+        if let Some(paging) = subcommand_name.and_then(|name| Settings::get().paging.get(name)) {
+            OutputTarget::set_paging(*paging);
+        }
+
+        if let Some(path) = self
+            .options
+            .log_file
+            .as_ref()
+            .or(Settings::get().log_file.as_ref())
+        {
+            AuditLog::open(path)?;
+        }
 
         if let Some(args) = &self.fallback {
             Git::pass_through(args)
@@ -111,3 +202,29 @@ impl Cli {
         info!("ℹ️ logging initialized at level {}", level);
     }
 }
+
+/// Validate a `-c` config override, which must be in the form `KEY=VALUE`.
+fn parse_config_override(config_override: &str) -> Result<String, String> {
+    match config_override.split_once('=') {
+        Some((key, _)) if !key.is_empty() => Ok(config_override.to_string()),
+        _ => Err(format!("expected KEY=VALUE, but got \"{config_override}\"")),
+    }
+}
+
+impl From<&RenameDetection> for crate::git::RenameDetection {
+    fn from(options: &RenameDetection) -> Self {
+        crate::git::RenameDetection {
+            find_renames: options.find_renames,
+            find_copies: options.find_copies,
+        }
+    }
+}
+
+impl From<&LogDateRange> for crate::git::LogDateRange {
+    fn from(range: &LogDateRange) -> Self {
+        crate::git::LogDateRange {
+            since: range.since.clone(),
+            until: range.until.clone(),
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use super::Cli;
+use crate::{
+    git::{commands::completions::Candidates, GitCommandResult, GitResult},
+    output::OutputTarget,
+};
+use clap::{Arg, Command, CommandFactory, ValueEnum};
+use std::{io::Write, iter};
+
+/// The shells that `completions` generates scripts for.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Fish,
+    /// Uses the bash script, through `bashcompinit`.
+    Zsh,
+}
+
+impl Shell {
+    /// Print the completion script for this shell.
+    pub fn print(&self) -> GitResult {
+        write!(OutputTarget::writer()?, "{}", self.script())?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// The completion script, generated from the subcommands and options of the CLI.
+    ///
+    /// The arguments that name branches, remotes, or commits are completed by running `git-util __complete`, so they
+    /// come from whichever repository the shell is in.
+    fn script(&self) -> String {
+        let mut command = Cli::command();
+        // add the generated `--help` and `help`
+        command.build();
+
+        match self {
+            Shell::Bash => bash_script(&command),
+            Shell::Fish => fish_script(&command),
+            Shell::Zsh => format!(
+                "autoload -U +X bashcompinit && bashcompinit\n\n{}",
+                bash_script(&command)
+            ),
+        }
+    }
+}
+
+fn bash_script(command: &Command) -> String {
+    let name = command.get_name();
+    let function = format!("_{}", name.replace('-', "_"));
+
+    let mut cases = vec![bash_case(
+        "\"\"",
+        &flags(command),
+        &visible_names(command),
+        Some(Candidates::Aliases),
+    )];
+    for subcommand in command.get_subcommands() {
+        let names: Vec<&str> = iter::once(subcommand.get_name())
+            .chain(subcommand.get_all_aliases())
+            .collect();
+
+        cases.push(bash_case(
+            &names.join("|"),
+            &flags(subcommand),
+            &visible_names(subcommand),
+            candidates(subcommand),
+        ));
+    }
+    // anything else is a **Git** alias or a subcommand that's passed through to `git`, e.g. `co`
+    cases.push(format!(
+        "        *)\n            candidates=\"{}\"\n            paths=1\n            ;;",
+        candidates_name(Candidates::Branches)
+    ));
+
+    format!(
+        r#"# bash completions for {name}; generated by `{name} completions bash`
+{function}() {{
+    local cur=${{COMP_WORDS[COMP_CWORD]}}
+    local subcommand="" i
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        if [[ ${{COMP_WORDS[i]}} != -* ]]; then
+            subcommand=${{COMP_WORDS[i]}}
+            break
+        fi
+    done
+
+    local flags="" words="" candidates="" paths=""
+    case $subcommand in
+{}
+    esac
+
+    if [[ $cur == -* ]]; then
+        words=$flags
+    elif [[ -n $candidates ]]; then
+        words="$words $({name} __complete "$candidates" 2>/dev/null | cut -f1)"
+    fi
+
+    COMPREPLY=($(compgen -W "$words" -- "$cur"))
+    if [[ -n $paths && $cur != -* ]]; then
+        COMPREPLY+=($(compgen -f -- "$cur"))
+    fi
+}}
+complete -o default -F {function} {name}
+"#,
+        cases.join("\n")
+    )
+}
+
+fn bash_case(
+    pattern: &str,
+    flags: &[String],
+    words: &[&str],
+    candidates: Option<Candidates>,
+) -> String {
+    format!(
+        "        {pattern})\n            flags=\"{}\"\n            words=\"{}\"\n            candidates=\"{}\"\n            ;;",
+        flags.join(" "),
+        words.join(" "),
+        candidates.map(candidates_name).unwrap_or_default()
+    )
+}
+
+fn fish_script(command: &Command) -> String {
+    let name = command.get_name();
+    let mut lines = vec![format!(
+        "# fish completions for {name}; generated by `{name} completions fish`"
+    )];
+
+    let top_level = "__fish_use_subcommand";
+    lines.extend(
+        command
+            .get_arguments()
+            .filter_map(|arg| fish_flag(name, top_level, arg)),
+    );
+    lines.push(format!(
+        "complete -c {name} -n {top_level} -f -a '({name} __complete aliases 2>/dev/null)' -d 'Git alias'"
+    ));
+
+    for subcommand in command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+    {
+        lines.push(format!(
+            "complete -c {name} -n {top_level} -f -a {} -d {}",
+            subcommand.get_name(),
+            fish_quote(&about(subcommand))
+        ));
+
+        let names: Vec<&str> = iter::once(subcommand.get_name())
+            .chain(subcommand.get_all_aliases())
+            .collect();
+        let condition = fish_quote(&format!("__fish_seen_subcommand_from {}", names.join(" ")));
+
+        lines.extend(
+            subcommand
+                .get_arguments()
+                .filter_map(|arg| fish_flag(name, &condition, arg)),
+        );
+
+        for nested in subcommand
+            .get_subcommands()
+            .filter(|nested| !nested.is_hide_set())
+        {
+            lines.push(format!(
+                "complete -c {name} -n {condition} -f -a {} -d {}",
+                nested.get_name(),
+                fish_quote(&about(nested))
+            ));
+        }
+
+        if let Some(candidates) = candidates(subcommand) {
+            lines.push(format!(
+                "complete -c {name} -n {condition} -f -a '({name} __complete {} 2>/dev/null)'",
+                candidates_name(candidates)
+            ));
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// `complete -c NAME -n CONDITION -l LONG -s SHORT -d HELP` for the option `arg`; `None` if it's positional.
+fn fish_flag(name: &str, condition: &str, arg: &Arg) -> Option<String> {
+    if arg.is_positional() || arg.is_hide_set() {
+        return None;
+    }
+
+    let mut line = format!("complete -c {name} -n {condition}");
+    if let Some(long) = arg.get_long() {
+        line.push_str(&format!(" -l {long}"));
+    }
+    if let Some(short) = arg.get_short() {
+        line.push_str(&format!(" -s {short}"));
+    }
+    if arg.get_action().takes_values() {
+        line.push_str(" -r");
+    }
+    if let Some(help) = arg.get_help() {
+        line.push_str(&format!(
+            " -d {}",
+            fish_quote(&first_line(&help.to_string()))
+        ));
+    }
+
+    Some(line)
+}
+
+/// `text` in single quotes, for fish.
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// The flags of `command`, e.g. `--verbose` and `-v`.
+fn flags(command: &Command) -> Vec<String> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+        .flat_map(|arg| {
+            arg.get_long()
+                .map(|long| format!("--{long}"))
+                .into_iter()
+                .chain(arg.get_short().map(|short| format!("-{short}")))
+        })
+        .collect()
+}
+
+/// The names and visible aliases of the subcommands of `command`.
+fn visible_names(command: &Command) -> Vec<&str> {
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .flat_map(|subcommand| {
+            iter::once(subcommand.get_name()).chain(subcommand.get_visible_aliases())
+        })
+        .collect()
+}
+
+/// What the positional arguments of `command` are completed with: the candidates of the first one that has any.
+fn candidates(command: &Command) -> Option<Candidates> {
+    command
+        .get_positionals()
+        .find_map(|arg| Candidates::for_argument(arg.get_id().as_str()))
+}
+
+/// The name that `__complete` takes for `candidates`, e.g. `branches`.
+fn candidates_name(candidates: Candidates) -> String {
+    candidates
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// The first line of the description of `command`.
+fn about(command: &Command) -> String {
+    command
+        .get_about()
+        .map(|about| first_line(&about.to_string()))
+        .unwrap_or_default()
+}
+
+fn first_line(text: &str) -> String {
+    text.lines()
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('.')
+        .to_string()
+}
@@ -1,15 +1,235 @@
-use super::GitConfigOpts;
+use super::{completions::Shell, GitConfigOpts, LogDateRange, RenameDetection};
 use crate::git::{
-    commands::{immutable::ImmutableCommands, mutable},
-    hooks::pre_commit::PreCommitHook,
-    GitCommandResult, GitResult,
+    commands::{
+        alias::Alias,
+        attrs::Attrs,
+        changelog::Changelog,
+        commit_picker::CommitPicker,
+        completions::Candidates,
+        doctor::Doctor,
+        immutable::{ImmutableCommands, StatsSort},
+        include::Include,
+        lfs::Lfs,
+        log_browser::LogBrowser,
+        mutable::{self, clone::CloneFilter, conflict::Side, release::Level},
+        profile::Profile,
+        self_update::SelfUpdate,
+        stage_browser::StageBrowser,
+    },
+    hooks::{self, post_commit::PostCommitHook, pre_commit::PreCommitHook, report::HookRun},
+    DateStyle, GitCommandResult, GitResult, WordDiff, DRY_RUN,
 };
 use clap::Subcommand;
+use std::{path::PathBuf, sync::atomic::Ordering};
 
-#[derive(Subcommand, Debug, Clone, Copy)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum HookSubcommands {
-    /// `pre-commit` hook
-    PreCommit {},
+    /// Install shims in the repository's hooks directory that call `git-util hook`.
+    Install {
+        /// Replace existing hooks that don't call git-util
+        #[arg(long, short = 'f')]
+        force: bool,
+        /// Also install the post-commit hook, which appends each commit to git-util's journal
+        #[arg(long)]
+        post_commit: bool,
+    },
+    /// `pre-commit` hook; or run its checks against files, outside of a commit
+    PreCommit {
+        /// Check these files (or the files under these directories) instead of the staged changes
+        #[arg(long, num_args = 1.., conflicts_with = "all_files")]
+        files: Vec<PathBuf>,
+        /// Check every tracked file instead of the staged changes
+        #[arg(long)]
+        all_files: bool,
+        /// Preview whether a commit would pass the checks, without caching or saving anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// `commit-msg` hook; runs the pre-commit checks instead of `pre-commit` if they're skipped for some subjects
+    CommitMsg {
+        /// The file with the commit message
+        file: PathBuf,
+    },
+    /// `post-commit` hook; appends the commit to git-util's journal of commits
+    PostCommit {},
+    /// Show the summary and output of the last run of the pre-commit checks.
+    Report {},
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AliasSubcommands {
+    /// Report the aliases that duplicate a subcommand, or could be migrated to a similar one.
+    Audit {},
+    /// Write every alias to a TOML file (else to `stdout`), to import on another machine.
+    Export {
+        /// The file to write, e.g. `aliases.toml`
+        file: Option<PathBuf>,
+    },
+    /// Set the aliases from a TOML file written by `alias export` in the global config, reporting the ones that
+    /// conflict with an existing alias.
+    Import {
+        /// The file to read
+        file: PathBuf,
+
+        /// Overwrite aliases that already have a different value
+        #[arg(long, short = 'f')]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileSubcommands {
+    /// List the profiles, marking the one that the current repository uses.
+    List {},
+    /// Show the current repository's identity and the profile it matches.
+    ///
+    /// Fails if the repository's `origin` is on the host of a different profile.
+    Status {},
+    /// Configure the current repository with a profile's identity.
+    Use {
+        /// The name of the profile
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfSubcommands {
+    /// Give every repository under a directory the identity of a profile (or the settings in a config file), with an
+    /// `[includeIf "gitdir:DIR/"]` section in the global config.
+    Route {
+        /// The directory whose repositories the settings apply to
+        directory: String,
+
+        /// The name of a profile (see `profile`), or the path of a config file
+        target: String,
+    },
+    /// List the conditional includes (`includeIf`), with the identity that each included file sets.
+    Routes {},
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AttrsSubcommands {
+    /// Set attributes for a pattern in the root `.gitattributes`, replacing any existing settings of the same
+    /// attributes, and stage it.
+    Set {
+        /// The pattern to set the attributes for, e.g. `Cargo.lock`
+        pattern: String,
+
+        /// The attributes to set, e.g. `-diff` or `eol=lf`
+        #[arg(required = true, allow_hyphen_values = true)]
+        attributes: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupsSubcommands {
+    /// List the backups, newest first, with the operation that each one was made before.
+    List {},
+    /// Reset the current branch to a backup, keeping uncommitted changes; `HEAD` is backed up first.
+    Restore {
+        /// The name of the backup, as it's listed (e.g. `1700000000`)
+        backup: String,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Delete the backups that are older than a number of days.
+    Prune {
+        /// How many days old a backup must be to be deleted
+        #[arg(long, default_value_t = 30)]
+        days: u64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IgnoreSubcommands {
+    /// Add patterns to the root `.gitignore`, skipping any that are already in it, and stage it.
+    Add {
+        /// The patterns to ignore, e.g. `*.log`
+        #[arg(required = true)]
+        patterns: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SparseSubcommands {
+    /// Enable a cone-mode sparse checkout that starts with only the files in the root directory.
+    Init {},
+    /// Add directories to the sparse checkout; each one must exist in `HEAD`.
+    Add {
+        /// The directories to add, relative to the root of the repository
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// List the directories in the sparse checkout.
+    List {},
+    /// Disable the sparse checkout and restore the full work tree.
+    Off {},
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StashSubcommands {
+    /// List stashes as a table of index, branch, age, and message.
+    List {
+        /// Include the number of files changed in each stash
+        #[arg(long, short = 'f')]
+        files: bool,
+    },
+    /// Pop a stash, but only if it applies cleanly; otherwise offer to apply it on a new branch.
+    Pop {
+        /// The index of the stash to pop (else defaults to 0)
+        index: Option<u16>,
+    },
+    /// Show the files changed in a stash and, optionally, its full patch.
+    Show {
+        /// The index of the stash to show (else defaults to 0)
+        index: Option<u16>,
+
+        /// Show the full patch
+        #[arg(long, short = 'p')]
+        patch: bool,
+    },
+    /// Any other `git-stash` subcommand, passed straight through.
+    #[command(external_subcommand)]
+    Other(Vec<String>),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrailerSubcommands {
+    /// Add a trailer to the message of the last commit (which is backed up first), or to a message file.
+    Add {
+        /// The trailer key, e.g. `Ticket`, or a shortcut for one: `ab`, `cab`, `rb`, `ref`, `sob`, `tb`, or one set
+        /// with `git-util.trailer.SHORTCUT`
+        key: String,
+        /// The value (else defaults to my `NAME <EMAIL>` for keys that end in `-by`)
+        value: Option<String>,
+        /// Add it to this message file instead, e.g. in a `commit-msg` hook
+        #[arg(long, short = 'f')]
+        file: Option<PathBuf>,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long, conflicts_with = "file")]
+        force_protected: bool,
+    },
+    /// Print the trailers of the last commit's message, or of a message file.
+    Show {
+        /// Only print the trailers with this key (or shortcut)
+        key: Option<String>,
+        /// Read this message file instead
+        #[arg(long, short = 'f')]
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum LfsSubcommands {
+    /// List the patterns tracked by LFS and the matching files that were committed as regular blobs.
+    Status {},
+    /// Track patterns with LFS by adding them to `.gitattributes`, and stage it.
+    Track {
+        /// The patterns to track, e.g. `*.psd`
+        #[arg(required = true)]
+        patterns: Vec<String>,
+    },
 }
 
 /// Specify which files to operate a command against
@@ -20,6 +240,8 @@ pub enum WhichFiles {
 
 #[derive(Subcommand, Debug)]
 pub enum Subcommands {
+    /// Abort the operation (merge, rebase, cherry-pick, revert, bisect, or am) that is in progress.
+    Abort {},
     /// Wrapper around `git-add`.
     #[command(allow_hyphen_values = true)]
     #[clap(alias = "a")]
@@ -40,7 +262,15 @@ pub enum Subcommands {
     ///
     /// Fails if the staging area is not empty before attempting to add files.
     #[command(allow_hyphen_values = true)]
-    Aac {},
+    Aac {
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+
+        /// Add a trailer, e.g. `rb` (a shortcut for `Reviewed-by` with my identity) or `Ticket=ABC-123`
+        #[arg(long = "trailer", value_name = "KEY[=VALUE]")]
+        trailers: Vec<String>,
+    },
     /// Add updated and untracked files and then commit.
     ///
     /// Unlike `Aa`, this not fail if there are already staged files (think: `aa --force`).
@@ -50,15 +280,75 @@ pub enum Subcommands {
     ///
     /// Fails if the staging area is not empty when subcommand is run.
     #[clap(alias = "aam")]
-    Aamend {},
+    Aamend {
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+
+        /// Add a trailer, e.g. `rb` (a shortcut for `Reviewed-by` with my identity) or `Ticket=ABC-123`
+        #[arg(long = "trailer", value_name = "KEY[=VALUE]")]
+        trailers: Vec<String>,
+
+        /// Command arguments, passed to `git commit`; everything from the first one on is, so the flags above go first
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Print how far a branch is ahead/behind its upstream, e.g. `+2 -1`; terse enough for a shell prompt.
+    ///
+    /// Prints nothing if the branch has no upstream.
+    Ab {
+        /// The branch to check (else defaults to the current branch)
+        branch: Option<String>,
+
+        /// Also print how far behind the default branch it is, e.g. `+2 -1 main:-5`
+        #[arg(long, short = 'd')]
+        default: bool,
+    },
+    /// Commit each staged hunk as a `fixup!` of the recent commit that last changed its lines, ready for `autosquash`.
+    ///
+    /// The hunks that can't be matched to a single commit since the base stay staged.
+    Absorb {
+        /// The commit to look back to (else defaults to the merge-base of HEAD and the upstream, or the default branch)
+        base: Option<String>,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
     /// List configured aliases
+    #[command(subcommand_negates_reqs = true)]
     Alias {
+        #[command(subcommand)]
+        command: Option<AliasSubcommands>,
+
         /// text to filter on
         filter: Option<String>,
 
         #[clap(flatten)]
         options: GitConfigOpts,
     },
+    /// Mark tracked files as assume-unchanged, so that git stops checking them for changes.
+    ///
+    /// Local changes to these files are hidden from `git status` and aren't staged by `git add`; `dash` and the
+    /// subcommands that print the status warn when any exist.
+    Assume {
+        /// List the files that are assumed unchanged instead
+        #[arg(long, short = 'l', conflicts_with = "paths")]
+        list: bool,
+
+        /// The files to assume unchanged
+        #[arg(required_unless_present = "list")]
+        paths: Vec<String>,
+    },
+    /// Show the attributes that apply to paths, from every `.gitattributes` file and `info/attributes`.
+    #[command(subcommand_negates_reqs = true)]
+    Attrs {
+        #[command(subcommand)]
+        command: Option<AttrsSubcommands>,
+
+        /// The paths to show the attributes of
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
     /// Add updated (but not untracked) files.
     ///
     /// Fails if the staging area is not empty before attempting to add files.
@@ -69,7 +359,15 @@ pub enum Subcommands {
     /// Fails if the staging area is not empty when subcommand is run.
     #[clap(alias = "ac")]
     #[command(allow_hyphen_values = true)]
-    Auc {},
+    Auc {
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+
+        /// Add a trailer, e.g. `rb` (a shortcut for `Reviewed-by` with my identity) or `Ticket=ABC-123`
+        #[arg(long = "trailer", value_name = "KEY[=VALUE]")]
+        trailers: Vec<String>,
+    },
     /// Add updated and (but not untracked) files.
     ///
     /// Unlike `Au`, this not fail if there are already staged files (think: `au --force`).
@@ -79,11 +377,143 @@ pub enum Subcommands {
     ///
     /// Fails if the staging area is not empty when subcommand is run.
     #[clap(alias = "aum")]
-    Aumend {},
+    Aumend {
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+
+        /// Add a trailer, e.g. `rb` (a shortcut for `Reviewed-by` with my identity) or `Ticket=ABC-123`
+        #[arg(long = "trailer", value_name = "KEY[=VALUE]")]
+        trailers: Vec<String>,
+
+        /// Command arguments, passed to `git commit`; everything from the first one on is, so the flags above go first
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
     /// Reset author to current value of `user.author` and `user.email` for the last n commits.
     Author {
         /// Number of commits to reset (else defaults to 1)
         num: Option<u16>,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Squash the `fixup!`, `squash!`, and `amend!` commits since a base into the commits they're for, without opening
+    /// the todo list in an editor.
+    Autosquash {
+        /// The commit to rebase onto (else defaults to the merge-base of HEAD and the upstream, or the default branch)
+        base: Option<String>,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Cherry-pick commits (with `-x`) onto a new branch from a target branch, e.g. a release branch, and optionally
+    /// push it to open a pull request.
+    Backport {
+        /// The commits to backport, which are picked in the order they're given (oldest first)
+        #[arg(required = true, num_args = 1..)]
+        commits: Vec<String>,
+        /// The branch to backport them to
+        target: String,
+        /// The name of the new branch (else defaults to `backport/TARGET/SHA`, for the first commit)
+        #[arg(long, short = 'b')]
+        branch: Option<String>,
+        /// Check out the new branch in a new worktree beside the repository, instead of here
+        #[arg(long, short = 'w')]
+        worktree: bool,
+        /// Push the new branch to origin, and print the URL for opening a pull request into the target branch
+        #[arg(long, short = 'p')]
+        push: bool,
+    },
+    /// List, restore, and prune the backups of `HEAD` that are made before commands rewrite it, e.g. `aamend`, `plan`,
+    /// and `undo`.
+    Backups {
+        #[command(subcommand)]
+        command: BackupsSubcommands,
+    },
+    /// Tag the next semantic version after the latest version tag (else `v0.0.0`).
+    ///
+    /// Use the global `--dry-run` flag to see the plan without changing anything.
+    Bump {
+        /// Which part of the version to increment
+        #[arg(value_enum)]
+        level: Level,
+
+        /// A file to update the version in (and commit) before tagging, e.g. `Cargo.toml`
+        #[arg(long, short = 'f')]
+        file: Option<PathBuf>,
+    },
+    /// Print the contents of a file as of a given ref (`git show REF:PATH`).
+    Cat {
+        /// The commit, branch, or tag to read the file from
+        #[arg(value_name = "REF")]
+        reference: String,
+
+        /// The path of the file, relative to either the current directory or the root of the repository
+        path: String,
+    },
+    /// List the files changed between two refs (`git diff --name-status BASE...HEAD`).
+    Changed {
+        /// The ref to compare against, e.g. the branch that a pull request targets
+        base: String,
+
+        /// The ref with the changes (else defaults to `HEAD`)
+        head: Option<String>,
+
+        /// Group the files by directory
+        #[arg(long, short = 'g')]
+        group: bool,
+    },
+    /// Generate a Markdown changelog, grouped by Conventional Commit type, from the commits in a range.
+    Changelog {
+        /// `FROM..TO` or `FROM` (else defaults to the commits since the latest tag)
+        range: Option<String>,
+
+        /// Prepend the changelog to `CHANGELOG.md` instead of printing it
+        #[arg(long, short = 'w')]
+        write: bool,
+    },
+    /// Set or clear the executable bit of files in the index, without changing them on disk.
+    ///
+    /// For filesystems where the bit can't be changed (e.g. on Windows), but the committed mode must be.
+    Chmod {
+        /// `+x` to make the files executable, or `-x` to make them not executable
+        #[arg(value_parser = ["+x", "-x"], allow_hyphen_values = true)]
+        mode: String,
+
+        /// The files to change; they must already be tracked
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Wrapper around `git-clone`, with shorthands for forges and presets for partial clones of large repositories.
+    #[command(allow_hyphen_values = true)]
+    Clone {
+        /// The URL of the repository, or a shorthand: `OWNER/REPO`, `gh:OWNER/REPO`, `gl:OWNER/REPO`, or `bb:OWNER/REPO`
+        repository: String,
+
+        /// The directory to clone into (else defaults to `git-util.clone.root/HOST/OWNER/REPO`, if that's set, or the
+        /// name of the repository)
+        directory: Option<String>,
+
+        /// Only download the blobs needed for the checkout; the rest are fetched on demand
+        #[arg(long, conflicts_with = "treeless")]
+        blobless: bool,
+
+        /// Only download the trees and blobs needed for the checkout; the rest are fetched on demand
+        #[arg(long)]
+        treeless: bool,
+
+        /// Install the git-util hook shims in the new repository
+        #[arg(long, short = 'i')]
+        install_hooks: bool,
+
+        /// Copy the files of this template (from `~/.config/git-wrapper/templates/`) into the repository and commit them
+        #[arg(long, short = 't', value_name = "NAME")]
+        template: Option<String>,
+
+        /// Command arguments
+        #[arg(last = true)]
+        args: Vec<String>,
     },
     /// Commit with message (alias for `git commit -m`).
     ///
@@ -91,37 +521,222 @@ pub enum Subcommands {
     Cm {
         /// The commit message
         message: String,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+        /// Add a trailer, e.g. `rb` (a shortcut for `Reviewed-by` with my identity) or `Ticket=ABC-123`
+        #[arg(long = "trailer", value_name = "KEY[=VALUE]")]
+        trailers: Vec<String>,
+
         /// Additional command arguments
         args: Option<Vec<String>>,
     },
+    /// Summarize how two branches differ: their merge-base, the commits unique to each, and a diffstat.
+    Compare {
+        /// The first branch
+        left: String,
+
+        /// The second branch (else defaults to `HEAD`)
+        right: Option<String>,
+    },
+    /// Print the values that the completion scripts complete an argument with, from the current repository.
+    #[command(name = "__complete", hide = true)]
+    Complete { candidates: Candidates },
+    /// Copy the todo list of a planned rebase over the one that `git rebase` made; the sequence editor of `plan` and
+    /// `drop`.
+    #[command(name = "__sequence-editor", hide = true)]
+    SequenceEditor {
+        /// The todo list that `git rebase` made
+        todo: PathBuf,
+    },
+    /// Print a completion script for bash, fish, or zsh, which also completes the names of branches, remotes, and
+    /// commits from the current repository.
+    Completions { shell: Shell },
+    /// List the files with unresolved merge conflicts.
+    Conflicts {
+        /// Print only the conflicting hunks of this file
+        #[arg(long, short = 's', value_name = "PATH")]
+        show: Option<String>,
+    },
+    /// Continue the operation (merge, rebase, cherry-pick, revert, or am) that is in progress.
+    Continue {
+        /// Stage the files that had conflicts before continuing
+        #[arg(long, short = 'a')]
+        add: bool,
+    },
     /// List config settings (excluding aliases).
+    #[command(subcommand_negates_reqs = true)]
     Conf {
+        #[command(subcommand)]
+        command: Option<ConfSubcommands>,
+
         /// The text to filter on
         filter: Option<String>,
 
         #[clap(flatten)]
         options: GitConfigOpts,
     },
+    /// Print a one-screen overview of the repository: branch, upstream status, changed files, stashes, recent commits,
+    /// and any operation in progress.
+    Dash {},
+    /// Check the environment for problems (git version, external tools, hooks, and config) and suggest fixes.
+    Doctor {},
+    /// Remove a commit from the history of the current branch, rebasing the commits after it.
+    Drop {
+        /// The commit to drop, or n for the nth last commit (1 is the last commit)
+        commit: String,
+        /// Drop the commit even if it has been pushed
+        #[arg(long, short = 'f')]
+        force: bool,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Find the commits that added or removed a string (or, with `--regex`, changed lines matching a regex).
+    #[command(allow_hyphen_values = true)]
+    Dig {
+        /// The string (or regex) to search for
+        pattern: String,
+
+        /// Treat the pattern as a regex, and match any added or removed line that contains it (`git log -G`)
+        #[arg(long, short = 'G')]
+        regex: bool,
+
+        /// Show the most recent matching commit instead of listing them
+        #[arg(long)]
+        show: bool,
+
+        /// Command arguments
+        args: Vec<String>,
+    },
     /// Call a git hook.
     Hook {
         // The hook to call
         #[command(subcommand)]
         hook: HookSubcommands,
     },
+    /// Move the last n commits (else the unpushed ones) of the current branch to a new branch, and switch to it.
+    ///
+    /// For when commits were made on the wrong branch, e.g. the default branch.
+    Extract {
+        /// The name of the new branch
+        new_branch: String,
+        /// The number of commits to move (else defaults to the commits that aren't on the upstream)
+        num: Option<u16>,
+    },
+    /// Fetch from all (or the given) remotes concurrently.
+    F {
+        /// The remotes to fetch from (else defaults to all remotes)
+        remotes: Vec<String>,
+    },
     /// List the files that changed in the last n commits.
     #[clap(alias = "shf")]
     Files {
         /// The number of commits to list files for (else defaults to 1)
         num: Option<u16>,
+
+        /// Separate the file names with NUL characters instead of newlines (e.g. for `xargs -0`)
+        #[arg(short = 'z')]
+        null_terminated: bool,
+
+        #[clap(flatten)]
+        renames: RenameDetection,
+    },
+    /// Search commit messages (subjects and bodies) on all branches, listing each match with the branch it's on.
+    #[command(allow_hyphen_values = true)]
+    Find {
+        /// The regex to search for
+        pattern: String,
+
+        /// Match case-insensitively
+        #[arg(long, short = 'i')]
+        ignore_case: bool,
+
+        /// Only search the current branch
+        #[arg(long)]
+        current: bool,
+
+        /// Command arguments
+        args: Vec<String>,
+    },
+    /// Make a fixup commit for a commit (else one picked from the recent commits); wrapper around `git commit --fixup`.
+    Fixup {
+        /// The commit to fix up
+        commit: Option<String>,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Show the effective `user.name` and `user.email` and the config scope they come from, or set them in the
+    /// repository's config.
+    Id {
+        /// The name to commit with
+        name: Option<String>,
+
+        /// The email to commit with
+        email: Option<String>,
+    },
+    /// Add the `.gitignore` templates for languages and platforms to the root `.gitignore`, and stage it.
+    ///
+    /// Templates are bundled for common languages; others are read from `~/.cache/git-util/gitignore/NAME.gitignore`.
+    /// Patterns that are already in the `.gitignore` aren't duplicated.
+    #[command(subcommand_negates_reqs = true)]
+    Ignore {
+        #[command(subcommand)]
+        command: Option<IgnoreSubcommands>,
+
+        /// The templates to add, e.g. `rust macos`
+        #[arg(required = true)]
+        templates: Vec<String>,
+    },
+    /// Create a repository on the default branch, with a starter hook configuration and the hook shims installed.
+    Init {
+        /// The directory to create the repository in (else defaults to the current directory)
+        path: Option<String>,
+
+        /// Copy the files of this template (from `~/.config/git-wrapper/templates/`) into the repository and make the
+        /// initial commit
+        #[arg(long, short = 't', value_name = "NAME")]
+        template: Option<String>,
+
+        /// Configure the repository with the identity of this profile (see `profile`)
+        #[arg(long, short = 'p', value_name = "NAME")]
+        profile: Option<String>,
     },
     /// Wrapper around `git-log`, formatted to 1 line per commit.
     #[command(allow_hyphen_values = true)]
     L {
-        /// The number of commits to list (else defaults to 25)
+        /// The number of commits to list (else defaults to the `git-util.logCount` setting, or 25)
         num: Option<u16>,
 
-        /// Command arguments
+        /// The pretty format to use: a name defined with `git-util.format.NAME`, or any format that `git log --pretty`
+        /// accepts (else defaults to `git-util.format.l`, or the built-in format)
+        #[arg(long, short = 'f', value_name = "NAME")]
+        format: Option<String>,
+
+        /// Browse the commits in a full-screen list with a preview of the selected one, from which it can be copied,
+        /// checked out, reverted, or fixed up
+        #[arg(long, short = 'i', conflicts_with = "format")]
+        interactive: bool,
+
+        /// How to show dates (else defaults to the `git-util.dates` setting, or `short`)
+        #[arg(long, value_enum, value_name = "STYLE")]
+        dates: Option<DateStyle>,
+
+        /// Prefix each commit with the status of its signature: `✓` good (yellow if it or its key has expired), `✗`
+        /// bad or revoked, `?` can't be checked (e.g. the key is missing), or `·` unsigned
+        #[arg(long, conflicts_with = "interactive")]
+        signatures: bool,
+
+        #[clap(flatten)]
+        range: LogDateRange,
+
+        /// Command arguments; trailing arguments that are existing paths are treated as pathspecs
         args: Vec<String>,
+
+        /// Only list commits that touch these paths
+        #[arg(last = true)]
+        paths: Vec<String>,
     },
     /// List commit message and of changed files for the last n commits; wrapper around `git-log --compact-summary`.
     #[clap(alias = "la")]
@@ -130,9 +745,116 @@ pub enum Subcommands {
         /// The number of commits to list (else defaults to 10)
         num: Option<u16>,
 
+        /// How to show dates (else defaults to the `git-util.dates` setting, or git's default)
+        #[arg(long, value_enum, value_name = "STYLE")]
+        dates: Option<DateStyle>,
+
+        #[clap(flatten)]
+        range: LogDateRange,
+
+        /// Command arguments; trailing arguments that are existing paths are treated as pathspecs
+        args: Vec<String>,
+
+        /// Only list commits that touch these paths
+        #[arg(last = true)]
+        paths: Vec<String>,
+    },
+    /// Inspect and configure the files stored with Git LFS.
+    Lfs {
+        #[command(subcommand)]
+        command: LfsSubcommands,
+    },
+    /// Merge a branch into the current branch, using the configured fast-forward policy.
+    ///
+    /// Merging the default branch uses `git-util.merge.defaultBranch` (default: `ff-only`); merging any other branch uses
+    /// `git-util.merge.featureBranch` (default: `no-ff`).
+    #[command(allow_hyphen_values = true)]
+    M {
+        /// The branch to merge
+        branch: String,
+
         /// Command arguments
         args: Vec<String>,
     },
+    /// Switch to the default branch and fast-forward it from its upstream.
+    Main {
+        /// Stash uncommitted changes beforehand and restore them afterwards
+        #[arg(long)]
+        autostash: bool,
+    },
+    /// Rename a branch and, optionally, its remote counterpart.
+    Mv {
+        /// The new branch name
+        new_name: String,
+
+        /// The branch to rename (else defaults to the current branch)
+        old_name: Option<String>,
+
+        /// Also push the branch under the new name, delete the old remote branch, and update the upstream
+        #[arg(long, short = 'r')]
+        remote: bool,
+    },
+    /// Show the commit that added a file and, if it no longer exists, the commit that deleted it.
+    Origin {
+        /// The path of the file
+        path: String,
+    },
+    /// Resolve conflicts by keeping the version on the current branch, then stage the files.
+    Ours {
+        /// Which files to operate on
+        #[command(subcommand)]
+        which: Option<WhichFiles>,
+
+        /// The conflicted files to resolve
+        paths: Vec<String>,
+    },
+    /// Cherry-pick commits (else one picked from the commits on other local branches); wrapper around `git cherry-pick`.
+    Pick {
+        /// The commits to cherry-pick
+        commits: Vec<String>,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Plan a rebase of the last commits in a full-screen editor: reorder, reword, squash, fix up, or drop them, then
+    /// run it without editing a todo list.
+    Plan {
+        /// The number of commits to plan (else defaults to 10)
+        num: Option<u16>,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Manage identity profiles (e.g. `work` and `personal`): the name, email, and signing key to commit with, and the
+    /// host of the repositories they're for.
+    ///
+    /// Profiles are defined with the `git-util.profile.NAME.name`, `.email`, `.signingKey`, and `.host` settings.
+    Profile {
+        #[command(subcommand)]
+        command: ProfileSubcommands,
+    },
+    /// Delete local branches that have been merged (or squash-merged) into the default branch.
+    #[clap(alias = "pb")]
+    PruneBranches {
+        /// Delete the branches without asking for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+    /// Push the current branch to origin, set it as the upstream, and print the URL for opening a pull request.
+    Publish {
+        /// Overwrite the remote branch (using `--force-with-lease`)
+        #[arg(long, short = 'f')]
+        force: bool,
+    },
+    /// Launch the configured merge tool for each conflicted file, then list any remaining conflicts.
+    Resolve {
+        /// Choose which conflicted files to resolve from a list
+        #[arg(long, short = 'p', conflicts_with = "paths")]
+        pick: bool,
+
+        /// The files to resolve (else defaults to all conflicted files)
+        paths: Vec<String>,
+    },
     /// Wrapper around `git-restore`.
     #[clap(alias = "rest")]
     #[command(allow_hyphen_values = true)]
@@ -144,6 +866,26 @@ pub enum Subcommands {
         /// Command arguments
         args: Vec<String>,
     },
+    /// Tag a release with notes generated from the commits since the previous tag, push the tag, and create a
+    /// GitHub/GitLab release if `git-util.releaseToken` is set.
+    Release {
+        /// The version to release, e.g. `v1.2.3`
+        version: String,
+    },
+    /// Edit the message of a commit (else one picked from the recent commits), rebasing the commits after it.
+    Reword {
+        /// The commit to reword
+        commit: Option<String>,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Update git-util to the latest release.
+    SelfUpdate {
+        /// Only report whether an update is available
+        #[arg(long)]
+        check: bool,
+    },
     /// Wrapper around `git-show`.
     #[command(allow_hyphen_values = true)]
     #[clap(alias = "sh")]
@@ -151,13 +893,137 @@ pub enum Subcommands {
         /// The number of commits to show (else defaults to 1)
         num: Option<u16>,
 
+        /// Pick the commit to show from the recent commits
+        #[arg(long, conflicts_with = "num")]
+        pick: bool,
+
+        #[clap(flatten)]
+        renames: RenameDetection,
+
+        /// Highlight the changed words within lines, instead of showing whole lines as removed and added
+        #[arg(long)]
+        words: bool,
+
+        /// Highlight the changed characters within lines, instead of showing whole lines as removed and added
+        #[arg(long, conflicts_with = "words")]
+        char: bool,
+
+        /// Command arguments
+        args: Vec<String>,
+    },
+    /// Resolve conflicts by keeping the version being merged in, then stage the files.
+    Theirs {
+        /// Which files to operate on
+        #[command(subcommand)]
+        which: Option<WhichFiles>,
+
+        /// The conflicted files to resolve
+        paths: Vec<String>,
+    },
+    /// Hide local changes to tracked files from `git status` by setting their skip-worktree bit.
+    ///
+    /// Useful for local-only tweaks to a tracked file (e.g. a config file) that should never be committed.
+    Skip {
+        /// List the files that are skipped instead
+        #[arg(long, short = 'l', conflicts_with = "paths")]
+        list: bool,
+
+        /// The files to skip
+        #[arg(required_unless_present = "list")]
+        paths: Vec<String>,
+    },
+    /// Manage a cone-mode sparse checkout, for working with only part of a large repository.
+    Sparse {
+        #[command(subcommand)]
+        command: SparseSubcommands,
+    },
+    /// Split the last commit into two or more, choosing the changes for each in the staging interface.
+    ///
+    /// The first commit starts with the original message. Fails if there are uncommitted changes.
+    Split {
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Stage and unstage files and individual hunks in a full-screen interface, with the diff of the selected file.
+    Stage {},
+    /// List my commits (by `user.email`) on all local branches from the last n days, grouped by day and branch.
+    Standup {
+        /// The number of days to go back
+        #[arg(long, short = 'd', default_value_t = 1)]
+        days: u16,
+
+        /// List the commits from every repository instead, as journaled by the post-commit hook (see `hook install
+        /// --post-commit`)
+        #[arg(long, short = 'j')]
+        journal: bool,
+    },
+    /// Wrapper around `git-stash`.
+    #[command(allow_hyphen_values = true)]
+    Stash {
+        #[command(subcommand)]
+        command: Option<StashSubcommands>,
+
         /// Command arguments
         args: Vec<String>,
     },
+    /// Count the commits, insertions, deletions, and files touched per author.
+    Stats {
+        /// The commits to count, e.g. `v1.0..HEAD` (else defaults to all of the history of `HEAD`)
+        range: Option<String>,
+
+        #[clap(flatten)]
+        dates: LogDateRange,
+
+        /// The column to sort the authors by
+        #[arg(long, short = 's', value_enum, default_value_t = StatsSort::Commits)]
+        sort: StatsSort,
+    },
+    /// Set (or remove) the upstream of the current branch.
+    Track {
+        /// The upstream branch, e.g. `origin/main` (else defaults to `origin/BRANCH`)
+        #[arg(conflicts_with = "unset")]
+        upstream: Option<String>,
+
+        /// Remove the upstream instead of setting it
+        #[arg(long)]
+        unset: bool,
+    },
+    /// Add and show the trailers (e.g. `Reviewed-by: NAME <EMAIL>`) of the last commit's message.
+    Trailer {
+        #[command(subcommand)]
+        command: TrailerSubcommands,
+    },
     /// Reset the last n commits and keep the undone changes in working directory.
     Undo {
         /// The number of commits to undo (else defaults to 1)
         num: Option<u16>,
+        /// Proceed even if the current branch is protected by the repository's hook configuration
+        #[arg(long)]
+        force_protected: bool,
+    },
+    /// Delete a branch from its remote and remove its upstream.
+    Unpublish {
+        /// The branch to unpublish (else defaults to the current branch)
+        branch: Option<String>,
+
+        /// Delete the remote branch without asking for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+    /// Clear the assume-unchanged bit of files, so that git checks them for changes again.
+    Unassume {
+        /// The files to unassume
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Fetch the complete history of a shallow clone.
+    Unshallow {},
+    /// Clear the skip-worktree bit of files, so that their local changes show up in `git status` again.
+    Unskip {
+        /// The files to unskip
+        #[arg(required = true)]
+        paths: Vec<String>,
     },
     /// Move staged files back to staging area; wrapper around `git-restore --staged`.
     #[clap(alias = "u")]
@@ -174,14 +1040,76 @@ pub enum Subcommands {
     #[clap(alias = "unwind")]
     #[command(allow_hyphen_values = true)]
     Update {
-        /// Command arguments
-        branch: String,
+        /// The branch to update (else defaults to the default branch)
+        branch: Option<String>,
+    },
+    /// Check the signatures on commits (and the annotated tags that point at them).
+    ///
+    /// Fails if any of them is unsigned or has a bad signature, so that it can be used as a CI gate.
+    Verify {
+        /// The number of commits to check (else defaults to 1), or a range of commits, e.g. `main..HEAD`
+        #[arg(value_name = "NUM|RANGE")]
+        commits: Option<String>,
     },
 }
 
 impl Subcommands {
+    /// Whether the subcommand's output can be written to a file with `--output`.
+    pub fn supports_output_file(&self) -> bool {
+        matches!(
+            self,
+            Subcommands::Ab { .. }
+                | Subcommands::Alias {
+                    command: None
+                        | Some(AliasSubcommands::Audit {} | AliasSubcommands::Export { .. }),
+                    ..
+                }
+                | Subcommands::Assume { list: true, .. }
+                | Subcommands::Attrs { command: None, .. }
+                | Subcommands::Backups {
+                    command: BackupsSubcommands::List {}
+                }
+                | Subcommands::Changed { .. }
+                | Subcommands::Changelog { .. }
+                | Subcommands::Compare { .. }
+                | Subcommands::Completions { .. }
+                | Subcommands::Conf {
+                    command: None | Some(ConfSubcommands::Routes {}),
+                    ..
+                }
+                | Subcommands::Dash { .. }
+                | Subcommands::Files { .. }
+                | Subcommands::Find { .. }
+                | Subcommands::Hook {
+                    hook: HookSubcommands::Report {}
+                }
+                | Subcommands::Id { name: None, .. }
+                | Subcommands::L {
+                    interactive: false,
+                    ..
+                }
+                | Subcommands::Last { .. }
+                | Subcommands::Lfs {
+                    command: LfsSubcommands::Status {}
+                }
+                | Subcommands::Origin { .. }
+                | Subcommands::Skip { list: true, .. }
+                | Subcommands::Standup { .. }
+                | Subcommands::Stats { .. }
+                | Subcommands::Trailer {
+                    command: TrailerSubcommands::Show { .. }
+                }
+                | Subcommands::Verify { .. }
+        )
+    }
+
     pub fn run(&self) -> Result<GitCommandResult, anyhow::Error> {
         match self {
+            Subcommands::Abort {} => mutable::operation::abort(),
+            Subcommands::Absorb {
+                base,
+                force_protected,
+            } => mutable::absorb::absorb(base.as_deref(), *force_protected),
             Subcommands::Add { which, args } => {
                 if let Some(which) = which {
                     match which {
@@ -192,42 +1120,278 @@ impl Subcommands {
                 }
             },
             Subcommands::Aa {} => mutable::add::updated_and_untracked(),
-            Subcommands::Aac {} => mutable::commit::updated_and_untracked(),
+            Subcommands::Aac {
+                force_protected,
+                trailers,
+            } => mutable::commit::updated_and_untracked(
+                &mutable::trailer::args(trailers)?,
+                *force_protected,
+            ),
             Subcommands::Aaf {} => mutable::add::updated_and_untracked_forced(),
-            Subcommands::Aamend {} => mutable::commit::amend_updated_and_untracked(),
-            Subcommands::Alias { filter, options } => ImmutableCommands::list_aliases(
-                filter.as_deref(),
-                crate::git::GitConfigOpts {
-                    show_origin: options.show_origin,
-                    show_scope: options.show_scope,
-                },
+            Subcommands::Aamend {
+                force_protected,
+                trailers,
+                args,
+            } => mutable::commit::amend_updated_and_untracked(
+                &[mutable::trailer::args(trailers)?, args.clone()].concat(),
+                *force_protected,
             ),
+            Subcommands::Ab { branch, default } => {
+                ImmutableCommands::ahead_behind(branch.as_deref(), *default)
+            }
+            Subcommands::Alias {
+                command,
+                filter,
+                options,
+            } => match command {
+                Some(command) => command.run(),
+                None => ImmutableCommands::list_aliases(
+                    filter.as_deref(),
+                    crate::git::GitConfigOpts {
+                        show_origin: options.show_origin,
+                        show_scope: options.show_scope,
+                    },
+                ),
+            },
+            Subcommands::Assume { list, paths } => {
+                if *list {
+                    mutable::index::list_assumed()
+                } else {
+                    mutable::index::assume(paths)
+                }
+            }
+            Subcommands::Attrs { command, paths } => match command {
+                Some(command) => command.run(),
+                None => Attrs::show(paths),
+            },
             Subcommands::Au {} => mutable::add::updated(),
             Subcommands::Auf {} => mutable::add::updated_forced(),
-            Subcommands::Auc {} => mutable::commit::updated(),
-            Subcommands::Aumend {} => mutable::commit::amend_updated(),
-            Subcommands::Author { num } => mutable::commit::change_author(*num),
-            Subcommands::Cm { message, args } => mutable::commit::with_message(
-                message,
-                match args {
-                    Some(args) => args,
-                    None => &[],
+            Subcommands::Auc {
+                force_protected,
+                trailers,
+            } => mutable::commit::updated(&mutable::trailer::args(trailers)?, *force_protected),
+            Subcommands::Aumend {
+                force_protected,
+                trailers,
+                args,
+            } => mutable::commit::amend_updated(
+                &[mutable::trailer::args(trailers)?, args.clone()].concat(),
+                *force_protected,
+            ),
+            Subcommands::Author {
+                num,
+                force_protected,
+            } => mutable::commit::change_author(*num, *force_protected),
+            Subcommands::Clone {
+                repository,
+                directory,
+                blobless,
+                treeless,
+                install_hooks,
+                template,
+                args,
+            } => mutable::clone::clone(
+                repository,
+                directory.as_deref(),
+                match (blobless, treeless) {
+                    (true, _) => Some(CloneFilter::Blobless),
+                    (_, true) => Some(CloneFilter::Treeless),
+                    _ => None,
                 },
+                *install_hooks,
+                template.as_deref(),
+                args,
             ),
-            Subcommands::Conf { filter, options } => {
-                ImmutableCommands::list_configuration_settings(
+            Subcommands::Cm {
+                message,
+                force_protected,
+                trailers,
+                args,
+            } => mutable::commit::with_message(
+                message,
+                &[
+                    mutable::trailer::args(trailers)?,
+                    args.clone().unwrap_or_default(),
+                ]
+                .concat(),
+                *force_protected,
+            ),
+            Subcommands::Conf {
+                command,
+                filter,
+                options,
+            } => match command {
+                Some(command) => command.run(),
+                None => ImmutableCommands::list_configuration_settings(
                     filter.as_deref(),
                     crate::git::GitConfigOpts {
                         show_origin: options.show_origin,
                         show_scope: options.show_scope,
                     },
-                )
+                ),
+            },
+            Subcommands::Autosquash {
+                base,
+                force_protected,
+            } => mutable::commit::autosquash(base.as_deref(), *force_protected),
+            Subcommands::Backport {
+                commits,
+                target,
+                branch,
+                worktree,
+                push,
+            } => mutable::backport::backport(commits, target, branch.as_deref(), *worktree, *push),
+            Subcommands::Backups { command } => command.run(),
+            Subcommands::Bump { level, file } => mutable::release::bump(*level, file.as_deref()),
+            Subcommands::Cat { reference, path } => ImmutableCommands::cat(reference, path),
+            Subcommands::Changelog { range, write } => Changelog::run(range.as_deref(), *write),
+            Subcommands::Changed { base, head, group } => {
+                ImmutableCommands::changed(base, head.as_deref(), *group)
             }
+            Subcommands::Chmod { mode, paths } => mutable::index::chmod(mode, paths),
+            Subcommands::Compare { left, right } => {
+                ImmutableCommands::compare(left, right.as_deref())
+            }
+            Subcommands::Complete { candidates } => candidates.print(),
+            Subcommands::SequenceEditor { todo } => mutable::plan::write_todo(todo),
+            Subcommands::Completions { shell } => shell.print(),
+            Subcommands::Conflicts { show } => match show {
+                Some(path) => ImmutableCommands::conflict_hunks(path),
+                None => ImmutableCommands::conflicts(),
+            },
+            Subcommands::Continue { add } => mutable::operation::continue_operation(*add),
+            Subcommands::Dash {} => ImmutableCommands::dashboard(),
+            Subcommands::Dig {
+                pattern,
+                regex,
+                show,
+                args,
+            } => ImmutableCommands::dig(pattern, *regex, *show, args),
+            Subcommands::Doctor {} => Doctor::run(),
+            Subcommands::Drop {
+                commit,
+                force,
+                force_protected,
+            } => mutable::commit::drop_commit(commit, *force, *force_protected),
             Subcommands::Hook { hook } => hook.run(),
-            Subcommands::Files { num } => ImmutableCommands::show_files(*num),
-            Subcommands::L { num, args } => ImmutableCommands::one_line_log(*num, args),
-            Subcommands::Last { num, args } => ImmutableCommands::compact_summary_log(*num, args),
-            Subcommands::Show { num, args } => ImmutableCommands::show(*num, args),
+            Subcommands::Extract { new_branch, num } => mutable::branch::extract(new_branch, *num),
+            Subcommands::F { remotes } => mutable::remote::fetch_all(remotes),
+            Subcommands::Find {
+                pattern,
+                ignore_case,
+                current,
+                args,
+            } => ImmutableCommands::find(pattern, *ignore_case, *current, args),
+            Subcommands::Files {
+                num,
+                null_terminated,
+                renames,
+            } => ImmutableCommands::show_files(*num, *null_terminated, &renames.into()),
+            Subcommands::Fixup {
+                commit,
+                force_protected,
+            } => mutable::commit::fixup(commit.as_deref(), *force_protected),
+            Subcommands::Id { name, email } => Profile::id(name.as_deref(), email.as_deref()),
+            Subcommands::Ignore { command, templates } => match command {
+                Some(command) => command.run(),
+                None => mutable::ignore::templates(templates),
+            },
+            Subcommands::Init {
+                path,
+                template,
+                profile,
+            } => mutable::init::init(path.as_deref(), template.as_deref(), profile.as_deref()),
+            Subcommands::L {
+                num,
+                interactive: true,
+                dates,
+                range,
+                args,
+                paths,
+                ..
+            } => LogBrowser::browse(*num, *dates, &range.into(), args, paths),
+            Subcommands::L {
+                num,
+                format,
+                dates,
+                signatures,
+                range,
+                args,
+                paths,
+                ..
+            } => ImmutableCommands::one_line_log(
+                *num,
+                format.as_deref(),
+                *dates,
+                *signatures,
+                &range.into(),
+                args,
+                paths,
+            ),
+            Subcommands::Last {
+                num,
+                dates,
+                range,
+                args,
+                paths,
+            } => ImmutableCommands::compact_summary_log(*num, *dates, &range.into(), args, paths),
+            Subcommands::Lfs { command } => command.run(),
+            Subcommands::M { branch, args } => mutable::merge::merge(branch, args),
+            Subcommands::Main { autostash } => mutable::branch::switch_to_default(*autostash),
+            Subcommands::Mv {
+                new_name,
+                old_name,
+                remote,
+            } => mutable::branch::rename(new_name, old_name.as_deref(), *remote),
+            Subcommands::Origin { path } => ImmutableCommands::origin(path),
+            Subcommands::Ours { which, paths } => match which {
+                Some(WhichFiles::All) => mutable::conflict::take_all(Side::Ours),
+                None => mutable::conflict::take(Side::Ours, paths),
+            },
+            Subcommands::Plan {
+                num,
+                force_protected,
+            } => mutable::plan::plan(*num, *force_protected),
+            Subcommands::Pick {
+                commits,
+                force_protected,
+            } => mutable::commit::cherry_pick(commits, *force_protected),
+            Subcommands::Profile { command } => command.run(),
+            Subcommands::PruneBranches { yes } => mutable::branch::prune_merged(*yes),
+            Subcommands::Publish { force } => mutable::remote::publish(*force),
+            Subcommands::Release { version } => mutable::release::release(version),
+            Subcommands::Reword {
+                commit,
+                force_protected,
+            } => mutable::commit::reword(commit.as_deref(), *force_protected),
+            Subcommands::SelfUpdate { check } => SelfUpdate::run(*check),
+            Subcommands::Show {
+                num,
+                pick,
+                renames,
+                words,
+                char,
+                args,
+            } => {
+                let args = if *pick {
+                    let mut picked = vec![CommitPicker::pick("show")?];
+                    picked.extend_from_slice(args);
+                    picked
+                } else {
+                    args.clone()
+                };
+
+                let word_diff = if *char {
+                    Some(WordDiff::Chars)
+                } else if *words {
+                    Some(WordDiff::Words)
+                } else {
+                    None
+                };
+
+                ImmutableCommands::show(*num, &renames.into(), word_diff, &args)
+            }
+            Subcommands::Resolve { pick, paths } => mutable::conflict::resolve(paths, *pick),
             Subcommands::Restore { which, args } => {
                 if let Some(all) = which {
                     match all {
@@ -237,7 +1401,52 @@ impl Subcommands {
                     mutable::index::restore(args)
                 }
             }
-            Subcommands::Undo { num } => mutable::commit::undo(*num),
+            Subcommands::Skip { list, paths } => {
+                if *list {
+                    mutable::index::list_skipped()
+                } else {
+                    mutable::index::skip(paths)
+                }
+            }
+            Subcommands::Sparse { command } => command.run(),
+            Subcommands::Split { force_protected } => mutable::split::split(*force_protected),
+            Subcommands::Stage {} => StageBrowser::browse(),
+            Subcommands::Standup { days, journal } => {
+                if *journal {
+                    ImmutableCommands::journal_standup(*days)
+                } else {
+                    ImmutableCommands::standup(*days)
+                }
+            }
+            Subcommands::Stash { command, args } => match command {
+                Some(command) => command.run(),
+                None => mutable::stash::stash(args),
+            },
+            Subcommands::Stats { range, dates, sort } => {
+                ImmutableCommands::stats(range.as_deref(), &dates.into(), *sort)
+            }
+            Subcommands::Theirs { which, paths } => match which {
+                Some(WhichFiles::All) => mutable::conflict::take_all(Side::Theirs),
+                None => mutable::conflict::take(Side::Theirs, paths),
+            },
+            Subcommands::Track { upstream, unset } => {
+                if *unset {
+                    mutable::branch::unset_upstream()
+                } else {
+                    mutable::branch::set_upstream(upstream.as_deref())
+                }
+            }
+            Subcommands::Trailer { command } => command.run(),
+            Subcommands::Undo {
+                num,
+                force_protected,
+            } => mutable::commit::undo(*num, *force_protected),
+            Subcommands::Unassume { paths } => mutable::index::unassume(paths),
+            Subcommands::Unpublish { branch, yes } => {
+                mutable::remote::unpublish(branch.as_deref(), *yes)
+            }
+            Subcommands::Unshallow {} => mutable::clone::unshallow(),
+            Subcommands::Unskip { paths } => mutable::index::unskip(paths),
             Subcommands::Unstage { which, args } => {
                 if let Some(which) = which {
                     match which {
@@ -247,7 +1456,8 @@ impl Subcommands {
                     mutable::index::unstage(args)
                 }
             }
-            Subcommands::Update { branch } => mutable::update_branch_from_remote(branch),
+            Subcommands::Update { branch } => mutable::update_branch_from_remote(branch.as_deref()),
+            Subcommands::Verify { commits } => ImmutableCommands::verify(commits.as_deref()),
         }
     }
 }
@@ -255,7 +1465,133 @@ impl Subcommands {
 impl HookSubcommands {
     fn run(&self) -> GitResult {
         match self {
-            HookSubcommands::PreCommit {} => PreCommitHook::run(),
+            HookSubcommands::Install { force, post_commit } => {
+                hooks::install::install(*force, *post_commit)
+            }
+            HookSubcommands::PreCommit {
+                files,
+                all_files,
+                dry_run,
+            } => {
+                let dry_run = *dry_run || DRY_RUN.load(Ordering::SeqCst);
+                match files.is_empty() && !all_files {
+                    true => PreCommitHook::run(dry_run),
+                    false => PreCommitHook::run_on_files(files, dry_run),
+                }
+            }
+            HookSubcommands::CommitMsg { file } => PreCommitHook::run_commit_msg(file),
+            HookSubcommands::PostCommit {} => PostCommitHook::run(),
+            HookSubcommands::Report {} => HookRun::report(),
+        }
+    }
+}
+
+impl AliasSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            AliasSubcommands::Audit {} => Alias::audit(),
+            AliasSubcommands::Export { file } => Alias::export(file.as_deref()),
+            AliasSubcommands::Import { file, force } => Alias::import(file, *force),
+        }
+    }
+}
+
+impl ProfileSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            ProfileSubcommands::List {} => Profile::list(),
+            ProfileSubcommands::Status {} => Profile::status(),
+            ProfileSubcommands::Use { name } => Profile::apply(name),
+        }
+    }
+}
+
+impl ConfSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            ConfSubcommands::Route { directory, target } => Include::route(directory, target),
+            ConfSubcommands::Routes {} => Include::routes(),
+        }
+    }
+}
+
+impl AttrsSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            AttrsSubcommands::Set {
+                pattern,
+                attributes,
+            } => Attrs::set(pattern, attributes),
+        }
+    }
+}
+
+impl IgnoreSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            IgnoreSubcommands::Add { patterns } => mutable::ignore::add(patterns),
+        }
+    }
+}
+
+impl LfsSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            LfsSubcommands::Status {} => Lfs::status(),
+            LfsSubcommands::Track { patterns } => Lfs::track(patterns),
+        }
+    }
+}
+
+impl BackupsSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            BackupsSubcommands::List {} => mutable::backup::list(),
+            BackupsSubcommands::Restore {
+                backup,
+                force_protected,
+            } => mutable::backup::restore(backup, *force_protected),
+            BackupsSubcommands::Prune { days } => mutable::backup::prune(*days),
+        }
+    }
+}
+
+impl SparseSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            SparseSubcommands::Init {} => mutable::sparse::init(),
+            SparseSubcommands::Add { paths } => mutable::sparse::add(paths),
+            SparseSubcommands::List {} => mutable::sparse::list(),
+            SparseSubcommands::Off {} => mutable::sparse::off(),
+        }
+    }
+}
+
+impl StashSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            StashSubcommands::List { files } => ImmutableCommands::stash_list(*files),
+            StashSubcommands::Pop { index } => mutable::stash::pop(*index),
+            StashSubcommands::Show { index, patch } => {
+                ImmutableCommands::stash_show(*index, *patch)
+            }
+            StashSubcommands::Other(args) => mutable::stash::stash(args),
+        }
+    }
+}
+
+impl TrailerSubcommands {
+    fn run(&self) -> GitResult {
+        match self {
+            TrailerSubcommands::Add {
+                key,
+                value,
+                file,
+                force_protected,
+            } => mutable::trailer::add(key, value.as_deref(), file.as_deref(), *force_protected),
+            TrailerSubcommands::Show { key, file } => {
+                mutable::trailer::show(key.as_deref(), file.as_deref())
+            }
         }
     }
 }
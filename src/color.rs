@@ -0,0 +1,42 @@
+use crate::output::OutputTarget;
+use nu_ansi_term::{AnsiString, Color};
+use std::{
+    env,
+    io::{stderr, IsTerminal},
+};
+
+/// Decides whether output is colored.
+///
+/// Colors are disabled if `NO_COLOR` is set (to anything but an empty string), if `TERM` is `dumb`, or if the output
+/// isn't going to a terminal.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Colors();
+
+impl Colors {
+    /// Whether output written to `OutputTarget` (`stdout`, or the `--output` file) should be colored.
+    pub fn stdout() -> bool {
+        Colors::allowed() && OutputTarget::is_terminal()
+    }
+
+    /// Whether output written to `stderr` should be colored.
+    pub fn stderr() -> bool {
+        Colors::allowed() && stderr().is_terminal()
+    }
+
+    /// Paint `text` in `color` if `enabled` is `true`; otherwise leave it plain.
+    pub fn paint(color: Color, text: &str, enabled: bool) -> AnsiString<'_> {
+        if enabled {
+            color.paint(text)
+        } else {
+            AnsiString::from(text)
+        }
+    }
+
+    /// Whether the environment allows colors at all, regardless of where the output is going.
+    fn allowed() -> bool {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let dumb_terminal = env::var("TERM").is_ok_and(|term| term == "dumb");
+
+        !no_color && !dumb_terminal
+    }
+}
@@ -1,46 +1,140 @@
-use crate::print::Print;
+use crate::{
+    audit_log::AuditLog,
+    git::{error::GitUtilError, Git},
+    print::Print,
+    settings::Settings,
+    timings::Timings,
+};
 use anyhow::{Context, Result};
-use std::process::{ChildStdout, Command, Output, Stdio};
+use log::debug;
+use std::{
+    collections::BTreeMap,
+    env,
+    io::{self, ErrorKind, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdout, Command, ExitStatus, Output, Stdio},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
 
-pub mod ripgrep;
+pub mod filter;
+pub mod tasks;
+
+/// Whether each program that has been looked up is installed; see `Commands::is_available()`.
+static AVAILABLE_PROGRAMS: Mutex<BTreeMap<String, bool>> = Mutex::new(BTreeMap::new());
 
 pub struct Commands();
 
 impl Commands {
     /// This is mainly a convenience function so that we can print the command.
+    ///
+    /// If `command` is `git`, the config overrides from the command line are added (as `-c key=value`) before `args`.
     pub fn new_command_with_args(command: &str, args: &[&str]) -> Command {
         let mut cmd = Command::new(command);
+        if command == "git" {
+            for config_override in Git::config_overrides() {
+                cmd.arg("-c").arg(config_override);
+            }
+        }
         cmd.args(args);
         Print::print_command(&cmd);
         cmd
     }
 
+    /// Whether `program` is installed (i.e. is on the `PATH`, or is a path to an executable).
+    ///
+    /// The result is cached, so the `PATH` is only searched once per program.
+    pub fn is_available(program: &str) -> bool {
+        let Ok(mut available_programs) = AVAILABLE_PROGRAMS.lock() else {
+            return Self::find_on_path(program).is_some();
+        };
+
+        *available_programs
+            .entry(program.to_string())
+            .or_insert_with(|| {
+                let available = Self::find_on_path(program).is_some();
+                debug!("{program} is available: {available}");
+                available
+            })
+    }
+
+    /// The first executable named `program` in the `PATH`, or `program` itself if it's a path to an executable.
+    ///
+    /// On Windows, `program` is also tried with each of the extensions in `PATHEXT` (e.g. `delta.exe` for `delta`).
+    pub fn find_on_path(program: &str) -> Option<PathBuf> {
+        let names = Self::executable_names(program);
+
+        if program.contains(std::path::MAIN_SEPARATOR) {
+            return names
+                .into_iter()
+                .map(PathBuf::from)
+                .find(|path| path.is_file() && Self::is_executable(path));
+        }
+
+        env::split_paths(&env::var_os("PATH")?)
+            .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+            .find(|path| path.is_file() && Self::is_executable(path))
+    }
+
+    /// `program`, followed on Windows by `program` with each of the extensions in `PATHEXT` (else the default
+    /// `.COM;.EXE;.BAT;.CMD`).
+    fn executable_names(program: &str) -> Vec<String> {
+        let mut names = vec![program.to_string()];
+
+        if cfg!(windows) {
+            let extensions =
+                env::var("PATHEXT").unwrap_or_else(|_| String::from(".COM;.EXE;.BAT;.CMD"));
+            names.extend(
+                extensions
+                    .split(';')
+                    .filter(|extension| !extension.is_empty())
+                    .map(|extension| format!("{program}{extension}")),
+            );
+        }
+
+        names
+    }
+
+    pub fn is_executable(path: &Path) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            path.metadata()
+                .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+        }
+        #[cfg(not(unix))]
+        {
+            path.is_file()
+        }
+    }
+
     /// Format `input` as a table, using `separator` as the separator.
     ///
     /// `column --table --separator 'SEPARATOR'`
     pub fn pipe_to_column(input: ChildStdout, separator: char) -> Result<Output> {
         #[cfg(any(target_os = "windows", target_os = "linux"))]
         {
-            Self::new_command_with_args(
-                "column",
-                &["--table", "--separator", separator.to_string().as_str()],
+            Self::output_with_timeout(
+                Self::new_command_with_args(
+                    Settings::get().column_program(),
+                    &["--table", "--separator", separator.to_string().as_str()],
+                )
+                .stdin(Stdio::from(input)),
             )
-            .stdin(Stdio::from(input))
-            .stdout(Stdio::piped())
-            .spawn()
-            .with_context(|| "Failed to pipe to column")?
-            .wait_with_output()
-            .with_context(|| "Failed to get column output")
+            .with_context(|| "Failed to pipe to column")
         }
         #[cfg(target_os = "macos")]
         {
-            Self::new_command_with_args("column", &["-t", "-s", separator.to_string().as_str()])
-                .stdin(Stdio::from(input))
-                .stdout(Stdio::piped())
-                .spawn()
-                .with_context(|| "Failed to pipe to column")?
-                .wait_with_output()
-                .with_context(|| "Failed to get column output")
+            Self::output_with_timeout(
+                Self::new_command_with_args(
+                    Settings::get().column_program(),
+                    &["-t", "-s", separator.to_string().as_str()],
+                )
+                .stdin(Stdio::from(input)),
+            )
+            .with_context(|| "Failed to pipe to column")
         }
     }
 
@@ -52,24 +146,285 @@ impl Commands {
         input: ChildStdout,
         args: &[&str],
     ) -> Result<ChildStdout> {
-        Self::new_command_with_args(command, args)
+        let mut cmd = Self::new_command_with_args(command, args);
+        let mut child = cmd
             .stdin(Stdio::from(input))
             .stdout(Stdio::piped())
             .spawn()
-            .with_context(|| format!("Failed to spawn {command}"))?
+            .with_context(|| format!("Failed to spawn {command}"))?;
+
+        let stdout = child
             .stdout
-            .with_context(|| format!("Failed to open stdout from {command} pipe"))
+            .take()
+            .with_context(|| format!("Failed to open stdout from {command} pipe"))?;
+        Timings::track(&cmd, child);
+
+        Ok(stdout)
     }
 
     /// Call `command` with arguments from `args` and pipe output to stdin.
     ///
     /// `COMMAND ARGS | ...`
     pub fn pipe_from_command(command: &str, args: &[&str]) -> Result<ChildStdout> {
-        Self::new_command_with_args(command, args)
+        let mut cmd = Self::new_command_with_args(command, args);
+        let mut child = cmd
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to execute {command} command"))?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .with_context(|| format!("Failed to spawn {command}"))?;
+        Timings::track(&cmd, child);
+
+        Ok(stdout)
+    }
+
+    /// Execute `command` with its `stdout` piped to `program` (which inherits this process's `stdout`), and wait for
+    /// both to exit.
+    ///
+    /// `COMMAND | PROGRAM ARGS`
+    ///
+    /// Returns the exit status of `command` if it failed, else that of `program`.
+    pub fn pipe_to(command: &mut Command, program: &str, args: &[&str]) -> Result<ExitStatus> {
+        let mut child = command
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to spawn {:?}", command.get_program()))?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .with_context(|| format!("Failed to open stdout from {:?}", command.get_program()))?;
+
+        let program_status = Self::status_with_timeout(
+            Self::new_command_with_args(program, args).stdin(Stdio::from(stdout)),
+        );
+
+        // wait for `command` even if `program` failed, so that it isn't left running
+        let status = child.wait()?;
+        let program_status = program_status?;
+
+        Ok(if status.success() {
+            program_status
+        } else {
+            status
+        })
+    }
+
+    /// Execute `command`, copying its `stdout` to `writer` as it's written (e.g. to the pager), and wait for it to exit.
+    ///
+    /// If `filter` is given, the `stdout` of `command` is piped through it first (`COMMAND | FILTER`), and the exit
+    /// status is that of `command` if it failed, else that of `filter`. There's no timeout, since `writer` may be a
+    /// pager that's waiting on me.
+    pub fn stream_to(
+        command: &mut Command,
+        filter: Option<&str>,
+        writer: &mut dyn Write,
+    ) -> Result<ExitStatus> {
+        let start = Instant::now();
+        let status = Timings::time_command(command, |command| {
+            Self::stream_with_filter(command, filter, writer)
+        });
+        AuditLog::command(
+            command,
+            status.as_ref().ok().and_then(ExitStatus::code),
+            start.elapsed(),
+        );
+
+        status
+    }
+
+    fn stream_with_filter(
+        command: &mut Command,
+        filter: Option<&str>,
+        writer: &mut dyn Write,
+    ) -> Result<ExitStatus> {
+        let mut child = command
             .stdout(Stdio::piped())
             .spawn()
-            .with_context(|| format!("Failed to execute {command} command"))?
+            .with_context(|| format!("Failed to spawn {:?}", command.get_program()))?;
+
+        let mut stdout = child
             .stdout
-            .with_context(|| format!("Failed to spawn {command}"))
+            .take()
+            .with_context(|| format!("Failed to open stdout from {:?}", command.get_program()))?;
+
+        let filter_status = match filter {
+            Some(program) => {
+                let mut filter_child = Self::new_command_with_args(program, &[])
+                    .stdin(Stdio::from(stdout))
+                    .stdout(Stdio::piped())
+                    .spawn()
+                    .with_context(|| format!("Failed to spawn {program}"))?;
+
+                if let Some(mut output) = filter_child.stdout.take() {
+                    io::copy(&mut output, writer)?;
+                }
+                Some(filter_child.wait()?)
+            }
+            None => {
+                io::copy(&mut stdout, writer)?;
+                None
+            }
+        };
+        writer.flush()?;
+
+        let status = child.wait()?;
+
+        Ok(match filter_status {
+            Some(filter_status) if status.success() => filter_status,
+            _ => status,
+        })
+    }
+
+    /// Execute `command` with `input` written to its `stdin`, capturing its `stdout` and `stderr`, and wait for it to
+    /// exit.
+    ///
+    /// `input` is written before the output is read, so it's only suitable for commands with little output.
+    pub fn output_with_input(command: &mut Command, input: &[u8]) -> Result<Output> {
+        let start = Instant::now();
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to spawn {:?}", command.get_program()))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // `stdin` is closed when it's dropped, so that the command sees the end of the input
+            match stdin.write_all(input) {
+                // the command exited (or closed `stdin`) without reading all of the input
+                Err(error) if error.kind() == ErrorKind::BrokenPipe => {}
+                result => result?,
+            }
+        }
+
+        let output = child.wait_with_output();
+        AuditLog::command(
+            command,
+            output.as_ref().ok().and_then(|output| output.status.code()),
+            start.elapsed(),
+        );
+
+        Ok(output?)
+    }
+
+    /// Execute `command` and wait for it to exit.
+    ///
+    /// If the `git-util.timeout` setting is set, `command` is killed once it runs longer than the timeout, unless
+    /// `stdin` is a terminal: `command` inherits it, so it may be an editor or pager (or a prompt) that's waiting on me.
+    pub fn status_with_timeout(command: &mut Command) -> Result<ExitStatus> {
+        let start = Instant::now();
+        let status = Timings::time_command(command, Self::status_with_optional_timeout);
+        AuditLog::command(
+            command,
+            status.as_ref().ok().and_then(ExitStatus::code),
+            start.elapsed(),
+        );
+
+        status
+    }
+
+    fn status_with_optional_timeout(command: &mut Command) -> Result<ExitStatus> {
+        match Settings::get().timeout {
+            Some(timeout) if !io::stdin().is_terminal() => {
+                let mut child = command.spawn()?;
+                Self::wait_with_timeout(&mut child, command, timeout)
+            }
+            _ => Ok(command.status()?),
+        }
+    }
+
+    /// Execute `command`, capturing its `stdout` and `stderr`, and wait for it to exit.
+    ///
+    /// If the `git-util.timeout` setting is set, `command` is killed once it runs longer than the timeout.
+    pub fn output_with_timeout(command: &mut Command) -> Result<Output> {
+        let start = Instant::now();
+        let output = Timings::time_command(command, Self::output_with_optional_timeout);
+        AuditLog::command(
+            command,
+            output.as_ref().ok().and_then(|output| output.status.code()),
+            start.elapsed(),
+        );
+
+        output
+    }
+
+    fn output_with_optional_timeout(command: &mut Command) -> Result<Output> {
+        let timeout = match Settings::get().timeout {
+            None => return Ok(command.output()?),
+            Some(timeout) => timeout,
+        };
+
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // read the pipes on separate threads, so that the child can't block on a full pipe while we wait for it
+        let stdout = child.stdout.take().map(Self::read_to_end);
+        let stderr = child.stderr.take().map(Self::read_to_end);
+
+        let status = Self::wait_with_timeout(&mut child, command, timeout)?;
+
+        let join = |handle: Option<thread::JoinHandle<Vec<u8>>>| {
+            handle
+                .map(|handle| handle.join().unwrap_or_default())
+                .unwrap_or_default()
+        };
+
+        Ok(Output {
+            status,
+            stdout: join(stdout),
+            stderr: join(stderr),
+        })
+    }
+
+    /// Poll `child` until it exits, killing it if it's still running after `timeout`.
+    fn wait_with_timeout(
+        child: &mut Child,
+        command: &Command,
+        timeout: Duration,
+    ) -> Result<ExitStatus> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status);
+            }
+
+            if Instant::now() >= deadline {
+                debug!("killing {:?} after {:?}", command, timeout);
+
+                child.kill()?;
+                child.wait()?;
+
+                return Err(GitUtilError::Timeout {
+                    command: format!(
+                        "{} {}",
+                        command.get_program().to_string_lossy(),
+                        command
+                            .get_args()
+                            .map(|arg| arg.to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    ),
+                    timeout,
+                }
+                .into());
+            }
+
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    fn read_to_end<R: Read + Send + 'static>(mut reader: R) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = reader.read_to_end(&mut buffer);
+            buffer
+        })
     }
 }
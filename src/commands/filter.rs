@@ -2,10 +2,15 @@ use anyhow::{anyhow, Result};
 use std::process::{ChildStdout, Command, ExitStatus, Stdio};
 
 use super::Commands;
+use crate::settings::Settings;
 
-pub struct Ripgrep {}
+/// A line filter: `rg` by default, or whichever program the `git-util.tools.filter` setting names.
+///
+/// A substitute (e.g. `ugrep` or `grep`) must read from stdin when no file is given and accept the flags used by
+/// `FilterOptions`.
+pub struct Filter {}
 
-pub enum RipgrepOptions {
+pub enum FilterOptions {
     #[allow(dead_code)]
     Context(u8),
     FixedStrings,
@@ -14,37 +19,46 @@ pub enum RipgrepOptions {
     InvertMatch,
 }
 
-impl Ripgrep {
-    /// Pipe from `input` to ripgrep to stdin.
+impl Filter {
+    /// Pipe from `input` to the filter to stdin.
     ///
     /// `INPUT | rg | ...`
     pub fn double_ended_pipe(
         input: ChildStdout,
         pattern: &str,
-        options: Option<&[RipgrepOptions]>,
+        options: Option<&[FilterOptions]>,
     ) -> Result<ChildStdout> {
         if let Some(opts) = options {
-            Commands::double_ended_pipe("rg", input, &Ripgrep::parse_options(opts, pattern))
+            Commands::double_ended_pipe(
+                Settings::get().filter_program(),
+                input,
+                &Filter::parse_options(opts, pattern),
+            )
         } else {
-            Commands::double_ended_pipe("rg", input, &Ripgrep::parse_options(&[], pattern))
+            Commands::double_ended_pipe(
+                Settings::get().filter_program(),
+                input,
+                &Filter::parse_options(&[], pattern),
+            )
         }
     }
 
-    /// Pipe `input` to ripgrep.
+    /// Pipe `input` to the filter.
     ///
     /// `INPUT | rg OPTIONS PATTERN`
     #[allow(dead_code)]
-    pub fn pipe_to_ripgrep(
+    pub fn pipe_to_filter(
         input: ChildStdout,
         pattern: &str,
-        options: Option<&[RipgrepOptions]>,
+        options: Option<&[FilterOptions]>,
     ) -> Result<ExitStatus> {
         let args = match options {
-            Some(opts) => Ripgrep::parse_options(opts, pattern),
+            Some(opts) => Filter::parse_options(opts, pattern),
             None => vec![pattern],
         };
 
-        let mut command: Command = Commands::new_command_with_args("rg", &args);
+        let mut command: Command =
+            Commands::new_command_with_args(Settings::get().filter_program(), &args);
 
         match command.stdin(Stdio::from(input)).status() {
             Ok(status) => Ok(status),
@@ -53,12 +67,12 @@ impl Ripgrep {
     }
 
     /// Parse `options` in a `Vec<&str>` that can be used in a `std::process::Command`.
-    fn parse_options<'a>(options: &'a [RipgrepOptions], pattern: &'a str) -> Vec<&'a str> {
+    fn parse_options<'a>(options: &'a [FilterOptions], pattern: &'a str) -> Vec<&'a str> {
         let mut args: Vec<&str> = Vec::new();
 
         for opt in options {
             args.push(match opt {
-                RipgrepOptions::Context(num) => {
+                FilterOptions::Context(num) => {
                     // TODO: get this borrow issue worked out
                     //context = &format!("-C{num}"),
                     match num {
@@ -70,9 +84,9 @@ impl Ripgrep {
                         _ => todo!(),
                     }
                 }
-                RipgrepOptions::FixedStrings => "--fixed-strings",
-                RipgrepOptions::IgnoreCase => "--ignore-case",
-                RipgrepOptions::InvertMatch => "--invert-match",
+                FilterOptions::FixedStrings => "--fixed-strings",
+                FilterOptions::IgnoreCase => "--ignore-case",
+                FilterOptions::InvertMatch => "--invert-match",
             })
         }
 
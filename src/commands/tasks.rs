@@ -0,0 +1,48 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+pub struct Tasks();
+
+impl Tasks {
+    /// Run `task` on each of `inputs` using up to `max_parallel` threads.
+    ///
+    /// The results are returned in the same order as `inputs`, regardless of the order in which the tasks finish.
+    pub fn run_parallel<I, O, F>(inputs: &[I], max_parallel: usize, task: F) -> Vec<O>
+    where
+        I: Sync,
+        O: Send,
+        F: Fn(&I) -> O + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<O>>> = Mutex::new((0..inputs.len()).map(|_| None).collect());
+
+        thread::scope(|scope| {
+            for _ in 0..max_parallel.clamp(1, inputs.len().max(1)) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+
+                    let Some(input) = inputs.get(i) else {
+                        break;
+                    };
+
+                    let result = task(input);
+                    results
+                        .lock()
+                        .expect("task results lock should not be poisoned")[i] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .expect("task results lock should not be poisoned")
+            .into_iter()
+            .map(|result| result.expect("every task should have run"))
+            .collect()
+    }
+}
@@ -1,16 +1,28 @@
-use anyhow::{Context, Ok, Result};
+use anyhow::{anyhow, Context, Ok, Result};
 use log::{debug, trace};
 use std::{
-    io::{stdout, IsTerminal},
-    process::Command,
-    sync::atomic::AtomicBool,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output},
+    sync::{atomic::AtomicBool, OnceLock},
 };
 
-use crate::{commands::Commands, print::Print};
+use crate::{
+    color::Colors,
+    commands::Commands,
+    output::{OutputTarget, Paging},
+    print::Print,
+    progress::Spinner,
+    settings::Settings,
+};
 
+pub mod autostash;
 pub mod commands;
 pub mod env_vars;
+pub mod error;
+pub mod forge;
 pub mod hooks;
+pub mod state;
 
 pub type GitResult = Result<GitCommandResult>;
 pub struct Git();
@@ -21,6 +33,21 @@ pub static PRINT_COMMANDS: AtomicBool = AtomicBool::new(false);
 /// Flag used to indicate whether subcommand is a dry run
 pub static DRY_RUN: AtomicBool = AtomicBool::new(false);
 
+/// Flag used to indicate whether to suppress informational output
+pub static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Flag used to indicate whether to report how long each spawned process and internal phase took
+pub static TIMINGS: AtomicBool = AtomicBool::new(false);
+
+/// The `key=value` config overrides passed to every `git` command as `git -c key=value`; set from the command line.
+pub static CONFIG_OVERRIDES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// The **Git Subcommands** that accept `--quiet` immediately after the subcommand name.
+const QUIET_SUBCOMMANDS: [&str; 12] = [
+    "branch", "checkout", "commit", "fetch", "merge", "pull", "push", "rebase", "reset", "restore",
+    "rm", "switch",
+];
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub struct DefaultMaxCount(pub u8);
 
@@ -46,6 +73,148 @@ pub struct GitConfigOpts {
     pub show_scope: bool,
 }
 
+/// The date filters for `git log`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+pub struct LogDateRange {
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+impl LogDateRange {
+    /// The `--since` and `--until` arguments for the range.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if let Some(since) = &self.since {
+            args.push(format!("--since={}", LogDateRange::expand(since)));
+        }
+        if let Some(until) = &self.until {
+            args.push(format!("--until={}", LogDateRange::expand(until)));
+        }
+
+        args
+    }
+
+    /// Expand shorthand durations (`2h`, `3d`, `2w`, `6mo`, `1y`) into dates that git understands (e.g. `2 weeks ago`).
+    ///
+    /// Anything else (e.g. `yesterday` or `2024-01-01`) is passed to git as is.
+    fn expand(date: &str) -> String {
+        let units = [
+            ("mo", "months"),
+            ("h", "hours"),
+            ("d", "days"),
+            ("w", "weeks"),
+            ("y", "years"),
+        ];
+
+        for (suffix, unit) in units {
+            if let Some(count) = date.strip_suffix(suffix) {
+                if !count.is_empty() && count.chars().all(|c| c.is_ascii_digit()) {
+                    return format!("{count} {unit} ago");
+                }
+            }
+        }
+
+        date.to_string()
+    }
+}
+
+/// The rename and copy detection options for diffs.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
+pub struct RenameDetection {
+    /// `Some(None)` to detect renames with git's default similarity threshold, or `Some(Some(PERCENT))` to use `PERCENT`.
+    pub find_renames: Option<Option<u8>>,
+    pub find_copies: bool,
+}
+
+impl RenameDetection {
+    /// Whether renames or copies are detected.
+    pub fn is_enabled(&self) -> bool {
+        self.find_renames.is_some() || self.find_copies
+    }
+
+    /// The `--find-renames` and `--find-copies` arguments for the options.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        match self.find_renames {
+            Some(Some(percent)) => args.push(format!("--find-renames={percent}%")),
+            Some(None) => args.push("--find-renames".to_string()),
+            None => (),
+        }
+        if self.find_copies {
+            args.push("--find-copies".to_string());
+        }
+
+        args
+    }
+}
+
+/// How changes within lines are highlighted in diffs, instead of showing whole lines as removed and added.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum WordDiff {
+    /// Highlight the changed words.
+    Words,
+    /// Highlight the changed characters.
+    Chars,
+}
+
+impl WordDiff {
+    /// The `--word-diff` arguments for the mode.
+    ///
+    /// Changes are highlighted with colors if they're enabled for `stdout`, else marked as `[-removed-]{+added+}`.
+    pub fn args(&self) -> Vec<&'static str> {
+        // `--word-diff=color` forces color, even when it's disabled
+        let mut args = vec![if Colors::stdout() {
+            "--word-diff=color"
+        } else {
+            "--word-diff=plain"
+        }];
+
+        if *self == WordDiff::Chars {
+            args.push("--word-diff-regex=.");
+        }
+
+        args
+    }
+}
+
+/// How dates are shown in log output; maps onto `git log --date=STYLE`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, clap::ValueEnum)]
+pub enum DateStyle {
+    /// e.g. `2 hours ago`
+    Relative,
+    /// e.g. `2024-01-31`
+    Short,
+    /// e.g. `2024-01-31 13:45:00 -0800`
+    Iso,
+}
+
+impl DateStyle {
+    /// Parse a setting `value`; valid values are `relative`, `short`, and `iso`.
+    pub fn from_setting(key: &str, value: &str) -> Result<DateStyle> {
+        match value.to_lowercase().as_str() {
+            "relative" => Ok(DateStyle::Relative),
+            "short" => Ok(DateStyle::Short),
+            "iso" => Ok(DateStyle::Iso),
+            _ => Err(anyhow!(
+                "Invalid value for {}: \"{}\". Expected one of: relative, short, iso",
+                key,
+                value
+            )),
+        }
+    }
+
+    /// The `--date` argument for this style.
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            DateStyle::Relative => "--date=relative",
+            DateStyle::Short => "--date=short",
+            DateStyle::Iso => "--date=iso",
+        }
+    }
+}
+
 impl Git {
     pub fn pass_through(args: &[String]) -> GitResult {
         trace!("<pass_through> called with: {:#?}", args);
@@ -60,6 +229,290 @@ impl Git {
         command.run()
     }
 
+    /// The `key=value` config overrides from the command line, or none if they haven't been set.
+    pub fn config_overrides() -> &'static [String] {
+        CONFIG_OVERRIDES.get().map_or(&[], Vec::as_slice)
+    }
+
+    /// The name of the repository's default branch.
+    ///
+    /// Resolved from (in order):
+    /// 1. the `git-util.defaultBranch` setting
+    /// 2. `origin/HEAD`
+    /// 3. the `init.defaultBranch` config setting, if that branch exists locally
+    /// 4. `main` or `master`, whichever exists locally
+    fn default_branch() -> Result<String> {
+        trace!("default_branch() called");
+
+        if let Some(default_branch) = &Settings::get().default_branch {
+            return Ok(default_branch.clone());
+        }
+
+        if let std::result::Result::Ok(remote_head) = GitCommand::new("symbolic-ref")
+            .with_default_args(&["--short", "refs/remotes/origin/HEAD"])
+            .stdout()
+        {
+            debug!("origin/HEAD => {remote_head}");
+            return Ok(remote_head.trim_start_matches("origin/").to_string());
+        }
+
+        if let Some(init_default) = Git::config_value("init.defaultBranch")? {
+            if Git::branch_exists(&init_default)? {
+                return Ok(init_default);
+            }
+        }
+
+        for candidate in ["main", "master"] {
+            if Git::branch_exists(candidate)? {
+                return Ok(candidate.to_string());
+            }
+        }
+
+        Err(anyhow!("Unable to determine the default branch"))
+    }
+
+    /// Check whether the local branch `branch` exists.
+    fn branch_exists(branch: &str) -> Result<bool> {
+        Ok(GitCommand::new("show-ref")
+            .with_default_args(&["--verify", "--quiet", &format!("refs/heads/{branch}")])
+            .output()?
+            .status
+            .success())
+    }
+
+    /// Check whether `object` (e.g. `REF:PATH`) names an object in the repository.
+    ///
+    /// `git cat-file -e OBJECT`
+    fn object_exists(object: &str) -> Result<bool> {
+        Ok(GitCommand::new("cat-file")
+            .with_default_args(&["-e", object])
+            .output()?
+            .status
+            .success())
+    }
+
+    /// The files in the index that have the skip-worktree bit set, relative to the repository root.
+    fn skipped_files() -> Result<Vec<String>> {
+        Git::index_files_tagged(|tag| tag == 'S')
+    }
+
+    /// The files in the index that are marked assume-unchanged, relative to the repository root.
+    fn assumed_unchanged_files() -> Result<Vec<String>> {
+        // `ls-files -v` shows the tags of assume-unchanged files in lowercase
+        Git::index_files_tagged(|tag| tag.is_ascii_lowercase())
+    }
+
+    /// The files in the index whose `git ls-files -v` tag matches `predicate`, relative to the repository root.
+    ///
+    /// `git ls-files -v -z --full-name -- :/`
+    fn index_files_tagged(predicate: fn(char) -> bool) -> Result<Vec<String>> {
+        Ok(GitCommand::new("ls-files")
+            .with_default_args(&["-v", "-z", "--full-name", "--", ":/"])
+            .stdout()?
+            .split('\0')
+            .filter_map(|entry| entry.split_once(' '))
+            .filter(|(tag, _)| tag.chars().next().is_some_and(predicate))
+            .map(|(_, path)| path.to_string())
+            .collect())
+    }
+
+    /// The name of the branch that is currently checked out.
+    ///
+    /// Fails if `HEAD` is detached.
+    fn current_branch() -> Result<String> {
+        let branch = GitCommand::new("branch")
+            .with_default_args(&["--show-current"])
+            .stdout()?;
+
+        if branch.is_empty() {
+            Err(anyhow!("HEAD is detached; not on any branch"))
+        } else {
+            Ok(branch)
+        }
+    }
+
+    /// Count the commits that are only reachable from `left` and only reachable from `right`, respectively.
+    ///
+    /// `git rev-list --left-right --count LEFT...RIGHT`
+    fn ahead_behind(left: &str, right: &str) -> Result<(u32, u32)> {
+        let counts = GitCommand::new("rev-list")
+            .with_default_args(&["--left-right", "--count", &format!("{left}...{right}")])
+            .stdout()?;
+
+        match counts.split_once('\t') {
+            Some((ahead, behind)) => Ok((ahead.parse()?, behind.parse()?)),
+            None => Err(anyhow!("Unexpected 'git rev-list' output: {:?}", counts)),
+        }
+    }
+
+    /// The upstream of `branch` (e.g. `origin/main`), or `None` if it doesn't have one.
+    ///
+    /// `git rev-parse --abbrev-ref BRANCH@{upstream}`
+    fn upstream(branch: &str) -> Result<Option<String>> {
+        let output = GitCommand::new("rev-parse")
+            .with_default_args(&["--abbrev-ref", &format!("{branch}@{{upstream}}")])
+            .output()?;
+
+        if output.status.success() {
+            Ok(Some(
+                String::from_utf8(output.stdout)?.trim_end().to_string(),
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The most recent tag reachable from `reference`, or `None` if there isn't one.
+    ///
+    /// `git describe --tags --abbrev=0 REF`
+    fn latest_tag(reference: &str) -> Result<Option<String>> {
+        let output = GitCommand::new("describe")
+            .with_default_args(&["--tags", "--abbrev=0", reference])
+            .output()?;
+
+        if output.status.success() {
+            Ok(Some(
+                String::from_utf8(output.stdout)?.trim_end().to_string(),
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The absolute path of the top-level directory of the work tree.
+    ///
+    /// `git rev-parse --show-toplevel`
+    fn repo_root() -> Result<PathBuf> {
+        Ok(PathBuf::from(
+            GitCommand::new("rev-parse")
+                .with_default_args(&["--show-toplevel"])
+                .stdout()?,
+        ))
+    }
+
+    /// Resolve `path` inside the `.git` directory.
+    ///
+    /// `git rev-parse --git-path PATH`
+    fn git_path(path: &str) -> Result<PathBuf> {
+        Ok(PathBuf::from(
+            GitCommand::new("rev-parse")
+                .with_default_args(&["--git-path", path])
+                .stdout()?,
+        ))
+    }
+
+    /// The names and values of the config settings whose names match `regex`.
+    ///
+    /// `git config --get-regexp REGEX`
+    pub fn config_values_matching(regex: &str) -> Result<Vec<(String, String)>> {
+        let output = GitCommand::new("config")
+            .with_default_args(&["--get-regexp", regex])
+            .output()?;
+
+        // `git config --get-regexp` exits with 1 if nothing matches
+        match output.status.code() {
+            Some(0) => Ok(String::from_utf8(output.stdout)?
+                .lines()
+                .map(|line| match line.split_once(' ') {
+                    Some((key, value)) => (key.to_string(), value.to_string()),
+                    // a key without a value is an implicit `true`
+                    None => (line.to_string(), "true".to_string()),
+                })
+                .collect()),
+            Some(1) => Ok(Vec::new()),
+            _ => Err(anyhow!(
+                "Failed to get config values matching '{}': {}",
+                regex,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        }
+    }
+
+    /// The paths of the system, global, and local config files (whether or not they exist).
+    fn config_files() -> Vec<PathBuf> {
+        let mut files = vec![PathBuf::from("/etc/gitconfig")];
+
+        if let Some(home) = std::env::var_os("HOME") {
+            let home = PathBuf::from(home);
+
+            files.push(match std::env::var_os("XDG_CONFIG_HOME") {
+                Some(config_home) if !config_home.is_empty() => {
+                    PathBuf::from(config_home).join("git/config")
+                }
+                _ => home.join(".config/git/config"),
+            });
+            files.push(home.join(".gitconfig"));
+        }
+
+        // outside of a repository, there's no local config file
+        if let std::result::Result::Ok(local) = Git::git_path("config") {
+            files.push(std::env::current_dir().unwrap_or_default().join(local));
+        }
+
+        files
+    }
+
+    /// The value of the config setting `key`, or `None` if it is not set.
+    fn config_value(key: &str) -> Result<Option<String>> {
+        let output = GitCommand::new("config")
+            .with_default_args(&["--get", key])
+            .output()?;
+
+        // `git config --get` exits with 1 if the key is not set
+        match output.status.code() {
+            Some(0) => Ok(Some(
+                String::from_utf8(output.stdout)?.trim_end().to_string(),
+            )),
+            Some(1) => Ok(None),
+            _ => Err(anyhow!(
+                "Failed to get config value for '{}': {}",
+                key,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        }
+    }
+
+    /// Combine `args` and `paths` into arguments for `git log`, with the pathspecs after a `--` separator.
+    ///
+    /// Trailing `args` that are existing paths are moved after the `--` too, so that `l 10 src/` works. Every path in
+    /// `paths` must exist in the work tree or in the history of `HEAD`.
+    fn with_pathspecs(args: &[String], paths: &[String]) -> Result<Vec<String>> {
+        for path in paths {
+            // don't try to validate pathspec magic (e.g. `:(glob)`) or wildcards
+            if path.starts_with(':') || path.contains(['*', '?', '[']) {
+                continue;
+            }
+
+            if !std::path::Path::new(path).exists()
+                && GitCommand::new("log")
+                    .with_default_args(&["--max-count=1", "--format=%h", "--", path])
+                    .stdout()
+                    .unwrap_or_default()
+                    .is_empty()
+            {
+                return Err(anyhow!("Path '{}' doesn't exist in the repository", path));
+            }
+        }
+
+        let implicit_paths = args
+            .iter()
+            .rev()
+            .take_while(|arg| !arg.starts_with('-') && std::path::Path::new(arg).exists())
+            .count();
+        let (args, trailing_paths) = args.split_at(args.len() - implicit_paths);
+
+        let mut log_args = args.to_vec();
+        if !trailing_paths.is_empty() || !paths.is_empty() {
+            log_args.push("--".to_string());
+            log_args.extend_from_slice(trailing_paths);
+            log_args.extend_from_slice(paths);
+        }
+
+        debug!("log args with pathspecs: {log_args:#?}");
+
+        Ok(log_args)
+    }
+
     fn parse_config_options(options: GitConfigOpts, config_args: &mut Vec<&str>) {
         if options.show_origin {
             config_args.push("--show-origin")
@@ -72,10 +525,10 @@ impl Git {
     /// Return `Success` if nothing is printed to stdout when `git diff --staged --name-only` is run.
     fn verify_staging_area_is_empty() -> GitResult {
         trace!("check_for_staged_files() called");
-        let output: std::process::Output =
-            Commands::new_command_with_args("git", &["diff", "--staged", "--name-only"])
-                .output()
-                .with_context(|| "Failed to execute git command")?;
+        let output: std::process::Output = Commands::output_with_timeout(
+            &mut Commands::new_command_with_args("git", &["diff", "--staged", "--name-only"]),
+        )
+        .with_context(|| "Failed to execute git command")?;
 
         if output.stdout.is_empty() {
             Ok(GitCommandResult::Success)
@@ -84,15 +537,42 @@ impl Git {
         }
     }
 
+    /// Return `Success` if there are no staged or unstaged changes to tracked files.
+    fn verify_clean_work_tree() -> GitResult {
+        trace!("verify_clean_work_tree() called");
+
+        let status = GitCommand::new("status")
+            .with_default_args(&["--porcelain", "--untracked-files=no"])
+            .stdout()?;
+
+        if status.is_empty() {
+            Ok(GitCommandResult::Success)
+        } else {
+            Ok(GitCommandResult::Error)
+        }
+    }
+
+    /// The paths of files with unresolved merge conflicts.
+    ///
+    /// `git diff --name-only --diff-filter=U`
+    fn conflicted_files() -> Result<Vec<String>> {
+        Ok(GitCommand::new("diff")
+            .with_default_args(&["--name-only", "--diff-filter=U"])
+            .stdout()?
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+
     /// Return `Success` if there are no unstaged changes in the work tree.
     ///
     /// The staging area can be empty or populated.
     fn verify_no_unstaged_changes() -> GitResult {
         trace!("check_for_staged_files() called");
-        let output: std::process::Output =
-            Commands::new_command_with_args("git", &["status", "--porcelain"])
-                .output()
-                .expect("git command should execute");
+        let output: std::process::Output = Commands::output_with_timeout(
+            &mut Commands::new_command_with_args("git", &["status", "--porcelain"]),
+        )
+        .expect("git command should execute");
 
         if output.stdout.is_empty() {
             Ok(GitCommandResult::Success)
@@ -192,20 +672,147 @@ impl GitCommand<'_> {
                 self.construct_git_command_string()
             ));
             Ok(GitCommandResult::Success)
-        } else if self.construct_git_command().status()?.success() {
+        } else if OutputTarget::is_paging() {
+            // the output may have to go through the pager, so it can't be written straight to `stdout`
+            if Commands::stream_to(
+                &mut self.construct_git_command(),
+                None,
+                &mut OutputTarget::writer()?,
+            )?
+            .success()
+            {
+                Ok(GitCommandResult::Success)
+            } else {
+                Ok(GitCommandResult::Error)
+            }
+        } else if Commands::status_with_timeout(
+            self.construct_git_command().stdout(OutputTarget::stdio()?),
+        )?
+        .success()
+        {
+            Ok(GitCommandResult::Success)
+        } else {
+            Ok(GitCommandResult::Error)
+        }
+    }
+
+    /// Same as `run()`, but pipes the output through the diff highlighter (`git-util.tools.diff`) when it's installed
+    /// and the output is a terminal.
+    ///
+    /// `git SUBCOMMAND ARGS | HIGHLIGHTER`
+    fn run_with_diff_highlighter(&self) -> GitResult {
+        trace!("run_with_diff_highlighter() called with: {:#?}", self);
+
+        let highlighter = match Settings::get().diff_program() {
+            Some(program)
+                if OutputTarget::is_terminal()
+                    && !DRY_RUN.load(std::sync::atomic::Ordering::SeqCst)
+                    && Commands::is_available(program) =>
+            {
+                program
+            }
+            _ => return self.run(),
+        };
+
+        let status = if OutputTarget::is_paging() {
+            Commands::stream_to(
+                &mut self.construct_git_command(),
+                Some(highlighter),
+                &mut OutputTarget::writer()?,
+            )?
+        } else {
+            Commands::pipe_to(&mut self.construct_git_command(), highlighter, &[])?
+        };
+
+        if status.success() {
             Ok(GitCommandResult::Success)
         } else {
             Ok(GitCommandResult::Error)
         }
     }
 
+    /// Same as `run()`, but shows a spinner labelled `label` instead of the command's output while it runs.
+    ///
+    /// The captured output is only printed if the command fails. Falls back to `run()` if the spinner can't be shown.
+    fn run_with_progress(&self, label: &str) -> GitResult {
+        trace!("run_with_progress() called with: {:#?}, {label}", self);
+
+        if DRY_RUN.load(std::sync::atomic::Ordering::SeqCst) || !Spinner::is_enabled() {
+            return self.run();
+        }
+
+        let spinner = Spinner::start(label);
+        let output = Commands::output_with_timeout(&mut Commands::new_command_with_args(
+            "git",
+            &self.parse_command_args_with_color(Colors::stdout()),
+        ))
+        .with_context(|| format!("Failed to execute 'git {}' command", self.subcommand))?;
+        spinner.finish(output.status.success());
+
+        if output.status.success() {
+            Ok(GitCommandResult::Success)
+        } else {
+            std::io::stdout().write_all(&output.stdout)?;
+            std::io::stderr().write_all(&output.stderr)?;
+            Ok(GitCommandResult::Error)
+        }
+    }
+
+    /// Execute the **Git Subcommand** represented by `self` and capture its output.
+    ///
+    /// Color is never forced and `DRY_RUN` is ignored, so this should only be used for read-only queries.
+    fn output(&self) -> Result<Output> {
+        trace!("output() called with: {:#?}", self);
+
+        Commands::output_with_timeout(&mut Commands::new_command_with_args(
+            "git",
+            &self.parse_command_args_with_color(false),
+        ))
+        .with_context(|| format!("Failed to execute 'git {}' command", self.subcommand))
+    }
+
+    /// Same as `output()`, but with `input` written to the command's `stdin`.
+    fn output_with_input(&self, input: &[u8]) -> Result<Output> {
+        trace!("output_with_input() called with: {:#?}", self);
+
+        Commands::output_with_input(
+            &mut Commands::new_command_with_args("git", &self.parse_command_args_with_color(false)),
+            input,
+        )
+        .with_context(|| format!("Failed to execute 'git {}' command", self.subcommand))
+    }
+
+    /// Execute the **Git Subcommand** represented by `self` and return its trimmed stdout.
+    ///
+    /// Fails with the contents of stderr if `git` exits with a non-zero status.
+    fn stdout(&self) -> Result<String> {
+        let output = self.output()?;
+
+        if output.status.success() {
+            Ok(String::from_utf8(output.stdout)?.trim_end().to_string())
+        } else {
+            Err(anyhow!(
+                "'git {}' failed: {}",
+                self.subcommand,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
     /// Construct a `std::process:Command` that calls `git` using the **Git Subcommand** represented by `self`.
     fn construct_git_command_string(&self) -> String {
         trace!("construct_git_command() called with: {:#?}", self);
 
-        let command_args = self.parse_command_args();
+        let mut command_args: Vec<&str> = Vec::new();
+
+        for config_override in Git::config_overrides() {
+            command_args.push("-c");
+            command_args.push(config_override);
+        }
 
-        format!("git {} {}", self.subcommand, command_args.join(" "))
+        command_args.extend(self.parse_command_args());
+
+        format!("git {}", command_args.join(" "))
     }
 
     /// Construct a `std::process:Command` that calls `git` using the **Git Subcommand** represented by `self`.
@@ -214,17 +821,43 @@ impl GitCommand<'_> {
 
         let command_args = self.parse_command_args();
 
-        Commands::new_command_with_args("git", &command_args)
+        let mut command = Commands::new_command_with_args("git", &command_args);
+
+        if let Some(pager) = &Settings::get().pager {
+            command.env(String::from(env_vars::GitEnvVars::Pager), pager);
+        }
+
+        command
     }
 
     fn parse_command_args(&self) -> Vec<&str> {
+        self.parse_command_args_with_color(Colors::stdout())
+    }
+
+    fn parse_command_args_with_color(&self, color: bool) -> Vec<&str> {
         trace!("parse_command_args() called with: {:#?}", self);
 
-        let mut command_args: Vec<&str> = if stdout().is_terminal() {
-            vec!["-c", "color.ui=always", self.subcommand]
+        let mut command_args: Vec<&str> = Vec::new();
+
+        if Settings::get().no_pager || OutputTarget::paging() == Some(Paging::Never) {
+            command_args.push("--no-pager");
+        }
+
+        // colors are decided by `Colors`, not by git's own detection (which ignores `NO_COLOR`)
+        command_args.push("-c");
+        command_args.push(if color {
+            "color.ui=always"
         } else {
-            vec![self.subcommand]
-        };
+            "color.ui=never"
+        });
+
+        command_args.push(self.subcommand);
+
+        if QUIET.load(std::sync::atomic::Ordering::SeqCst)
+            && QUIET_SUBCOMMANDS.contains(&self.subcommand)
+        {
+            command_args.push("--quiet");
+        }
 
         if !self.default_args.is_empty() {
             self.default_args
@@ -241,3 +874,72 @@ impl GitCommand<'_> {
         command_args
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_date_range_expands_shorthand_durations() {
+        assert_eq!(LogDateRange::expand("2h"), "2 hours ago");
+        assert_eq!(LogDateRange::expand("3d"), "3 days ago");
+        assert_eq!(LogDateRange::expand("10w"), "10 weeks ago");
+        assert_eq!(LogDateRange::expand("6mo"), "6 months ago");
+        assert_eq!(LogDateRange::expand("1y"), "1 years ago");
+    }
+
+    #[test]
+    fn log_date_range_passes_other_dates_through() {
+        for date in [
+            "yesterday",
+            "2024-01-01",
+            "d",
+            "mo",
+            "1.5d",
+            "-2d",
+            "2 days ago",
+        ] {
+            assert_eq!(LogDateRange::expand(date), date);
+        }
+    }
+
+    #[test]
+    fn log_date_range_args() {
+        let range = LogDateRange {
+            since: Some("2w".to_string()),
+            until: Some("2024-01-01".to_string()),
+        };
+
+        assert_eq!(range.args(), ["--since=2 weeks ago", "--until=2024-01-01"]);
+        assert!(LogDateRange::default().args().is_empty());
+    }
+
+    #[test]
+    fn date_style_from_setting() {
+        assert_eq!(
+            DateStyle::from_setting("key", "ISO").unwrap(),
+            DateStyle::Iso
+        );
+        assert_eq!(
+            DateStyle::from_setting("git-util.dateStyle", "long")
+                .unwrap_err()
+                .to_string(),
+            "Invalid value for git-util.dateStyle: \"long\". Expected one of: relative, short, iso"
+        );
+    }
+
+    #[test]
+    fn rename_detection_args() {
+        let detection = |find_renames, find_copies| RenameDetection {
+            find_renames,
+            find_copies,
+        };
+
+        assert!(detection(None, false).args().is_empty());
+        assert_eq!(detection(Some(None), false).args(), ["--find-renames"]);
+        assert_eq!(
+            detection(Some(Some(80)), true).args(),
+            ["--find-renames=80%", "--find-copies"]
+        );
+    }
+}
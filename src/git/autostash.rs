@@ -0,0 +1,112 @@
+use crate::{
+    git::{Git, GitCommand, GitCommandResult},
+    print::Print,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+
+/// The message of the stash entries made by `AutoStash`.
+const MESSAGE: &str = "git-util autostash";
+
+/// Stashes uncommitted changes when created, and restores them when dropped.
+///
+/// Because the changes are restored in `Drop`, they are restored even if the operation in between returns early with
+/// an error. The stash commit is remembered, so that it's the one restored even if other entries are pushed onto the
+/// stash in the meantime.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AutoStash {
+    stash: Option<String>,
+}
+
+impl AutoStash {
+    /// Stash any uncommitted changes to tracked files.
+    ///
+    /// `git stash create 'git-util autostash' && git stash store --message 'git-util autostash' SHA && git reset --hard`
+    pub fn new() -> Result<AutoStash> {
+        trace!("AutoStash::new() called");
+
+        if let GitCommandResult::Success = Git::verify_clean_work_tree()? {
+            debug!("work tree is clean; nothing to autostash");
+            return Ok(AutoStash { stash: None });
+        }
+
+        let stash = GitCommand::new("stash")
+            .with_default_args(&["create", MESSAGE])
+            .stdout()?;
+        if stash.is_empty() {
+            return Ok(AutoStash { stash: None });
+        }
+
+        if GitCommand::new("stash")
+            .with_default_args(&["store", "--message", MESSAGE, &stash])
+            .run()?
+            == GitCommandResult::Error
+        {
+            return Err(anyhow!("Failed to stash uncommitted changes"));
+        }
+
+        // from here on, the changes are restored when the guard is dropped
+        let autostash = AutoStash { stash: Some(stash) };
+
+        match GitCommand::new("reset")
+            .with_default_args(&["--hard", "--quiet"])
+            .run()?
+        {
+            GitCommandResult::Success => Ok(autostash),
+            GitCommandResult::Error => Err(anyhow!("Failed to stash uncommitted changes")),
+        }
+    }
+
+    /// The stash entry (e.g. `stash@{1}`) for the commit `stash`, or `None` if it's no longer on the stash.
+    fn entry(stash: &str) -> Result<Option<String>> {
+        Ok(GitCommand::new("stash")
+            .with_default_args(&["list", "--format=%H %gd"])
+            .stdout()?
+            .lines()
+            .find_map(|line| match line.split_once(' ') {
+                Some((sha, entry)) if sha == stash => Some(entry.to_string()),
+                _ => None,
+            }))
+    }
+}
+
+impl Drop for AutoStash {
+    /// `git stash apply SHA && git stash drop ENTRY`
+    fn drop(&mut self) {
+        trace!("AutoStash::drop() called");
+
+        let Some(stash) = &self.stash else {
+            return;
+        };
+
+        match GitCommand::new("stash")
+            .with_default_args(&["apply", stash])
+            .run()
+        {
+            Ok(GitCommandResult::Success) => debug!("restored autostashed changes"),
+            _ => {
+                Print::error(&format!(
+                    "Failed to restore autostashed changes; run `git stash apply {stash}` to restore them"
+                ));
+                return;
+            }
+        }
+
+        match AutoStash::entry(stash) {
+            Ok(Some(entry)) => {
+                if !matches!(
+                    GitCommand::new("stash")
+                        .with_default_args(&["drop", "--quiet", &entry])
+                        .run(),
+                    Ok(GitCommandResult::Success)
+                ) {
+                    Print::error(&format!(
+                        "Restored the autostashed changes, but failed to drop {entry}"
+                    ));
+                }
+            }
+            Ok(None) => debug!("{stash} is no longer on the stash"),
+            Err(e) => debug!("failed to find {stash} on the stash: {e}"),
+        }
+    }
+}
@@ -1,2 +1,14 @@
+pub mod alias;
+pub mod attrs;
+pub mod changelog;
+pub mod commit_picker;
+pub mod completions;
+pub mod doctor;
 pub mod immutable;
+pub mod include;
+pub mod lfs;
+pub mod log_browser;
 pub mod mutable;
+pub mod profile;
+pub mod self_update;
+pub mod stage_browser;
@@ -0,0 +1,472 @@
+use crate::{
+    git::{GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    output::OutputTarget,
+    print::Print,
+    toml::{self, Document},
+};
+use anyhow::{anyhow, Context};
+use log::{debug, trace};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::Write,
+    path::Path,
+    sync::atomic::Ordering,
+};
+
+/// The subcommands that run a fixed **Git** command, and the command they run, for `alias audit`.
+///
+/// Commands joined with `&&` run one after the other, like in a shell alias.
+const SUBCOMMANDS: [(&str, &str); 35] = [
+    ("aa", "add --all"),
+    ("aac", "add --all && commit"),
+    ("aamend", "add --all && commit --amend"),
+    ("ab", "rev-list --left-right --count"),
+    ("abort", "cherry-pick --abort"),
+    ("abort", "merge --abort"),
+    ("abort", "rebase --abort"),
+    ("abort", "revert --abort"),
+    ("alias", "config --get-regexp alias"),
+    ("assume", "update-index --assume-unchanged"),
+    ("au", "add --update"),
+    ("auc", "commit --all"),
+    ("aumend", "commit --all --amend"),
+    ("changed", "diff --name-status"),
+    ("cm", "commit --message"),
+    ("conf", "config --list"),
+    ("conflicts", "diff --name-only --diff-filter=U"),
+    ("continue", "cherry-pick --continue"),
+    ("continue", "merge --continue"),
+    ("continue", "rebase --continue"),
+    ("continue", "revert --continue"),
+    ("dig", "log -S"),
+    ("find", "log --all --grep"),
+    ("fixup", "commit --fixup"),
+    ("l", "log --oneline"),
+    ("last", "log --compact-summary"),
+    ("ours", "checkout --ours"),
+    ("pick", "cherry-pick"),
+    ("publish", "push --set-upstream origin HEAD"),
+    ("resolve", "mergetool"),
+    ("skip", "update-index --skip-worktree"),
+    ("stats", "shortlog --summary --numbered"),
+    ("theirs", "checkout --theirs"),
+    ("unstage", "restore --staged"),
+    ("unshallow", "fetch --unshallow"),
+];
+
+/// Short options and synonyms, and the long option they're normalized to, by **Git** command.
+///
+/// Options that format a log the same way are normalized to `--pretty`, so that e.g. `log --oneline` is similar to
+/// `log --graph --pretty=format:...`.
+const OPTION_SYNONYMS: [(&str, &str, &str); 19] = [
+    ("add", "-A", "--all"),
+    ("add", "-u", "--update"),
+    ("add", "-p", "--patch"),
+    ("branch", "-u", "--set-upstream-to"),
+    ("commit", "-a", "--all"),
+    ("commit", "-m", "--message"),
+    ("config", "-l", "--list"),
+    ("diff", "--staged", "--cached"),
+    ("log", "--format", "--pretty"),
+    ("log", "--oneline", "--pretty"),
+    ("log", "-G", "-S"),
+    ("push", "-u", "--set-upstream"),
+    ("push", "-d", "--delete"),
+    ("restore", "-S", "--staged"),
+    ("shortlog", "-s", "--summary"),
+    ("shortlog", "-n", "--numbered"),
+    ("shortlog", "-e", "--email"),
+    ("status", "-s", "--short"),
+    ("status", "-b", "--branch"),
+];
+
+/// Exports and imports **Git** aliases, to sync them between machines.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Alias();
+
+impl Alias {
+    /// Write every alias to `file` (else `stdout`) as a TOML document, with the aliases in an `[alias]` table.
+    ///
+    /// The aliases are read from every config file, so an alias that's defined more than once has the value that
+    /// `git` would use.
+    pub fn export(file: Option<&Path>) -> GitResult {
+        trace!("Alias::export() called with: {file:?}");
+
+        let aliases = Alias::aliases(&[])?;
+
+        let mut document =
+            String::from("# git aliases, exported by `git-util alias export`\n[alias]\n");
+        for (name, value) in &aliases {
+            document.push_str(&format!("{} = {}\n", toml::key(name), toml::quote(value)));
+        }
+
+        match file {
+            Some(file) if DRY_RUN.load(Ordering::SeqCst) => {
+                Print::stderr_purple(&format!(
+                    "would write {} aliases to {}",
+                    aliases.len(),
+                    file.display()
+                ));
+            }
+            Some(file) => {
+                fs::write(file, document)
+                    .with_context(|| format!("Failed to write {}", file.display()))?;
+                Print::info(&format!(
+                    "Exported {} aliases to {}",
+                    aliases.len(),
+                    file.display()
+                ));
+            }
+            None => write!(OutputTarget::writer()?, "{document}")?,
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Set the aliases in the `[alias]` table of the TOML document `file` in the global config.
+    ///
+    /// An alias that already has a different value in the global config is a conflict: it's only overwritten if `force`
+    /// is `true`. Fails (after importing the others) if any conflicts were left as they were.
+    pub fn import(file: &Path, force: bool) -> GitResult {
+        trace!("Alias::import() called with: {file:?}, {force}");
+
+        let contents = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        let document = Document::parse(&contents)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", file.display(), e))?;
+
+        let table = document
+            .table("alias")
+            .ok_or_else(|| anyhow!("{} has no [alias] table", file.display()))?;
+
+        let mut imported = BTreeMap::new();
+        for (name, value) in table {
+            let value = value.as_str().ok_or_else(|| {
+                anyhow!(
+                    "The alias '{}' in {} must be a string, but it's {}",
+                    name,
+                    file.display(),
+                    value.type_name()
+                )
+            })?;
+            imported.insert(name.as_str(), value);
+        }
+
+        let existing = Alias::aliases(&["--global"])?;
+        let mut writer = OutputTarget::writer()?;
+        let (mut added, mut unchanged, mut overwritten, mut conflicts) = (0, 0, 0, 0);
+
+        for (name, value) in imported {
+            // alias names are case-insensitive, and `git config` prints them in lowercase
+            match existing.get(&name.to_lowercase()) {
+                Some(current) if current == value => {
+                    unchanged += 1;
+                    continue;
+                }
+                Some(current) if !force => {
+                    writeln!(
+                        writer,
+                        "conflict: {name}\n    global:   {current}\n    imported: {value}"
+                    )?;
+                    conflicts += 1;
+                    continue;
+                }
+                Some(_) => {
+                    writeln!(writer, "overwrote: {name} = {value}")?;
+                    overwritten += 1;
+                }
+                None => {
+                    writeln!(writer, "added: {name} = {value}")?;
+                    added += 1;
+                }
+            }
+
+            if GitCommand::new("config")
+                .with_default_args(&["--global", &format!("alias.{name}"), value])
+                .run()?
+                == GitCommandResult::Error
+            {
+                return Err(anyhow!("Failed to set the alias '{}'", name));
+            }
+        }
+
+        writeln!(
+            writer,
+            "{added} added, {overwritten} overwritten, {unchanged} unchanged, {conflicts} conflicts"
+        )?;
+
+        if conflicts > 0 {
+            Print::error(
+                "Some aliases conflict with the global config; use --force to overwrite them",
+            );
+            return Ok(GitCommandResult::Error);
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Compare every alias with the subcommands, reporting the aliases that do the same as a subcommand and the ones
+    /// that are similar to one (they run the same command with extra options, which most subcommands pass through).
+    ///
+    /// Both are normalized first: `!git` and short options are expanded, the order of options is ignored, and so are
+    /// arguments that aren't options (e.g. branch names).
+    pub fn audit() -> GitResult {
+        trace!("Alias::audit() called");
+
+        let subcommands: Vec<(&str, Vec<Invocation>)> = SUBCOMMANDS
+            .iter()
+            .map(|(subcommand, command)| (*subcommand, normalize(command).unwrap_or_default()))
+            .collect();
+
+        let mut duplicates = Vec::new();
+        let mut similar = Vec::new();
+        let mut unmatched = 0;
+
+        for (name, value) in Alias::aliases(&[])? {
+            let Some(alias) = normalize(&value) else {
+                debug!("not a git command: {name}");
+                unmatched += 1;
+                continue;
+            };
+
+            if let Some((subcommand, _)) = subcommands.iter().find(|(_, command)| *command == alias)
+            {
+                duplicates.push((name, value, subcommand.to_string(), String::new()));
+            } else if let Some((subcommand, command)) = subcommands
+                .iter()
+                .find(|(_, command)| is_similar(command, &alias))
+            {
+                let extra = alias
+                    .iter()
+                    .zip(command)
+                    .flat_map(|(alias, command)| alias.options.difference(&command.options))
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                similar.push((name, value, subcommand.to_string(), extra));
+            } else {
+                unmatched += 1;
+            }
+        }
+
+        let mut writer = OutputTarget::writer()?;
+        let width = duplicates
+            .iter()
+            .chain(&similar)
+            .map(|(name, ..)| name.len())
+            .max()
+            .unwrap_or(0);
+
+        for (heading, aliases) in [
+            ("Aliases that duplicate a subcommand:", &duplicates),
+            (
+                "Aliases that could be migrated to a similar subcommand:",
+                &similar,
+            ),
+        ] {
+            if aliases.is_empty() {
+                continue;
+            }
+
+            writeln!(writer, "{heading}")?;
+            for (name, value, subcommand, extra) in aliases {
+                // multi-line aliases are shown on one line
+                let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+                write!(writer, "  {name:width$}  git-util {subcommand}")?;
+                if !extra.is_empty() {
+                    write!(writer, " {extra}")?;
+                }
+                writeln!(writer, "  # {value}")?;
+            }
+            writeln!(writer)?;
+        }
+
+        writeln!(
+            writer,
+            "{} duplicate, {} similar, {} without an equivalent subcommand",
+            duplicates.len(),
+            similar.len(),
+            unmatched
+        )?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// The aliases in the config files selected by `scope` (e.g. `--global`), by name.
+    ///
+    /// `git config -z SCOPE --get-regexp ^alias\.`
+    fn aliases(scope: &[&str]) -> anyhow::Result<BTreeMap<String, String>> {
+        let mut args = vec!["-z"];
+        args.extend(scope);
+        args.extend(["--get-regexp", r"^alias\."]);
+
+        let output = GitCommand::new("config")
+            .with_default_args(&args)
+            .output()?;
+
+        // `git config --get-regexp` exits with 1 if nothing matches
+        if !output.status.success() && output.status.code() != Some(1) {
+            return Err(anyhow!(
+                "Failed to read the aliases: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        // each entry is `alias.NAME\nVALUE\0`, so values can have newlines
+        let aliases: BTreeMap<String, String> = String::from_utf8(output.stdout)?
+            .split('\0')
+            .filter_map(|entry| entry.strip_prefix("alias."))
+            .map(|entry| {
+                let (name, value) = entry.split_once('\n').unwrap_or((entry, ""));
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+
+        debug!("aliases: {aliases:#?}");
+
+        Ok(aliases)
+    }
+}
+
+/// A **Git** command, normalized for comparing aliases: its name, and the names of its options.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+struct Invocation {
+    command: String,
+    options: BTreeSet<String>,
+}
+
+/// The **Git** commands that an alias (or a `SUBCOMMANDS` entry) runs, normalized; `None` if it runs anything else,
+/// e.g. a shell alias that runs another program or defines a function.
+fn normalize(alias: &str) -> Option<Vec<Invocation>> {
+    let (shell, alias) = match alias.trim().strip_prefix('!') {
+        Some(alias) => (true, alias),
+        None => (false, alias),
+    };
+
+    alias
+        .split("&&")
+        .map(|command| {
+            let mut words = command
+                .split_whitespace()
+                .map(|word| word.trim_matches(['"', '\'']));
+
+            // a shell alias has to call `git` itself
+            if shell && words.next() != Some("git") {
+                return None;
+            }
+
+            let command = words.next()?.to_string();
+            let mut options = BTreeSet::new();
+
+            // the rest are paths
+            for word in words.take_while(|&word| word != "--") {
+                if word.starts_with("--") {
+                    // `--pretty=format:%h` is the option `--pretty`
+                    let option = word.split_once('=').map_or(word, |(option, _)| option);
+                    options.insert(synonym(&command, option));
+                } else if word.starts_with('-') && word.len() > 1 {
+                    // `-sn` is `-s -n`; numbers (e.g. `-10`) are arguments
+                    if word[1..].chars().all(|c| c.is_ascii_digit()) {
+                        continue;
+                    }
+                    let short = if command == "log" && word[1..].starts_with(['S', 'G']) {
+                        // `-Sstring` is a single option with an argument
+                        vec![word[..2].to_string()]
+                    } else {
+                        word[1..].chars().map(|c| format!("-{c}")).collect()
+                    };
+                    for option in short {
+                        options.insert(synonym(&command, &option));
+                    }
+                }
+            }
+
+            Some(Invocation { command, options })
+        })
+        .collect()
+}
+
+/// `option` of `command`, or the long option it's a synonym of.
+fn synonym(command: &str, option: &str) -> String {
+    OPTION_SYNONYMS
+        .iter()
+        .find(|(c, synonym, _)| *c == command && *synonym == option)
+        .map_or(option, |(_, _, long)| long)
+        .to_string()
+}
+
+/// Whether `alias` runs the same commands as `subcommand`, with at least the same options.
+fn is_similar(subcommand: &[Invocation], alias: &[Invocation]) -> bool {
+    subcommand.len() == alias.len()
+        && subcommand.iter().zip(alias).all(|(subcommand, alias)| {
+            subcommand.command == alias.command && subcommand.options.is_subset(&alias.options)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invocation(command: &str, options: &[&str]) -> Invocation {
+        Invocation {
+            command: command.to_string(),
+            options: options.iter().map(|option| option.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn normalize_options_and_synonyms() {
+        assert_eq!(
+            normalize("log --oneline -10 -Sfoo --graph -- path"),
+            Some(vec![invocation("log", &["--graph", "--pretty", "-S"])])
+        );
+        assert_eq!(
+            normalize("shortlog -sne"),
+            Some(vec![invocation(
+                "shortlog",
+                &["--email", "--numbered", "--summary"]
+            )])
+        );
+        assert_eq!(
+            normalize("log --pretty=format:'%h %s'"),
+            Some(vec![invocation("log", &["--pretty"])])
+        );
+    }
+
+    #[test]
+    fn normalize_shell_aliases() {
+        assert_eq!(
+            normalize("!git add -A && git commit -m"),
+            Some(vec![
+                invocation("add", &["--all"]),
+                invocation("commit", &["--message"])
+            ])
+        );
+        assert_eq!(normalize("!f() { git log; }; f"), None);
+        assert_eq!(normalize("!git add -A && echo done"), None);
+        assert_eq!(normalize(""), None);
+    }
+
+    #[test]
+    fn is_similar_needs_the_same_commands_and_at_least_the_same_options() {
+        let subcommand = normalize("add --all && commit").unwrap();
+
+        assert!(is_similar(
+            &subcommand,
+            &normalize("!git add -A && git commit -v").unwrap()
+        ));
+        assert!(!is_similar(
+            &subcommand,
+            &normalize("!git add -u && git commit").unwrap()
+        ));
+        assert!(!is_similar(&subcommand, &normalize("add -A").unwrap()));
+    }
+
+    #[test]
+    fn every_subcommand_normalizes() {
+        for (name, command) in SUBCOMMANDS {
+            assert!(normalize(command).is_some(), "normalizing {name}");
+        }
+    }
+}
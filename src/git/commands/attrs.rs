@@ -0,0 +1,133 @@
+use crate::{
+    git::{Git, GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    output::OutputTarget,
+    print::Print,
+    table::Table,
+};
+use anyhow::{anyhow, Context};
+use log::{debug, trace};
+use std::{fs, io::Write, sync::atomic::Ordering};
+
+/// Shows and edits the attributes in `.gitattributes`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Attrs();
+
+impl Attrs {
+    /// Print the attributes that apply to each of `paths`, from every `.gitattributes` file and `info/attributes`.
+    ///
+    /// `git check-attr --all -z -- PATHS`
+    pub fn show(paths: &[String]) -> GitResult {
+        trace!("Attrs::show() called with: {paths:#?}");
+
+        let output = GitCommand::new("check-attr")
+            .with_default_args(&["--all", "-z", "--"])
+            .with_user_args(paths)
+            .stdout()?;
+
+        // `PATH\0ATTRIBUTE\0VALUE\0` for each attribute that's set on a path
+        let fields: Vec<&str> = output.split('\0').collect();
+
+        let mut table = Table::new();
+        table.add_row(["Path", "Attribute", "Value"]);
+
+        for attribute in fields.chunks_exact(3) {
+            let value = match attribute[2] {
+                "set" => "set".to_string(),
+                "unset" => format!("unset (-{})", attribute[1]),
+                "unspecified" => format!("unspecified (!{})", attribute[1]),
+                value => value.to_string(),
+            };
+
+            table.add_row([attribute[0], attribute[1], &value]);
+        }
+
+        let mut writer = OutputTarget::writer()?;
+
+        for path in paths {
+            if !fields.chunks_exact(3).any(|attribute| attribute[0] == path) {
+                writeln!(writer, "No attributes apply to {path}")?;
+            }
+        }
+
+        // otherwise the table only has the header
+        if fields.len() >= 3 {
+            write!(writer, "{table}")?;
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Set `attributes` (e.g. `-diff` or `eol=lf`) for `pattern` in the root `.gitattributes`, then stage it.
+    ///
+    /// If there's already a line for `pattern`, the attributes are merged into it: each one replaces any setting of the
+    /// same attribute, so that the line never has duplicates. Otherwise a new line is appended.
+    pub fn set(pattern: &str, attributes: &[String]) -> GitResult {
+        trace!("Attrs::set() called with: {pattern}, {attributes:#?}");
+
+        if let Some(attribute) = attributes.iter().find(|attribute| {
+            attribute_name(attribute).is_empty() || attribute.contains(char::is_whitespace)
+        }) {
+            return Err(anyhow!("'{}' is not a valid attribute", attribute));
+        }
+
+        let path = Git::repo_root()?.join(".gitattributes");
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+
+        let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+        let existing = lines
+            .iter()
+            .position(|line| line.split_whitespace().next() == Some(pattern));
+
+        let line = match existing {
+            Some(index) => &mut lines[index],
+            None => {
+                lines.push(pattern.to_string());
+                lines.last_mut().expect("a line was just pushed")
+            }
+        };
+
+        let mut merged: Vec<String> = line.split_whitespace().skip(1).map(String::from).collect();
+
+        for attribute in attributes {
+            merged.retain(|existing| attribute_name(existing) != attribute_name(attribute));
+            merged.push(attribute.clone());
+        }
+
+        let updated = format!("{pattern} {}", merged.join(" "));
+        debug!("'{line}' -> '{updated}'");
+
+        if existing.is_some() && line.split_whitespace().eq(updated.split_whitespace()) {
+            Print::info(&format!(
+                "{pattern} already has those attributes in .gitattributes"
+            ));
+            return Ok(GitCommandResult::Success);
+        }
+
+        *line = updated;
+
+        if DRY_RUN.load(Ordering::SeqCst) {
+            Print::stderr_purple(&format!(
+                "would set in {}: {}",
+                path.display(),
+                lines[existing.unwrap_or(lines.len() - 1)]
+            ));
+        } else {
+            fs::write(&path, format!("{}\n", lines.join("\n")))
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        GitCommand::new("add")
+            .with_default_args(&[&path.to_string_lossy()])
+            .run()
+    }
+}
+
+/// The name of an attribute setting, e.g. `diff` for `-diff`, `!diff`, or `diff=lfs`.
+fn attribute_name(attribute: &str) -> &str {
+    let attribute = attribute.trim_start_matches(['-', '!']);
+
+    match attribute.split_once('=') {
+        Some((name, _)) => name,
+        None => attribute,
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    git::{forge::ForgeRepo, Git, GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    output::OutputTarget,
+    print::Print,
+};
+use anyhow::{Context, Result};
+use log::{debug, trace};
+use regex::Regex;
+use std::{collections::BTreeMap, fs, io::Write, sync::atomic::Ordering};
+
+/// The Conventional Commit types, in the order that their sections appear in the changelog.
+const SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance Improvements"),
+    ("revert", "Reverts"),
+    ("refactor", "Code Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build System"),
+    ("ci", "Continuous Integration"),
+    ("style", "Styles"),
+    ("chore", "Chores"),
+];
+
+/// The section for commits that don't follow the Conventional Commits format (or have an unknown type).
+const OTHER_SECTION: &str = "Other Changes";
+
+/// The section for commits marked as breaking with `!` (e.g. `feat!: ...`), which comes before all of the others.
+const BREAKING_SECTION: &str = "Breaking Changes";
+
+/// A Conventional Commit subject: `TYPE[(SCOPE)][!]: DESCRIPTION`.
+const CONVENTIONAL_COMMIT: &str = r"^(\w+)(?:\(([^)]*)\))?(!)?:\s*(.+)$";
+
+/// The file that `Changelog::run` prepends to, in the root of the repository.
+const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// Generates Markdown changelogs from Conventional Commit messages.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Changelog();
+
+impl Changelog {
+    /// Print the changelog for `range` (else defaults to the commits since the latest tag), or prepend it to
+    /// `CHANGELOG.md` if `write` is `true`.
+    ///
+    /// `range` is either `FROM..TO` or `FROM` (i.e. `FROM..HEAD`).
+    pub fn run(range: Option<&str>, write: bool) -> GitResult {
+        trace!("Changelog::run() called with: {range:?}, {write}");
+
+        let (from, to) = match range {
+            Some(range) => match range.split_once("..") {
+                Some((from, to)) => (
+                    Some(from.to_string()),
+                    if to.is_empty() { "HEAD" } else { to }.to_string(),
+                ),
+                None => (Some(range.to_string()), "HEAD".to_string()),
+            },
+            None => (Git::latest_tag("HEAD")?, "HEAD".to_string()),
+        };
+
+        let heading = if to == "HEAD" {
+            "Unreleased".to_string()
+        } else {
+            let date = GitCommand::new("log")
+                .with_default_args(&["--max-count=1", "--format=%as", &to])
+                .stdout()?;
+            format!("{to} ({date})")
+        };
+
+        let markdown = Changelog::markdown(from.as_deref(), &to, &heading)?;
+
+        if !write {
+            write!(OutputTarget::writer()?, "{markdown}")?;
+            return Ok(GitCommandResult::Success);
+        }
+
+        let path = Git::repo_root()?.join(CHANGELOG_FILE);
+
+        if DRY_RUN.load(Ordering::SeqCst) {
+            Print::stderr_purple(&format!(
+                "would prepend to {}:\n\n{markdown}",
+                path.display()
+            ));
+            return Ok(GitCommandResult::Success);
+        }
+
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+
+        // keep a top-level title (e.g. `# Changelog`) at the top of the file
+        let contents = match existing.split_once('\n') {
+            Some((title, rest)) if title.starts_with("# ") => {
+                format!("{title}\n\n{markdown}\n{}", rest.trim_start_matches('\n'))
+            }
+            _ if existing.is_empty() => format!("# Changelog\n\n{markdown}"),
+            _ => format!("{markdown}\n{existing}"),
+        };
+
+        fs::write(&path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Print::info(&format!("Updated {}", path.display()));
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Render the commits after `from` (else the whole history) up to `to` as a Markdown section titled `heading`, with
+    /// the commits grouped by their Conventional Commit type.
+    ///
+    /// Merge commits are skipped. If `origin` is hosted on a known forge, each commit links to its web page.
+    pub fn markdown(from: Option<&str>, to: &str, heading: &str) -> Result<String> {
+        trace!("Changelog::markdown() called with: {from:?}, {to}, {heading}");
+
+        let range = match from {
+            Some(from) => format!("{from}..{to}"),
+            None => to.to_string(),
+        };
+
+        let log = GitCommand::new("log")
+            .with_default_args(&["--no-merges", "--format=%H%x00%h%x00%s", &range])
+            .stdout()?;
+
+        // a missing `origin` just means that the commits aren't linked
+        let forge = ForgeRepo::from_remote("origin").ok().flatten();
+        let conventional = Regex::new(CONVENTIONAL_COMMIT)?;
+
+        let mut sections: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+
+        for line in log.lines() {
+            let mut fields = line.splitn(3, '\0');
+            let (Some(hash), Some(short_hash), Some(subject)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+
+            let link = match forge.as_ref().and_then(|forge| forge.commit_url(hash)) {
+                Some(url) => format!("[{short_hash}]({url})"),
+                None => short_hash.to_string(),
+            };
+
+            let (section, entry) = Changelog::entry(&conventional, subject);
+
+            sections
+                .entry(section)
+                .or_default()
+                .push(format!("- {entry} ({link})"));
+        }
+
+        debug!("changelog sections: {sections:#?}");
+
+        let mut markdown = format!("## {heading}\n");
+
+        if sections.is_empty() {
+            markdown.push_str("\nNo changes.\n");
+        }
+
+        for (section, entries) in sections {
+            let title = match section {
+                0 => BREAKING_SECTION,
+                section if section <= SECTIONS.len() => SECTIONS[section - 1].1,
+                _ => OTHER_SECTION,
+            };
+
+            markdown.push_str(&format!("\n### {title}\n\n{}\n", entries.join("\n")));
+        }
+
+        Ok(markdown)
+    }
+
+    /// The section of the changelog for the commit with `subject`, and its entry (without the link).
+    ///
+    /// Sections are numbered by their position: breaking changes first, then `SECTIONS`, then everything else.
+    fn entry(conventional: &Regex, subject: &str) -> (usize, String) {
+        let Some(captures) = conventional.captures(subject) else {
+            return (SECTIONS.len() + 1, subject.to_string());
+        };
+
+        let kind = captures[1].to_lowercase();
+        let description = match captures.get(2) {
+            Some(scope) if !scope.as_str().is_empty() => {
+                format!("**{}:** {}", scope.as_str(), &captures[4])
+            }
+            _ => captures[4].to_string(),
+        };
+
+        let section = if captures.get(3).is_some() {
+            0
+        } else {
+            SECTIONS
+                .iter()
+                .position(|(name, _)| *name == kind)
+                .map_or(SECTIONS.len() + 1, |position| position + 1)
+        };
+
+        (section, description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(subject: &str) -> (usize, String) {
+        Changelog::entry(&Regex::new(CONVENTIONAL_COMMIT).unwrap(), subject)
+    }
+
+    #[test]
+    fn entry_sections_by_type() {
+        assert_eq!(entry("feat: add a thing"), (1, "add a thing".to_string()));
+        assert_eq!(entry("Fix: a bug"), (2, "a bug".to_string()));
+        assert_eq!(
+            entry("chore:tidy up"),
+            (SECTIONS.len(), "tidy up".to_string())
+        );
+    }
+
+    #[test]
+    fn entry_with_a_scope() {
+        assert_eq!(
+            entry("fix(parser): handle escapes"),
+            (2, "**parser:** handle escapes".to_string())
+        );
+        assert_eq!(entry("fix(): empty scope"), (2, "empty scope".to_string()));
+    }
+
+    #[test]
+    fn entry_breaking_changes_come_first() {
+        assert_eq!(entry("feat!: drop v1"), (0, "drop v1".to_string()));
+        assert_eq!(
+            entry("refactor(api)!: rename everything"),
+            (0, "**api:** rename everything".to_string())
+        );
+    }
+
+    #[test]
+    fn entry_other_changes() {
+        let other = SECTIONS.len() + 1;
+
+        assert_eq!(entry("unknown: type"), (other, "type".to_string()));
+        assert_eq!(
+            entry("Merge branch 'main'"),
+            (other, "Merge branch 'main'".to_string())
+        );
+        assert_eq!(entry("fix:"), (other, "fix:".to_string()));
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    git::{error::GitUtilError, GitCommand},
+    tui::picker::Picker,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+
+/// The number of recent commits to pick from.
+const MAX_COMMITS: &str = "--max-count=200";
+
+/// Picks a commit with the configured picker, for the subcommands whose commit argument is optional.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct CommitPicker();
+
+impl CommitPicker {
+    /// Pick one of the recent commits on `HEAD`, which are listed as `SHORT_SHA SUBJECT (RELATIVE_DATE)`.
+    ///
+    /// Returns the full SHA of the commit. Fails if the picker is cancelled.
+    pub fn pick(prompt: &str) -> Result<String> {
+        CommitPicker::pick_from(prompt, &[])
+    }
+
+    /// Same as `pick()`, but picks from the commits on other local branches that aren't on `HEAD`.
+    pub fn pick_from_other_branches(prompt: &str) -> Result<String> {
+        CommitPicker::pick_from(prompt, &["--branches", "--not", "HEAD"])
+    }
+
+    /// `git log --max-count=200 --format=%H%x00%h %s (%ar) REVISIONS`
+    fn pick_from(prompt: &str, revisions: &[&str]) -> Result<String> {
+        trace!("pick_from() called with: {prompt}, {revisions:?}");
+
+        let mut args = vec![MAX_COMMITS, "--format=%H%x00%h %s (%ar)"];
+        args.extend(revisions);
+
+        let log = GitCommand::new("log").with_default_args(&args).stdout()?;
+        let (shas, items): (Vec<&str>, Vec<String>) = log
+            .lines()
+            .filter_map(|line| line.split_once('\0'))
+            .map(|(sha, item)| (sha, item.to_string()))
+            .unzip();
+
+        if items.is_empty() {
+            return Err(anyhow!("There are no commits to pick from"));
+        }
+
+        match Picker::select(prompt, &items)? {
+            Some(index) => {
+                debug!("picked {}", items[index]);
+                Ok(shas[index].to_string())
+            }
+            None => Err(GitUtilError::Cancelled.into()),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{
+    git::{GitCommand, GitCommandResult, GitResult},
+    output::OutputTarget,
+};
+use anyhow::Result;
+use log::{debug, trace};
+use std::io::Write;
+
+/// The values that the shell completion scripts complete from the current repository.
+///
+/// They're printed one per line, optionally followed by a tab and a description.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, clap::ValueEnum)]
+pub enum Candidates {
+    /// The names of the **Git** aliases, which can be run in place of a subcommand.
+    Aliases,
+    /// Local and remote-tracking branches.
+    Branches,
+    /// Recent commits on the local branches, described by their subjects.
+    Commits,
+    /// The names of the remotes.
+    Remotes,
+}
+
+impl Candidates {
+    /// The candidates for an argument with the id `id` (e.g. `branch`), if it's completed from the repository.
+    pub fn for_argument(id: &str) -> Option<Candidates> {
+        match id {
+            "base" | "branch" | "head" | "left" | "reference" | "right" | "upstream" => {
+                Some(Candidates::Branches)
+            }
+            "commit" | "commits" => Some(Candidates::Commits),
+            "remote" | "remotes" => Some(Candidates::Remotes),
+            _ => None,
+        }
+    }
+
+    /// Print the candidates.
+    ///
+    /// Nothing is printed outside of a repository, since this is run by the completion scripts wherever the shell is.
+    pub fn print(&self) -> GitResult {
+        trace!("print() called with: {self:?}");
+
+        let candidates = self.list().unwrap_or_else(|error| {
+            debug!("no candidates: {error}");
+            Vec::new()
+        });
+
+        let mut writer = OutputTarget::writer()?;
+        for candidate in candidates {
+            writeln!(writer, "{candidate}")?;
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let output = match self {
+            Candidates::Aliases => GitCommand::new("config")
+                .with_default_args(&["--name-only", "--get-regexp", r"^alias\."])
+                .stdout()?,
+            Candidates::Branches => GitCommand::new("for-each-ref")
+                .with_default_args(&["--format=%(refname:short)", "refs/heads", "refs/remotes"])
+                .stdout()?,
+            Candidates::Commits => GitCommand::new("log")
+                .with_default_args(&["--branches", "--max-count=100", "--format=%h%x09%s"])
+                .stdout()?,
+            Candidates::Remotes => GitCommand::new("remote").stdout()?,
+        };
+
+        Ok(output
+            .lines()
+            .map(|line| line.strip_prefix("alias.").unwrap_or(line))
+            // `origin/HEAD` only points at another branch
+            .filter(|line| *self != Candidates::Branches || !line.ends_with("/HEAD"))
+            .map(String::from)
+            .collect())
+    }
+}
@@ -0,0 +1,529 @@
+use crate::{
+    color::Colors,
+    commands::Commands,
+    git::{Git, GitCommand, GitCommandResult, GitResult},
+    settings::{expand_home, Settings},
+    table::Table,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+use nu_ansi_term::Color;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The oldest **Git** version that supports everything **git-util** runs (e.g. `git config --show-scope`).
+const MIN_GIT_VERSION: (u32, u32) = (2, 26);
+
+/// The `Doctor` check of `user.signingkey` itself.
+const SIGNING_KEY: &str = "signing key";
+
+/// The `Doctor` check that `user.signingkey` is for `user.email`.
+const SIGNING_EMAIL: &str = "signing email";
+
+/// The outcome of a single `Doctor` check.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum CheckStatus {
+    Pass,
+    /// Something works, but not as well as it could.
+    Warn,
+    /// Something is broken.
+    Fail,
+}
+
+/// The result of a single `Doctor` check, with a suggested fix if it didn't pass.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Check {
+    name: &'static str,
+    status: CheckStatus,
+    message: String,
+    fix: Option<String>,
+}
+
+impl CheckStatus {
+    fn symbol(&self) -> String {
+        let (symbol, color) = match self {
+            CheckStatus::Pass => ("✔", Color::Green),
+            CheckStatus::Warn => ("!", Color::Yellow),
+            CheckStatus::Fail => ("✘", Color::Red),
+        };
+
+        Colors::paint(color, symbol, Colors::stdout()).to_string()
+    }
+}
+
+impl Check {
+    fn pass(name: &'static str, message: impl Into<String>) -> Check {
+        Check {
+            name,
+            status: CheckStatus::Pass,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Check {
+        Check {
+            name,
+            status: CheckStatus::Warn,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Check {
+        Check {
+            name,
+            status: CheckStatus::Fail,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Diagnoses problems with the environment that **git-util** runs in.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Doctor();
+
+impl Doctor {
+    /// Run every check and print the results, followed by the fixes for any that didn't pass.
+    ///
+    /// Returns `Error` if any check failed (warnings don't count).
+    pub fn run() -> GitResult {
+        trace!("Doctor::run() called");
+
+        let mut checks = vec![Doctor::git_version()];
+
+        // the external programs that `alias` and `conf` pipe through
+        let settings = Settings::get();
+        checks.push(Doctor::external_tool("filter", settings.filter_program()));
+        checks.push(Doctor::external_tool("sed", settings.sed_program()));
+        checks.push(Doctor::external_tool("column", settings.column_program()));
+
+        checks.push(Doctor::pre_commit_hook());
+        checks.push(Doctor::config_value_is_set("user.name"));
+        checks.push(Doctor::config_value_is_set("user.email"));
+        checks.extend(Doctor::signing());
+
+        debug!("checks: {checks:#?}");
+
+        let mut table = Table::new();
+        for check in &checks {
+            table.add_row([
+                check.status.symbol(),
+                check.name.to_string(),
+                check.message.clone(),
+            ]);
+        }
+        print!("{table}");
+
+        let fixes: Vec<String> = checks
+            .iter()
+            .filter_map(|check| {
+                check
+                    .fix
+                    .as_ref()
+                    .map(|fix| format!("{}: {fix}", check.name))
+            })
+            .collect();
+
+        if !fixes.is_empty() {
+            println!("\nSuggested fixes:\n\n  {}", fixes.join("\n  "));
+        }
+
+        if checks.iter().any(|check| check.status == CheckStatus::Fail) {
+            Ok(GitCommandResult::Error)
+        } else {
+            Ok(GitCommandResult::Success)
+        }
+    }
+
+    /// Check that the installed **Git** is at least `MIN_GIT_VERSION`.
+    fn git_version() -> Check {
+        const NAME: &str = "git version";
+        let minimum = format!("{}.{}", MIN_GIT_VERSION.0, MIN_GIT_VERSION.1);
+
+        let version = match GitCommand::new("--version").stdout() {
+            Ok(version) => version,
+            Err(e) => return Check::fail(NAME, format!("Unable to run git: {e}"), "Install git"),
+        };
+
+        match parse_git_version(&version) {
+            Ok(parsed) if parsed >= MIN_GIT_VERSION => Check::pass(NAME, version),
+            Ok(_) => Check::fail(
+                NAME,
+                format!("{version} is older than {minimum}"),
+                format!("Upgrade git to {minimum} or newer"),
+            ),
+            Err(e) => Check::warn(
+                NAME,
+                e.to_string(),
+                format!("Make sure git {minimum} or newer is installed"),
+            ),
+        }
+    }
+
+    /// Check that `program` (the configured `tool`) can be found on the `PATH`.
+    fn external_tool(tool: &'static str, program: &str) -> Check {
+        match Commands::find_on_path(program) {
+            Some(path) => Check::pass(tool, path.display().to_string()),
+            // `alias` and `conf` fall back to filtering and aligning internally
+            None => Check::warn(
+                tool,
+                format!("{program} not found on the PATH; using the built-in fallback"),
+                format!(
+                    "Install {program}, or set git-util.tools.{tool} to the path of a substitute"
+                ),
+            ),
+        }
+    }
+
+    /// Check that the repository's `pre-commit` hook calls `git-util hook pre-commit`.
+    fn pre_commit_hook() -> Check {
+        const NAME: &str = "pre-commit hook";
+
+        // `--git-path` respects `core.hooksPath`
+        let hook = match Git::git_path("hooks/pre-commit") {
+            Ok(hook) => hook,
+            Err(_) => return Check::pass(NAME, "Not in a repository; skipped"),
+        };
+
+        let Ok(contents) = std::fs::read_to_string(&hook) else {
+            return Check::warn(
+                NAME,
+                format!("{} doesn't exist", hook.display()),
+                format!(
+                    "Create {} containing `git-util hook pre-commit`, and make it executable",
+                    hook.display()
+                ),
+            );
+        };
+
+        if !contents.contains("hook pre-commit") {
+            return Check::warn(
+                NAME,
+                format!("{} doesn't call git-util", hook.display()),
+                format!("Add `git-util hook pre-commit` to {}", hook.display()),
+            );
+        }
+
+        if !Commands::is_executable(&hook) {
+            return Check::fail(
+                NAME,
+                format!("{} isn't executable", hook.display()),
+                format!("chmod +x {}", hook.display()),
+            );
+        }
+
+        Check::pass(NAME, format!("{} calls git-util", hook.display()))
+    }
+
+    /// Check that the config setting `key` has a value.
+    fn config_value_is_set(key: &'static str) -> Check {
+        match Git::config_value(key) {
+            Ok(Some(value)) if !value.trim().is_empty() => Check::pass(key, value),
+            Ok(_) => Check::fail(key, "Not set", format!("git config --global {key} 'VALUE'")),
+            Err(e) => Check::fail(key, e.to_string(), format!("Fix the value of {key}")),
+        }
+    }
+
+    /// Check that commit signing, if it's configured, will work: `gpg.format` is valid and suits `user.signingkey`,
+    /// and the key exists, hasn't expired, and is for `user.email`.
+    fn signing() -> Vec<Check> {
+        const NAME: &str = "gpg.format";
+
+        let signing_enabled = config("commit.gpgsign").is_some_and(|value| value == "true");
+        let Some(key) = config("user.signingkey") else {
+            return vec![if signing_enabled {
+                Check::warn(
+                    SIGNING_KEY,
+                    "commit.gpgsign is enabled, but user.signingkey is not set",
+                    "git config --global user.signingkey 'KEY'",
+                )
+            } else {
+                Check::pass(SIGNING_KEY, "Commit signing is not configured")
+            }];
+        };
+
+        let format = config("gpg.format").unwrap_or_else(|| "openpgp".to_string());
+        if !matches!(format.as_str(), "openpgp" | "x509" | "ssh") {
+            return vec![Check::fail(
+                NAME,
+                format!("\"{format}\" isn't a signing format"),
+                "git config --global gpg.format ssh (or openpgp, or x509)",
+            )];
+        }
+
+        // the SSH key is either a public key literal or a path to a key file; the others are key IDs
+        let ssh_key = key.starts_with("key::")
+            || key.starts_with("ssh-")
+            || key.starts_with("ecdsa-")
+            || key.starts_with("sk-")
+            || expand_home(&key).is_file();
+        if ssh_key != (format == "ssh") {
+            return vec![if ssh_key {
+                Check::fail(
+                    NAME,
+                    format!("{key} is an SSH key, but gpg.format is {format}"),
+                    "git config --global gpg.format ssh",
+                )
+            } else {
+                Check::fail(
+                    NAME,
+                    format!("{key} isn't an SSH key, but gpg.format is ssh"),
+                    "git config --global --unset gpg.format",
+                )
+            }];
+        }
+
+        let mut checks = vec![Check::pass(NAME, format.clone())];
+        let email = config("user.email");
+
+        if format == "ssh" {
+            checks.extend(Doctor::ssh_signing_key(&key, email.as_deref()));
+        } else {
+            checks.extend(Doctor::gpg_signing_key(&key, &format, email.as_deref()));
+        }
+
+        checks
+    }
+
+    /// Check that the GPG (or X.509) signing `key` has a secret key in the keyring, hasn't expired or been revoked,
+    /// and has a user ID for `email`.
+    fn gpg_signing_key(key: &str, format: &str, email: Option<&str>) -> Vec<Check> {
+        let program = if format == "x509" {
+            config("gpg.x509.program").unwrap_or_else(|| "gpgsm".to_string())
+        } else {
+            config("gpg.openpgp.program")
+                .or_else(|| config("gpg.program"))
+                .unwrap_or_else(|| "gpg".to_string())
+        };
+
+        let output = Commands::output_with_timeout(&mut Commands::new_command_with_args(
+            &program,
+            &["--list-secret-keys", "--with-colons", key],
+        ));
+        let listing = match output {
+            Ok(output) if output.status.success() => {
+                String::from_utf8_lossy(&output.stdout).to_string()
+            }
+            _ => {
+                return vec![Check::fail(
+                    SIGNING_KEY,
+                    format!("{key} ({format}) isn't in the {program} keyring"),
+                    format!(
+                        "Find your key with `{program} --list-secret-keys`, then `git config --global user.signingkey KEY_ID`"
+                    ),
+                )]
+            }
+        };
+
+        debug!("{program} --list-secret-keys {key}: {listing}");
+
+        // `sec` (or `crs` for X.509) is the key itself, whose second field is its validity and seventh its expiry
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut validity = ' ';
+        let mut expired = false;
+        let mut user_ids = Vec::new();
+
+        for fields in listing
+            .lines()
+            .map(|line| line.split(':').collect::<Vec<_>>())
+        {
+            match fields.first().copied() {
+                Some("sec" | "crs") => {
+                    validity = fields
+                        .get(1)
+                        .and_then(|field| field.chars().next())
+                        .unwrap_or(' ');
+                    expired |= fields
+                        .get(6)
+                        .and_then(|field| field.parse::<u64>().ok())
+                        .is_some_and(|expires| expires <= now);
+                }
+                Some("uid") => user_ids.extend(fields.get(9).map(|field| field.to_string())),
+                _ => {}
+            }
+        }
+
+        let mut checks = vec![match validity {
+            'r' => Check::fail(
+                SIGNING_KEY,
+                format!("{key} ({format}) has been revoked"),
+                "Create a new key, then `git config --global user.signingkey KEY_ID`",
+            ),
+            _ if validity == 'e' || expired => Check::fail(
+                SIGNING_KEY,
+                format!("{key} ({format}) has expired"),
+                format!(
+                    "Extend it with `{program} --quick-set-expire FINGERPRINT 1y` (and re-upload the public key), or set user.signingkey to another key"
+                ),
+            ),
+            _ => Check::pass(SIGNING_KEY, format!("{key} ({format})")),
+        }];
+
+        if let Some(email) = email {
+            let matches = user_ids.iter().any(|user_id| {
+                user_id
+                    .to_lowercase()
+                    .contains(&format!("<{}>", email.to_lowercase()))
+            });
+
+            checks.push(if matches {
+                Check::pass(SIGNING_EMAIL, format!("{key} is for {email}"))
+            } else {
+                Check::warn(
+                    SIGNING_EMAIL,
+                    format!("None of the user IDs of {key} is {email}, so forges won't verify its signatures"),
+                    format!(
+                        "Add it with `{program} --quick-add-uid FINGERPRINT 'NAME <{email}>'`, or set user.signingkey to a key for {email}"
+                    ),
+                )
+            });
+        }
+
+        checks
+    }
+
+    /// Check that the SSH signing `key` (a public key literal, or the path of a key file) can sign, and that
+    /// `gpg.ssh.allowedSignersFile` lists it for `email`, without which git can't verify its signatures.
+    fn ssh_signing_key(key: &str, email: Option<&str>) -> Vec<Check> {
+        let literal = key
+            .strip_prefix("key::")
+            .or_else(|| (!expand_home(key).is_file()).then_some(key));
+
+        let public_key = match literal {
+            Some(literal) => Some(literal.to_string()),
+            None => {
+                let path = expand_home(key);
+                let contents = std::fs::read_to_string(&path).unwrap_or_default();
+
+                if contents.contains("PRIVATE KEY") {
+                    // the public key isn't needed to sign, but it is to check the allowed signers
+                    std::fs::read_to_string(format!("{}.pub", path.display())).ok()
+                } else {
+                    // `ssh-keygen` finds the private key next to the public one, or else in the agent
+                    let private_key = path.with_extension("");
+                    if path.extension().is_none_or(|extension| extension != "pub")
+                        || !private_key.is_file()
+                    {
+                        if let Some(check) = Doctor::ssh_agent_has(&contents, key) {
+                            return vec![check];
+                        }
+                    }
+                    Some(contents)
+                }
+            }
+        };
+
+        let mut checks = vec![Check::pass(SIGNING_KEY, format!("{key} (ssh)"))];
+
+        // the key type and the base64 of the key, without the comment
+        let public_key: Option<String> = public_key.map(|public_key| {
+            public_key
+                .split_whitespace()
+                .take(2)
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+        let (Some(email), Some(public_key)) = (email, public_key) else {
+            return checks;
+        };
+
+        let add_signer = format!(
+            "echo '{email} {public_key}' >> ~/.config/git/allowed_signers && git config --global gpg.ssh.allowedSignersFile ~/.config/git/allowed_signers"
+        );
+
+        let Some(allowed_signers) = config("gpg.ssh.allowedSignersFile") else {
+            checks.push(Check::warn(
+                SIGNING_EMAIL,
+                "gpg.ssh.allowedSignersFile is not set, so git can't verify SSH signatures",
+                add_signer,
+            ));
+            return checks;
+        };
+
+        let path = expand_home(&allowed_signers);
+        let signers = std::fs::read_to_string(&path).unwrap_or_default();
+        let principals: Vec<&str> = signers
+            .lines()
+            .filter(|line| line.contains(&public_key))
+            .filter_map(|line| line.split_whitespace().next())
+            .flat_map(|principals| principals.split(','))
+            .collect();
+
+        checks.push(
+            if principals
+                .iter()
+                .any(|principal| *principal == "*" || principal.eq_ignore_ascii_case(email))
+            {
+                Check::pass(
+                    SIGNING_EMAIL,
+                    format!("{} lists the key for {email}", path.display()),
+                )
+            } else if principals.is_empty() {
+                Check::warn(
+                    SIGNING_EMAIL,
+                    format!(
+                        "{} doesn't list the key, so git can't verify its signatures",
+                        path.display()
+                    ),
+                    add_signer,
+                )
+            } else {
+                Check::warn(
+                    SIGNING_EMAIL,
+                    format!(
+                        "{} lists the key for {}, not {email}",
+                        path.display(),
+                        principals.join(", ")
+                    ),
+                    format!("Add {email} to the key's principals in {}", path.display()),
+                )
+            },
+        );
+
+        checks
+    }
+
+    /// A failed check if `ssh-agent` doesn't have the private key of `public_key` (from `key`); `None` if it does.
+    fn ssh_agent_has(public_key: &str, key: &str) -> Option<Check> {
+        let base64 = public_key.split_whitespace().nth(1).unwrap_or_default();
+
+        let listed =
+            Commands::output_with_timeout(&mut Commands::new_command_with_args("ssh-add", &["-L"]))
+                .is_ok_and(|output| {
+                    output.status.success()
+                        && String::from_utf8_lossy(&output.stdout).contains(base64)
+                });
+
+        (!listed || base64.is_empty()).then(|| {
+            Check::fail(
+                SIGNING_KEY,
+                format!("The private key of {key} isn't next to it or in ssh-agent"),
+                "ssh-add PATH_OF_THE_PRIVATE_KEY",
+            )
+        })
+    }
+}
+
+/// The value of the config setting `key`, if it's set (and can be read).
+fn config(key: &str) -> Option<String> {
+    Git::config_value(key).ok().flatten()
+}
+
+/// Parse the `(major, minor)` version from the output of `git --version`, e.g. `git version 2.39.5`.
+fn parse_git_version(version: &str) -> Result<(u32, u32)> {
+    let number = version
+        .split_whitespace()
+        .nth(2)
+        .ok_or_else(|| anyhow!("Unrecognized git version: \"{}\"", version))?;
+
+    let mut parts = number.split('.').map(|part| part.parse::<u32>());
+
+    match (parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor))) => Ok((major, minor)),
+        _ => Err(anyhow!("Unrecognized git version: \"{}\"", version)),
+    }
+}
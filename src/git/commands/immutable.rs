@@ -1,37 +1,760 @@
 use crate::git::{GitCommandResult, GitResult};
 use crate::{
-    commands::ripgrep::{Ripgrep, RipgrepOptions},
-    git::{Git, GitCommand},
+    cache::Cache,
+    color::Colors,
+    commands::filter::{Filter, FilterOptions},
+    git::{
+        state::{Conflict, Operation},
+        DateStyle, Git, GitCommand, LogDateRange, RenameDetection, WordDiff,
+    },
+    journal::{Journal, JournalEntry},
+    output::OutputTarget,
+    print::Print,
+    settings::Settings,
+    table::Table,
+    timings::Timings,
 };
 use crate::{commands::Commands, git::GitConfigOpts};
-use anyhow::Context;
-use log::trace;
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use nu_ansi_term::Color;
+use regex::Regex;
 use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    fs,
     io::{self, Write},
+    path::Path,
     process::{ChildStdout, Output},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub struct ImmutableCommands();
 
+/// The pretty format used by `l` when no other is configured.
+const DEFAULT_ONE_LINE_FORMAT: &str = "%C(yellow)%h %C(magenta)%ad %C(blue)%aL %C(cyan)%s%C(reset)";
+
+/// Separates the `%G?` status that `l --signatures` prefixes each commit with from the rest of its line.
+const SIGNATURE_STATUS_SEPARATOR: char = '\u{1f}';
+
+/// The pretty format used by `find`: the default `l` format, plus the ref that each commit was reached from (`%S`).
+const FIND_FORMAT: &str = "%C(yellow)%h %C(magenta)%ad %C(green)%S %C(blue)%aL %C(cyan)%s%C(reset)";
+
 impl ImmutableCommands {
-    /// `git log --compact-summary --max-count=NUM ARGS`
-    pub fn compact_summary_log(num: Option<u16>, args: &[String]) -> GitResult {
-        trace!("last() called with: {:#?}, {:#?}", num, args);
+    /// `git log --compact-summary --max-count=NUM [--date=STYLE] [--since=DATE] [--until=DATE] ARGS -- PATHS`
+    ///
+    /// `dates` defaults to the `git-util.dates` setting; if neither is set, git's default date format is used.
+    pub fn compact_summary_log(
+        num: Option<u16>,
+        dates: Option<DateStyle>,
+        range: &LogDateRange,
+        args: &[String],
+        paths: &[String],
+    ) -> GitResult {
+        trace!(
+            "last() called with: {:#?}, {:#?}, {:#?}, {:#?}, {:#?}",
+            num,
+            dates,
+            range,
+            args,
+            paths
+        );
+
+        let args = Git::with_pathspecs(args, paths)?;
+
+        let max_count = format!("--max-count={}", num.unwrap_or(1));
+        let mut default_args = vec!["--compact-summary", &max_count];
+        if let Some(dates) = dates.or(Settings::get().dates) {
+            default_args.push(dates.as_arg());
+        }
+        let range_args = range.args();
+        default_args.extend(range_args.iter().map(String::as_str));
 
         GitCommand::new("log")
-            .with_default_args(&[
-                "--compact-summary",
-                &format!("--max-count={}", num.unwrap_or(1)),
-            ])
-            .with_user_args(args)
+            .with_default_args(&default_args)
+            .with_user_args(&args)
             .run()
     }
 
+    /// Print the contents of the file at `path` as of `reference`.
+    ///
+    /// `git show REF:PATH`
+    ///
+    /// `path` is resolved relative to the current directory first, then relative to the root of the repository.
+    pub fn cat(reference: &str, path: &str) -> GitResult {
+        trace!("cat() called with: {reference}, {path}");
+
+        if !Git::object_exists(&format!("{reference}^{{commit}}"))? {
+            return Err(anyhow!("'{}' is not a valid commit", reference));
+        }
+
+        // `REF:./PATH` is relative to the current directory, whereas `REF:PATH` is relative to the repository root
+        let object = [
+            format!("{reference}:./{path}"),
+            format!("{reference}:{path}"),
+        ]
+        .into_iter()
+        .find(|object| Git::object_exists(object).unwrap_or(false))
+        .ok_or_else(|| anyhow!("'{}' did not exist at {}", path, reference))?;
+
+        debug!("resolved {path} at {reference} to {object}");
+
+        GitCommand::new("show").with_default_args(&[&object]).run()
+    }
+
+    /// List the files changed on `head` (else defaults to `HEAD`) since it diverged from `base`, with color-coded status
+    /// letters.
+    ///
+    /// `git diff --name-status -z BASE...HEAD`
+    ///
+    /// If `group` is `true`, the files are grouped under their directories.
+    pub fn changed(base: &str, head: Option<&str>, group: bool) -> GitResult {
+        trace!("changed() called with: {base}, {head:?}, {group}");
+
+        let range = format!("{base}...{}", head.unwrap_or("HEAD"));
+        let diff = GitCommand::new("diff")
+            .with_default_args(&["--name-status", "-z", &range])
+            .stdout()?;
+
+        // each entry is `STATUS\0PATH\0`, or `STATUS\0OLD_PATH\0NEW_PATH\0` for renames and copies
+        let mut changes: Vec<(char, String)> = Vec::new();
+        let mut fields = diff.split('\0').filter(|field| !field.is_empty());
+
+        while let Some(status) = fields.next() {
+            let Some(path) = fields.next() else {
+                break;
+            };
+            let status = status.chars().next().unwrap_or('?');
+
+            let path = if matches!(status, 'R' | 'C') {
+                format!("{path} -> {}", fields.next().unwrap_or_default())
+            } else {
+                path.to_string()
+            };
+
+            changes.push((status, path));
+        }
+
+        if changes.is_empty() {
+            Print::info(&format!("No changes in {range}"));
+            return Ok(GitCommandResult::Success);
+        }
+
+        let colors = Colors::stdout();
+        let status_letter = |status: char| {
+            let color = match status {
+                'A' => Color::Green,
+                'M' => Color::Yellow,
+                'D' => Color::Red,
+                'R' | 'C' => Color::Cyan,
+                _ => Color::Purple,
+            };
+
+            Colors::paint(color, &status.to_string(), colors).to_string()
+        };
+
+        let mut writer = OutputTarget::writer()?;
+
+        if group {
+            let mut directories: BTreeMap<String, Table> = BTreeMap::new();
+
+            for (status, path) in &changes {
+                // group renames under the directory they were moved to
+                let new_path = path.rsplit(" -> ").next().unwrap_or(path);
+                let directory = match new_path.rsplit_once('/') {
+                    Some((directory, _)) => format!("{directory}/"),
+                    None => "./".to_string(),
+                };
+
+                directories
+                    .entry(directory)
+                    .or_default()
+                    .add_row([format!("  {}", status_letter(*status)), path.clone()]);
+            }
+
+            for (directory, files) in directories {
+                write!(writer, "{directory}\n{files}")?;
+            }
+        } else {
+            let mut table = Table::new();
+            for (status, path) in &changes {
+                table.add_row([status_letter(*status), path.clone()]);
+            }
+
+            write!(writer, "{table}")?;
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Print a report comparing `left` and `right` (else defaults to `HEAD`): their merge-base, the commits that are only
+    /// on each side, and the diffstat between them.
+    ///
+    /// `git merge-base LEFT RIGHT`, `git log --left-only|--right-only LEFT...RIGHT`, and `git diff --stat LEFT RIGHT`
+    pub fn compare(left: &str, right: Option<&str>) -> GitResult {
+        trace!("compare() called with: {left}, {right:?}");
+
+        let right = right.unwrap_or("HEAD");
+        let symmetric_range = format!("{left}...{right}");
+        let pretty = format!("--pretty={DEFAULT_ONE_LINE_FORMAT}");
+        let date = Settings::get().dates.unwrap_or(DateStyle::Short).as_arg();
+
+        let (left_count, right_count) = Git::ahead_behind(left, right)?;
+
+        // unrelated histories have no merge-base
+        let merge_base = match GitCommand::new("merge-base")
+            .with_default_args(&[left, right])
+            .stdout()
+        {
+            Ok(merge_base) => colored_stdout(GitCommand::new("log").with_default_args(&[
+                "--max-count=1",
+                &pretty,
+                date,
+                &merge_base,
+            ]))?,
+            Err(e) => {
+                debug!("no merge-base: {e}");
+                "none\n".to_string()
+            }
+        };
+
+        let mut writer = OutputTarget::writer()?;
+        write!(writer, "Merge base: {merge_base}")?;
+
+        for (side, branch, count) in [
+            ("--left-only", left, left_count),
+            ("--right-only", right, right_count),
+        ] {
+            writeln!(writer, "\nOnly on {branch} ({count}):")?;
+
+            if count > 0 {
+                write!(
+                    writer,
+                    "{}",
+                    colored_stdout(GitCommand::new("log").with_default_args(&[
+                        side,
+                        &pretty,
+                        date,
+                        &symmetric_range,
+                    ]))?
+                )?;
+            }
+        }
+
+        let diffstat =
+            colored_stdout(GitCommand::new("diff").with_default_args(&["--stat", left, right]))?;
+        writeln!(writer, "\nDiffstat {left}..{right}:")?;
+        write!(
+            writer,
+            "{}",
+            if diffstat.is_empty() {
+                " no differences\n"
+            } else {
+                &diffstat
+            }
+        )?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// List the files with unresolved merge conflicts, along with how each conflict arose.
+    pub fn conflicts() -> GitResult {
+        trace!("conflicts() called");
+
+        let mut table = Table::new();
+        for conflict in Conflict::list()? {
+            table.add_row([conflict.kind.to_string(), conflict.path]);
+        }
+
+        if table.is_empty() {
+            Print::info("No conflicts");
+        } else {
+            print!("{table}");
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Print only the conflicting hunks (from `<<<<<<<` to `>>>>>>>`) of `path`, prefixed by their line numbers.
+    pub fn conflict_hunks(path: &str) -> GitResult {
+        trace!("conflict_hunks() called with: {path}");
+
+        let contents = fs::read(path).with_context(|| format!("Failed to read {path}"))?;
+        let contents = String::from_utf8_lossy(&contents);
+
+        let mut in_hunk = false;
+        let mut found = false;
+
+        for (i, line) in contents.lines().enumerate() {
+            if line.starts_with("<<<<<<<") {
+                if found {
+                    println!();
+                }
+                in_hunk = true;
+                found = true;
+            }
+
+            if in_hunk {
+                println!("{:>5}: {line}", i + 1);
+            }
+
+            if line.starts_with(">>>>>>>") {
+                in_hunk = false;
+            }
+        }
+
+        if found {
+            Ok(GitCommandResult::Success)
+        } else {
+            Err(anyhow!("No conflict markers found in {path}"))
+        }
+    }
+
+    /// Print how far `branch` (else defaults to the current branch) is ahead/behind its upstream, as `+AHEAD -BEHIND`.
+    ///
+    /// If `default` is `true`, also print how far behind the default branch it is, as `DEFAULT_BRANCH:-BEHIND`.
+    ///
+    /// `git rev-list --left-right --count BRANCH...UPSTREAM`
+    pub fn ahead_behind(branch: Option<&str>, default: bool) -> GitResult {
+        trace!("ahead_behind() called with: {branch:?}, {default}");
+
+        // `HEAD` also works when it's detached
+        let branch = branch.unwrap_or("HEAD");
+        let mut parts = Vec::new();
+
+        if let Some(upstream) = Git::upstream(branch)? {
+            let (ahead, behind) = Git::ahead_behind(branch, &upstream)?;
+            parts.push(format!("+{ahead} -{behind}"));
+        }
+
+        if default {
+            let default_branch = Git::default_branch()?;
+            let (_, behind) = Git::ahead_behind(branch, &default_branch)?;
+            parts.push(format!("{default_branch}:-{behind}"));
+        }
+
+        if !parts.is_empty() {
+            writeln!(OutputTarget::writer()?, "{}", parts.join(" "))?;
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Print an overview of the repository: the current branch and how it compares to its upstream, the number of staged,
+    /// unstaged, untracked, and conflicted files, the number of stashes, any operation in progress, and the last 5
+    /// commits.
+    pub fn dashboard() -> GitResult {
+        trace!("dashboard() called");
+
+        let mut table = Table::new();
+
+        let branch = match Git::current_branch() {
+            Ok(branch) => branch,
+            Err(_) => format!(
+                "(detached at {})",
+                GitCommand::new("rev-parse")
+                    .with_default_args(&["--short", "HEAD"])
+                    .stdout()?
+            ),
+        };
+        let upstream = match Git::upstream("HEAD")? {
+            Some(upstream) => {
+                let (ahead, behind) = Git::ahead_behind("HEAD", &upstream)?;
+                format!("{upstream} (+{ahead} -{behind})")
+            }
+            None => "none".to_string(),
+        };
+        table.add_row(["Branch:".to_string(), branch]);
+        table.add_row(["Upstream:".to_string(), upstream]);
+
+        let (mut staged, mut unstaged, mut untracked, mut conflicted) = (0, 0, 0, 0);
+        for line in GitCommand::new("status")
+            .with_default_args(&["--porcelain"])
+            .stdout()?
+            .lines()
+        {
+            match line.get(..2).unwrap_or_default() {
+                "??" => untracked += 1,
+                "DD" | "AU" | "UD" | "UA" | "DU" | "AA" | "UU" => conflicted += 1,
+                xy => {
+                    if !xy.starts_with(' ') {
+                        staged += 1;
+                    }
+                    if !xy.ends_with(' ') {
+                        unstaged += 1;
+                    }
+                }
+            }
+        }
+        let mut changes = format!("{staged} staged, {unstaged} unstaged, {untracked} untracked");
+        if conflicted > 0 {
+            changes.push_str(&format!(", {conflicted} conflicted"));
+        }
+        table.add_row(["Changes:".to_string(), changes]);
+
+        let stashes = GitCommand::new("stash")
+            .with_default_args(&["list"])
+            .stdout()?
+            .lines()
+            .count();
+        table.add_row(["Stashes:".to_string(), stashes.to_string()]);
+
+        // changes to these files aren't shown above, which is easy to forget
+        let assumed_unchanged = Git::assumed_unchanged_files()?.len();
+        if assumed_unchanged > 0 {
+            table.add_row([
+                "Assumed unchanged:".to_string(),
+                Colors::paint(
+                    Color::Yellow,
+                    &format!("{assumed_unchanged} (see `git-util assume --list`)"),
+                    Colors::stdout(),
+                )
+                .to_string(),
+            ]);
+        }
+
+        if let Some(operation) = Operation::in_progress()? {
+            table.add_row([
+                "In progress:".to_string(),
+                Colors::paint(Color::Red, &operation.to_string(), Colors::stdout()).to_string(),
+            ]);
+        }
+
+        let recent_commits = colored_stdout(GitCommand::new("log").with_default_args(&[
+            "--max-count=5",
+            &format!("--pretty={DEFAULT_ONE_LINE_FORMAT}"),
+            Settings::get().dates.unwrap_or(DateStyle::Short).as_arg(),
+        ]))?;
+
+        write!(
+            OutputTarget::writer()?,
+            "{table}\nRecent commits:\n{recent_commits}"
+        )?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// List the commits authored by `user.email` on any local branch in the last `days` days, grouped by day (most recent
+    /// first) and then by branch.
+    ///
+    /// `git log --branches --source --fixed-strings --author=EMAIL --since='DAYS days ago'`
+    pub fn standup(days: u16) -> GitResult {
+        trace!("standup() called with: {days}");
+
+        let email = Git::config_value("user.email")?
+            .ok_or_else(|| anyhow!("user.email is not set; unable to find your commits"))?;
+
+        let author = format!("--author={email}");
+        let since = format!("--since={days} days ago");
+        // the short date and the branch come first so that the commits can be grouped by them
+        let pretty = format!("--pretty=%ad%x00%S%x00{DEFAULT_ONE_LINE_FORMAT}");
+
+        let log = colored_stdout(GitCommand::new("log").with_default_args(&[
+            "--branches",
+            "--source",
+            // `user.email` isn't a pattern, e.g. the `+` in `me+work@example.com`
+            "--fixed-strings",
+            &author,
+            &since,
+            "--date=short",
+            &pretty,
+        ]))?;
+
+        let mut commits_by_day: BTreeMap<&str, BTreeMap<&str, Vec<&str>>> = BTreeMap::new();
+        for line in log.lines() {
+            let mut fields = line.splitn(3, '\0');
+            let (Some(day), Some(branch), Some(commit)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+
+            commits_by_day
+                .entry(day)
+                .or_default()
+                .entry(branch.trim_start_matches("refs/heads/"))
+                .or_default()
+                .push(commit);
+        }
+
+        if commits_by_day.is_empty() {
+            Print::info(&format!("No commits by {email} in the last {days} day(s)"));
+            return Ok(GitCommandResult::Success);
+        }
+
+        let colors = Colors::stdout();
+        let mut writer = OutputTarget::writer()?;
+
+        for (day, branches) in commits_by_day.iter().rev() {
+            writeln!(writer, "{}", Colors::paint(Color::Magenta, day, colors))?;
+
+            for (branch, commits) in branches {
+                writeln!(writer, "  {}", Colors::paint(Color::Green, branch, colors))?;
+
+                for commit in commits {
+                    writeln!(writer, "    {commit}")?;
+                }
+            }
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// List the commits in the `Journal` from the last `days` days, from every repository (including the ones made in
+    /// other repositories, and without **git-util**), grouped by repository and then by branch.
+    ///
+    /// The journal is only written by the `post-commit` hook, which is installed with `hook install --post-commit`.
+    pub fn journal_standup(days: u16) -> GitResult {
+        trace!("journal_standup() called with: {days}");
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let since = now.saturating_sub(u64::from(days) * 24 * 60 * 60);
+
+        let entries = Journal::entries_since(since.try_into()?)?;
+
+        let mut commits_by_repository: BTreeMap<&Path, BTreeMap<&str, Vec<&JournalEntry>>> =
+            BTreeMap::new();
+        for entry in &entries {
+            commits_by_repository
+                .entry(&entry.repository)
+                .or_default()
+                .entry(entry.branch.as_deref().unwrap_or("(no branch)"))
+                .or_default()
+                .push(entry);
+        }
+
+        if commits_by_repository.is_empty() {
+            Print::info(&format!(
+                "No commits were journaled in the last {} day(s); the post-commit hook journals them (`{}`)",
+                days, "git-util hook install --post-commit"
+            ));
+            return Ok(GitCommandResult::Success);
+        }
+
+        let colors = Colors::stdout();
+        let mut writer = OutputTarget::writer()?;
+
+        for (repository, branches) in commits_by_repository {
+            writeln!(
+                writer,
+                "{}",
+                Colors::paint(Color::Magenta, &repository.to_string_lossy(), colors)
+            )?;
+
+            for (branch, commits) in branches {
+                writeln!(writer, "  {}", Colors::paint(Color::Green, branch, colors))?;
+
+                // most recent first, like `standup`
+                for commit in commits.iter().rev() {
+                    writeln!(
+                        writer,
+                        "    {} {} ({} {})",
+                        Colors::paint(
+                            Color::Yellow,
+                            &commit.sha[..commit.sha.len().min(7)],
+                            colors
+                        ),
+                        commit.subject,
+                        commit.files_changed,
+                        if commit.files_changed == 1 {
+                            "file"
+                        } else {
+                            "files"
+                        }
+                    )?;
+                }
+            }
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Print a table of the commits, insertions, deletions, and files touched per author in `range` (else defaults to
+    /// all of the history of `HEAD`), sorted by `sort`.
+    ///
+    /// `git log --numstat --no-renames [--since=DATE] [--until=DATE] [RANGE]`
+    pub fn stats(range: Option<&str>, dates: &LogDateRange, sort: StatsSort) -> GitResult {
+        trace!("stats() called with: {range:?}, {dates:?}, {sort:?}");
+
+        let date_args = dates.args();
+        let mut args = vec!["--numstat", "--no-renames", "--format=%x00%aN"];
+        args.extend(date_args.iter().map(String::as_str));
+        args.extend(range);
+
+        let log = GitCommand::new("log").with_default_args(&args).stdout()?;
+
+        let mut authors: BTreeMap<String, AuthorStats> = BTreeMap::new();
+        let mut author = None;
+
+        for line in log.lines() {
+            // each commit starts with `\0AUTHOR`, followed by a `INSERTIONS\tDELETIONS\tPATH` line per file
+            if let Some(name) = line.strip_prefix('\0') {
+                let stats = authors.entry(name.to_string()).or_default();
+                stats.commits += 1;
+                author = Some(name.to_string());
+                continue;
+            }
+
+            let (Some(stats), Some((insertions, rest))) = (
+                author.as_ref().and_then(|name| authors.get_mut(name)),
+                line.split_once('\t'),
+            ) else {
+                continue;
+            };
+            let Some((deletions, path)) = rest.split_once('\t') else {
+                continue;
+            };
+
+            // binary files are listed with `-` instead of line counts
+            stats.insertions += insertions.parse::<usize>().unwrap_or(0);
+            stats.deletions += deletions.parse::<usize>().unwrap_or(0);
+            stats.files.insert(path.to_string());
+        }
+
+        if authors.is_empty() {
+            Print::info("No commits found");
+            return Ok(GitCommandResult::Success);
+        }
+
+        let mut authors: Vec<(String, AuthorStats)> = authors.into_iter().collect();
+        match sort {
+            // already sorted by name
+            StatsSort::Author => (),
+            StatsSort::Commits => authors.sort_by_key(|(_, stats)| Reverse(stats.commits)),
+            StatsSort::Insertions => authors.sort_by_key(|(_, stats)| Reverse(stats.insertions)),
+            StatsSort::Deletions => authors.sort_by_key(|(_, stats)| Reverse(stats.deletions)),
+            StatsSort::Files => authors.sort_by_key(|(_, stats)| Reverse(stats.files.len())),
+        }
+
+        let mut table = Table::new();
+        table.add_row(["Author", "Commits", "Insertions", "Deletions", "Files"]);
+        for (name, stats) in &authors {
+            table.add_row([
+                name.clone(),
+                stats.commits.to_string(),
+                format!("+{}", stats.insertions),
+                format!("-{}", stats.deletions),
+                stats.files.len().to_string(),
+            ]);
+        }
+
+        write!(OutputTarget::writer()?, "{table}")?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Print a table of the signature status of the last `commits` commits (else defaults to 1), or of the commits in the
+    /// range `commits`, along with any annotated tags that point at them.
+    ///
+    /// `git log --format=%G?` and `git verify-tag --raw`
+    ///
+    /// Returns `Error` if any commit or annotated tag is unsigned or doesn't have a good signature.
+    pub fn verify(commits: Option<&str>) -> GitResult {
+        trace!("verify() called with: {commits:?}");
+
+        let commits = match commits {
+            Some(commits) => match commits.parse::<u16>() {
+                Ok(num) => vec![format!("--max-count={num}")],
+                Err(_) => vec![commits.to_string()],
+            },
+            None => vec!["--max-count=1".to_string()],
+        };
+        let mut args = vec!["--format=%H%x00%h%x00%G?%x00%GS%x00%GK%x00%s"];
+        args.extend(commits.iter().map(String::as_str));
+
+        let log = GitCommand::new("log").with_default_args(&args).stdout()?;
+
+        // annotated tags, keyed by the commit they point at
+        let mut tags: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for line in GitCommand::new("for-each-ref")
+            .with_default_args(&["--format=%(*objectname)%00%(refname:short)", "refs/tags"])
+            .stdout()?
+            .lines()
+        {
+            // lightweight tags aren't tag objects, so they have no peeled object name
+            if let Some((commit, tag)) = line
+                .split_once('\0')
+                .filter(|(commit, _)| !commit.is_empty())
+            {
+                tags.entry(commit.to_string())
+                    .or_default()
+                    .push(tag.to_string());
+            }
+        }
+
+        let mut table = Table::new();
+        table.add_row(["Commit", "Signer", "Key", "Status"].map(String::from));
+        let mut all_valid = true;
+
+        for line in log.lines() {
+            let fields: Vec<&str> = line.splitn(6, '\0').collect();
+            let [hash, short_hash, status, signer, key, subject] = fields[..] else {
+                continue;
+            };
+
+            let status = status.chars().next().unwrap_or('N');
+            all_valid &= SignatureStatus::is_valid(status);
+            table.add_row([
+                format!("{short_hash} {subject}"),
+                signer.to_string(),
+                key.to_string(),
+                SignatureStatus::describe(status),
+            ]);
+
+            for tag in tags.get(hash).into_iter().flatten() {
+                let (status, signer, key) = verify_tag(tag)?;
+
+                all_valid &= SignatureStatus::is_valid(status);
+                table.add_row([
+                    format!("tag {tag}"),
+                    signer,
+                    key,
+                    SignatureStatus::describe(status),
+                ]);
+            }
+        }
+
+        write!(OutputTarget::writer()?, "{table}")?;
+
+        if all_valid {
+            Ok(GitCommandResult::Success)
+        } else {
+            Ok(GitCommandResult::Error)
+        }
+    }
+
     /// List configured aliases, optionally filtering on those containing `filter`.
     pub fn list_aliases(filter: Option<&str>, options: GitConfigOpts) -> GitResult {
         trace!("alias() called with: {:#?}", filter);
 
+        let aliases_table = ImmutableCommands::cached_config_listing(
+            &format!("alias {:?} {:?}", filter, options),
+            || ImmutableCommands::aliases_table(filter, options),
+        )?;
+
+        OutputTarget::writer()?
+            .write_all(&aliases_table)
+            .with_context(|| "Failed to write column output to stdout")?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// The configured aliases (optionally filtered on those containing `filter`), formatted as a table.
+    fn aliases_table(filter: Option<&str>, options: GitConfigOpts) -> anyhow::Result<Vec<u8>> {
+        let settings = Settings::get();
+
+        if !Commands::is_available(settings.sed_program())
+            || !Commands::is_available(settings.column_program())
+            || (filter.is_some() && !Commands::is_available(settings.filter_program()))
+        {
+            debug!("sed, column, or the filter is missing; formatting the aliases internally");
+            return ImmutableCommands::aliases_table_internal(filter, options);
+        }
+
         let mut config_args = vec!["config"];
 
         Git::parse_config_options(options, &mut config_args);
@@ -44,33 +767,114 @@ impl ImmutableCommands {
         let aliases = Commands::pipe_from_command("git", &config_args)?;
 
         // strip out the initial "alias." from the config name
-        let aliases = Commands::double_ended_pipe("sed", aliases, &[r"s/^alias\.//"])?;
+        let aliases =
+            Commands::double_ended_pipe(settings.sed_program(), aliases, &[r"s/^alias\.//"])?;
 
         let filtered_aliases: ChildStdout = match filter {
             Some(pattern) => {
                 // filter on `pattern`
-                Ripgrep::double_ended_pipe(aliases, pattern, Some(&[RipgrepOptions::FixedStrings]))?
+                Filter::double_ended_pipe(aliases, pattern, Some(&[FilterOptions::FixedStrings]))?
             }
             None => aliases,
         };
 
         // replace the first space (which separates the alias name and value) with a semicolon
         let delimited_aliases =
-            Commands::double_ended_pipe("sed", filtered_aliases, &[r"s/ /\t/"])?;
+            Commands::double_ended_pipe(settings.sed_program(), filtered_aliases, &[r"s/ /\t/"])?;
 
         let aliases_table: Output = Commands::pipe_to_column(delimited_aliases, '\t')?;
 
-        io::stdout()
-            .write_all(&aliases_table.stdout)
-            .with_context(|| "Failed to write column output to stdout")?;
+        Ok(aliases_table.stdout)
+    }
 
-        Ok(GitCommandResult::Success)
+    /// Same as `aliases_table()`, but filters and aligns the aliases without spawning `sed`, the filter, or `column`.
+    fn aliases_table_internal(
+        filter: Option<&str>,
+        options: GitConfigOpts,
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut config_args = Vec::new();
+
+        Git::parse_config_options(options, &mut config_args);
+
+        // this arg has to be last
+        config_args.push("--get-regexp");
+        config_args.push(r"^alias\.");
+
+        let mut table = Table::new();
+
+        for line in ImmutableCommands::config_lines(&config_args)? {
+            // the origin and/or scope (if requested) are separated from the entry by tabs
+            let mut columns: Vec<&str> = line.split('\t').collect();
+            let entry = columns.pop().unwrap_or_default();
+            let entry = entry.strip_prefix("alias.").unwrap_or(entry);
+
+            if filter.is_some_and(|pattern| {
+                !columns
+                    .iter()
+                    .chain([&entry])
+                    .any(|column| column.contains(pattern))
+            }) {
+                continue;
+            }
+
+            let (name, value) = entry.split_once(' ').unwrap_or((entry, ""));
+            columns.push(name);
+            columns.push(value);
+
+            table.add_row(columns);
+        }
+
+        Ok(table.to_string().into_bytes())
+    }
+
+    /// The lines printed by `git config ARGS`; empty if no config entries match.
+    fn config_lines(args: &[&str]) -> anyhow::Result<Vec<String>> {
+        let output = GitCommand::new("config").with_default_args(args).output()?;
+
+        // `git config --get-regexp` exits with 1 if nothing matches
+        match output.status.code() {
+            Some(0) => Ok(String::from_utf8(output.stdout)?
+                .lines()
+                .map(String::from)
+                .collect()),
+            Some(1) => Ok(Vec::new()),
+            _ => Err(anyhow!(
+                "'git config' failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        }
     }
 
     /// List configuration settings (excluding aliases), optionally filtering on those containing `filter`.
     pub fn list_configuration_settings(filter: Option<&str>, options: GitConfigOpts) -> GitResult {
         trace!("conf() called with: {:#?}", filter);
 
+        let config_table = ImmutableCommands::cached_config_listing(
+            &format!("conf {:?} {:?}", filter, options),
+            || ImmutableCommands::configuration_settings_table(filter, options),
+        )?;
+
+        OutputTarget::writer()?
+            .write_all(&config_table)
+            .with_context(|| "Failed to write column output to stdout")?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// The configuration settings (optionally filtered on those containing `filter`), formatted as a table.
+    fn configuration_settings_table(
+        filter: Option<&str>,
+        options: GitConfigOpts,
+    ) -> anyhow::Result<Vec<u8>> {
+        let settings = Settings::get();
+
+        if !Commands::is_available(settings.filter_program())
+            || !Commands::is_available(settings.column_program())
+        {
+            debug!("column or the filter is missing; formatting the config settings internally");
+            return ImmutableCommands::configuration_settings_table_internal(filter, options);
+        }
+
         let mut config_args = vec!["config", "--list"];
 
         Git::parse_config_options(options, &mut config_args);
@@ -81,15 +885,15 @@ impl ImmutableCommands {
         // filter out config entries that start with "alias."
         // `rg -v ^alias\.`
         let configs_no_aliases =
-            Ripgrep::double_ended_pipe(configs, r"^alias\.", Some(&[RipgrepOptions::InvertMatch]))?;
+            Filter::double_ended_pipe(configs, r"^alias\.", Some(&[FilterOptions::InvertMatch]))?;
 
         let filtered_configs: ChildStdout = match filter {
             Some(pattern) => {
                 // filter on `pattern`
-                Ripgrep::double_ended_pipe(
+                Filter::double_ended_pipe(
                     configs_no_aliases,
                     pattern,
-                    Some(&[RipgrepOptions::FixedStrings]),
+                    Some(&[FilterOptions::FixedStrings]),
                 )?
             }
             None => configs_no_aliases,
@@ -97,46 +901,158 @@ impl ImmutableCommands {
 
         let config_table: Output = Commands::pipe_to_column(filtered_configs, '=')?;
 
-        io::stdout()
-            .write_all(&config_table.stdout)
-            .with_context(|| "Failed to write column output to stdout")?;
-
-        Ok(GitCommandResult::Success)
+        Ok(config_table.stdout)
     }
 
-    /// `git log --pretty='%C(yellow)%h %C(magenta)%as %C(blue)%aL %C(cyan)%s%C(reset)' --max-count=NUM ARGS`
-    pub fn one_line_log(num: Option<u16>, args: &[String]) -> GitResult {
-        trace!("log_oneline() called with: {:#?}", num);
+    /// Same as `configuration_settings_table()`, but filters and aligns the settings without spawning the filter or
+    /// `column`.
+    fn configuration_settings_table_internal(
+        filter: Option<&str>,
+        options: GitConfigOpts,
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut config_args = vec!["--list"];
 
-        let log_output: Output = GitCommand::new("log")
-            .with_default_args(&[
-                "--pretty='%C(yellow)%h %C(magenta)%as %C(blue)%aL %C(cyan)%s%C(reset)'",
-                &format!("--max-count={}", num.unwrap_or(25)),
-            ])
-            .with_user_args(args)
-            .construct_git_command()
-            .output()
-            .with_context(|| "Failed to execute 'git log' command")?;
+        Git::parse_config_options(options, &mut config_args);
 
-        if log_output.status.success() {
-            let log_output_string = String::from_utf8(log_output.stdout)?;
-            let log_lines = log_output_string.lines();
+        let mut table = Table::new();
+
+        for line in ImmutableCommands::config_lines(&config_args)? {
+            if filter.is_some_and(|pattern| !line.contains(pattern)) {
+                continue;
+            }
 
-            let mut trimmed_log_output: String = String::new();
+            // the origin and/or scope (if requested) are separated from the entry by tabs
+            let mut columns: Vec<&str> = line.split('\t').collect();
+            let entry = columns.pop().unwrap_or_default();
 
-            // Since git thinks this is not a tty, it wraps the log lines in single quotes; we remove them here.
-            for line in log_lines.into_iter() {
-                trimmed_log_output.push_str(line.trim_matches('\''));
-                trimmed_log_output.push('\n');
+            if entry.starts_with("alias.") {
+                continue;
             }
 
-            println!("{}", trimmed_log_output.trim_end());
+            let (key, value) = entry.split_once('=').unwrap_or((entry, ""));
+            columns.push(key);
+            columns.push(value);
+
+            table.add_row(columns);
+        }
+
+        Ok(table.to_string().into_bytes())
+    }
+
+    /// Return the cached output for the config listing described by `key`, or generate it with `list` (and cache it).
+    ///
+    /// Caching is only used if the `git-util.cache` setting is enabled; entries are invalidated whenever any of the
+    /// config files is modified. It's skipped when a config file has an `[include]` or `[includeIf]` section, since
+    /// the included files (and which of them apply) can change without any of the config files changing. A failure to
+    /// write the cache is logged, and the listing returned anyway.
+    fn cached_config_listing<F>(key: &str, list: F) -> anyhow::Result<Vec<u8>>
+    where
+        F: FnOnce() -> anyhow::Result<Vec<u8>>,
+    {
+        if !Settings::get().cache {
+            return list();
+        }
+
+        let config_files = Git::config_files();
+        if config_files.iter().any(|file| {
+            fs::read_to_string(file).is_ok_and(|config| config.to_lowercase().contains("[include"))
+        }) {
+            debug!("not caching {key}, since the config includes other files");
+            return list();
+        }
+
+        let key = format!("{key} {config_files:?}");
+        let signature = Cache::signature(&config_files);
+
+        if let Some(listing) = Timings::time("cache", || Cache::get(&key, &signature)) {
+            return Ok(listing);
+        }
+
+        let listing = list()?;
+        if let Err(e) = Cache::put(&key, &signature, &listing) {
+            debug!("failed to cache {key}: {e}");
+        }
+
+        Ok(listing)
+    }
+
+    /// `git log --pretty=FORMAT --date=STYLE --max-count=NUM [--since=DATE] [--until=DATE] ARGS -- PATHS`
+    ///
+    /// `format` is the name of a format defined with `git-util.format.NAME`, or else is passed to `--pretty` as is (so
+    /// git's built-in formats and `pretty.NAME` aliases work too). Defaults to `git-util.format.l`, or
+    /// `DEFAULT_ONE_LINE_FORMAT`. `dates` defaults to the `git-util.dates` setting, or `short`. The `%C(...)` colors
+    /// follow `color.ui`, so they're dropped when colors are disabled.
+    ///
+    /// With `signatures`, each commit is prefixed with a glyph for the status of its signature (see
+    /// `SignatureStatus::glyph()`), which fails if the format is one of git's built-in formats.
+    pub fn one_line_log(
+        num: Option<u16>,
+        format: Option<&str>,
+        dates: Option<DateStyle>,
+        signatures: bool,
+        range: &LogDateRange,
+        args: &[String],
+        paths: &[String],
+    ) -> GitResult {
+        trace!(
+            "log_oneline() called with: {:#?}, {:#?}, {:#?}, {}, {:#?}, {:#?}, {:#?}",
+            num,
+            format,
+            dates,
+            signatures,
+            range,
+            args,
+            paths
+        );
+
+        let args = Git::with_pathspecs(args, paths)?;
+
+        // `%ad` (and the other `%*d` placeholders) follow `--date`
+        let dates = dates.or(Settings::get().dates).unwrap_or(DateStyle::Short);
+
+        let formats = &Settings::get().formats;
+        let pretty = match format {
+            Some(name) => formats
+                .get(&name.to_lowercase())
+                .map_or(name, String::as_str),
+            None => formats
+                .get("l")
+                .map_or(DEFAULT_ONE_LINE_FORMAT, String::as_str),
+        };
+
+        let pretty = match signatures {
+            true => format!("--pretty={}", SignatureStatus::with_status(pretty)?),
+            false => format!("--pretty={pretty}"),
+        };
+        let max_count = format!("--max-count={}", num.unwrap_or(Settings::get().log_count));
+        let mut default_args = vec![pretty.as_str(), dates.as_arg(), &max_count];
+        let range_args = range.args();
+        default_args.extend(range_args.iter().map(String::as_str));
+
+        let log_output: Output = Commands::output_with_timeout(
+            &mut GitCommand::new("log")
+                .with_default_args(&default_args)
+                .with_user_args(&args)
+                .construct_git_command(),
+        )
+        .with_context(|| "Failed to execute 'git log' command")?;
+
+        if log_output.status.success() {
+            match signatures {
+                true => OutputTarget::writer()?.write_all(
+                    SignatureStatus::replace_with_glyphs(&String::from_utf8_lossy(
+                        &log_output.stdout,
+                    ))
+                    .as_bytes(),
+                )?,
+                false => OutputTarget::writer()?.write_all(&log_output.stdout)?,
+            }
 
             io::stderr().write_all(&log_output.stderr)?;
 
             Ok(GitCommandResult::Success)
         } else {
-            io::stdout().write_all(&log_output.stdout)?;
+            OutputTarget::writer()?.write_all(&log_output.stdout)?;
             io::stderr().write_all(&log_output.stderr)?;
 
             Ok(GitCommandResult::Error)
@@ -144,37 +1060,483 @@ impl ImmutableCommands {
     }
 
     /// `git show --expand-tabs=4 --max-count=NUM ARGS`
-    pub fn show(num: Option<u16>, args: &[String]) -> GitResult {
-        trace!("show() called with: {:#?}", num);
-
-        GitCommand::new("show")
-            .with_default_args(&[
-                "--expand-tabs=4",
-                &format!("--max-count={}", num.unwrap_or(1)),
-            ])
+    ///
+    /// Unless `word_diff` is given, the output is piped through the diff highlighter (see `git-util.tools.diff`) when
+    /// it's shown in a terminal.
+    pub fn show(
+        num: Option<u16>,
+        renames: &RenameDetection,
+        word_diff: Option<WordDiff>,
+        args: &[String],
+    ) -> GitResult {
+        trace!("show() called with: {:#?}, {renames:?}, {word_diff:?}", num);
+
+        let max_count = format!("--max-count={}", num.unwrap_or(1));
+        let rename_args = renames.args();
+
+        let mut default_args = vec!["--expand-tabs=4", &max_count];
+        default_args.extend(rename_args.iter().map(String::as_str));
+        if let Some(word_diff) = word_diff {
+            default_args.extend(word_diff.args());
+        }
+
+        // highlighters expect line diffs, so they'd mangle word diffs
+        if word_diff.is_some() {
+            GitCommand::new("show")
+                .with_default_args(&default_args)
+                .with_user_args(args)
+                .run()
+        } else {
+            GitCommand::new("show")
+                .with_default_args(&default_args)
+                .with_user_args(args)
+                .run_with_diff_highlighter()
+        }
+    }
+
+    /// `git log -S PATTERN --pretty=FORMAT ARGS` (or `-G PATTERN` if `regex` is `true`)
+    ///
+    /// Lists the matching commits in the same format as `l`. If `show` is `true`, the most recent match is shown with
+    /// `show` instead.
+    pub fn dig(pattern: &str, regex: bool, show: bool, args: &[String]) -> GitResult {
+        trace!("dig() called with: {pattern}, {regex}, {show}, {args:#?}");
+
+        let pickaxe = if regex {
+            format!("-G{pattern}")
+        } else {
+            format!("-S{pattern}")
+        };
+
+        if show {
+            let first_match = GitCommand::new("log")
+                .with_default_args(&[&pickaxe, "--max-count=1", "--format=%H"])
+                .with_user_args(args)
+                .stdout()?;
+
+            if first_match.is_empty() {
+                Print::info(&format!("No commits found for '{pattern}'"));
+                return Ok(GitCommandResult::Error);
+            }
+
+            return ImmutableCommands::show(
+                Some(1),
+                &RenameDetection::default(),
+                None,
+                &[first_match],
+            );
+        }
+
+        let pretty = format!("--pretty={DEFAULT_ONE_LINE_FORMAT}");
+        let date = Settings::get().dates.unwrap_or(DateStyle::Short).as_arg();
+
+        GitCommand::new("log")
+            .with_default_args(&[&pickaxe, &pretty, date])
             .with_user_args(args)
             .run()
     }
 
-    /// `git show --pretty='' --name-only --max-count=NUM`
-    pub fn show_files(num: Option<u16>) -> GitResult {
-        trace!("show_files() called with: {:#?}", num);
+    /// `git log --all --source --grep=PATTERN [--regexp-ignore-case] --pretty=FORMAT ARGS`
+    ///
+    /// Uses the same format as `l`, plus the branch (or other ref) each commit was found on. If `current` is `true`,
+    /// only the current branch is searched (and the branch isn't shown).
+    pub fn find(pattern: &str, ignore_case: bool, current: bool, args: &[String]) -> GitResult {
+        trace!("find() called with: {pattern}, {ignore_case}, {current}, {args:#?}");
 
-        GitCommand::new("show")
-            .with_default_args(&[
-                "--pretty=",
-                "--name-only",
-                &format!("--max-count={}", num.unwrap_or(1)),
-            ])
-            .run()
+        let grep = format!("--grep={pattern}");
+        let pretty = format!(
+            "--pretty={}",
+            if current {
+                DEFAULT_ONE_LINE_FORMAT
+            } else {
+                FIND_FORMAT
+            }
+        );
+        let date = Settings::get().dates.unwrap_or(DateStyle::Short).as_arg();
+
+        let mut default_args = vec![grep.as_str(), pretty.as_str(), date];
+        if !current {
+            default_args.extend(["--all", "--source"]);
+        }
+        if ignore_case {
+            default_args.push("--regexp-ignore-case");
+        }
+
+        let log_output: Output = Commands::output_with_timeout(
+            &mut GitCommand::new("log")
+                .with_default_args(&default_args)
+                .with_user_args(args)
+                .construct_git_command(),
+        )
+        .with_context(|| "Failed to execute 'git log' command")?;
+
+        // `%S` is the full ref name; shorten it the same way `git branch` does (it follows a space or a color code)
+        let ref_prefix = Regex::new(r"(?m)^(.*?[ m])refs/(?:heads|remotes)/")?;
+        let matches = ref_prefix.replace_all(std::str::from_utf8(&log_output.stdout)?, "$1");
+
+        OutputTarget::writer()?.write_all(matches.as_bytes())?;
+        io::stderr().write_all(&log_output.stderr)?;
+
+        if log_output.status.success() {
+            Ok(GitCommandResult::Success)
+        } else {
+            Ok(GitCommandResult::Error)
+        }
+    }
+
+    /// Show the commit that added `path` (following renames) and, if `path` no longer exists, the commit that deleted it.
+    ///
+    /// `git log --diff-filter=A --follow -- PATH` and `git log --diff-filter=D --max-count=1 -- PATH`
+    pub fn origin(path: &str) -> GitResult {
+        trace!("origin() called with: {path}");
+
+        const FORMAT: &str = "--format=%h%x00%ad%x00%an%x00%s";
+        let date = Settings::get().dates.unwrap_or(DateStyle::Short).as_arg();
+
+        // with `--follow`, the last (i.e. oldest) addition is the one under the file's original name
+        let added = GitCommand::new("log")
+            .with_default_args(&["--diff-filter=A", "--follow", FORMAT, date, "--", path])
+            .stdout()?;
+        let added = added.lines().last();
+
+        let deleted = if std::path::Path::new(path).exists() {
+            None
+        } else {
+            Some(
+                GitCommand::new("log")
+                    .with_default_args(&[
+                        "--diff-filter=D",
+                        "--max-count=1",
+                        FORMAT,
+                        date,
+                        "--",
+                        path,
+                    ])
+                    .stdout()?,
+            )
+            .filter(|deleted| !deleted.is_empty())
+        };
+
+        if added.is_none() && deleted.is_none() {
+            return Err(anyhow!("'{}' has never been tracked", path));
+        }
+
+        let mut table = Table::new();
+        for (label, commit) in [("added", added), ("deleted", deleted.as_deref())] {
+            if let Some(commit) = commit {
+                table.add_row(std::iter::once(label).chain(commit.split('\0')));
+            }
+        }
+
+        write!(OutputTarget::writer()?, "{table}")?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// `git show --pretty='' --name-only --max-count=NUM [-z]`
+    ///
+    /// If `null_terminated` is `true`, the file names are separated by NUL characters instead of newlines. If rename
+    /// (or copy) detection is enabled, renamed files are listed as `OLD -> NEW`.
+    pub fn show_files(
+        num: Option<u16>,
+        null_terminated: bool,
+        renames: &RenameDetection,
+    ) -> GitResult {
+        trace!(
+            "show_files() called with: {:#?}, {null_terminated}, {renames:?}",
+            num
+        );
+
+        let max_count = format!("--max-count={}", num.unwrap_or(1));
+
+        if !renames.is_enabled() {
+            let mut args = vec!["--pretty=", "--name-only", &max_count];
+
+            if null_terminated {
+                args.push("-z");
+            }
+
+            return GitCommand::new("show").with_default_args(&args).run();
+        }
+
+        let rename_args = renames.args();
+        let mut args = vec!["--pretty=", "--name-status", "-z", &max_count];
+        args.extend(rename_args.iter().map(String::as_str));
+
+        let output = GitCommand::new("show").with_default_args(&args).stdout()?;
+
+        // `STATUS\0PATH\0`, or `STATUS\0OLD\0NEW\0` for renames and copies (whose status is e.g. `R087`)
+        let mut fields = output.split('\0').filter(|field| !field.trim().is_empty());
+        let mut files = Vec::new();
+
+        while let Some(status) = fields.next() {
+            let Some(path) = fields.next() else {
+                break;
+            };
+
+            if status.trim_start().starts_with(['R', 'C']) {
+                if let Some(new_path) = fields.next() {
+                    files.push(format!("{path} -> {new_path}"));
+                    continue;
+                }
+            }
+
+            files.push(path.to_string());
+        }
+
+        let separator = if null_terminated { '\0' } else { '\n' };
+        let mut writer = OutputTarget::writer()?;
+        for file in files {
+            write!(writer, "{file}{separator}")?;
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// List stashes as a table of index, the branch the stash was created on, relative age, and message.
+    ///
+    /// If `file_counts` is `true`, the number of files changed in each stash is included.
+    pub fn stash_list(file_counts: bool) -> GitResult {
+        trace!("stash_list() called with: {file_counts}");
+
+        let stashes = GitCommand::new("stash")
+            .with_default_args(&["list", "--format=%gd%x09%cr%x09%gs"])
+            .stdout()?;
+
+        let mut table = Table::new();
+
+        for line in stashes.lines() {
+            let mut fields = line.splitn(3, '\t');
+            let (Some(stash), Some(age), Some(subject)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+
+            let index = stash.trim_start_matches("stash@{").trim_end_matches('}');
+
+            // the subject is either "WIP on BRANCH: SHA SUBJECT" or "On BRANCH: MESSAGE"
+            let (branch, message) = subject
+                .trim_start_matches("WIP on ")
+                .trim_start_matches("On ")
+                .split_once(": ")
+                .unwrap_or(("", subject));
+
+            let mut row = vec![index.to_string(), branch.to_string(), age.to_string()];
+
+            if file_counts {
+                let files = GitCommand::new("stash")
+                    .with_default_args(&["show", "--name-only", stash])
+                    .stdout()?;
+                row.push(format!("{} files", files.lines().count()));
+            }
+
+            row.push(message.to_string());
+            table.add_row(row);
+        }
+
+        print!("{table}");
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// `git stash show --stat [--patch] stash@{INDEX}`
+    pub fn stash_show(index: Option<u16>, patch: bool) -> GitResult {
+        trace!("stash_show() called with: {index:?}, {patch}");
+
+        let stash = format!("stash@{{{}}}", index.unwrap_or(0));
+
+        let mut args = vec!["show", "--stat"];
+        if patch {
+            args.push("--patch");
+        }
+        args.push(&stash);
+
+        GitCommand::new("stash").with_default_args(&args).run()
     }
 
     /// `git status --short`
+    ///
+    /// Warns if any files are assumed unchanged, since their changes aren't shown.
     pub fn status_short() -> GitResult {
         trace!("status_short() called");
 
-        GitCommand::new("status")
+        let result = GitCommand::new("status")
             .with_default_args(&["--short"])
-            .run()
+            .run()?;
+
+        let assumed_unchanged = Git::assumed_unchanged_files()?.len();
+        if assumed_unchanged > 0 {
+            Print::stderr_purple(&format!(
+                "{} files are assumed unchanged, so changes to them aren't shown (see `{}`)",
+                assumed_unchanged, "git-util assume --list"
+            ));
+        }
+
+        Ok(result)
+    }
+}
+
+/// Interprets the signature status codes of `git log --format=%G?`.
+struct SignatureStatus();
+
+impl SignatureStatus {
+    /// Whether `status` is a good signature (even if the validity of the key is unknown or it has since expired).
+    fn is_valid(status: char) -> bool {
+        matches!(status, 'G' | 'U' | 'X' | 'Y')
+    }
+
+    /// A colored description of `status`.
+    fn describe(status: char) -> String {
+        let description = match status {
+            'G' => "good",
+            'U' => "good (unknown validity)",
+            'X' => "good (expired signature)",
+            'Y' => "good (expired key)",
+            'R' => "revoked key",
+            'E' => "can't be checked (missing key)",
+            'B' => "bad",
+            _ => "unsigned",
+        };
+
+        Colors::paint(
+            SignatureStatus::color(status),
+            description,
+            Colors::stdout(),
+        )
+        .to_string()
+    }
+
+    /// A colored glyph for `status`: `✓` for a good signature, `✗` for a bad one or a revoked key, `?` if it can't be
+    /// checked, and `·` if there's none.
+    fn glyph(status: char) -> String {
+        let glyph = match status {
+            'G' | 'U' | 'X' | 'Y' => "✓",
+            'R' | 'B' => "✗",
+            'E' => "?",
+            _ => "·",
+        };
+
+        Colors::paint(SignatureStatus::color(status), glyph, Colors::stdout()).to_string()
+    }
+
+    fn color(status: char) -> Color {
+        match status {
+            'G' | 'U' => Color::Green,
+            'X' | 'Y' => Color::Yellow,
+            _ => Color::Red,
+        }
+    }
+
+    /// Replace the status that `with_status()` prefixed each commit in `log` with by its glyph.
+    fn replace_with_glyphs(log: &str) -> String {
+        log.split_inclusive('\n')
+            .map(|line| match line.split_once(SIGNATURE_STATUS_SEPARATOR) {
+                // with `--graph`, the status comes after the graph
+                Some((before, after)) => match before.chars().last() {
+                    Some(status) => format!(
+                        "{}{} {}",
+                        &before[..before.len() - status.len_utf8()],
+                        SignatureStatus::glyph(status),
+                        after
+                    ),
+                    None => line.to_string(),
+                },
+                None => line.to_string(),
+            })
+            .collect()
+    }
+
+    /// `pretty` with each commit prefixed by its `%G?` status, for `replace_with_glyphs()`.
+    ///
+    /// Fails if `pretty` is the name of a built-in format (e.g. `oneline`) rather than a format string.
+    fn with_status(pretty: &str) -> Result<String> {
+        let format = pretty
+            .strip_prefix("tformat:")
+            .or_else(|| pretty.strip_prefix("format:"))
+            .unwrap_or(pretty);
+
+        if !format.contains('%') {
+            return Err(anyhow!(
+                "--signatures needs a format string, not the built-in format '{}'",
+                pretty
+            ));
+        }
+
+        Ok(format!("tformat:%G?{SIGNATURE_STATUS_SEPARATOR}{format}"))
+    }
+}
+
+/// Verify the signature on the annotated tag `tag`, returning its status (as a `%G?` code), signer, and key.
+///
+/// `git verify-tag --raw TAG`
+fn verify_tag(tag: &str) -> Result<(char, String, String)> {
+    let signed = !GitCommand::new("for-each-ref")
+        .with_default_args(&[
+            "--format=%(contents:signature)",
+            &format!("refs/tags/{tag}"),
+        ])
+        .stdout()?
+        .is_empty();
+
+    if !signed {
+        return Ok(('N', String::new(), String::new()));
+    }
+
+    let output = GitCommand::new("verify-tag")
+        .with_default_args(&["--raw", tag])
+        .output()?;
+    let raw = String::from_utf8_lossy(&output.stderr);
+
+    debug!("verify-tag --raw {tag}: {raw}");
+
+    // GPG prints `[GNUPG:] GOODSIG KEY SIGNER`; SSH prints `Good "git" signature for SIGNER with KEY_TYPE key KEY`
+    let (signer, key) = match raw.lines().find_map(|line| line.split_once("GOODSIG ")) {
+        Some((_, rest)) => match rest.split_once(' ') {
+            Some((key, signer)) => (signer.to_string(), key.to_string()),
+            None => (String::new(), rest.to_string()),
+        },
+        None => Regex::new(r#"Good "git" signature for (\S+) with \S+ key (\S+)"#)?
+            .captures(&raw)
+            .map(|captures| (captures[1].to_string(), captures[2].to_string()))
+            .unwrap_or_default(),
+    };
+
+    Ok((if output.status.success() { 'G' } else { 'B' }, signer, key))
+}
+
+/// The column that `ImmutableCommands::stats` sorts authors by.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, clap::ValueEnum)]
+pub enum StatsSort {
+    Author,
+    Commits,
+    Insertions,
+    Deletions,
+    Files,
+}
+
+/// The contributions of a single author, as counted by `ImmutableCommands::stats`.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct AuthorStats {
+    commits: usize,
+    insertions: usize,
+    deletions: usize,
+    files: BTreeSet<String>,
+}
+
+/// Execute `command` with colors (if stdout supports them) and return its stdout.
+///
+/// Fails with the contents of stderr if `git` exits with a non-zero status.
+fn colored_stdout(command: GitCommand) -> Result<String> {
+    let output = Commands::output_with_timeout(&mut command.construct_git_command())
+        .with_context(|| format!("Failed to execute 'git {}' command", command.subcommand))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8(output.stdout)?)
+    } else {
+        Err(anyhow!(
+            "'git {}' failed: {}",
+            command.subcommand,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
     }
 }
@@ -0,0 +1,210 @@
+use crate::{
+    git::{commands::profile::Profile, Git, GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    output::OutputTarget,
+    print::Print,
+    settings::{self, expand_home, Settings},
+    table::Table,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+/// Manages the conditional includes (`[includeIf "gitdir:DIR/"]`) in the global config that give every repository
+/// under a directory its own config, e.g. the identity of a profile.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Include();
+
+impl Include {
+    /// Include `target` in the config of every repository under `directory`, by setting
+    /// `includeIf.gitdir:DIRECTORY/.path` in the global config.
+    ///
+    /// `target` is the name of a profile (whose identity is written to `profiles/NAME.gitconfig` in git-util's config
+    /// directory) or the path of a config file.
+    pub fn route(directory: &str, target: &str) -> GitResult {
+        trace!("Include::route() called with: {directory}, {target}");
+
+        let directory = fs::canonicalize(expand_home(directory))
+            .ok()
+            .filter(|directory| directory.is_dir())
+            .ok_or_else(|| anyhow!("{} is not a directory", directory))?;
+
+        let file = match Settings::get().profiles.get(target) {
+            Some(profile) => Include::write_profile(target, profile)?,
+            None => fs::canonicalize(expand_home(target))
+                .ok()
+                .filter(|file| file.is_file())
+                .ok_or_else(|| anyhow!("{} is neither a profile nor a config file", target))?,
+        };
+
+        // the trailing slash makes the pattern match every repository under the directory
+        let condition = format!("gitdir:{}/", Include::config_path(&directory));
+        let key = format!("includeIf.{condition}.path");
+        let file = Include::config_path(&file);
+
+        // `git config --get-all` exits with 1 if the key isn't set
+        let current = GitCommand::new("config")
+            .with_default_args(&["--global", "--get-all", &key])
+            .output()?;
+        if String::from_utf8_lossy(&current.stdout)
+            .lines()
+            .any(|current| current == file)
+        {
+            Print::info(&format!("{} already includes {}", condition, file));
+            return Ok(GitCommandResult::Success);
+        }
+
+        // the directory may already include other files, which are kept
+        GitCommand::new("config")
+            .with_default_args(&["--global", "--add", &key, &file])
+            .run()
+    }
+
+    /// List the conditional includes from every config file, with the identity that each included file sets.
+    ///
+    /// `git config --get-regexp ^includeif\..*\.path$`
+    pub fn routes() -> GitResult {
+        trace!("Include::routes() called");
+
+        let includes = Git::config_values_matching(r"^includeif\..*\.path$")?;
+        debug!("includes: {includes:#?}");
+
+        if includes.is_empty() {
+            Print::info("There are no conditional includes");
+            return Ok(GitCommandResult::Success);
+        }
+
+        let mut table = Table::new();
+        table.add_row(["CONDITION", "FILE", "USER"]);
+
+        for (key, file) in includes {
+            let condition = key
+                .strip_prefix("includeif.")
+                .and_then(|key| key.strip_suffix(".path"))
+                .unwrap_or(&key);
+
+            // relative paths are relative to the file that includes them, which is usually in `$HOME`
+            let path = expand_home(&file);
+            let user = if path.is_file() {
+                Include::identity(&path)?
+            } else {
+                "(missing)".to_string()
+            };
+
+            table.add_row([condition, &file, &user]);
+        }
+
+        write!(OutputTarget::writer()?, "{table}")?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Write the identity of the profile `name` to `profiles/NAME.gitconfig` in git-util's config directory, for
+    /// including in other config files; returns its path.
+    fn write_profile(name: &str, profile: &Profile) -> Result<PathBuf> {
+        let file = settings::config_directory()?
+            .join("profiles")
+            .join(format!("{name}.gitconfig"));
+
+        if DRY_RUN.load(Ordering::SeqCst) {
+            Print::stderr_purple(&format!(
+                "would write the {name} profile to {}",
+                file.display()
+            ));
+            return Ok(file);
+        }
+
+        if let Some(directory) = file.parent() {
+            fs::create_dir_all(directory)
+                .with_context(|| format!("Failed to create {}", directory.display()))?;
+        }
+
+        let mut settings = vec![
+            ("user.name", profile.name.as_deref()),
+            ("user.email", profile.email.as_deref()),
+            ("user.signingKey", profile.signing_key.as_deref()),
+        ];
+        if profile.signing_key.is_some() {
+            settings.push(("commit.gpgSign", Some("true")));
+        }
+
+        let file_arg = file.to_string_lossy();
+        for (key, value) in settings {
+            let mut args = vec!["--file", &file_arg];
+            match value {
+                Some(value) => args.extend([key, value]),
+                // a field that was removed from the profile since the file was written
+                None => args.extend(["--unset-all", key]),
+            }
+
+            let output = GitCommand::new("config")
+                .with_default_args(&args)
+                .output()?;
+            // `git config --unset-all` exits with 5 if the key isn't set
+            if !output.status.success() && value.is_some() {
+                return Err(anyhow!(
+                    "Failed to write {}: {}",
+                    file.display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+
+        Print::info(&format!("Wrote the {name} profile to {}", file.display()));
+
+        Ok(file)
+    }
+
+    /// `path` as it's written in a config file: with forward slashes, and without the `\\?\` prefix that
+    /// `fs::canonicalize()` adds on Windows, which `gitdir:` patterns don't match.
+    fn config_path(path: &Path) -> String {
+        let path = path.to_string_lossy();
+
+        path.strip_prefix(r"\\?\")
+            .unwrap_or(&path)
+            .replace('\\', "/")
+    }
+
+    /// `NAME <EMAIL>`, as set by the config file `path` (without following its own includes).
+    fn identity(path: &Path) -> Result<String> {
+        let value = |key: &str| -> Result<Option<String>> {
+            let output = GitCommand::new("config")
+                .with_default_args(&["--file", &path.to_string_lossy(), "--get", key])
+                .output()?;
+
+            Ok(output.status.success().then(|| {
+                String::from_utf8_lossy(&output.stdout)
+                    .trim_end()
+                    .to_string()
+            }))
+        };
+
+        Ok(match (value("user.name")?, value("user.email")?) {
+            (Some(name), Some(email)) => format!("{name} <{email}>"),
+            (Some(name), None) => name,
+            (None, Some(email)) => format!("<{email}>"),
+            (None, None) => "-".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_path_uses_forward_slashes() {
+        assert_eq!(
+            Include::config_path(Path::new("/home/me/work")),
+            "/home/me/work"
+        );
+        assert_eq!(
+            Include::config_path(Path::new(r"\\?\C:\Users\me\work")),
+            "C:/Users/me/work"
+        );
+    }
+}
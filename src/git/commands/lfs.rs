@@ -0,0 +1,249 @@
+use crate::{
+    commands::Commands,
+    git::{Git, GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    output::OutputTarget,
+    print::Print,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use std::{collections::BTreeSet, fs, io::Write, sync::atomic::Ordering};
+
+/// The first line of every Git LFS pointer file.
+const POINTER_PREFIX: &str = "version https://git-lfs.github.com/spec/";
+
+/// Pointer files are always smaller than this, so anything bigger must be a regular blob.
+const MAX_POINTER_SIZE: u64 = 1024;
+
+/// Matches every file in the repository (not just under the current directory) that has the LFS filter.
+const LFS_PATHSPEC: &str = ":(top,attr:filter=lfs)";
+
+/// The attributes that `git lfs track` writes for each pattern.
+const LFS_ATTRIBUTES: &str = "filter=lfs diff=lfs merge=lfs -text";
+
+/// Inspects and configures the files that are stored with **Git LFS**, without needing `git-lfs` itself.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Lfs();
+
+impl Lfs {
+    /// Print the patterns that are tracked by LFS and the files in `HEAD` that match them but were committed as
+    /// regular blobs instead of LFS pointers.
+    ///
+    /// Returns `Error` if there are any such files.
+    pub fn status() -> GitResult {
+        trace!("Lfs::status() called");
+
+        let patterns = Lfs::tracked_patterns()?;
+
+        if patterns.is_empty() {
+            Print::info("No patterns are tracked by LFS");
+            return Ok(GitCommandResult::Success);
+        }
+
+        let mut writer = OutputTarget::writer()?;
+        writeln!(writer, "Tracked patterns:\n\n  {}", patterns.join("\n  "))?;
+
+        if !Commands::is_available("git-lfs") {
+            Print::stderr_purple(
+                "\ngit-lfs is not installed; LFS files won't be converted to pointers",
+            );
+        }
+
+        let lfs_files = Lfs::files_with_filter()?;
+        let mut regular_blobs = Vec::new();
+
+        for line in GitCommand::new("ls-tree")
+            .with_default_args(&["-r", "-l", "-z", "--full-tree", "HEAD"])
+            .stdout()?
+            .split('\0')
+        {
+            // `MODE TYPE OBJECT SIZE\tPATH`
+            let Some((metadata, path)) = line.split_once('\t') else {
+                continue;
+            };
+            if !lfs_files.contains(path) {
+                continue;
+            }
+
+            let mut fields = metadata.split_whitespace().skip(2);
+            let (Some(object), Some(size)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+
+            if !is_pointer(object, size.parse().unwrap_or(u64::MAX))? {
+                regular_blobs.push(path.to_string());
+            }
+        }
+
+        writeln!(
+            writer,
+            "\n{} files tracked by LFS, {} committed as regular blobs",
+            lfs_files.len(),
+            regular_blobs.len()
+        )?;
+
+        if regular_blobs.is_empty() {
+            return Ok(GitCommandResult::Success);
+        }
+
+        Print::stderr_purple(&format!("\n  {}", regular_blobs.join("\n  ")));
+
+        Ok(GitCommandResult::Error)
+    }
+
+    /// Track `patterns` with LFS by adding them to the root `.gitattributes`, then stage it.
+    ///
+    /// Patterns that are already tracked are skipped.
+    pub fn track(patterns: &[String]) -> GitResult {
+        trace!("Lfs::track() called with: {patterns:#?}");
+
+        let path = Git::repo_root()?.join(".gitattributes");
+        let mut attributes = fs::read_to_string(&path).unwrap_or_default();
+        let tracked = Lfs::tracked_patterns()?;
+
+        let new_patterns: Vec<&String> = patterns
+            .iter()
+            .filter(|pattern| !tracked.contains(pattern))
+            .collect();
+
+        if new_patterns.is_empty() {
+            Print::info("Those patterns are already tracked by LFS");
+            return Ok(GitCommandResult::Success);
+        }
+
+        if !attributes.is_empty() && !attributes.ends_with('\n') {
+            attributes.push('\n');
+        }
+        for pattern in &new_patterns {
+            attributes.push_str(&format!("{pattern} {LFS_ATTRIBUTES}\n"));
+        }
+
+        if DRY_RUN.load(Ordering::SeqCst) {
+            Print::stderr_purple(&format!(
+                "would add to {}: {}",
+                path.display(),
+                new_patterns
+                    .iter()
+                    .map(|pattern| pattern.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        } else {
+            fs::write(&path, attributes)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        GitCommand::new("add")
+            .with_default_args(&[&path.to_string_lossy()])
+            .run()
+    }
+
+    /// The added or modified staged files that match an LFS pattern, but are staged as regular blobs instead of LFS
+    /// pointers.
+    ///
+    /// This happens when `git-lfs` isn't installed, or the pattern was added after the file was staged.
+    pub fn staged_regular_blobs() -> Result<Vec<String>> {
+        trace!("Lfs::staged_regular_blobs() called");
+
+        let staged: BTreeSet<String> = GitCommand::new("diff")
+            .with_default_args(&[
+                "--cached",
+                "--name-only",
+                "--diff-filter=AM",
+                "-z",
+                "--",
+                LFS_PATHSPEC,
+            ])
+            .stdout()?
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(String::from)
+            .collect();
+
+        Lfs::regular_blobs(&staged)
+    }
+
+    /// The files among `paths` (relative to the root of the work tree) that match an LFS pattern, but are in the index
+    /// as regular blobs instead of LFS pointers.
+    pub fn regular_blobs(paths: &BTreeSet<String>) -> Result<Vec<String>> {
+        trace!("Lfs::regular_blobs() called with: {paths:?}");
+
+        let mut regular_blobs = Vec::new();
+        if paths.is_empty() {
+            return Ok(regular_blobs);
+        }
+
+        // `MODE OBJECT STAGE\tPATH` for each file in the index with the LFS filter
+        for line in GitCommand::new("ls-files")
+            .with_default_args(&["--stage", "--full-name", "-z", "--", LFS_PATHSPEC])
+            .stdout()?
+            .split('\0')
+        {
+            let Some((metadata, path)) = line.split_once('\t') else {
+                continue;
+            };
+            if !paths.contains(path) {
+                continue;
+            }
+            let Some(object) = metadata.split_whitespace().nth(1) else {
+                continue;
+            };
+
+            let size = GitCommand::new("cat-file")
+                .with_default_args(&["-s", object])
+                .stdout()?
+                .parse()
+                .map_err(|_| anyhow!("Unable to get the size of {}", path))?;
+
+            if !is_pointer(object, size)? {
+                regular_blobs.push(path.to_string());
+            }
+        }
+
+        debug!("regular blobs: {regular_blobs:#?}");
+
+        Ok(regular_blobs)
+    }
+
+    /// The patterns in the root `.gitattributes` that have the LFS filter.
+    fn tracked_patterns() -> Result<Vec<String>> {
+        let path = Git::repo_root()?.join(".gitattributes");
+
+        Ok(fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| {
+                line.split_whitespace()
+                    .any(|attribute| attribute == "filter=lfs")
+            })
+            .filter_map(|line| line.split_whitespace().next())
+            .map(String::from)
+            .collect())
+    }
+
+    /// The paths of the files in the index that have the LFS filter.
+    ///
+    /// `git ls-files --full-name -z -- ':(top,attr:filter=lfs)'`
+    fn files_with_filter() -> Result<BTreeSet<String>> {
+        Ok(GitCommand::new("ls-files")
+            .with_default_args(&["--full-name", "-z", "--", LFS_PATHSPEC])
+            .stdout()?
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(String::from)
+            .collect())
+    }
+}
+
+/// Check whether the blob `object` (which is `size` bytes) is an LFS pointer.
+fn is_pointer(object: &str, size: u64) -> Result<bool> {
+    if size >= MAX_POINTER_SIZE {
+        return Ok(false);
+    }
+
+    // the blob may well be binary, so don't decode it
+    let output = GitCommand::new("cat-file")
+        .with_default_args(&["blob", object])
+        .output()?;
+
+    Ok(output.stdout.starts_with(POINTER_PREFIX.as_bytes()))
+}
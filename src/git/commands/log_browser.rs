@@ -0,0 +1,316 @@
+use crate::{
+    color::Colors,
+    commands::Commands,
+    git::{
+        commands::mutable::{branch, commit},
+        DateStyle, Git, GitCommand, GitCommandResult, GitResult, LogDateRange,
+    },
+    print::Print,
+    settings::Settings,
+    tui::{self, Key, Terminal},
+};
+use anyhow::Result;
+use log::{debug, trace};
+use nu_ansi_term::Style;
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// The programs that `y` copies the SHA with, in order of preference.
+const CLIPBOARD_PROGRAMS: [(&str, &[&str]); 5] = [
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+const HELP: &str =
+    "j/k: select  space/b: scroll preview  y: copy SHA  c: check out  r: revert  f: fixup  q: quit";
+
+/// A full-screen browser for the one-line log: a scrollable list of commits, with a preview of the selected one.
+#[derive(Debug)]
+pub struct LogBrowser {
+    commits: Vec<Commit>,
+    selected: usize,
+    /// The index of the first commit in the list that's on screen.
+    list_offset: usize,
+    /// The number of lines of the preview that are scrolled past.
+    preview_offset: usize,
+    /// The `git show` output of each commit that has been selected.
+    previews: BTreeMap<String, Vec<String>>,
+    /// A message shown in place of the help, until the next key is pressed.
+    message: Option<String>,
+}
+
+#[derive(Debug)]
+struct Commit {
+    sha: String,
+    line: String,
+}
+
+/// What to do with the selected commit once the browser has closed.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Action {
+    Checkout,
+    Revert,
+    Fixup,
+}
+
+impl LogBrowser {
+    /// Browse the commits that `l` would list; checking out, reverting, or making a fixup commit for a commit closes
+    /// the browser first, so that `git`'s output is left on the screen.
+    pub fn browse(
+        num: Option<u16>,
+        dates: Option<DateStyle>,
+        range: &LogDateRange,
+        args: &[String],
+        paths: &[String],
+    ) -> GitResult {
+        trace!(
+            "browse() called with: {:#?}, {:#?}, {:#?}, {:#?}, {:#?}",
+            num,
+            dates,
+            range,
+            args,
+            paths
+        );
+
+        let commits = LogBrowser::commits(num, dates, range, args, paths)?;
+
+        if commits.is_empty() {
+            Print::info("No commits to browse");
+            return Ok(GitCommandResult::Success);
+        }
+
+        let mut browser = LogBrowser {
+            commits,
+            selected: 0,
+            list_offset: 0,
+            preview_offset: 0,
+            previews: BTreeMap::new(),
+            message: None,
+        };
+
+        // the terminal is restored before the action runs
+        let action = browser.run(&mut Terminal::open()?)?;
+        debug!("action: {action:?}");
+
+        match action {
+            None => Ok(GitCommandResult::Success),
+            Some((Action::Checkout, sha)) => branch::checkout(&sha),
+            Some((Action::Revert, sha)) => commit::revert(&sha, false),
+            Some((Action::Fixup, sha)) => commit::fixup(Some(&sha), false),
+        }
+    }
+
+    /// The commits to list, with the same arguments as `l`.
+    ///
+    /// `git log --max-count=NUM --format=%H%x00... ARGS -- PATHS`
+    fn commits(
+        num: Option<u16>,
+        dates: Option<DateStyle>,
+        range: &LogDateRange,
+        args: &[String],
+        paths: &[String],
+    ) -> Result<Vec<Commit>> {
+        let args = Git::with_pathspecs(args, paths)?;
+        let dates = dates.or(Settings::get().dates).unwrap_or(DateStyle::Short);
+        let max_count = format!("--max-count={}", num.unwrap_or(Settings::get().log_count));
+        let color = if Colors::stdout() {
+            "--color=always"
+        } else {
+            "--color=never"
+        };
+
+        let mut default_args = vec![
+            "--format=%H%x00%C(yellow)%h%C(reset) %s %C(blue)(%ad)%C(reset) %C(green)<%an>%C(reset)",
+            dates.as_arg(),
+            &max_count,
+            color,
+        ];
+        let range_args = range.args();
+        default_args.extend(range_args.iter().map(String::as_str));
+
+        let log = GitCommand::new("log")
+            .with_default_args(&default_args)
+            .with_user_args(&args)
+            .stdout()?;
+
+        Ok(log
+            .lines()
+            .filter_map(|line| line.split_once('\0'))
+            .map(|(sha, line)| Commit {
+                sha: sha.to_string(),
+                line: line.to_string(),
+            })
+            .collect())
+    }
+
+    /// Handle keys until the browser is closed; returns the action to take, if one was chosen.
+    fn run(&mut self, terminal: &mut Terminal) -> Result<Option<(Action, String)>> {
+        loop {
+            let (rows, _) = terminal.size()?;
+            let list_height = self.commits.len().clamp(1, (rows / 3).max(1));
+            let preview_height = rows.saturating_sub(list_height + 2);
+
+            terminal.draw(&self.render(list_height, preview_height)?)?;
+
+            let key = terminal.read_key()?;
+            self.message = None;
+
+            match key {
+                Key::Char('q') | Key::Escape | Key::Interrupt => return Ok(None),
+                Key::Char('j') | Key::Down => self.select(self.selected + 1),
+                Key::Char('k') | Key::Up => self.select(self.selected.saturating_sub(1)),
+                Key::Char('g') | Key::Home => self.select(0),
+                Key::Char('G') | Key::End => self.select(self.commits.len() - 1),
+                Key::Char(' ') | Key::PageDown => {
+                    let lines = self.preview().map_or(0, Vec::len);
+                    self.preview_offset = (self.preview_offset + preview_height)
+                        .min(lines.saturating_sub(preview_height));
+                }
+                Key::Char('b') | Key::PageUp => {
+                    self.preview_offset = self.preview_offset.saturating_sub(preview_height);
+                }
+                Key::Char('y') => self.copy_sha(),
+                Key::Char(c @ ('c' | 'r' | 'f')) => {
+                    let (action, verb) = match c {
+                        'c' => (Action::Checkout, "Check out"),
+                        'r' => (Action::Revert, "Revert"),
+                        _ => (Action::Fixup, "Make a fixup commit for"),
+                    };
+
+                    let commit = &self.commits[self.selected];
+                    self.message = Some(format!("{verb} {}? [y/N]", short_sha(&commit.sha)));
+                    terminal.draw(&self.render(list_height, preview_height)?)?;
+
+                    if terminal.read_key()? == Key::Char('y') {
+                        return Ok(Some((action, self.commits[self.selected].sha.clone())));
+                    }
+                    self.message = None;
+                }
+                _ => {}
+            }
+
+            // keep the selected commit on screen
+            if self.selected < self.list_offset {
+                self.list_offset = self.selected;
+            } else if self.selected >= self.list_offset + list_height {
+                self.list_offset = self.selected + 1 - list_height;
+            }
+        }
+    }
+
+    /// Select the commit at `index` (clamped to the list), resetting the preview's scroll position if it changed.
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.commits.len() - 1);
+
+        if index != self.selected {
+            self.selected = index;
+            self.preview_offset = 0;
+        }
+    }
+
+    /// The lines of the screen: the list of commits, a divider, the preview, and the help (or a message).
+    fn render(&mut self, list_height: usize, preview_height: usize) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        for (i, commit) in self
+            .commits
+            .iter()
+            .enumerate()
+            .skip(self.list_offset)
+            .take(list_height)
+        {
+            lines.push(if i == self.selected {
+                format!("{} {}", Style::new().bold().paint(">"), commit.line)
+            } else {
+                format!("  {}", commit.line)
+            });
+        }
+
+        lines.push(tui::divider());
+
+        self.load_preview()?;
+        let preview = self.preview().map(Vec::as_slice).unwrap_or_default();
+        lines.extend(
+            preview
+                .iter()
+                .skip(self.preview_offset)
+                .take(preview_height)
+                .cloned(),
+        );
+        lines.resize(list_height + 1 + preview_height, String::new());
+
+        let footer = self.message.as_deref().unwrap_or(HELP);
+        lines.push(tui::footer(footer));
+
+        Ok(lines)
+    }
+
+    /// The preview of the selected commit, if it has been loaded.
+    fn preview(&self) -> Option<&Vec<String>> {
+        self.previews.get(&self.commits[self.selected].sha)
+    }
+
+    /// Load the preview of the selected commit, unless it has been already.
+    ///
+    /// `git show --stat --patch SHA`
+    fn load_preview(&mut self) -> Result<()> {
+        let sha = &self.commits[self.selected].sha;
+
+        if !self.previews.contains_key(sha) {
+            let color = if Colors::stdout() {
+                "--color=always"
+            } else {
+                "--color=never"
+            };
+
+            let show = GitCommand::new("show")
+                .with_default_args(&["--stat", "--patch", color, sha])
+                .stdout()?;
+
+            self.previews
+                .insert(sha.clone(), show.lines().map(String::from).collect());
+        }
+
+        Ok(())
+    }
+
+    /// Copy the SHA of the selected commit to the clipboard, with the first clipboard program that's installed.
+    fn copy_sha(&mut self) {
+        let sha = &self.commits[self.selected].sha;
+
+        let copied = CLIPBOARD_PROGRAMS
+            .iter()
+            .find(|(program, _)| Commands::is_available(program))
+            .map(|(program, args)| {
+                let mut child = Command::new(program)
+                    .args(*args)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()?;
+
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(sha.as_bytes())?;
+                }
+
+                Ok::<_, anyhow::Error>(child.wait()?.success())
+            });
+
+        self.message = Some(match copied {
+            Some(Ok(true)) => format!("Copied {sha} to the clipboard"),
+            Some(_) => format!("Failed to copy to the clipboard; the SHA is {sha}"),
+            None => format!("No clipboard program is installed; the SHA is {sha}"),
+        });
+    }
+}
+
+/// The abbreviated form of `sha`, for messages.
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(10)]
+}
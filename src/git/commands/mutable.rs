@@ -1,15 +1,51 @@
-use crate::git::{Git, GitCommand, GitCommandResult, GitResult};
+use crate::{
+    git::{
+        env_vars::GitUtilEnvVars,
+        hooks::config::HookConfig,
+        state::{Head, Operation},
+        Git, GitCommand, GitCommandResult, GitResult,
+    },
+    print::Print,
+    prompt::Prompt,
+};
 use anyhow::anyhow;
 use log::{debug, trace};
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
 
+pub mod absorb;
 pub mod add;
+pub mod backport;
+pub mod backup;
+pub mod branch;
+pub mod clone;
 pub mod commit;
+pub mod conflict;
+pub mod ignore;
 pub mod index;
+pub mod init;
+pub mod merge;
+pub mod operation;
+pub mod plan;
+pub mod release;
+pub mod remote;
+pub mod sparse;
+pub mod split;
+pub mod stash;
+pub mod template;
+pub mod trailer;
 
-// `git fetch --verbose origin:BRANCH`
-pub fn update_branch_from_remote(branch: &String) -> GitResult {
+// `git fetch --verbose origin BRANCH:BRANCH`
+pub fn update_branch_from_remote(branch: Option<&str>) -> GitResult {
     debug!("update() called with: {:#?}", branch);
 
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => Git::default_branch()?,
+    };
+
     if branch.is_empty() {
         return Err(anyhow!("Must supply branch name"));
     }
@@ -17,7 +53,7 @@ pub fn update_branch_from_remote(branch: &String) -> GitResult {
     GitCommand::new("fetch")
         .with_default_args(&["--verbose", "origin"])
         .with_user_args(&[format!("{0}:{0}", branch)])
-        .run()
+        .run_with_progress(&format!("updating {branch} from origin"))
 }
 
 /// Run `command` if the staging area is empty.
@@ -32,3 +68,128 @@ fn run_if_staging_empty(command: GitCommand) -> GitResult {
 
     command.run()
 }
+
+/// `base`, else the merge-base of `HEAD` and the current branch's upstream (else the default branch): where the
+/// commits that are only on the current branch start.
+fn branch_base(base: Option<&str>) -> anyhow::Result<String> {
+    trace!("branch_base() called with: {base:?}");
+
+    if let Some(base) = base {
+        return Ok(base.to_string());
+    }
+
+    let branch = Git::current_branch()?;
+    let target = match Git::upstream(&branch)? {
+        Some(upstream) => upstream,
+        None => Git::default_branch()?,
+    };
+
+    let merge_base = GitCommand::new("merge-base")
+        .with_default_args(&["HEAD", &target])
+        .stdout()
+        .map_err(|_| anyhow!("{} has no commits in common with {}", branch, target))?;
+    debug!("merge-base of HEAD and {target}: {merge_base}");
+
+    Ok(merge_base)
+}
+
+/// Fail if the current branch is protected by the repository's hook configuration (see `HookConfig::protection()`),
+/// unless `force_protected` is `true`.
+///
+/// With `force_protected`, `$GIT_UTIL_ALLOW_PROTECTED` is set for the commands that are run, so that the pre-commit
+/// hook allows the commit too.
+fn ensure_unprotected(force_protected: bool) -> anyhow::Result<()> {
+    trace!("ensure_unprotected() called with: {force_protected}");
+
+    // a detached `HEAD` isn't on any branch
+    let Ok(branch) = Git::current_branch() else {
+        return Ok(());
+    };
+    let Some(reason) = HookConfig::load()?.protection(&branch)? else {
+        return Ok(());
+    };
+
+    if !force_protected {
+        return Err(anyhow!(
+            "{} is protected, since {}; create a branch with `git switch -c NAME`, or use --force-protected",
+            branch,
+            reason
+        ));
+    }
+
+    debug!("{branch} is protected, since {reason}; continuing anyway");
+    env::set_var(String::from(GitUtilEnvVars::AllowProtected), "1");
+
+    Ok(())
+}
+
+/// Fail if a merge, rebase, cherry-pick, or other multi-step operation is in progress, since switching branches or
+/// rewriting history in the middle of it is confusing at best; it should be continued or aborted first.
+///
+/// Commands that just commit use `ensure_can_commit()` instead, since committing is part of some operations.
+fn ensure_no_operation() -> anyhow::Result<()> {
+    trace!("ensure_no_operation() called");
+
+    let Some(operation) = Operation::in_progress()? else {
+        return Ok(());
+    };
+
+    Err(match operation.continue_command() {
+        Some(_) => anyhow!(
+            "A {} is in progress; finish it with `git-util continue` (or `git-util continue --add` to stage the resolved conflicts), or cancel it with `git-util abort`",
+            operation
+        ),
+        None => anyhow!(
+            "A {} is in progress; end it with `git-util abort` first",
+            operation
+        ),
+    })
+}
+
+/// Fail if a commit made now would be lost (see `ensure_on_branch()`), except in the middle of an operation, where
+/// committing is part of it: e.g. amending at an `edit` stop of a rebase (where `HEAD` is detached), or concluding a
+/// merge once its conflicts are resolved.
+fn ensure_can_commit() -> anyhow::Result<()> {
+    trace!("ensure_can_commit() called");
+
+    match Operation::in_progress()? {
+        Some(operation) => {
+            debug!("committing in the middle of a {operation}");
+            Ok(())
+        }
+        None => ensure_on_branch(),
+    }
+}
+
+/// Fail if `HEAD` is detached, since commits made there are easily lost once another branch is checked out; on a
+/// terminal, offer to create a branch for them first instead.
+///
+/// `HEAD` is detached in the middle of a rebase too, so this should follow `ensure_no_operation()`.
+fn ensure_on_branch() -> anyhow::Result<()> {
+    trace!("ensure_on_branch() called");
+
+    let Some(commit) = Head::detached()? else {
+        return Ok(());
+    };
+
+    let problem = format!("HEAD is detached at {commit}, so new commits won't be on any branch");
+    let guidance = format!("{problem}; create one first with `git switch -c NAME`");
+
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(anyhow!(guidance));
+    }
+
+    Print::stderr_purple(&problem);
+    if !Prompt::confirm("Create a branch first?")? {
+        return Err(anyhow!(guidance));
+    }
+
+    let branch = Prompt::input("Branch name:", &format!("wip-{commit}"))?;
+    match GitCommand::new("switch")
+        .with_default_args(&["--create", &branch])
+        .run()?
+    {
+        GitCommandResult::Success => Ok(()),
+        GitCommandResult::Error => Err(anyhow!("Failed to create the branch {}", branch)),
+    }
+}
@@ -0,0 +1,468 @@
+use crate::{
+    commands::Commands,
+    git::{Git, GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    print::Print,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+use std::{collections::BTreeMap, env, sync::atomic::Ordering};
+
+/// The staged diff of a modified file, with no context lines, so that each hunk only spans the lines it changes.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+struct FileDiff {
+    path: String,
+    /// The lines before the first hunk, e.g. `diff --git a/x b/x` and `+++ b/x`.
+    header: Vec<String>,
+    hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+struct Hunk {
+    /// The lines of the hunk, starting with its `@@ -1,2 +1,3 @@` line.
+    lines: Vec<String>,
+    /// The first line in `HEAD` that the hunk changes; for a hunk that only adds lines, the line they follow.
+    old_start: usize,
+    /// The number of lines in `HEAD` that the hunk changes.
+    old_count: usize,
+    /// The index (in the commits since the base, oldest first) of the commit to fix up with the hunk, if one was found.
+    target: Option<usize>,
+}
+
+/// Commit each staged hunk as a `fixup!` of the commit (since `base`) that last changed the lines it changes, so that
+/// `git-util autosquash` can squash it into place; a git-absorb equivalent.
+///
+/// `base` defaults to where the current branch forked (see `branch_base()`). A hunk that only adds lines is matched
+/// to the commit that changed the lines around it. The hunks whose lines were last changed by more than one commit,
+/// or before `base`, stay staged, as do renames, new and deleted files, and changes to binary files and file modes.
+///
+/// `git blame BASE..HEAD -- PATH` for each staged file, then `git apply --cached --unidiff-zero` and `git commit
+/// --fixup=COMMIT` for each commit that's fixed up; the staging area is restored if one of them fails. Fails if the
+/// current branch is protected, unless `force_protected` is `true`.
+pub fn absorb(base: Option<&str>, force_protected: bool) -> GitResult {
+    trace!("absorb() called with: {base:?}, {force_protected}");
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+
+    if let GitCommandResult::Success = Git::verify_staging_area_is_empty()? {
+        Print::info("There are no staged changes to absorb");
+        return Ok(GitCommandResult::Success);
+    }
+
+    // the paths in the diff are relative to the root of the repository
+    env::set_current_dir(Git::repo_root()?)?;
+
+    let base = super::branch_base(base)?;
+    let commits: Vec<String> = GitCommand::new("rev-list")
+        .with_default_args(&["--reverse", &format!("{base}..HEAD")])
+        .stdout()?
+        .lines()
+        .map(String::from)
+        .collect();
+
+    if commits.is_empty() {
+        return Err(anyhow!(
+            "There are no commits since {} to absorb the staged changes into",
+            base
+        ));
+    }
+
+    let mut files = staged_files()?;
+    for file in &mut files {
+        let blame = blame(&base, &file.path)?;
+
+        for hunk in &mut file.hunks {
+            hunk.target = target(hunk, &blame, &commits);
+        }
+    }
+
+    let targets: BTreeMap<usize, usize> =
+        files
+            .iter()
+            .flat_map(|file| &file.hunks)
+            .fold(BTreeMap::new(), |mut targets, hunk| {
+                if let Some(target) = hunk.target {
+                    *targets.entry(target).or_default() += 1;
+                }
+                targets
+            });
+    let absorbed: usize = targets.values().sum();
+    let left = files.iter().map(|file| file.hunks.len()).sum::<usize>() - absorbed;
+
+    if targets.is_empty() {
+        Print::info(&format!(
+            "None of the staged changes could be matched to a single commit since {}",
+            short(&base)
+        ));
+        return Ok(GitCommandResult::Success);
+    }
+
+    for (target, count) in &targets {
+        let subject = GitCommand::new("log")
+            .with_default_args(&["--max-count=1", "--format=%h %s", &commits[*target]])
+            .stdout()?;
+        Print::info(&format!(
+            "{count} {} -> {subject}",
+            if *count == 1 { "hunk" } else { "hunks" }
+        ));
+    }
+
+    if DRY_RUN.load(Ordering::SeqCst) {
+        return Ok(GitCommandResult::Success);
+    }
+
+    let head = GitCommand::new("rev-parse")
+        .with_default_args(&["HEAD"])
+        .stdout()?;
+    let index = GitCommand::new("write-tree").stdout()?;
+
+    for target in targets.keys() {
+        if let Err(e) = fixup(&files, &head, *target, &commits[*target]) {
+            // undo the fixups that were made, and restore the staging area as it was
+            git(&["reset", "--quiet", "--soft", &head], None)?;
+            git(&["read-tree", &index], None)?;
+
+            return Err(
+                e.context("Failed to absorb the staged changes; they're staged as they were")
+            );
+        }
+    }
+
+    // the hunks that were absorbed are in `HEAD` now, so just the rest are staged
+    git(&["read-tree", &index], None)?;
+
+    if left > 0 {
+        Print::stderr_purple(&format!(
+            "{left} {} be matched to a single commit since {}, so {} left staged",
+            if left == 1 {
+                "hunk couldn't"
+            } else {
+                "hunks couldn't"
+            },
+            short(&base),
+            if left == 1 { "it was" } else { "they were" },
+        ));
+    }
+    Print::info("Run `git-util autosquash` to squash the fixups into place");
+
+    Ok(GitCommandResult::Success)
+}
+
+/// Commit the hunks for the `target` commit (whose sha is `sha`) as a `fixup!` of it.
+///
+/// The hunks' line numbers are relative to `head`, so the staging area is reset to it, and the hunks for `target` and
+/// the commits fixed up before it are applied together; the commit only adds the ones for `target`, since the others
+/// are already in `HEAD`.
+fn fixup(files: &[FileDiff], head: &str, target: usize, sha: &str) -> Result<()> {
+    trace!("fixup() called with: {head}, {target}, {sha}");
+
+    let mut patch = String::new();
+    for file in files {
+        let hunks: Vec<&Hunk> = file
+            .hunks
+            .iter()
+            .filter(|hunk| hunk.target.is_some_and(|t| t <= target))
+            .collect();
+        if hunks.is_empty() {
+            continue;
+        }
+
+        for line in file
+            .header
+            .iter()
+            .chain(hunks.iter().flat_map(|hunk| &hunk.lines))
+        {
+            patch.push_str(line);
+            patch.push('\n');
+        }
+    }
+
+    git(&["read-tree", head], None)?;
+    git(&["apply", "--cached", "--unidiff-zero", "-"], Some(&patch))?;
+
+    match GitCommand::new("commit")
+        .with_default_args(&[&format!("--fixup={sha}")])
+        .run()?
+    {
+        GitCommandResult::Success => Ok(()),
+        GitCommandResult::Error => Err(anyhow!("Failed to commit a fixup of {}", short(sha))),
+    }
+}
+
+/// The staged diffs of the modified files (without a change to their mode), whose paths are relative to the root.
+///
+/// `git diff --cached -z --name-only --no-renames --diff-filter=M`, then `git diff --cached --unified=0 -- PATH` for
+/// each file, so that a file's diff can't run into the next one's, however its path is quoted
+fn staged_files() -> Result<Vec<FileDiff>> {
+    let paths = GitCommand::new("diff")
+        .with_default_args(&[
+            "--cached",
+            "-z",
+            "--name-only",
+            "--no-renames",
+            "--diff-filter=M",
+        ])
+        .stdout()?;
+
+    let mut files = Vec::new();
+
+    for path in paths.split('\0').filter(|path| !path.is_empty()) {
+        let diff = GitCommand::new("diff")
+            .with_default_args(&[
+                "--cached",
+                "--no-renames",
+                "--unified=0",
+                "--no-color",
+                "--no-ext-diff",
+                "--src-prefix=a/",
+                "--dst-prefix=b/",
+                "--",
+                &format!(":(literal){path}"),
+            ])
+            .stdout()?;
+
+        let file = parse_diff(path, &diff)?;
+
+        // a mode change would be fixed up along with the first hunk
+        if !file.hunks.is_empty() && !file.header.iter().any(|line| line.starts_with("old mode")) {
+            files.push(file);
+        }
+    }
+
+    debug!(
+        "staged files: {:?}",
+        files.iter().map(|file| &file.path).collect::<Vec<_>>()
+    );
+
+    Ok(files)
+}
+
+/// Parse the diff of the single file `path`, made with `--unified=0`: the lines up to the first `@@` line are its
+/// header, and each `@@` line starts a hunk.
+fn parse_diff(path: &str, diff: &str) -> Result<FileDiff> {
+    let mut file = FileDiff {
+        path: path.to_string(),
+        ..FileDiff::default()
+    };
+
+    for line in diff.lines() {
+        if let Some(range) = line.strip_prefix("@@ -") {
+            let old = range.split(' ').next().unwrap_or_default();
+            let (start, count) = old.split_once(',').unwrap_or((old, "1"));
+
+            file.hunks.push(Hunk {
+                lines: vec![line.to_string()],
+                old_start: start.parse()?,
+                old_count: count.parse()?,
+                target: None,
+            });
+        } else if let Some(hunk) = file.hunks.last_mut() {
+            hunk.lines.push(line.to_string());
+        } else {
+            file.header.push(line.to_string());
+        }
+    }
+
+    Ok(file)
+}
+
+/// The commit that last changed each line of `path` in `HEAD`, or `None` if it was before `base`.
+///
+/// `git blame --porcelain BASE..HEAD -- PATH`, whose lines older than `BASE` are blamed on a `boundary` commit
+fn blame(base: &str, path: &str) -> Result<Vec<Option<String>>> {
+    let output = GitCommand::new("blame")
+        .with_default_args(&["--porcelain", &format!("{base}..HEAD"), "--", path])
+        .output()?;
+
+    // e.g. a submodule, which can't be blamed
+    if !output.status.success() {
+        debug!(
+            "couldn't blame {path}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return Ok(Vec::new());
+    }
+
+    Ok(parse_blame(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The commit of each line in the output of `git blame --porcelain`, or `None` for a line of a `boundary` commit.
+fn parse_blame(blame: &str) -> Vec<Option<String>> {
+    let mut lines = Vec::new();
+    let mut commit = "";
+    let mut boundaries = Vec::new();
+
+    for line in blame.lines() {
+        if line.starts_with('\t') {
+            lines.push(Some(commit.to_string()).filter(|commit| !boundaries.contains(commit)));
+        } else if line == "boundary" {
+            boundaries.push(commit.to_string());
+        } else if let Some((sha, _)) = line
+            .split_once(' ')
+            .filter(|(sha, _)| sha.len() >= 40 && sha.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            commit = sha;
+        }
+    }
+
+    lines
+}
+
+/// The index in `commits` of the commit that last changed all of the lines that `hunk` changes (or, if it only adds
+/// lines, the ones around them), if there's just one.
+fn target(hunk: &Hunk, blame: &[Option<String>], commits: &[String]) -> Option<usize> {
+    let lines = if hunk.old_count > 0 {
+        hunk.old_start..hunk.old_start + hunk.old_count
+    } else {
+        hunk.old_start.max(1)..hunk.old_start + 2
+    };
+
+    let mut blamed = lines
+        .filter_map(|line| blame.get(line - 1))
+        .collect::<Vec<_>>();
+    blamed.dedup();
+
+    match blamed[..] {
+        [Some(commit)] => commits.iter().position(|sha| sha == commit),
+        _ => None,
+    }
+}
+
+/// Run `git ARGS` with `input` (if any) on its `stdin`, failing with its error output if it fails.
+fn git(args: &[&str], input: Option<&str>) -> Result<()> {
+    let output = Commands::output_with_input(
+        &mut Commands::new_command_with_args("git", args),
+        input.unwrap_or_default().as_bytes(),
+    )?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "'git {}' failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// The abbreviated form of the sha `sha`.
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(10)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_diff_splits_the_header_and_hunks() {
+        let diff = "diff --git \"a/caf\\303\\251 x\" \"b/caf\\303\\251 x\"\n\
+                    index 1234567..89abcde 100644\n\
+                    --- \"a/caf\\303\\251 x\"\n\
+                    +++ \"b/caf\\303\\251 x\"\n\
+                    @@ -2 +2 @@ fn main() {\n\
+                    -old\n\
+                    +new\n\
+                    @@ -5,0 +6,2 @@\n\
+                    +one\n\
+                    +two\n";
+
+        let file = parse_diff("café x", diff).unwrap();
+
+        assert_eq!(file.path, "café x");
+        assert_eq!(file.header.len(), 4);
+        assert_eq!(file.hunks.len(), 2);
+        assert_eq!((file.hunks[0].old_start, file.hunks[0].old_count), (2, 1));
+        assert_eq!(
+            file.hunks[0].lines,
+            vec!["@@ -2 +2 @@ fn main() {", "-old", "+new"]
+        );
+        assert_eq!((file.hunks[1].old_start, file.hunks[1].old_count), (5, 0));
+        assert_eq!(file.hunks[1].lines.len(), 3);
+    }
+
+    #[test]
+    fn parse_diff_without_hunks() {
+        let diff = "diff --git a/x.png b/x.png\n\
+                    index 1234567..89abcde 100644\n\
+                    Binary files a/x.png and b/x.png differ\n";
+
+        let file = parse_diff("x.png", diff).unwrap();
+
+        assert!(file.hunks.is_empty());
+        assert_eq!(file.header.len(), 3);
+    }
+
+    #[test]
+    fn parse_diff_rejects_a_malformed_range() {
+        assert!(parse_diff("x", "@@ -x,1 +1 @@\n").is_err());
+    }
+
+    const A: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const B: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    const C: &str = "cccccccccccccccccccccccccccccccccccccccc";
+
+    fn hunk(old_start: usize, old_count: usize) -> Hunk {
+        Hunk {
+            old_start,
+            old_count,
+            ..Hunk::default()
+        }
+    }
+
+    #[test]
+    fn parse_blame_skips_boundary_commits() {
+        // a commit's details are only given the first time it's blamed
+        let blame = format!(
+            "{A} 1 1 2\nauthor Someone\nboundary\nsummary {B} is in the summary\nfilename x\n\tone\n\
+             {A} 2 2\n\ttwo\n\
+             {B} 3 3 1\nauthor Someone\nprevious {C} x\nfilename x\n\tthree\n\
+             {C} 1 4 1\nauthor Someone\nfilename y\n\t{A} is in the file\n"
+        );
+
+        assert_eq!(
+            parse_blame(&blame),
+            vec![None, None, Some(B.to_string()), Some(C.to_string())]
+        );
+    }
+
+    #[test]
+    fn target_of_changed_lines() {
+        let commits = [A.to_string(), B.to_string()];
+        let blame = [
+            Some(A.to_string()),
+            Some(A.to_string()),
+            Some(B.to_string()),
+            None,
+        ];
+
+        assert_eq!(target(&hunk(1, 2), &blame, &commits), Some(0));
+        assert_eq!(target(&hunk(3, 1), &blame, &commits), Some(1));
+        // more than one commit, or before the base
+        assert_eq!(target(&hunk(2, 2), &blame, &commits), None);
+        assert_eq!(target(&hunk(4, 1), &blame, &commits), None);
+        // a commit that isn't since the base
+        assert_eq!(target(&hunk(1, 1), &[Some(C.to_string())], &commits), None);
+    }
+
+    #[test]
+    fn target_of_added_lines_is_the_commit_around_them() {
+        let commits = [A.to_string(), B.to_string()];
+        let blame = [
+            Some(A.to_string()),
+            Some(A.to_string()),
+            Some(B.to_string()),
+        ];
+
+        // between lines 1 and 2, before line 1, and after the last line
+        assert_eq!(target(&hunk(1, 0), &blame, &commits), Some(0));
+        assert_eq!(target(&hunk(0, 0), &blame, &commits), Some(0));
+        assert_eq!(target(&hunk(3, 0), &blame, &commits), Some(1));
+        // between lines of different commits
+        assert_eq!(target(&hunk(2, 0), &blame, &commits), None);
+        // a file that couldn't be blamed
+        assert_eq!(target(&hunk(1, 1), &[], &commits), None);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{
+    git::{forge::ForgeRepo, Git, GitCommand, GitCommandResult, GitResult},
+    print::Print,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+use std::{env, path::PathBuf};
+
+/// Cherry-pick `commits` onto a new branch (`branch`, else `backport/TARGET/SHA`, for the first commit) that starts at
+/// `target` (else `origin/TARGET`), e.g. to backport fixes to a release branch.
+///
+/// The commits are picked in the order they're given (so the oldest should come first), with `-x`, so that each message
+/// records the commit it was picked from. With `worktree`, the branch is checked out in a new worktree beside the
+/// repository, so the current one isn't disturbed; otherwise it's checked out here, which fails if there are
+/// uncommitted changes. With `push`, the branch is pushed to `origin`, and the URL for opening a pull request into
+/// `target` is printed.
+///
+/// `git switch --create BRANCH TARGET` (or `git worktree add -b BRANCH PATH TARGET`), then `git cherry-pick -x
+/// COMMITS`, then `git push --set-upstream origin BRANCH`
+pub fn backport(
+    commits: &[String],
+    target: &str,
+    branch: Option<&str>,
+    worktree: bool,
+    push: bool,
+) -> GitResult {
+    trace!("backport() called with: {commits:#?}, {target}, {branch:?}, {worktree}, {push}");
+
+    super::ensure_no_operation()?;
+
+    let commits = resolve(commits)?;
+
+    let start = if Git::branch_exists(target)? {
+        target.to_string()
+    } else if Git::object_exists(&format!("refs/remotes/origin/{target}"))? {
+        format!("origin/{target}")
+    } else {
+        return Err(anyhow!(
+            "There is no branch {} (or origin/{}) to backport to",
+            target,
+            target
+        ));
+    };
+
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => format!(
+            "backport/{target}/{}",
+            &commits[0][..commits[0].len().min(7)]
+        ),
+    };
+    if Git::branch_exists(&branch)? {
+        return Err(anyhow!(
+            "The branch {} already exists; name another one with --branch",
+            branch
+        ));
+    }
+
+    debug!("backporting {commits:?} onto {start} as {branch}");
+
+    let path = if worktree {
+        let path = worktree_path(&branch)?;
+        if path.exists() {
+            return Err(anyhow!("{} already exists", path.display()));
+        }
+
+        if GitCommand::new("worktree")
+            .with_default_args(&["add", "-b", &branch, &path.to_string_lossy(), &start])
+            .run()?
+            == GitCommandResult::Error
+        {
+            return Ok(GitCommandResult::Error);
+        }
+
+        env::set_current_dir(&path)?;
+        Some(path)
+    } else {
+        if let GitCommandResult::Error = Git::verify_clean_work_tree()? {
+            return Err(anyhow!(
+                "There are uncommitted changes; commit or stash them, or use --worktree"
+            ));
+        }
+
+        if GitCommand::new("switch")
+            .with_default_args(&["--create", &branch, &start])
+            .run()?
+            == GitCommandResult::Error
+        {
+            return Ok(GitCommandResult::Error);
+        }
+
+        None
+    };
+    let location = path
+        .as_ref()
+        .map(|path| format!(" in {}", path.display()))
+        .unwrap_or_default();
+
+    if GitCommand::new("cherry-pick")
+        .with_default_args(&["-x"])
+        .with_user_args(&commits)
+        .run()?
+        == GitCommandResult::Error
+    {
+        Print::stderr_purple(&format!(
+            "Resolve the conflicts{location}, then run `git-util continue --add`; or run `git-util abort` to cancel."
+        ));
+        return Ok(GitCommandResult::Error);
+    }
+
+    Print::info(&format!(
+        "Backported {} {} to {target} on {branch}{location}",
+        commits.len(),
+        if commits.len() == 1 {
+            "commit"
+        } else {
+            "commits"
+        }
+    ));
+
+    if !push {
+        return Ok(GitCommandResult::Success);
+    }
+
+    if let GitCommandResult::Error = GitCommand::new("push")
+        .with_default_args(&["--set-upstream", "origin", &branch])
+        .run_with_progress(&format!("pushing {branch}"))?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    if let Some(url) =
+        ForgeRepo::from_remote("origin")?.and_then(|repo| repo.pull_request_url(&branch, target))
+    {
+        println!("{url}");
+    }
+
+    Ok(GitCommandResult::Success)
+}
+
+/// The shas of `commits`, in the same order.
+///
+/// `git rev-list --no-walk=unsorted COMMITS`
+fn resolve(commits: &[String]) -> Result<Vec<String>> {
+    for commit in commits {
+        if !Git::object_exists(&format!("{commit}^{{commit}}"))? {
+            return Err(anyhow!("{} isn't a commit", commit));
+        }
+    }
+
+    Ok(GitCommand::new("rev-list")
+        .with_default_args(&["--no-walk=unsorted"])
+        .with_user_args(commits)
+        .stdout()?
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+/// Where the worktree for `branch` goes: beside the repository, named for it and the branch, e.g. `../repo-backport-x`.
+fn worktree_path(branch: &str) -> Result<PathBuf> {
+    let root = Git::repo_root()?;
+    let name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    Ok(root.with_file_name(format!("{name}-{}", branch.replace('/', "-"))))
+}
@@ -0,0 +1,203 @@
+use crate::{
+    git::{hooks::report::format_age, Git, GitCommand, GitCommandResult, GitResult},
+    output::OutputTarget,
+    print::Print,
+    table::Table,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+use std::{
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The namespace of the backup refs, each of which is named for when it was made, in seconds since the Unix epoch.
+const BACKUP_REFS: &str = "refs/git-wrapper/backup";
+
+/// Save `HEAD` as a backup ref before `operation` rewrites it, so that it can be restored with `restore()`.
+///
+/// The operation and branch are recorded in the ref's reflog, so every operation gets its own backup, even if another
+/// one already points at the same commit. Nothing is saved if `HEAD` has no commits yet. Old backups are deleted with
+/// `prune()`.
+pub fn create(operation: &str) -> Result<()> {
+    trace!("create() called with: {operation}");
+
+    if !Git::object_exists("HEAD")? {
+        return Ok(());
+    }
+
+    let timestamp = now();
+
+    // more than one backup can be made in the same second
+    let mut name = format!("{BACKUP_REFS}/{timestamp}");
+    let mut suffix = 1;
+    while Git::object_exists(&name)? {
+        name = format!("{BACKUP_REFS}/{timestamp}-{suffix}");
+        suffix += 1;
+    }
+
+    let message = match Git::current_branch() {
+        Ok(branch) => format!("{operation} on {branch}"),
+        Err(_) => format!("{operation} on a detached HEAD"),
+    };
+
+    debug!("backing up HEAD to {name}: {message}");
+
+    match GitCommand::new("update-ref")
+        .with_default_args(&["--create-reflog", "-m", &message, &name, "HEAD"])
+        .run()?
+    {
+        GitCommandResult::Success => Ok(()),
+        GitCommandResult::Error => Err(anyhow!("Failed to back up HEAD to {}", name)),
+    }
+}
+
+/// List the backups, newest first, as a table of name, age, operation, commit, and subject.
+pub fn list() -> GitResult {
+    trace!("list() called");
+
+    let backups = GitCommand::new("for-each-ref")
+        .with_default_args(&[
+            "--sort=-refname",
+            "--format=%(refname)%09%(objectname:short)%09%(subject)",
+            BACKUP_REFS,
+        ])
+        .stdout()?;
+
+    if backups.is_empty() {
+        Print::info("There are no backups");
+        return Ok(GitCommandResult::Success);
+    }
+
+    let now = now();
+
+    let mut table = Table::new();
+    table.add_row(["BACKUP", "AGE", "OPERATION", "COMMIT", "SUBJECT"]);
+
+    for line in backups.lines() {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(reference), Some(commit), Some(subject)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+
+        let name = reference.trim_start_matches(&format!("{BACKUP_REFS}/"));
+        let age = timestamp(name)
+            .map(|timestamp| format!("{} ago", format_age(now.saturating_sub(timestamp))))
+            .unwrap_or_default();
+
+        // the reflog is missing if the ref wasn't made by `create()`
+        let operation = GitCommand::new("reflog")
+            .with_default_args(&["show", "-n", "1", "--format=%gs", reference])
+            .stdout()
+            .unwrap_or_default();
+
+        table.add_row([name, &age, &operation, commit, subject]);
+    }
+
+    write!(OutputTarget::writer()?, "{table}")?;
+
+    Ok(GitCommandResult::Success)
+}
+
+/// Reset the current branch to `backup` (a name from `list()`), keeping uncommitted changes.
+///
+/// `HEAD` is backed up first, so the restore can itself be undone.
+///
+/// `git reset --keep refs/git-wrapper/backup/BACKUP`, which fails if an uncommitted change is in a file that differs
+/// between `HEAD` and the backup.
+pub fn restore(backup: &str, force_protected: bool) -> GitResult {
+    trace!("restore() called with: {backup}, {force_protected}");
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+
+    let reference = match backup.strip_prefix("refs/") {
+        Some(_) => backup.to_string(),
+        None => format!("{BACKUP_REFS}/{backup}"),
+    };
+
+    if !Git::object_exists(&reference)? {
+        return Err(anyhow!(
+            "There is no backup named '{}'; run `git-util backups list` to see them",
+            backup
+        ));
+    }
+
+    create("restore")?;
+
+    GitCommand::new("reset")
+        .with_default_args(&["--keep", &reference])
+        .run()
+}
+
+/// Delete the backups that are more than `days` days old.
+///
+/// `git update-ref -d refs/git-wrapper/backup/BACKUP` for each of them.
+pub fn prune(days: u64) -> GitResult {
+    trace!("prune() called with: {days}");
+
+    let cutoff = now().saturating_sub(days * 24 * 60 * 60);
+
+    let expired: Vec<String> = GitCommand::new("for-each-ref")
+        .with_default_args(&["--format=%(refname)", BACKUP_REFS])
+        .stdout()?
+        .lines()
+        .filter(|reference| {
+            timestamp(reference.trim_start_matches(&format!("{BACKUP_REFS}/")))
+                .is_some_and(|timestamp| timestamp < cutoff)
+        })
+        .map(String::from)
+        .collect();
+
+    if expired.is_empty() {
+        Print::info(&format!("There are no backups older than {days} day(s)"));
+        return Ok(GitCommandResult::Success);
+    }
+
+    for reference in &expired {
+        debug!("deleting {reference}");
+
+        if GitCommand::new("update-ref")
+            .with_default_args(&["-d", reference])
+            .run()?
+            == GitCommandResult::Error
+        {
+            return Ok(GitCommandResult::Error);
+        }
+    }
+
+    Print::info(&format!(
+        "Deleted {} backup(s) older than {days} day(s)",
+        expired.len()
+    ));
+
+    Ok(GitCommandResult::Success)
+}
+
+/// The current time, in seconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// When the backup `name` (e.g. `1700000000` or `1700000000-1`) was made, in seconds since the Unix epoch.
+fn timestamp(name: &str) -> Option<u64> {
+    name.split('-').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_of_backup_names() {
+        assert_eq!(timestamp("1700000000"), Some(1700000000));
+        assert_eq!(timestamp("1700000000-2"), Some(1700000000));
+        assert_eq!(timestamp("before-rebase"), None);
+    }
+}
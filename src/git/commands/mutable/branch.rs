@@ -0,0 +1,393 @@
+use crate::{
+    commands::Commands,
+    git::{autostash::AutoStash, Git, GitCommand, GitCommandResult, GitResult},
+    print::Print,
+    prompt::Prompt,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+
+/// Why a branch is considered safe to delete.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum MergeKind {
+    /// The branch tip is reachable from the base branch.
+    Merged,
+    /// The branch's combined changes already exist on the base branch as a single commit.
+    SquashMerged,
+}
+
+/// Delete local branches that have been merged (or squash-merged) into the default branch.
+///
+/// Prompts for confirmation unless `yes` is `true`.
+pub fn prune_merged(yes: bool) -> GitResult {
+    trace!("prune_merged() called with: {:#?}", yes);
+
+    let base = Git::default_branch()?;
+    let current = Git::current_branch().unwrap_or_default();
+
+    let merged: Vec<String> = GitCommand::new("branch")
+        .with_default_args(&["--format=%(refname:short)", "--merged", &base])
+        .stdout()?
+        .lines()
+        .map(String::from)
+        .collect();
+
+    let mut candidates: Vec<(String, MergeKind)> = Vec::new();
+
+    for branch in local_branches()? {
+        if branch == base || branch == current {
+            continue;
+        }
+
+        if merged.contains(&branch) {
+            candidates.push((branch, MergeKind::Merged));
+        } else if is_squash_merged(&branch, &base)? {
+            candidates.push((branch, MergeKind::SquashMerged));
+        }
+    }
+
+    if candidates.is_empty() {
+        Print::info(&format!("No branches have been merged into '{base}'"));
+        return Ok(GitCommandResult::Success);
+    }
+
+    println!("Branches merged into '{base}':");
+    for (branch, kind) in &candidates {
+        match kind {
+            MergeKind::Merged => println!("  {branch}"),
+            MergeKind::SquashMerged => println!("  {branch} (squash-merged)"),
+        }
+    }
+
+    if !yes && !Prompt::confirm("Delete these branches?")? {
+        return Ok(GitCommandResult::Error);
+    }
+
+    // squash-merged branches are not reachable from `base`, so `--force` is required to delete them
+    let branches: Vec<String> = candidates.into_iter().map(|(branch, _)| branch).collect();
+
+    GitCommand::new("branch")
+        .with_default_args(&["--delete", "--force"])
+        .with_user_args(&branches)
+        .run()
+}
+
+/// Rename `old_name` (or the current branch) to `new_name`.
+///
+/// If `remote` is `true`, the branch is also pushed to its remote under the new name, the old remote branch is deleted,
+/// and the upstream is set to the new remote branch.
+pub fn rename(new_name: &str, old_name: Option<&str>, remote: bool) -> GitResult {
+    trace!("rename() called with: {new_name}, {old_name:?}, {remote}");
+
+    if new_name.trim().is_empty() {
+        return Err(anyhow!("Must supply new branch name"));
+    }
+
+    let old_name = match old_name {
+        Some(name) => name.to_string(),
+        None => Git::current_branch()?,
+    };
+
+    // read the upstream before renaming, since the rename moves the branch's config section
+    let upstream_remote = Git::config_value(&format!("branch.{old_name}.remote"))?;
+    let upstream_branch = Git::config_value(&format!("branch.{old_name}.merge"))?;
+
+    if let GitCommandResult::Error = GitCommand::new("branch")
+        .with_default_args(&["--move", &old_name, new_name])
+        .run()?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    if !remote {
+        return Ok(GitCommandResult::Success);
+    }
+
+    let remote_name = upstream_remote.unwrap_or_else(|| "origin".to_string());
+
+    if let GitCommandResult::Error = GitCommand::new("push")
+        .with_default_args(&["--set-upstream", &remote_name, new_name])
+        .run()?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    match upstream_branch {
+        // the old upstream already had the new name, so it's the branch that was just pushed
+        Some(upstream_branch) if upstream_branch.trim_start_matches("refs/heads/") == new_name => {
+            debug!("the upstream is already {remote_name}/{new_name}; not deleting it");
+            Ok(GitCommandResult::Success)
+        }
+        Some(upstream_branch) => {
+            let upstream_branch = upstream_branch.trim_start_matches("refs/heads/");
+            debug!("deleting old remote branch {remote_name}/{upstream_branch}");
+
+            GitCommand::new("push")
+                .with_default_args(&[&remote_name, "--delete", upstream_branch])
+                .run()
+        }
+        None => Ok(GitCommandResult::Success),
+    }
+}
+
+/// Set the upstream of the current branch to `upstream` (else defaults to `origin/BRANCH`) and print how far ahead/behind
+/// of it the branch is.
+pub fn set_upstream(upstream: Option<&str>) -> GitResult {
+    trace!("set_upstream() called with: {upstream:?}");
+
+    let branch = Git::current_branch()?;
+
+    let upstream = match upstream {
+        Some(upstream) => upstream.to_string(),
+        None => format!("origin/{branch}"),
+    };
+
+    if let GitCommandResult::Error = GitCommand::new("branch")
+        .with_default_args(&[&format!("--set-upstream-to={upstream}")])
+        .run()?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    let (ahead, behind) = Git::ahead_behind(&branch, &upstream)?;
+    Print::info(&format!(
+        "'{branch}' is {ahead} ahead, {behind} behind '{upstream}'"
+    ));
+
+    Ok(GitCommandResult::Success)
+}
+
+/// Remove the upstream of the current branch.
+///
+/// `git branch --unset-upstream`
+pub fn unset_upstream() -> GitResult {
+    trace!("unset_upstream() called");
+
+    GitCommand::new("branch")
+        .with_default_args(&["--unset-upstream"])
+        .run()
+}
+
+/// Switch to the default branch and fast-forward it from its upstream.
+///
+/// If `autostash` is `true`, uncommitted changes are stashed beforehand and restored afterwards (see `AutoStash`).
+///
+/// `git switch DEFAULT_BRANCH && git pull --ff-only`
+pub fn switch_to_default(autostash: bool) -> GitResult {
+    trace!("switch_to_default() called with: {autostash}");
+
+    super::ensure_no_operation()?;
+
+    let _autostash = if autostash {
+        Some(AutoStash::new()?)
+    } else {
+        None
+    };
+
+    let default_branch = Git::default_branch()?;
+
+    match GitCommand::new("switch")
+        .with_default_args(&[&default_branch])
+        .run()?
+    {
+        GitCommandResult::Success => GitCommand::new("pull")
+            .with_default_args(&["--ff-only"])
+            .run_with_progress(&format!("pulling {default_branch}")),
+        GitCommandResult::Error => Ok(GitCommandResult::Error),
+    }
+}
+
+/// `git checkout COMMIT`, which detaches `HEAD` at `commit` (unless it's a branch).
+pub fn checkout(commit: &str) -> GitResult {
+    trace!("checkout() called with: {commit}");
+
+    super::ensure_no_operation()?;
+
+    GitCommand::new("checkout")
+        .with_default_args(&[commit])
+        .run()
+}
+
+/// Move the last `num` commits (else the commits that aren't on the upstream) of the current branch to the new branch
+/// `new_branch`, e.g. after committing on the default branch by accident, then switch to it.
+///
+/// `git branch NEW_BRANCH && git reset --keep HEAD~NUM && git switch NEW_BRANCH`, after backing up `HEAD`; if a step
+/// fails, the ones before it are undone. Uncommitted changes are carried along, unless they're in a file that the
+/// commits change, in which case the reset fails.
+///
+/// Fails if any of the commits have been pushed to the upstream, since they would come back with the next pull.
+pub fn extract(new_branch: &str, num: Option<u16>) -> GitResult {
+    trace!("extract() called with: {new_branch}, {num:?}");
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+
+    let branch = Git::current_branch()?;
+    let upstream = Git::upstream(&branch)?;
+
+    if Git::branch_exists(new_branch)? {
+        return Err(anyhow!("The branch {} already exists", new_branch));
+    }
+
+    let target = match (num, &upstream) {
+        (Some(num), _) => format!("HEAD~{num}"),
+        (None, Some(upstream)) => upstream.clone(),
+        (None, None) => {
+            return Err(anyhow!(
+                "{} has no upstream; give the number of commits to move",
+                branch
+            ))
+        }
+    };
+
+    let count = |range: &str| -> Result<usize> {
+        Ok(GitCommand::new("rev-list")
+            .with_default_args(&["--count", range])
+            .stdout()?
+            .parse()?)
+    };
+
+    if !Git::object_exists(&target)? {
+        return Err(anyhow!("{} doesn't have that many commits", branch));
+    }
+    let commits = count(&format!("{target}..HEAD"))?;
+    if commits == 0 {
+        Print::info(&format!("There are no commits to move from {branch}"));
+        return Ok(GitCommandResult::Success);
+    }
+    if let Some(upstream) = &upstream {
+        let unpushed = count(&format!("{upstream}..HEAD"))?;
+        if unpushed < commits {
+            return Err(anyhow!(
+                "Only {} of the {} commits haven't been pushed to {}; the rest would come back with the next pull",
+                unpushed,
+                commits,
+                upstream
+            ));
+        }
+    }
+
+    super::backup::create("extract")?;
+
+    if GitCommand::new("branch")
+        .with_default_args(&[new_branch, "HEAD"])
+        .run()?
+        == GitCommandResult::Error
+    {
+        return Err(anyhow!("Failed to create the branch {}", new_branch));
+    }
+
+    if GitCommand::new("reset")
+        .with_default_args(&["--keep", &target])
+        .run()?
+        == GitCommandResult::Error
+    {
+        debug!("rolling back: deleting {new_branch}");
+        GitCommand::new("branch")
+            .with_default_args(&["--delete", "--force", new_branch])
+            .run()?;
+
+        return Err(anyhow!(
+            "Failed to reset {} to {}; nothing was moved",
+            branch,
+            target
+        ));
+    }
+
+    if GitCommand::new("switch")
+        .with_default_args(&[new_branch])
+        .run()?
+        == GitCommandResult::Error
+    {
+        debug!("rolling back: resetting {branch} to {new_branch} and deleting it");
+        GitCommand::new("reset")
+            .with_default_args(&["--keep", new_branch])
+            .run()?;
+        GitCommand::new("branch")
+            .with_default_args(&["--delete", "--force", new_branch])
+            .run()?;
+
+        return Err(anyhow!(
+            "Failed to switch to {}; nothing was moved",
+            new_branch
+        ));
+    }
+
+    Print::info(&format!(
+        "Moved {} {} from {} to {}",
+        commits,
+        if commits == 1 { "commit" } else { "commits" },
+        branch,
+        new_branch
+    ));
+
+    Ok(GitCommandResult::Success)
+}
+
+/// The names of all local branches.
+fn local_branches() -> Result<Vec<String>> {
+    Ok(GitCommand::new("branch")
+        .with_default_args(&["--format=%(refname:short)"])
+        .stdout()?
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+/// Check whether the changes on `branch` have been applied to `base` as a single (squashed) commit.
+///
+/// The patch ID of the combined diff between `branch` and its merge-base is compared against the patch IDs of the
+/// commits on `base` since the merge-base (which is how `git cherry` matches commits), so no objects are written.
+fn is_squash_merged(branch: &str, base: &str) -> Result<bool> {
+    trace!("is_squash_merged() called with: {branch}, {base}");
+
+    let merge_base = match GitCommand::new("merge-base")
+        .with_default_args(&[base, branch])
+        .stdout()
+    {
+        Ok(merge_base) => merge_base,
+        // unrelated histories
+        Err(_) => return Ok(false),
+    };
+
+    let Some(squashed) =
+        patch_ids(GitCommand::new("diff-tree").with_default_args(&["-p", &merge_base, branch]))?
+            .pop()
+    else {
+        // the branch has no changes of its own
+        return Ok(false);
+    };
+
+    let applied = patch_ids(GitCommand::new("log").with_default_args(&[
+        "-p",
+        "--no-merges",
+        &format!("{merge_base}..{base}"),
+    ]))?;
+
+    debug!("patch ID of {branch}: {squashed}; patch IDs on {base}: {applied:?}");
+
+    Ok(applied.contains(&squashed))
+}
+
+/// The patch IDs (`git patch-id --stable`) of the patches printed by `command`.
+fn patch_ids(command: GitCommand) -> Result<Vec<String>> {
+    let patches = command.output()?;
+
+    if !patches.status.success() {
+        return Err(anyhow!(
+            "{}",
+            String::from_utf8_lossy(&patches.stderr).trim()
+        ));
+    }
+
+    let ids = Commands::output_with_input(
+        &mut Commands::new_command_with_args("git", &["patch-id", "--stable"]),
+        &patches.stdout,
+    )?;
+
+    Ok(String::from_utf8(ids.stdout)?
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(String::from)
+        .collect())
+}
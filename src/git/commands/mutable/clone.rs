@@ -0,0 +1,294 @@
+use super::template;
+use crate::{
+    git::{forge::ForgeRepo, hooks, GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    print::Print,
+    settings::Settings,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use std::{env, path::Path, sync::atomic::Ordering};
+
+/// How much of the history a clone downloads up front; anything that's missing is fetched on demand.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum CloneFilter {
+    /// Download all commits and trees, but only the blobs needed for the checkout (`--filter=blob:none`).
+    Blobless,
+    /// Download all commits, but only the trees and blobs needed for the checkout (`--filter=tree:0`).
+    Treeless,
+}
+
+impl CloneFilter {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            CloneFilter::Blobless => "--filter=blob:none",
+            CloneFilter::Treeless => "--filter=tree:0",
+        }
+    }
+}
+
+/// `git clone [--filter=FILTER] ARGS URL [DIRECTORY]`
+///
+/// `repository` is either a URL or a shorthand (see `expand_shorthand()`). If no `directory` is given and
+/// `git-util.clone.root` is set, the repository is cloned into `ROOT/HOST/OWNER/REPO`.
+///
+/// If `install_hooks` is `true`, the **git-util** hook shims are installed in the new repository. If `template` is
+/// given, its files are copied into the new repository and committed.
+pub fn clone(
+    repository: &str,
+    directory: Option<&str>,
+    filter: Option<CloneFilter>,
+    install_hooks: bool,
+    template: Option<&str>,
+    args: &[String],
+) -> GitResult {
+    trace!(
+        "clone() called with: {repository}, {directory:?}, {filter:?}, {install_hooks}, {template:?}, {args:#?}"
+    );
+
+    // fail before cloning if the template doesn't exist
+    if let Some(template) = template {
+        template::directory(template)?;
+    }
+
+    let url = expand_shorthand(repository)?;
+    debug!("{repository} => {url}");
+
+    let directory = match (directory, &Settings::get().clone_root) {
+        (Some(directory), _) => directory.to_string(),
+        (None, Some(root)) => {
+            let repo = ForgeRepo::parse(&url).ok_or_else(|| {
+                anyhow!(
+                    "Unable to get the owner and name of the repository from {}",
+                    url
+                )
+            })?;
+
+            root.join(&repo.host)
+                .join(&repo.owner)
+                .join(&repo.name)
+                .to_string_lossy()
+                .to_string()
+        }
+        // the same directory that `git clone` would pick
+        (None, None) => url
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .rsplit(['/', ':'])
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    };
+
+    let mut default_args = Vec::new();
+    if let Some(filter) = filter {
+        default_args.push(filter.as_arg());
+    }
+
+    let mut user_args = args.to_vec();
+    user_args.push(url);
+    user_args.push(directory.clone());
+
+    if let GitCommandResult::Error = GitCommand::new("clone")
+        .with_default_args(&default_args)
+        .with_user_args(&user_args)
+        .run()?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    if !install_hooks && template.is_none() {
+        return Ok(GitCommandResult::Success);
+    }
+
+    // the directory doesn't exist, since nothing was cloned
+    if DRY_RUN.load(Ordering::SeqCst) {
+        Print::stderr_purple(&format!("would set up {directory}"));
+        return Ok(GitCommandResult::Success);
+    }
+
+    env::set_current_dir(&directory).with_context(|| format!("Failed to change to {directory}"))?;
+
+    if install_hooks {
+        if let GitCommandResult::Error = hooks::install::install(false, false)? {
+            return Ok(GitCommandResult::Error);
+        }
+    }
+
+    match template {
+        Some(template) => {
+            let files = template::copy(template)?;
+            template::commit(&files, &format!("Apply the {template} template"))
+        }
+        None => Ok(GitCommandResult::Success),
+    }
+}
+
+/// Expand a shorthand for a repository into its URL:
+///
+/// - `gh:OWNER/REPO`, `gl:OWNER/REPO`, or `bb:OWNER/REPO` for GitHub, GitLab, or Bitbucket
+/// - `OWNER/REPO` for the forge in `git-util.clone.forge` (else defaults to GitHub)
+///
+/// URLs use the protocol in `git-util.clone.protocol` (else defaults to `https`). Anything else (i.e. a URL or a local
+/// path) is returned as is.
+fn expand_shorthand(repository: &str) -> Result<String> {
+    if repository.contains("://") || repository.contains('@') || Path::new(repository).exists() {
+        return Ok(repository.to_string());
+    }
+
+    let settings = Settings::get();
+
+    expand_repository(
+        repository,
+        settings.clone_forge.as_deref(),
+        settings.clone_protocol.as_deref(),
+    )
+}
+
+/// The URL for the shorthand `repository` (see `expand_shorthand()`), given the `forge` and `protocol` settings.
+fn expand_repository(
+    repository: &str,
+    forge: Option<&str>,
+    protocol: Option<&str>,
+) -> Result<String> {
+    let (host, path) = match repository.split_once(':') {
+        Some(("gh", path)) => ("github.com", path),
+        Some(("gl", path)) => ("gitlab.com", path),
+        Some(("bb", path)) => ("bitbucket.org", path),
+        // scp-like syntax without a user, e.g. `example.com:owner/repo`
+        Some(_) => return Ok(repository.to_string()),
+        None => (
+            match forge {
+                None | Some("github") => "github.com",
+                Some("gitlab") => "gitlab.com",
+                Some("bitbucket") => "bitbucket.org",
+                Some(host) => host,
+            },
+            repository,
+        ),
+    };
+
+    let path = path.trim_matches('/').trim_end_matches(".git");
+    if path.split('/').filter(|part| !part.is_empty()).count() < 2 {
+        return Err(anyhow!("Expected OWNER/REPO, but got '{}'", repository));
+    }
+
+    Ok(match protocol {
+        Some("ssh") => format!("git@{host}:{path}.git"),
+        _ => format!("https://{host}/{path}.git"),
+    })
+}
+
+/// Fetch the complete history of a shallow clone, then report how many commits were added.
+///
+/// `git fetch --unshallow --progress`
+pub fn unshallow() -> GitResult {
+    trace!("unshallow() called");
+
+    let is_shallow = GitCommand::new("rev-parse")
+        .with_default_args(&["--is-shallow-repository"])
+        .stdout()?;
+
+    if is_shallow != "true" {
+        Print::info("The repository already has its complete history");
+        return Ok(GitCommandResult::Success);
+    }
+
+    let before = commit_count()?;
+
+    // `--progress` keeps git's progress meter, even when `git-util` isn't attached to a terminal
+    if let GitCommandResult::Error = GitCommand::new("fetch")
+        .with_default_args(&["--unshallow", "--progress"])
+        .run()?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    let after = commit_count()?;
+    Print::info(&format!(
+        "Fetched {} more commits ({after} in total)",
+        after.saturating_sub(before)
+    ));
+
+    Ok(GitCommandResult::Success)
+}
+
+/// The number of commits reachable from `HEAD`.
+///
+/// `git rev-list --count HEAD`
+fn commit_count() -> Result<u64> {
+    let count = GitCommand::new("rev-list")
+        .with_default_args(&["--count", "HEAD"])
+        .stdout()?;
+
+    count
+        .parse()
+        .map_err(|_| anyhow!("Unexpected 'git rev-list --count' output: {:?}", count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_repository_forge_prefixes() {
+        assert_eq!(
+            expand_repository("gh:owner/repo", None, None).unwrap(),
+            "https://github.com/owner/repo.git"
+        );
+        assert_eq!(
+            expand_repository("gl:group/sub/repo", Some("github"), None).unwrap(),
+            "https://gitlab.com/group/sub/repo.git"
+        );
+        assert_eq!(
+            expand_repository("bb:owner/repo.git", None, Some("ssh")).unwrap(),
+            "git@bitbucket.org:owner/repo.git"
+        );
+    }
+
+    #[test]
+    fn expand_repository_default_forge() {
+        assert_eq!(
+            expand_repository("owner/repo", None, None).unwrap(),
+            "https://github.com/owner/repo.git"
+        );
+        assert_eq!(
+            expand_repository("owner/repo", Some("gitlab"), Some("ssh")).unwrap(),
+            "git@gitlab.com:owner/repo.git"
+        );
+        assert_eq!(
+            expand_repository("/owner/repo/", Some("git.example.com"), None).unwrap(),
+            "https://git.example.com/owner/repo.git"
+        );
+    }
+
+    #[test]
+    fn expand_repository_leaves_scp_like_urls() {
+        assert_eq!(
+            expand_repository("example.com:owner/repo", None, None).unwrap(),
+            "example.com:owner/repo"
+        );
+    }
+
+    #[test]
+    fn expand_repository_needs_an_owner_and_a_name() {
+        for repository in ["repo", "gh:repo", "gh:/repo/"] {
+            assert_eq!(
+                expand_repository(repository, None, None)
+                    .unwrap_err()
+                    .to_string(),
+                format!("Expected OWNER/REPO, but got '{repository}'")
+            );
+        }
+    }
+
+    #[test]
+    fn expand_shorthand_leaves_urls() {
+        for url in [
+            "https://github.com/owner/repo.git",
+            "ssh://git@example.com:2222/owner/repo.git",
+            "git@github.com:owner/repo.git",
+        ] {
+            assert_eq!(expand_shorthand(url).unwrap(), url);
+        }
+    }
+}
@@ -1,60 +1,97 @@
-use crate::git::{Git, GitCommand, GitCommandResult, GitResult};
+use crate::{
+    git::{commands::commit_picker::CommitPicker, Git, GitCommand, GitCommandResult, GitResult},
+    print::Print,
+};
 use anyhow::anyhow;
-use log::trace;
+use log::{debug, trace};
+use std::env;
 
-/// `git add --all && git commit`
+/// `git add --all && git commit ARGS`
 ///
-/// Fails if there are already staged files.
-pub fn updated_and_untracked() -> GitResult {
-    trace!("aac() called");
+/// Fails if there are already staged files, or if the current branch is protected (unless `force_protected` is `true`).
+pub fn updated_and_untracked(args: &[String], force_protected: bool) -> GitResult {
+    trace!("aac() called with: {args:#?}, {force_protected}");
+
+    super::ensure_can_commit()?;
+    super::ensure_unprotected(force_protected)?;
 
     match super::add::updated_and_untracked()? {
-        GitCommandResult::Success => GitCommand::new("commit").run(),
+        GitCommandResult::Success => GitCommand::new("commit").with_user_args(args).run(),
         GitCommandResult::Error => Err(anyhow!("git add --all returned an error")),
     }
 }
 
-/// `git add --all && git commit --amend`
+/// `git add --all && git commit --amend ARGS`
 ///
-/// Fails if there are already staged files.
-pub fn amend_updated_and_untracked() -> GitResult {
-    trace!("commit_all_amended called");
+/// Fails if there are already staged files, or if the current branch is protected (unless `force_protected` is `true`).
+/// `HEAD` is backed up first (see `backup::create()`).
+pub fn amend_updated_and_untracked(args: &[String], force_protected: bool) -> GitResult {
+    trace!("commit_all_amended called with: {args:#?}, {force_protected}");
+
+    super::ensure_can_commit()?;
+    super::ensure_unprotected(force_protected)?;
+    super::backup::create("amend")?;
 
     let result = super::add::updated_and_untracked();
 
     match result.is_ok() {
         true => GitCommand::new("commit")
             .with_default_args(&["--amend"])
+            .with_user_args(args)
             .run(),
         false => result,
     }
 }
 
-/// `git commit --all`
+/// `git commit --all ARGS`
 ///
 /// The success case is logically equivalent to `git add --update && git commit`, but differs in the failure case.
 /// In the case of 2 separate **Git** commands, cancelling out of the commit (e.g. `:q!` in **Vim**) will still
 /// leave the staging area updated. In this version, the staging area is not updated if the commit is cancelled.
 ///
-/// Fails if there are already staged files.
-pub fn updated() -> GitResult {
-    trace!("auc() called");
+/// Fails if there are already staged files, or if the current branch is protected (unless `force_protected` is `true`).
+pub fn updated(args: &[String], force_protected: bool) -> GitResult {
+    trace!("auc() called with: {args:#?}, {force_protected}");
+
+    super::ensure_can_commit()?;
+    super::ensure_unprotected(force_protected)?;
 
-    super::run_if_staging_empty(GitCommand::new("commit").with_default_args(&["--all"]))
+    super::run_if_staging_empty(
+        GitCommand::new("commit")
+            .with_default_args(&["--all"])
+            .with_user_args(args),
+    )
 }
 
-/// `git commit --all --amend`
+/// `git commit --all --amend ARGS`
 ///
-/// Fails if there are already staged files.
-pub fn amend_updated() -> GitResult {
-    trace!("commit_all_updated_files_amended() called");
+/// Fails if there are already staged files, or if the current branch is protected (unless `force_protected` is `true`).
+/// `HEAD` is backed up first (see `backup::create()`).
+pub fn amend_updated(args: &[String], force_protected: bool) -> GitResult {
+    trace!("commit_all_updated_files_amended() called with: {args:#?}, {force_protected}");
 
-    super::run_if_staging_empty(GitCommand::new("commit").with_default_args(&["--all", "--amend"]))
+    super::ensure_can_commit()?;
+    super::ensure_unprotected(force_protected)?;
+    super::backup::create("amend")?;
+
+    super::run_if_staging_empty(
+        GitCommand::new("commit")
+            .with_default_args(&["--all", "--amend"])
+            .with_user_args(args),
+    )
 }
 
 /// Changes the author on the last n commits to the current git user.
-pub fn change_author(num: Option<u16>) -> GitResult {
-    trace!("author() called with: {:#?}", num);
+///
+/// Fails if the current branch is protected, unless `force_protected` is `true`. `HEAD` is backed up first (see
+/// `backup::create()`).
+pub fn change_author(num: Option<u16>, force_protected: bool) -> GitResult {
+    trace!("author() called with: {:#?}, {force_protected}", num);
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+    super::backup::create("author")?;
 
     GitCommand::new("rebase")
         .with_default_args(&[
@@ -66,8 +103,16 @@ pub fn change_author(num: Option<u16>) -> GitResult {
 }
 
 /// `git reset --mixed HEAD~NUM`
-pub fn undo(num: Option<u16>) -> GitResult {
-    trace!("undo() called with: {:#?}", num);
+///
+/// Fails if the current branch is protected, unless `force_protected` is `true`. `HEAD` is backed up first (see
+/// `backup::create()`).
+pub fn undo(num: Option<u16>, force_protected: bool) -> GitResult {
+    trace!("undo() called with: {:#?}, {force_protected}", num);
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+    super::backup::create("undo")?;
 
     GitCommand::new("reset")
         .with_default_args(&["--mixed", &format!("HEAD~{}", num.unwrap_or(1))])
@@ -76,14 +121,19 @@ pub fn undo(num: Option<u16>) -> GitResult {
 
 /// `git commit -m`
 ///
-/// Fails if there are unstaged changes in the work tree.
-pub fn with_message(message: &str, args: &[String]) -> GitResult {
+/// Fails if there are unstaged changes in the work tree, or if the current branch is protected (unless
+/// `force_protected` is `true`).
+pub fn with_message(message: &str, args: &[String], force_protected: bool) -> GitResult {
     trace!(
-        "with_message() called with: message={:#?} args={:#?}",
+        "with_message() called with: message={:#?} args={:#?} force_protected={}",
         message,
-        args
+        args,
+        force_protected
     );
 
+    super::ensure_can_commit()?;
+    super::ensure_unprotected(force_protected)?;
+
     if message.trim().is_empty() {
         return Err(anyhow!("Must supply non-empty message!"));
     }
@@ -97,3 +147,270 @@ pub fn with_message(message: &str, args: &[String]) -> GitResult {
         .with_user_args(args)
         .run()
 }
+
+/// `git commit --fixup=COMMIT`
+///
+/// If `commit` isn't given, it's picked from the recent commits. Fails if the current branch is protected, unless
+/// `force_protected` is `true`.
+pub fn fixup(commit: Option<&str>, force_protected: bool) -> GitResult {
+    trace!("fixup() called with: {:#?}, {force_protected}", commit);
+
+    super::ensure_can_commit()?;
+    super::ensure_unprotected(force_protected)?;
+
+    let commit = match commit {
+        Some(commit) => commit.to_string(),
+        None => CommitPicker::pick("fixup")?,
+    };
+
+    GitCommand::new("commit")
+        .with_default_args(&[&format!("--fixup={commit}")])
+        .run()
+}
+
+/// `git cherry-pick COMMITS`
+///
+/// If no `commits` are given, one is picked from the commits on other local branches that aren't on `HEAD`. Fails if
+/// the current branch is protected, unless `force_protected` is `true`.
+pub fn cherry_pick(commits: &[String], force_protected: bool) -> GitResult {
+    trace!(
+        "cherry_pick() called with: {:#?}, {force_protected}",
+        commits
+    );
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+
+    let commits = match commits {
+        [] => vec![CommitPicker::pick_from_other_branches("cherry-pick")?],
+        commits => commits.to_vec(),
+    };
+
+    GitCommand::new("cherry-pick")
+        .with_user_args(&commits)
+        .run()
+}
+
+/// `git revert --no-edit COMMIT`
+///
+/// Fails if the current branch is protected, unless `force_protected` is `true`.
+pub fn revert(commit: &str, force_protected: bool) -> GitResult {
+    trace!("revert() called with: {commit}, {force_protected}");
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+
+    GitCommand::new("revert")
+        .with_default_args(&["--no-edit", commit])
+        .run()
+}
+
+/// Edit the message of `commit` (else a commit picked from the recent commits).
+///
+/// `git commit --amend --only` if it's `HEAD`; otherwise `git commit --fixup=reword:COMMIT` and then an autosquash
+/// rebase onto its parent, which applies the new message. `HEAD` is backed up first (see `backup::create()`). Fails if
+/// the current branch is protected, unless `force_protected` is `true`.
+pub fn reword(commit: Option<&str>, force_protected: bool) -> GitResult {
+    trace!("reword() called with: {:#?}, {force_protected}", commit);
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+
+    let commit = match commit {
+        Some(commit) => commit.to_string(),
+        None => CommitPicker::pick("reword")?,
+    };
+
+    let sha = GitCommand::new("rev-parse")
+        .with_default_args(&["--verify", &format!("{commit}^{{commit}}")])
+        .stdout()?;
+
+    super::backup::create("reword")?;
+
+    if sha
+        == GitCommand::new("rev-parse")
+            .with_default_args(&["HEAD"])
+            .stdout()?
+    {
+        return GitCommand::new("commit")
+            .with_default_args(&["--amend", "--only"])
+            .run();
+    }
+
+    if GitCommand::new("commit")
+        .with_default_args(&[&format!("--fixup=reword:{sha}")])
+        .run()?
+        == GitCommandResult::Error
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    // accept the todo list as it is, since `--autosquash` has already moved the fixup into place
+    env::set_var("GIT_SEQUENCE_EDITOR", "true");
+
+    let parent = format!("{sha}~");
+    let base = if Git::object_exists(&parent)? {
+        parent.as_str()
+    } else {
+        "--root"
+    };
+
+    GitCommand::new("rebase")
+        .with_default_args(&["--interactive", "--autosquash", "--autostash", base])
+        .run()
+}
+
+/// Squash the `fixup!`, `squash!`, and `amend!` commits since `base` into the commits they're for, without opening the
+/// todo list in an editor; print "Nothing to squash" if there are none.
+///
+/// `base` defaults to where the current branch forked (see `branch_base()`).
+///
+/// `git rebase --interactive --autosquash --autostash BASE`, after backing up `HEAD`. Fails if there's a merge commit
+/// since `base`, or if the current branch is protected (unless `force_protected` is `true`).
+pub fn autosquash(base: Option<&str>, force_protected: bool) -> GitResult {
+    trace!("autosquash() called with: {base:?}, {force_protected}");
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+
+    let base = super::branch_base(base)?;
+
+    let subjects = GitCommand::new("log")
+        .with_default_args(&["--format=%s", &format!("{base}..HEAD")])
+        .stdout()?;
+    let count = subjects
+        .lines()
+        .filter(|subject| {
+            ["fixup! ", "squash! ", "amend! "]
+                .iter()
+                .any(|prefix| subject.starts_with(prefix))
+        })
+        .count();
+
+    if count == 0 {
+        Print::info("Nothing to squash");
+        return Ok(GitCommandResult::Success);
+    }
+
+    // the rebase would flatten them
+    let merges = GitCommand::new("rev-list")
+        .with_default_args(&["--merges", "--max-count=1", &format!("{base}..HEAD")])
+        .stdout()?;
+    if !merges.is_empty() {
+        return Err(anyhow!(
+            "There's a merge commit ({}) since {}, so the fixups can't be squashed",
+            &merges[..merges.len().min(10)],
+            &base[..base.len().min(10)]
+        ));
+    }
+
+    debug!("{count} commits to squash");
+
+    super::backup::create("autosquash")?;
+
+    // accept the todo list as it is, since `--autosquash` has already moved the fixups into place
+    env::set_var("GIT_SEQUENCE_EDITOR", "true");
+
+    GitCommand::new("rebase")
+        .with_default_args(&["--interactive", "--autosquash", "--autostash", &base])
+        .run()
+}
+
+/// Remove `commit` from the history of the current branch, rebasing the commits after it onto its parent.
+///
+/// `commit` is a commit, or `n` for the nth last commit (`1` is `HEAD`); a number with fewer than 4 digits is always
+/// `n`, since git never abbreviates a sha that short. `HEAD` is backed up first (see `backup::create()`).
+///
+/// Fails if `commit` is a merge, if there's a merge after it, or if the current branch is protected (unless
+/// `force_protected` is `true`); also if `commit` has been pushed to a remote, unless `force` is `true`, since dropping
+/// it means force-pushing.
+///
+/// `git rebase --interactive --autostash COMMIT~`, with a todo list that drops `COMMIT` and picks the rest (see
+/// `plan::rebase()`)
+pub fn drop_commit(commit: &str, force: bool, force_protected: bool) -> GitResult {
+    trace!("drop_commit() called with: {commit}, {force}, {force_protected}");
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+
+    let commit = match commit.parse::<u16>() {
+        Ok(0) => return Err(anyhow!("n must be at least 1 (the last commit)")),
+        Ok(n) if commit.len() < 4 => format!("HEAD~{}", n - 1),
+        _ => commit.to_string(),
+    };
+    let sha = GitCommand::new("rev-parse")
+        .with_default_args(&["--verify", "--quiet", &format!("{commit}^{{commit}}")])
+        .stdout()
+        .map_err(|_| anyhow!("{} isn't a commit", commit))?;
+    let short = &sha[..sha.len().min(10)];
+
+    if !GitCommand::new("merge-base")
+        .with_default_args(&["--is-ancestor", &sha, "HEAD"])
+        .output()?
+        .status
+        .success()
+    {
+        return Err(anyhow!("{} isn't on the current branch", short));
+    }
+    if Git::object_exists(&format!("{sha}^2"))? {
+        return Err(anyhow!(
+            "{} is a merge commit, which can't be dropped",
+            short
+        ));
+    }
+
+    // the todo list would flatten them
+    let merges = GitCommand::new("rev-list")
+        .with_default_args(&["--merges", "--max-count=1", &format!("{sha}..HEAD")])
+        .stdout()?;
+    if !merges.is_empty() {
+        return Err(anyhow!(
+            "There's a merge commit ({}) after {}, so it can't be dropped",
+            &merges[..merges.len().min(10)],
+            short
+        ));
+    }
+
+    let remotes = GitCommand::new("for-each-ref")
+        .with_default_args(&[
+            &format!("--contains={sha}"),
+            "--format=%(refname:short)",
+            "refs/remotes",
+        ])
+        .stdout()?;
+    if !remotes.is_empty() && !force {
+        return Err(anyhow!(
+            "{} has been pushed to {}, so dropping it means force-pushing; use --force to drop it anyway",
+            short,
+            remotes.lines().collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    let range = if Git::object_exists(&format!("{sha}~"))? {
+        format!("{sha}~..HEAD")
+    } else {
+        "HEAD".to_string()
+    };
+    let todo: String = GitCommand::new("log")
+        .with_default_args(&["--reverse", "--format=%H %s", &range])
+        .stdout()?
+        .lines()
+        .map(|line| {
+            let action = if line.starts_with(&sha) {
+                "drop"
+            } else {
+                "pick"
+            };
+            format!("{action} {line}\n")
+        })
+        .collect();
+
+    super::backup::create("drop")?;
+
+    super::plan::rebase(&sha, &todo)
+}
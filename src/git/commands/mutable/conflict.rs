@@ -0,0 +1,198 @@
+use crate::{
+    git::{
+        state::{Conflict, ConflictKind},
+        Git, GitCommand, GitCommandResult, GitResult,
+    },
+    print::Print,
+    table::Table,
+    tui::picker::Picker,
+};
+use anyhow::anyhow;
+use log::{debug, trace};
+
+/// Which side of a conflict to keep.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Side {
+    /// The version on the current branch.
+    Ours,
+    /// The version being merged in.
+    Theirs,
+}
+
+impl Side {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            Side::Ours => "--ours",
+            Side::Theirs => "--theirs",
+        }
+    }
+
+    /// Whether keeping this side of a conflict of `kind` means deleting the file, since this side doesn't have it.
+    fn deletes(&self, kind: ConflictKind) -> bool {
+        matches!(
+            (self, kind),
+            (_, ConflictKind::BothDeleted)
+                | (
+                    Side::Ours,
+                    ConflictKind::DeletedByUs | ConflictKind::AddedByThem
+                )
+                | (
+                    Side::Theirs,
+                    ConflictKind::DeletedByThem | ConflictKind::AddedByUs
+                )
+        )
+    }
+}
+
+/// Run `git mergetool` on each conflicted file, then list any conflicts that remain.
+///
+/// Operates on `paths` if any are given; otherwise on the files chosen from a list if `pick` is `true`, or else on all
+/// conflicted files. `git mergetool` stages each file whose merge tool exits successfully.
+pub fn resolve(paths: &[String], pick: bool) -> GitResult {
+    trace!("resolve() called with: {paths:#?}, {pick}");
+
+    let conflicts = Conflict::list()?;
+
+    if conflicts.is_empty() {
+        Print::info("No conflicts");
+        return Ok(GitCommandResult::Success);
+    }
+
+    let targets: Vec<String> = if !paths.is_empty() {
+        paths.to_vec()
+    } else {
+        let conflicts: Vec<String> = conflicts.into_iter().map(|c| c.path).collect();
+
+        let selected: Vec<String> = if pick {
+            Picker::select_many(&conflicts)?
+                .into_iter()
+                .map(|i| conflicts[i].clone())
+                .collect()
+        } else {
+            conflicts
+        };
+
+        // conflict paths are relative to the repository root, not the current directory
+        let root = Git::repo_root()?;
+        selected
+            .into_iter()
+            .map(|path| root.join(path).to_string_lossy().to_string())
+            .collect()
+    };
+
+    for path in &targets {
+        if let GitCommandResult::Error = GitCommand::new("mergetool")
+            .with_default_args(&["--no-prompt", "--"])
+            .with_user_args(&[path.to_string()])
+            .run()?
+        {
+            Print::stderr_purple(&format!("{path} was not resolved"));
+        }
+    }
+
+    let remaining = Conflict::list()?;
+
+    if remaining.is_empty() {
+        Print::info("All conflicts resolved");
+        Ok(GitCommandResult::Success)
+    } else {
+        let mut table = Table::new();
+        for conflict in remaining {
+            table.add_row([conflict.kind.to_string(), conflict.path]);
+        }
+
+        println!("Remaining conflicts:\n{table}");
+        Ok(GitCommandResult::Error)
+    }
+}
+
+/// Resolve the conflicts in `paths` by keeping `side`, then stage them.
+///
+/// `git checkout --ours|--theirs -- PATHS && git add -- PATHS`
+pub fn take(side: Side, paths: &[String]) -> GitResult {
+    trace!("take() called with: {side:?}, {paths:#?}");
+
+    if paths.is_empty() {
+        return Err(anyhow!("Must supply paths"));
+    }
+
+    match GitCommand::new("checkout")
+        .with_default_args(&[side.as_arg(), "--"])
+        .with_user_args(paths)
+        .run()?
+    {
+        GitCommandResult::Success => GitCommand::new("add")
+            .with_default_args(&["--"])
+            .with_user_args(paths)
+            .run(),
+        GitCommandResult::Error => Ok(GitCommandResult::Error),
+    }
+}
+
+/// Resolve every conflicted file by keeping `side`, then stage them.
+///
+/// Files that were deleted on the kept side are removed with `git rm`.
+pub fn take_all(side: Side) -> GitResult {
+    trace!("take_all() called with: {side:?}");
+
+    let root = Git::repo_root()?;
+    let mut kept: Vec<String> = Vec::new();
+    let mut deleted: Vec<String> = Vec::new();
+
+    for conflict in Conflict::list()? {
+        let path = root.join(&conflict.path).to_string_lossy().to_string();
+
+        if side.deletes(conflict.kind) {
+            deleted.push(path);
+        } else {
+            kept.push(path);
+        }
+    }
+
+    debug!("keeping: {kept:#?}, deleting: {deleted:#?}");
+
+    if kept.is_empty() && deleted.is_empty() {
+        Print::info("No conflicts");
+        return Ok(GitCommandResult::Success);
+    }
+
+    if !kept.is_empty() {
+        if let GitCommandResult::Error = take(side, &kept)? {
+            return Ok(GitCommandResult::Error);
+        }
+    }
+
+    if deleted.is_empty() {
+        Ok(GitCommandResult::Success)
+    } else {
+        GitCommand::new("rm")
+            .with_default_args(&["--quiet", "--"])
+            .with_user_args(&deleted)
+            .run()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deletes_when_the_side_has_no_version() {
+        assert!(Side::Ours.deletes(ConflictKind::DeletedByUs));
+        assert!(Side::Ours.deletes(ConflictKind::AddedByThem));
+        assert!(Side::Theirs.deletes(ConflictKind::DeletedByThem));
+        assert!(Side::Theirs.deletes(ConflictKind::AddedByUs));
+        assert!(Side::Ours.deletes(ConflictKind::BothDeleted));
+        assert!(Side::Theirs.deletes(ConflictKind::BothDeleted));
+    }
+
+    #[test]
+    fn keeps_when_the_side_has_a_version() {
+        assert!(!Side::Ours.deletes(ConflictKind::AddedByUs));
+        assert!(!Side::Ours.deletes(ConflictKind::DeletedByThem));
+        assert!(!Side::Theirs.deletes(ConflictKind::AddedByThem));
+        assert!(!Side::Theirs.deletes(ConflictKind::DeletedByUs));
+        assert!(!Side::Ours.deletes(ConflictKind::BothModified));
+        assert!(!Side::Theirs.deletes(ConflictKind::BothAdded));
+    }
+}
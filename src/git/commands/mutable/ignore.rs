@@ -0,0 +1,214 @@
+use crate::{
+    cache::Cache,
+    git::{Git, GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    print::Print,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+/// The `.gitignore` templates that are built in, by name.
+const BUNDLED_TEMPLATES: &[(&str, &str)] = &[
+    ("Go", "*.exe\n*.test\n*.out\n/vendor/\ngo.work\n"),
+    (
+        "Java",
+        "*.class\n*.jar\n*.war\n*.ear\n*.log\nhs_err_pid*\n/target/\n/build/\n.gradle/\n",
+    ),
+    (
+        "JetBrains",
+        ".idea/\n*.iml\n*.iws\n*.ipr\nout/\n",
+    ),
+    ("Linux", "*~\n.fuse_hidden*\n.directory\n.Trash-*\n.nfs*\n"),
+    (
+        "macOS",
+        ".DS_Store\n.AppleDouble\n.LSOverride\n._*\n.Spotlight-V100\n.Trashes\n",
+    ),
+    (
+        "Node",
+        "node_modules/\nnpm-debug.log*\nyarn-debug.log*\nyarn-error.log*\n.npm/\n.env\ndist/\ncoverage/\n",
+    ),
+    (
+        "Python",
+        "__pycache__/\n*.py[cod]\n*.egg-info/\n.eggs/\nbuild/\ndist/\n.venv/\nvenv/\n.pytest_cache/\n.mypy_cache/\n.coverage\n",
+    ),
+    ("Rust", "/target/\n**/*.rs.bk\n*.pdb\n"),
+    (
+        "VisualStudioCode",
+        ".vscode/*\n!.vscode/settings.json\n!.vscode/tasks.json\n!.vscode/launch.json\n!.vscode/extensions.json\n",
+    ),
+    (
+        "Windows",
+        "Thumbs.db\nehthumbs.db\nDesktop.ini\n$RECYCLE.BIN/\n*.lnk\n",
+    ),
+];
+
+/// Add the `.gitignore` templates `names` to the root `.gitignore`, then stage it.
+///
+/// Each template's new patterns are added under a `# NAME` comment; patterns that are already in the file are skipped.
+/// Names are matched case-insensitively, first against the cached templates in `~/.cache/git-util/gitignore/` (so
+/// that a bundled template can be overridden), then against the bundled ones.
+pub fn templates(names: &[String]) -> GitResult {
+    trace!("templates() called with: {names:#?}");
+
+    let mut sections = Vec::new();
+    for name in names {
+        let (name, contents) = template(name)?;
+        sections.push((format!("# {name}"), contents));
+    }
+
+    update(&sections)
+}
+
+/// Add `patterns` to the root `.gitignore`, skipping any that are already in it, then stage it.
+pub fn add(patterns: &[String]) -> GitResult {
+    trace!("add() called with: {patterns:#?}");
+
+    update(&[(String::new(), patterns.join("\n"))])
+}
+
+/// Append the patterns in each `(HEADER, PATTERNS)` section that aren't already in the root `.gitignore` (preceded by
+/// `HEADER`, if it isn't empty), then stage it.
+///
+/// `git add .gitignore`
+fn update(sections: &[(String, String)]) -> GitResult {
+    let path = Git::repo_root()?.join(".gitignore");
+    let mut contents = fs::read_to_string(&path).unwrap_or_default();
+
+    let mut existing: HashSet<String> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| is_pattern(line))
+        .map(String::from)
+        .collect();
+
+    let mut added = Vec::new();
+
+    for (header, patterns) in sections {
+        let new_patterns: Vec<&str> = patterns
+            .lines()
+            .map(str::trim)
+            .filter(|line| is_pattern(line))
+            .filter(|pattern| existing.insert(pattern.to_string()))
+            .collect();
+
+        debug!("new patterns for '{header}': {new_patterns:#?}");
+
+        if new_patterns.is_empty() {
+            continue;
+        }
+
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        if !header.is_empty() {
+            // separate each template from what comes before it
+            if !contents.is_empty() {
+                contents.push('\n');
+            }
+            contents.push_str(&format!("{header}\n"));
+        }
+        for pattern in &new_patterns {
+            contents.push_str(&format!("{pattern}\n"));
+        }
+
+        added.extend(new_patterns);
+    }
+
+    if added.is_empty() {
+        Print::info("Those patterns are already in .gitignore");
+        return Ok(GitCommandResult::Success);
+    }
+
+    if DRY_RUN.load(Ordering::SeqCst) {
+        Print::stderr_purple(&format!(
+            "would add to {}: {}",
+            path.display(),
+            added.join(", ")
+        ));
+    } else {
+        fs::write(&path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Print::info(&format!("Added {} patterns to .gitignore", added.len()));
+    }
+
+    GitCommand::new("add")
+        .with_default_args(&[&path.to_string_lossy()])
+        .run()
+}
+
+/// The name and contents of the template `name`, from the cache directory or else the bundled templates.
+///
+/// Fails with the names of the available templates if there's no such template.
+fn template(name: &str) -> Result<(String, String)> {
+    let cached = cached_templates();
+
+    if let Some(path) = cached
+        .iter()
+        .find(|path| template_name(path).eq_ignore_ascii_case(name))
+    {
+        debug!("using the cached template {}", path.display());
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        return Ok((template_name(path), contents));
+    }
+
+    if let Some((bundled_name, contents)) = BUNDLED_TEMPLATES
+        .iter()
+        .find(|(bundled_name, _)| bundled_name.eq_ignore_ascii_case(name))
+    {
+        return Ok((bundled_name.to_string(), contents.to_string()));
+    }
+
+    let mut available: Vec<String> = BUNDLED_TEMPLATES
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .chain(cached.iter().map(|path| template_name(path)))
+        .collect();
+    available.sort_by_key(|name| name.to_lowercase());
+    available.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+    Err(anyhow!(
+        "No .gitignore template named '{}' (available: {})",
+        name,
+        available.join(", ")
+    ))
+}
+
+/// The `*.gitignore` files in `~/.cache/git-util/gitignore/`.
+fn cached_templates() -> Vec<PathBuf> {
+    let Some(dir) = Cache::dir() else {
+        return Vec::new();
+    };
+
+    let Ok(entries) = fs::read_dir(dir.join("gitignore")) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "gitignore")
+        })
+        .collect()
+}
+
+/// The name of the template at `path`, e.g. `Rust` for `Rust.gitignore`.
+fn template_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Whether `line` of a `.gitignore` is a pattern, rather than a blank line or a comment.
+fn is_pattern(line: &str) -> bool {
+    !line.is_empty() && !line.starts_with('#')
+}
@@ -1,6 +1,11 @@
-use crate::git::{GitCommand, GitResult};
+use crate::{
+    git::{Git, GitCommand, GitCommandResult, GitResult},
+    output::OutputTarget,
+    print::Print,
+};
 use anyhow::anyhow;
 use log::{debug, trace};
+use std::io::Write;
 
 /// Wrapper around `git-restore`
 pub fn restore(args: &[String]) -> GitResult {
@@ -38,3 +43,97 @@ pub fn unstage_all() -> GitResult {
         .with_default_args(&["--staged", ":/"])
         .run()
 }
+
+/// Set the skip-worktree bit of `paths`, so that local changes to them are hidden from `git status`.
+///
+/// `git update-index --skip-worktree -- PATHS`
+pub fn skip(paths: &[String]) -> GitResult {
+    trace!("skip() called with: {paths:#?}");
+
+    GitCommand::new("update-index")
+        .with_default_args(&["--skip-worktree", "--"])
+        .with_user_args(paths)
+        .run()
+}
+
+/// Clear the skip-worktree bit of `paths`.
+///
+/// `git update-index --no-skip-worktree -- PATHS`
+pub fn unskip(paths: &[String]) -> GitResult {
+    trace!("unskip() called with: {paths:#?}");
+
+    GitCommand::new("update-index")
+        .with_default_args(&["--no-skip-worktree", "--"])
+        .with_user_args(paths)
+        .run()
+}
+
+/// Print the files in the repository that have the skip-worktree bit set.
+pub fn list_skipped() -> GitResult {
+    trace!("list_skipped() called");
+
+    print_files(&Git::skipped_files()?, "No files are skipped")
+}
+
+/// Mark `paths` as assume-unchanged, so that git doesn't check them for changes.
+///
+/// `git update-index --assume-unchanged -- PATHS`
+pub fn assume(paths: &[String]) -> GitResult {
+    trace!("assume() called with: {paths:#?}");
+
+    GitCommand::new("update-index")
+        .with_default_args(&["--assume-unchanged", "--"])
+        .with_user_args(paths)
+        .run()
+}
+
+/// Clear the assume-unchanged bit of `paths`.
+///
+/// `git update-index --no-assume-unchanged -- PATHS`
+pub fn unassume(paths: &[String]) -> GitResult {
+    trace!("unassume() called with: {paths:#?}");
+
+    GitCommand::new("update-index")
+        .with_default_args(&["--no-assume-unchanged", "--"])
+        .with_user_args(paths)
+        .run()
+}
+
+/// Print the files in the repository that are marked assume-unchanged.
+pub fn list_assumed() -> GitResult {
+    trace!("list_assumed() called");
+
+    print_files(
+        &Git::assumed_unchanged_files()?,
+        "No files are assumed unchanged",
+    )
+}
+
+/// Set the mode of `paths` in the index to `mode` (`+x` or `-x`), leaving the files on disk alone.
+///
+/// `git update-index --chmod=MODE -- PATHS`
+pub fn chmod(mode: &str, paths: &[String]) -> GitResult {
+    trace!("chmod() called with: {mode}, {paths:#?}");
+
+    GitCommand::new("update-index")
+        .with_default_args(&[&format!("--chmod={mode}"), "--"])
+        .with_user_args(paths)
+        .run()
+}
+
+/// Print `files` one per line, or `empty_message` if there are none.
+fn print_files(files: &[String], empty_message: &str) -> GitResult {
+    debug!("files: {files:#?}");
+
+    if files.is_empty() {
+        Print::info(empty_message);
+        return Ok(GitCommandResult::Success);
+    }
+
+    let mut writer = OutputTarget::writer()?;
+    for path in files {
+        writeln!(writer, "{path}")?;
+    }
+
+    Ok(GitCommandResult::Success)
+}
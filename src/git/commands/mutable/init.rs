@@ -0,0 +1,123 @@
+use super::template;
+use crate::{
+    git::{
+        commands::profile::Profile,
+        hooks::{self, config::PRE_COMMIT_CONFIG},
+        GitCommand, GitCommandResult, GitResult, DRY_RUN,
+    },
+    print::Print,
+    settings::Settings,
+};
+use anyhow::Context;
+use log::trace;
+use std::{env, fs, path::Path, sync::atomic::Ordering};
+
+/// The contents of a new `PRE_COMMIT_CONFIG`; everything is commented out, so it changes nothing until edited.
+const PRE_COMMIT_CONFIG_TEMPLATE: &str = r#"# Configuration for `git-util hook pre-commit`.
+
+# Fail commits whose author email isn't this address (overridden by `$GIT_UTIL_USER_EMAIL`).
+# user_email = "me@example.com"
+
+# Fail commits that add a line matching this case-insensitive regex (overridden by `$GIT_UTIL_DISALLOWED_STRINGS`).
+# disallowed_strings = "do not commit|nocommit"
+
+# Refuse commits made directly on the default branch and on branches matching these patterns.
+# protected_branches = ["release/*"]
+
+# Skip the checks for commits whose subject starts with one of these prefixes, merge commits, and commits on branches
+# matching one of these patterns; with `warn = true`, they run but only warn.
+# [skip]
+# subjects = ["WIP:", "fixup!", "squash!"]
+# merges = true
+# branches = ["spike/*"]
+
+# Run a command as a check, which fails if it exits with a non-zero status; `{staged_files}` is replaced with the staged
+# files, and `{root}` with the root of the work tree.
+# [checks.lint]
+# command = ["./scripts/lint.sh", "{staged_files}"]
+"#;
+
+/// Create a repository at `path` (else defaults to the current directory), on the default branch from the
+/// `git-util.defaultBranch` setting (if set), with a starter pre-commit hook configuration and the hook shims installed.
+///
+/// `git init [--initial-branch=BRANCH] [PATH]`
+///
+/// If `template` is given, its files (and the hook configuration) are copied into the work tree and committed. If
+/// `profile` is given, the repository is configured with its identity first, so that the initial commit uses it.
+pub fn init(path: Option<&str>, template: Option<&str>, profile: Option<&str>) -> GitResult {
+    trace!("init() called with: {path:?}, {template:?}, {profile:?}");
+
+    // fail before creating anything if the template or the profile doesn't exist
+    if let Some(template) = template {
+        template::directory(template)?;
+    }
+    if let Some(profile) = profile {
+        Profile::get(profile)?;
+    }
+
+    let initial_branch = Settings::get()
+        .default_branch
+        .as_ref()
+        .map(|branch| format!("--initial-branch={branch}"));
+
+    let mut args: Vec<&str> = initial_branch.iter().map(String::as_str).collect();
+    args.extend(path);
+
+    if let GitCommandResult::Error = GitCommand::new("init").with_default_args(&args).run()? {
+        return Ok(GitCommandResult::Error);
+    }
+
+    let path = Path::new(path.unwrap_or("."));
+
+    if DRY_RUN.load(Ordering::SeqCst) {
+        Print::stderr_purple(&format!(
+            "would write {} and install the hook shims",
+            path.join(PRE_COMMIT_CONFIG).display()
+        ));
+        if let Some(profile) = profile {
+            Print::stderr_purple(&format!("would use the {profile} profile"));
+        }
+        return match template {
+            Some(template) => template::copy(template).map(|_| GitCommandResult::Success),
+            None => Ok(GitCommandResult::Success),
+        };
+    }
+
+    env::set_current_dir(path)
+        .with_context(|| format!("Failed to change to {}", path.display()))?;
+
+    if let Some(profile) = profile {
+        if let GitCommandResult::Error = Profile::apply(profile)? {
+            return Ok(GitCommandResult::Error);
+        }
+    }
+
+    // the template may have its own hook configuration
+    let mut files = match template {
+        Some(template) => template::copy(template)?,
+        None => Vec::new(),
+    };
+
+    // re-initializing an existing repository shouldn't clobber its configuration
+    let config = Path::new(PRE_COMMIT_CONFIG);
+    if !config.exists() {
+        if let Some(directory) = config.parent() {
+            fs::create_dir_all(directory)
+                .with_context(|| format!("Failed to create {}", directory.display()))?;
+        }
+        fs::write(config, PRE_COMMIT_CONFIG_TEMPLATE)
+            .with_context(|| format!("Failed to write {}", config.display()))?;
+
+        Print::info(&format!("Created {}", config.display()));
+        files.push(PRE_COMMIT_CONFIG.to_string());
+    }
+
+    if let GitCommandResult::Error = hooks::install::install(false, false)? {
+        return Ok(GitCommandResult::Error);
+    }
+
+    match template {
+        Some(_) => template::commit(&files, "Initial commit"),
+        None => Ok(GitCommandResult::Success),
+    }
+}
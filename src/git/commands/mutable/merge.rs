@@ -0,0 +1,110 @@
+use crate::{
+    git::{Git, GitCommand, GitCommandResult, GitResult},
+    print::Print,
+    settings::Settings,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+
+/// How `git merge` is allowed to fast-forward.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum MergePolicy {
+    /// `--ff`
+    FastForward,
+    /// `--ff-only`
+    FastForwardOnly,
+    /// `--no-ff`
+    NoFastForward,
+}
+
+impl MergePolicy {
+    /// Parse the policy from a setting `value`, falling back to `default` if it is not set.
+    ///
+    /// Valid values are `ff`, `ff-only`, and `no-ff`.
+    fn from_setting(key: &str, value: Option<&str>, default: MergePolicy) -> Result<MergePolicy> {
+        match value {
+            None => Ok(default),
+            Some("ff") => Ok(MergePolicy::FastForward),
+            Some("ff-only") => Ok(MergePolicy::FastForwardOnly),
+            Some("no-ff") => Ok(MergePolicy::NoFastForward),
+            Some(other) => Err(anyhow!(
+                "Invalid value for {}: \"{}\". Expected one of: ff, ff-only, no-ff",
+                key,
+                other
+            )),
+        }
+    }
+
+    fn as_arg(&self) -> &'static str {
+        match self {
+            MergePolicy::FastForward => "--ff",
+            MergePolicy::FastForwardOnly => "--ff-only",
+            MergePolicy::NoFastForward => "--no-ff",
+        }
+    }
+}
+
+/// Merge `branch` into the current branch, using the fast-forward policy configured for it.
+///
+/// Merging the default branch (or its remote-tracking branch) uses `git-util.merge.defaultBranch` (defaults to
+/// `ff-only`); merging any other branch uses `git-util.merge.featureBranch` (defaults to `no-ff`).
+///
+/// Fails if there are uncommitted changes, or if another operation is in progress. If the merge results in conflicts,
+/// the conflicted files are listed.
+pub fn merge(branch: &str, args: &[String]) -> GitResult {
+    trace!("merge() called with: {branch}, {args:#?}");
+
+    super::ensure_no_operation()?;
+
+    if let GitCommandResult::Error = Git::verify_clean_work_tree()? {
+        return Err(anyhow!(
+            "There are uncommitted changes; commit or stash them before merging!"
+        ));
+    }
+
+    let default_branch = Git::default_branch()?;
+    let merging_default_branch = branch == default_branch
+        || branch
+            .split_once('/')
+            .is_some_and(|(_, name)| name == default_branch);
+
+    let settings = Settings::get();
+    let policy = if merging_default_branch {
+        MergePolicy::from_setting(
+            "git-util.merge.defaultBranch",
+            settings.merge_default_branch.as_deref(),
+            MergePolicy::FastForwardOnly,
+        )?
+    } else {
+        MergePolicy::from_setting(
+            "git-util.merge.featureBranch",
+            settings.merge_feature_branch.as_deref(),
+            MergePolicy::NoFastForward,
+        )?
+    };
+
+    debug!("merge policy for {branch}: {policy:?}");
+
+    match GitCommand::new("merge")
+        .with_default_args(&[policy.as_arg(), branch])
+        .with_user_args(args)
+        .run()?
+    {
+        GitCommandResult::Success => Ok(GitCommandResult::Success),
+        GitCommandResult::Error => {
+            let conflicts = Git::conflicted_files()?;
+
+            if !conflicts.is_empty() {
+                Print::stderr_purple(&format!(
+                    "Merge conflicts in:\n\n  {}\n",
+                    conflicts.join("\n  ")
+                ));
+                Print::stderr_purple(
+                    "Resolve the conflicts, then run `git-util continue --add`; or run `git-util abort` to cancel.",
+                );
+            }
+
+            Ok(GitCommandResult::Error)
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::git::{state::Operation, Git, GitCommand, GitCommandResult, GitResult};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use std::fs;
+
+/// Abort whichever operation (merge, rebase, cherry-pick, revert, bisect, or am) is in progress.
+///
+/// Fails if no operation is in progress.
+pub fn abort() -> GitResult {
+    trace!("abort() called");
+
+    match Operation::in_progress()? {
+        Some(operation) => operation.abort_command().run(),
+        None => Err(anyhow!("There is no operation in progress to abort")),
+    }
+}
+
+/// Continue whichever operation (merge, rebase, cherry-pick, revert, or am) is in progress.
+///
+/// If `add` is `true`, the files that had conflicts are staged first; this fails if any of them still contain conflict
+/// markers.
+pub fn continue_operation(add: bool) -> GitResult {
+    trace!("continue_operation() called with: {add}");
+
+    let operation = match Operation::in_progress()? {
+        Some(operation) => operation,
+        None => return Err(anyhow!("There is no operation in progress to continue")),
+    };
+
+    let continue_command = match operation.continue_command() {
+        Some(command) => command,
+        None => {
+            return Err(anyhow!(
+            "'{}' has no --continue; mark the commit with `git bisect good` or `git bisect bad`",
+            operation
+        ))
+        }
+    };
+
+    if add {
+        let resolved = resolved_files()?;
+
+        if !resolved.is_empty() {
+            if let GitCommandResult::Error = GitCommand::new("add")
+                .with_default_args(&["--"])
+                .with_user_args(&resolved)
+                .run()?
+            {
+                return Ok(GitCommandResult::Error);
+            }
+        }
+    }
+
+    continue_command.run()
+}
+
+/// The absolute paths of the conflicted files that no longer contain conflict markers.
+///
+/// Fails if any conflicted file still has conflict markers.
+fn resolved_files() -> Result<Vec<String>> {
+    let root = Git::repo_root()?;
+    let conflicts: Vec<String> = Git::conflicted_files()?
+        .iter()
+        .map(|path| root.join(path).to_string_lossy().to_string())
+        .collect();
+    let mut unresolved: Vec<&str> = Vec::new();
+
+    for path in &conflicts {
+        // a file deleted on one side may not exist in the work tree
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err).with_context(|| format!("Failed to read {path}")),
+        };
+
+        if String::from_utf8_lossy(&contents)
+            .lines()
+            .any(|line| line.starts_with("<<<<<<< ") || line.starts_with(">>>>>>> "))
+        {
+            unresolved.push(path);
+        }
+    }
+
+    debug!("unresolved conflicts: {unresolved:#?}");
+
+    if unresolved.is_empty() {
+        Ok(conflicts)
+    } else {
+        Err(anyhow!(
+            "There are still conflict markers in: {}",
+            unresolved.join(", ")
+        ))
+    }
+}
@@ -0,0 +1,427 @@
+use crate::{
+    git::{Git, GitCommand, GitCommandResult, GitResult},
+    print::Print,
+    tui::{self, Key, Terminal},
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use nu_ansi_term::{Color, Style};
+use std::{env, fmt::Display, fs, path::Path};
+
+/// The todo list that `rebase()` gives the rebase, relative to the `.git` directory.
+const TODO: &str = "git-wrapper/rebase-plan";
+
+const HELP: &str =
+    "j/k: select  J/K: move  p: pick  r: reword  s: squash  f: fixup  d: drop  enter: run  q: cancel";
+
+/// What the rebase does with a commit; the commands of a rebase todo list that a plan can use.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Action {
+    Pick,
+    /// Pick the commit, then edit its message.
+    Reword,
+    /// Meld the commit into the previous one, and edit the combined message.
+    Squash,
+    /// Meld the commit into the previous one, keeping the previous one's message.
+    Fixup,
+    Drop,
+}
+
+#[derive(Debug)]
+struct Commit {
+    /// Where the commit was in the list before it was moved.
+    position: usize,
+    sha: String,
+    short_sha: String,
+    subject: String,
+    action: Action,
+}
+
+/// A full-screen editor for rebasing recent commits: they're listed oldest first (as in a rebase todo list), and can be
+/// reordered, reworded, squashed, fixed up, or dropped, without editing the todo list by hand.
+#[derive(Debug)]
+struct RebasePlan {
+    commits: Vec<Commit>,
+    selected: usize,
+    /// The index of the first commit in the list that's on screen.
+    list_offset: usize,
+    /// A message shown in place of the help, until the next key is pressed.
+    message: Option<String>,
+}
+
+impl Action {
+    fn color(&self) -> Color {
+        match self {
+            Action::Pick => Color::Green,
+            Action::Reword => Color::Cyan,
+            Action::Squash | Action::Fixup => Color::Yellow,
+            Action::Drop => Color::Red,
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Action::Pick => "pick",
+                Action::Reword => "reword",
+                Action::Squash => "squash",
+                Action::Fixup => "fixup",
+                Action::Drop => "drop",
+            }
+        )
+    }
+}
+
+/// Plan a rebase of the last `num` commits (else 10) in a full-screen editor, then run it.
+///
+/// The plan is written as the todo list of `git rebase --interactive --autostash`, which is given to the rebase with
+/// `GIT_SEQUENCE_EDITOR`, so the todo list isn't opened in an editor (though the messages of reworded and squashed
+/// commits are). `HEAD` is backed up first.
+///
+/// Fails if there's a merge commit among them, or if the current branch is protected (unless `force_protected` is
+/// `true`).
+pub fn plan(num: Option<u16>, force_protected: bool) -> GitResult {
+    trace!("plan() called with: {num:?}, {force_protected}");
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+
+    let num = num.unwrap_or(10).max(1);
+    let commits = RebasePlan::commits(num)?;
+
+    if commits.is_empty() {
+        Print::info("No commits to plan");
+        return Ok(GitCommandResult::Success);
+    }
+
+    let mut plan = RebasePlan {
+        commits,
+        selected: 0,
+        list_offset: 0,
+        message: None,
+    };
+
+    // the terminal is restored before the rebase runs
+    if !plan.run(&mut Terminal::open()?)? {
+        return Ok(GitCommandResult::Success);
+    }
+
+    let todo = plan.todo();
+
+    if plan
+        .commits
+        .iter()
+        .enumerate()
+        .all(|(i, commit)| commit.action == Action::Pick && commit.position == i)
+    {
+        Print::info("The plan doesn't change anything");
+        return Ok(GitCommandResult::Success);
+    }
+
+    super::backup::create("plan")?;
+
+    rebase(&plan.commits[0].sha, &todo)
+}
+
+/// Rebase the commits since `oldest` (inclusive) with the todo list `todo` instead of the one that `git rebase` makes,
+/// without opening it in an editor.
+///
+/// `git rebase --interactive --autostash OLDEST~` (or `--root`), with `GIT_SEQUENCE_EDITOR` set to `git-util
+/// __sequence-editor`, which copies `todo` over the todo list (see `write_todo()`)
+pub fn rebase(oldest: &str, todo: &str) -> GitResult {
+    trace!("rebase() called with: {oldest}");
+    debug!("todo:\n{todo}");
+
+    let path = Git::git_path(TODO)?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)
+            .with_context(|| format!("Failed to create {}", directory.display()))?;
+    }
+    fs::write(&path, todo).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    // git runs the sequence editor (with `sh`) with the path of its todo list
+    let executable =
+        env::current_exe().with_context(|| "Failed to find the git-util executable")?;
+    env::set_var(
+        "GIT_SEQUENCE_EDITOR",
+        format!(
+            "'{}' __sequence-editor",
+            executable.to_string_lossy().replace('\'', r"'\''")
+        ),
+    );
+
+    let parent = format!("{oldest}~");
+    let base = if Git::object_exists(&parent)? {
+        parent.as_str()
+    } else {
+        "--root"
+    };
+
+    GitCommand::new("rebase")
+        .with_default_args(&["--interactive", "--autostash", base])
+        .run()
+}
+
+/// Copy the todo list that `rebase()` wrote over the rebase's todo list at `path`; `rebase()` makes this the sequence
+/// editor of the rebase.
+pub fn write_todo(path: &Path) -> GitResult {
+    trace!("write_todo() called with: {}", path.display());
+
+    let todo = Git::git_path(TODO)?;
+    fs::copy(&todo, path)
+        .with_context(|| format!("Failed to copy {} to {}", todo.display(), path.display()))?;
+
+    Ok(GitCommandResult::Success)
+}
+
+impl RebasePlan {
+    /// The last `num` commits on `HEAD`, oldest first.
+    ///
+    /// Fails if one of them is a merge, since the todo list replaces the one that `git rebase` makes, and that would
+    /// include the commits that were merged.
+    ///
+    /// `git log --reverse --first-parent --max-count=NUM --format=%H%x00%h%x00%P%x00%s`
+    fn commits(num: u16) -> Result<Vec<Commit>> {
+        let log = GitCommand::new("log")
+            .with_default_args(&[
+                "--reverse",
+                "--first-parent",
+                &format!("--max-count={num}"),
+                "--format=%H%x00%h%x00%P%x00%s",
+                "HEAD",
+            ])
+            .stdout()?;
+
+        let mut commits = Vec::new();
+
+        for (position, line) in log.lines().enumerate() {
+            let mut fields = line.splitn(4, '\0');
+            let (Some(sha), Some(short_sha), Some(parents), Some(subject)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+
+            if parents.contains(' ') {
+                return Err(anyhow!(
+                    "{} is a merge commit, which a plan can't rebase; plan fewer commits",
+                    short_sha
+                ));
+            }
+
+            commits.push(Commit {
+                position,
+                sha: sha.to_string(),
+                short_sha: short_sha.to_string(),
+                subject: subject.to_string(),
+                action: Action::Pick,
+            });
+        }
+
+        Ok(commits)
+    }
+
+    /// Handle keys until the plan is run (`true`) or cancelled (`false`).
+    fn run(&mut self, terminal: &mut Terminal) -> Result<bool> {
+        loop {
+            let (rows, _) = terminal.size()?;
+            let list_height = rows.saturating_sub(3).max(1);
+
+            terminal.draw(&self.render(list_height))?;
+
+            let key = terminal.read_key()?;
+            self.message = None;
+
+            match key {
+                Key::Char('q') | Key::Escape | Key::Interrupt => return Ok(false),
+                Key::Char('j') | Key::Down => {
+                    self.selected = (self.selected + 1).min(self.commits.len() - 1)
+                }
+                Key::Char('k') | Key::Up => self.selected = self.selected.saturating_sub(1),
+                Key::Char('g') | Key::Home => self.selected = 0,
+                Key::Char('G') | Key::End => self.selected = self.commits.len() - 1,
+                Key::Char('J') if self.selected + 1 < self.commits.len() => {
+                    self.commits.swap(self.selected, self.selected + 1);
+                    self.selected += 1;
+                }
+                Key::Char('K') if self.selected > 0 => {
+                    self.commits.swap(self.selected, self.selected - 1);
+                    self.selected -= 1;
+                }
+                Key::Char(c @ ('p' | 'r' | 's' | 'f' | 'd')) => {
+                    self.commits[self.selected].action = match c {
+                        'p' => Action::Pick,
+                        'r' => Action::Reword,
+                        's' => Action::Squash,
+                        'f' => Action::Fixup,
+                        _ => Action::Drop,
+                    };
+                }
+                Key::Enter => match self.problem() {
+                    Some(problem) => self.message = Some(problem),
+                    None => {
+                        self.message = Some("Run the rebase? [y/N]".to_string());
+                        terminal.draw(&self.render(list_height))?;
+
+                        if terminal.read_key()? == Key::Char('y') {
+                            return Ok(true);
+                        }
+                        self.message = None;
+                    }
+                },
+                _ => {}
+            }
+
+            // keep the selected commit on screen
+            if self.selected < self.list_offset {
+                self.list_offset = self.selected;
+            } else if self.selected >= self.list_offset + list_height {
+                self.list_offset = self.selected + 1 - list_height;
+            }
+        }
+    }
+
+    /// Why the plan can't be run, if it can't.
+    fn problem(&self) -> Option<String> {
+        // a squash or fixup needs a commit before it to meld into
+        match self
+            .commits
+            .iter()
+            .find(|commit| commit.action != Action::Drop)
+        {
+            None => Some("The plan drops every commit; use `undo` for that".to_string()),
+            Some(first) if matches!(first.action, Action::Squash | Action::Fixup) => Some(format!(
+                "{} can't be a {}, since there's no commit before it to meld it into",
+                first.short_sha, first.action
+            )),
+            Some(_) => None,
+        }
+    }
+
+    /// The lines of the screen: a heading, the commits, and the help (or a message).
+    fn render(&self, list_height: usize) -> Vec<String> {
+        let mut lines = vec![Style::new()
+            .bold()
+            .paint(format!(
+                "Rebase plan for the last {} commits, oldest first",
+                self.commits.len()
+            ))
+            .to_string()];
+
+        for (i, commit) in self
+            .commits
+            .iter()
+            .enumerate()
+            .skip(self.list_offset)
+            .take(list_height)
+        {
+            let action = commit.action.color().paint(format!("{:<6}", commit.action));
+            let line = format!(
+                "{action} {} {}",
+                Color::Yellow.paint(&commit.short_sha),
+                commit.subject
+            );
+            let line = match commit.action {
+                Action::Drop => Style::new()
+                    .dimmed()
+                    .strikethrough()
+                    .paint(line)
+                    .to_string(),
+                _ => line,
+            };
+
+            lines.push(if i == self.selected {
+                format!("{} {line}", Style::new().bold().paint(">"))
+            } else {
+                format!("  {line}")
+            });
+        }
+
+        lines.resize(list_height + 1, String::new());
+        lines.push(tui::footer(self.message.as_deref().unwrap_or(HELP)));
+
+        lines
+    }
+
+    /// The plan as a rebase todo list.
+    fn todo(&self) -> String {
+        self.commits
+            .iter()
+            .map(|commit| format!("{} {} {}\n", commit.action, commit.sha, commit.subject))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(actions: &[Action]) -> RebasePlan {
+        RebasePlan {
+            commits: actions
+                .iter()
+                .enumerate()
+                .map(|(position, &action)| Commit {
+                    position,
+                    sha: format!("{position}000000000"),
+                    short_sha: format!("{position}000"),
+                    subject: format!("commit {position}"),
+                    action,
+                })
+                .collect(),
+            selected: 0,
+            list_offset: 0,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn todo_lists_the_commits_in_order() {
+        let mut plan = plan(&[Action::Pick, Action::Fixup, Action::Drop]);
+        plan.commits.swap(0, 2);
+
+        assert_eq!(
+            plan.todo(),
+            "drop 2000000000 commit 2\nfixup 1000000000 commit 1\npick 0000000000 commit 0\n"
+        );
+    }
+
+    #[test]
+    fn problem_with_nothing_to_meld_into() {
+        assert_eq!(
+            plan(&[Action::Pick, Action::Squash, Action::Reword]).problem(),
+            None
+        );
+        assert_eq!(
+            plan(&[Action::Drop, Action::Reword, Action::Fixup]).problem(),
+            None
+        );
+        assert_eq!(
+            plan(&[Action::Drop, Action::Fixup, Action::Pick]).problem(),
+            Some(
+                "1000 can't be a fixup, since there's no commit before it to meld it into"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            plan(&[Action::Squash]).problem(),
+            Some(
+                "0000 can't be a squash, since there's no commit before it to meld it into"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn problem_with_every_commit_dropped() {
+        assert_eq!(
+            plan(&[Action::Drop, Action::Drop]).problem(),
+            Some("The plan drops every commit; use `undo` for that".to_string())
+        );
+    }
+}
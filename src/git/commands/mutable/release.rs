@@ -0,0 +1,501 @@
+use crate::{
+    audit_log::json_string,
+    commands::Commands,
+    git::{
+        commands::changelog::Changelog,
+        forge::{Forge, ForgeRepo},
+        Git, GitCommand, GitCommandResult, GitResult, DRY_RUN,
+    },
+    print::Print,
+    settings::Settings,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use regex::Regex;
+use std::{env, fmt, fs, io::Write, path::Path, process, sync::atomic::Ordering};
+
+/// Which part of a semantic version to increment.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, clap::ValueEnum)]
+pub enum Level {
+    Major,
+    Minor,
+    Patch,
+}
+
+/// A `MAJOR.MINOR.PATCH` version parsed from a tag, e.g. `v1.2.3`.
+///
+/// The field order matters: the derived `Ord` compares the numbers before the prefix.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    /// The text before the version number in the tag, e.g. `v`.
+    prefix: String,
+}
+
+impl Version {
+    /// Parse a tag of the form `[PREFIX]MAJOR.MINOR.PATCH`; pre-release and build suffixes are not supported.
+    fn parse(tag: &str) -> Option<Version> {
+        let captures = Regex::new(r"^(\D*)(\d+)\.(\d+)\.(\d+)$")
+            .ok()?
+            .captures(tag)?;
+
+        Some(Version {
+            major: captures[2].parse().ok()?,
+            minor: captures[3].parse().ok()?,
+            patch: captures[4].parse().ok()?,
+            prefix: captures[1].to_string(),
+        })
+    }
+
+    /// The next version at `level`, e.g. `1.2.3` bumped at `Minor` is `1.3.0`.
+    fn bump(&self, level: Level) -> Version {
+        let (major, minor, patch) = match level {
+            Level::Major => (self.major + 1, 0, 0),
+            Level::Minor => (self.major, self.minor + 1, 0),
+            Level::Patch => (self.major, self.minor, self.patch + 1),
+        };
+
+        Version {
+            major,
+            minor,
+            patch,
+            prefix: self.prefix.clone(),
+        }
+    }
+
+    /// The version without its prefix, e.g. `1.2.3`.
+    fn number(&self) -> String {
+        format!("{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.prefix, self.number())
+    }
+}
+
+/// Create an annotated tag for the next version after the latest semver tag (else `v0.0.0`), incrementing `level`.
+///
+/// If `file` is given, the version in it (e.g. `version = "1.2.3"` in `Cargo.toml`) is updated and committed first.
+pub fn bump(level: Level, file: Option<&Path>) -> GitResult {
+    trace!("bump() called with: {level:?}, {file:?}");
+
+    super::ensure_no_operation()?;
+
+    let current = match latest_version()? {
+        Some(version) => version,
+        None => {
+            Print::info("No semver tags found; starting from v0.0.0");
+            Version {
+                major: 0,
+                minor: 0,
+                patch: 0,
+                prefix: "v".to_string(),
+            }
+        }
+    };
+    let next = current.bump(level);
+
+    Print::info(&format!("{current} -> {next}"));
+
+    if let Some(file) = file {
+        if let GitCommandResult::Error = update_version_file(file, &current, &next)? {
+            return Ok(GitCommandResult::Error);
+        }
+    }
+
+    GitCommand::new("tag")
+        .with_default_args(&[
+            "--annotate",
+            &next.to_string(),
+            "--message",
+            &format!("Release {next}"),
+        ])
+        .run()
+}
+
+/// Tag `version` with release notes generated from the commits since the previous tag, and push the tag to `origin`.
+///
+/// If `origin` is on GitHub or GitLab and `git-util.releaseToken` is set, a release is also created through the forge's
+/// API (using `curl`).
+pub fn release(version: &str) -> GitResult {
+    trace!("release() called with: {version}");
+
+    super::ensure_no_operation()?;
+
+    if Version::parse(version).is_none() {
+        return Err(anyhow!(
+            "'{}' is not a semantic version (e.g. v1.2.3)",
+            version
+        ));
+    }
+    if Git::object_exists(&format!("refs/tags/{version}"))? {
+        return Err(anyhow!("Tag '{}' already exists", version));
+    }
+
+    let previous = Git::latest_tag("HEAD")?;
+    let notes = Changelog::markdown(previous.as_deref(), "HEAD", version)?;
+
+    Print::info(&format!(
+        "Release notes for {version} (since {}):\n\n{notes}",
+        previous.as_deref().unwrap_or("the first commit")
+    ));
+
+    // `verbatim`, so that the Markdown headings aren't stripped as comments
+    if let GitCommandResult::Error = GitCommand::new("tag")
+        .with_default_args(&[
+            "--annotate",
+            version,
+            "--cleanup=verbatim",
+            "--message",
+            &notes,
+        ])
+        .run()?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    if let GitCommandResult::Error = GitCommand::new("push")
+        .with_default_args(&["origin", version])
+        .run_with_progress(&format!("pushing {version}"))?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    let Some(forge) = ForgeRepo::from_remote("origin")?
+        .filter(|repo| matches!(repo.forge, Forge::GitHub | Forge::GitLab))
+    else {
+        debug!("origin is not on GitHub or GitLab; not creating a release");
+        return Ok(GitCommandResult::Success);
+    };
+
+    let Some(token) = &Settings::get().release_token else {
+        Print::info("git-util.releaseToken is not set; skipping creating the release");
+        return Ok(GitCommandResult::Success);
+    };
+
+    if DRY_RUN.load(Ordering::SeqCst) {
+        Print::stderr_purple(&format!(
+            "would create a {:?} release for {version} in {}",
+            forge.forge,
+            forge.web_url()
+        ));
+        return Ok(GitCommandResult::Success);
+    }
+
+    let release_url = publish(&forge, version, &notes, token)?;
+    Print::info(&format!("Created release {release_url}"));
+
+    Ok(GitCommandResult::Success)
+}
+
+/// Create a release for the existing tag `version` through the API of `forge`, and return the release's web URL.
+///
+/// The token is passed to `curl` in a header file, rather than as an argument, so that it isn't visible in the process
+/// list or written to the audit log.
+fn publish(forge: &ForgeRepo, version: &str, notes: &str, token: &str) -> Result<String> {
+    trace!("publish() called with: {forge:?}, {version}");
+
+    let web_url = forge.web_url();
+    let (api_url, header, body_field, release_url) = match forge.forge {
+        Forge::GitHub => (
+            match forge.host.as_str() {
+                "github.com" => format!(
+                    "https://api.github.com/repos/{}/{}/releases",
+                    forge.owner, forge.name
+                ),
+                // GitHub Enterprise Server
+                host => format!(
+                    "https://{host}/api/v3/repos/{}/{}/releases",
+                    forge.owner, forge.name
+                ),
+            },
+            format!("Authorization: Bearer {token}"),
+            "body",
+            format!("{web_url}/releases/tag/{version}"),
+        ),
+        Forge::GitLab => (
+            format!(
+                "https://{}/api/v4/projects/{}%2F{}/releases",
+                forge.host,
+                forge.owner.replace('/', "%2F"),
+                forge.name
+            ),
+            format!("PRIVATE-TOKEN: {token}"),
+            "description",
+            format!("{web_url}/-/releases/{version}"),
+        ),
+        _ => {
+            return Err(anyhow!(
+                "Creating releases on {} is not supported",
+                forge.host
+            ))
+        }
+    };
+
+    let body = format!(
+        "{{\"tag_name\":{},\"name\":{},\"{body_field}\":{}}}",
+        json_string(version),
+        json_string(version),
+        json_string(notes)
+    );
+
+    let header_file = env::temp_dir().join(format!("git-util-release-{}", process::id()));
+    write_private_file(&header_file, &header)?;
+
+    let output = Commands::output_with_timeout(&mut Commands::new_command_with_args(
+        "curl",
+        &[
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--header",
+            &format!("@{}", header_file.display()),
+            "--header",
+            "Content-Type: application/json",
+            "--data-binary",
+            &body,
+            &api_url,
+        ],
+    ));
+    let _ = fs::remove_file(&header_file);
+
+    let output = output.with_context(|| "Failed to execute curl; is it installed?")?;
+
+    if output.status.success() {
+        Ok(release_url)
+    } else {
+        Err(anyhow!(
+            "Failed to create the release: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Write `contents` to a new file at `path` that only the current user can read.
+fn write_private_file(path: &Path, contents: &str) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The highest version among the tags that look like semantic versions.
+///
+/// `git tag --list`
+fn latest_version() -> Result<Option<Version>> {
+    let tags = GitCommand::new("tag")
+        .with_default_args(&["--list"])
+        .stdout()?;
+
+    // versions are ordered by their numbers first, so the prefix only breaks ties
+    let latest = tags.lines().filter_map(Version::parse).max();
+
+    debug!("latest version: {latest:?}");
+
+    Ok(latest)
+}
+
+/// Replace `current` with `next` in `file`, then commit just it (`git commit --only -- FILE`).
+///
+/// In a `Cargo.toml`, the first `version = "..."` line is updated, whatever its value, and the tracked `Cargo.lock` of
+/// its workspace (if there is one) is updated to match and committed with it; in any other file, the first occurrence
+/// of the current version number is.
+fn update_version_file(file: &Path, current: &Version, next: &Version) -> GitResult {
+    trace!("update_version_file() called with: {file:?}, {current}, {next}");
+
+    let contents =
+        fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?;
+
+    let is_manifest = file.file_name().is_some_and(|name| name == "Cargo.toml");
+
+    let updated = if is_manifest {
+        let version_line = Regex::new(r#"(?m)^version\s*=\s*"[^"]*""#)?;
+
+        if !version_line.is_match(&contents) {
+            return Err(anyhow!("No version found in {}", file.display()));
+        }
+
+        version_line
+            .replace(&contents, format!(r#"version = "{}""#, next.number()))
+            .to_string()
+    } else if contents.contains(&current.number()) {
+        contents.replacen(&current.number(), &next.number(), 1)
+    } else {
+        return Err(anyhow!(
+            "Version {} not found in {}",
+            current.number(),
+            file.display()
+        ));
+    };
+
+    let path = file.to_string_lossy();
+
+    if DRY_RUN.load(Ordering::SeqCst) {
+        Print::stderr_purple(&format!(
+            "would update the version in {path} to {}",
+            next.number()
+        ));
+    } else {
+        fs::write(file, updated).with_context(|| format!("Failed to write {path}"))?;
+    }
+
+    let mut paths = vec![path.to_string()];
+    if is_manifest {
+        paths.extend(update_lockfile(file)?);
+    }
+
+    let mut add_args = vec!["--"];
+    add_args.extend(paths.iter().map(String::as_str));
+
+    if let GitCommandResult::Error = GitCommand::new("add").with_default_args(&add_args).run()? {
+        return Ok(GitCommandResult::Error);
+    }
+
+    let message = format!("chore(release): {next}");
+    let mut commit_args = vec!["--message", &message, "--only", "--"];
+    commit_args.extend(paths.iter().map(String::as_str));
+
+    // just the version file (and lockfile), even if other changes are staged
+    GitCommand::new("commit")
+        .with_default_args(&commit_args)
+        .run()
+}
+
+/// Update the version of the workspace's own packages in the `Cargo.lock` of the workspace that `manifest` belongs to,
+/// and return its path; or `None` if the workspace has no tracked `Cargo.lock`.
+///
+/// `cargo update --workspace --offline --manifest-path MANIFEST`
+fn update_lockfile(manifest: &Path) -> Result<Option<String>> {
+    trace!("update_lockfile() called with: {manifest:?}");
+
+    if !Commands::is_available("cargo") {
+        return Err(anyhow!(
+            "cargo is needed to update the Cargo.lock for {}",
+            manifest.display()
+        ));
+    }
+
+    let manifest = manifest.to_string_lossy();
+
+    let output = Commands::output_with_timeout(&mut Commands::new_command_with_args(
+        "cargo",
+        &[
+            "locate-project",
+            "--workspace",
+            "--message-format",
+            "plain",
+            "--manifest-path",
+            &manifest,
+        ],
+    ))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to find the workspace of {}: {}",
+            manifest,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let root = String::from_utf8(output.stdout)?;
+    let lockfile = Path::new(root.trim_end()).with_file_name("Cargo.lock");
+    let lockfile = lockfile.to_string_lossy();
+
+    // libraries often don't commit their lockfile
+    if GitCommand::new("ls-files")
+        .with_default_args(&["--", &lockfile])
+        .stdout()?
+        .is_empty()
+    {
+        debug!("{lockfile} isn't tracked");
+        return Ok(None);
+    }
+
+    let args = [
+        "update",
+        "--workspace",
+        "--offline",
+        "--manifest-path",
+        &manifest,
+    ];
+
+    if DRY_RUN.load(Ordering::SeqCst) {
+        Print::stderr_purple(&format!("would run: cargo {}", args.join(" ")));
+    } else if !Commands::status_with_timeout(&mut Commands::new_command_with_args("cargo", &args))?
+        .success()
+    {
+        return Err(anyhow!("Failed to update {}", lockfile));
+    }
+
+    Ok(Some(lockfile.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(tag: &str) -> Version {
+        Version::parse(tag).unwrap()
+    }
+
+    #[test]
+    fn version_parse() {
+        assert_eq!(
+            version("v1.2.3"),
+            Version {
+                major: 1,
+                minor: 2,
+                patch: 3,
+                prefix: "v".to_string(),
+            }
+        );
+        assert_eq!(version("release-10.0.1").prefix, "release-");
+        assert_eq!(version("0.0.0").prefix, "");
+    }
+
+    #[test]
+    fn version_parse_rejects_other_tags() {
+        for tag in [
+            "v1.2",
+            "v1.2.3-rc.1",
+            "v1.2.3+build",
+            "1.2.3.4",
+            "latest",
+            "",
+        ] {
+            assert_eq!(Version::parse(tag), None, "parsing {tag:?}");
+        }
+    }
+
+    #[test]
+    fn version_bump() {
+        assert_eq!(version("v1.2.3").bump(Level::Major).to_string(), "v2.0.0");
+        assert_eq!(version("v1.2.3").bump(Level::Minor).to_string(), "v1.3.0");
+        assert_eq!(version("v1.2.3").bump(Level::Patch).to_string(), "v1.2.4");
+    }
+
+    #[test]
+    fn versions_are_ordered_by_number_first() {
+        assert!(version("v1.10.0") > version("v1.9.9"));
+        assert!(version("a2.0.0") > version("v1.0.0"));
+        assert_eq!(
+            ["v1.2.3", "v1.10.0", "v1.9.0"]
+                .into_iter()
+                .filter_map(Version::parse)
+                .max(),
+            Some(version("v1.10.0"))
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    commands::tasks::Tasks,
+    git::{forge::ForgeRepo, Git, GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    progress::Spinner,
+    prompt::Prompt,
+};
+use anyhow::anyhow;
+use log::trace;
+use std::{
+    io::{self, Write},
+    sync::atomic::Ordering,
+};
+
+/// The maximum number of remotes to fetch from at the same time.
+const MAX_PARALLEL_FETCHES: usize = 4;
+
+/// Fetch from `remotes` (else defaults to all remotes) concurrently.
+///
+/// The output of each fetch is buffered and printed in order once all of them have finished, so it isn't interleaved.
+pub fn fetch_all(remotes: &[String]) -> GitResult {
+    trace!("fetch_all() called with: {remotes:#?}");
+
+    let remotes: Vec<String> = if remotes.is_empty() {
+        GitCommand::new("remote")
+            .stdout()?
+            .lines()
+            .map(String::from)
+            .collect()
+    } else {
+        remotes.to_vec()
+    };
+
+    if remotes.is_empty() {
+        return Err(anyhow!("There are no remotes to fetch from"));
+    }
+
+    if DRY_RUN.load(Ordering::SeqCst) {
+        for remote in &remotes {
+            GitCommand::new("fetch")
+                .with_default_args(&[remote])
+                .run()?;
+        }
+
+        return Ok(GitCommandResult::Success);
+    }
+
+    let spinner = Spinner::start(&format!("fetching {}", remotes.join(", ")));
+
+    let outputs = Tasks::run_parallel(&remotes, MAX_PARALLEL_FETCHES, |remote| {
+        GitCommand::new("fetch")
+            .with_default_args(&[remote])
+            .output()
+    });
+
+    let success = outputs
+        .iter()
+        .all(|output| output.as_ref().is_ok_and(|output| output.status.success()));
+    spinner.finish(success);
+
+    for (remote, output) in remotes.iter().zip(outputs) {
+        let output = output?;
+
+        if !output.stdout.is_empty() || !output.stderr.is_empty() {
+            eprintln!("{remote}:");
+            io::stdout().write_all(&output.stdout)?;
+            io::stderr().write_all(&output.stderr)?;
+        }
+    }
+
+    if success {
+        Ok(GitCommandResult::Success)
+    } else {
+        Ok(GitCommandResult::Error)
+    }
+}
+
+/// Push the current branch to `origin` and set it as the upstream, then print the URL for opening a pull request.
+///
+/// `git push --set-upstream origin BRANCH`
+///
+/// If `force` is `true`, `--force-with-lease` is added.
+pub fn publish(force: bool) -> GitResult {
+    trace!("publish() called with: {force}");
+
+    let branch = Git::current_branch()?;
+
+    let mut push_args = vec!["--set-upstream"];
+    if force {
+        push_args.push("--force-with-lease");
+    }
+    push_args.push("origin");
+    push_args.push(&branch);
+
+    if let GitCommandResult::Error = GitCommand::new("push")
+        .with_default_args(&push_args)
+        .run()?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    if let Some(compare_url) =
+        ForgeRepo::from_remote("origin")?.and_then(|repo| repo.compare_url(&branch))
+    {
+        println!("{compare_url}");
+    }
+
+    Ok(GitCommandResult::Success)
+}
+
+/// Delete `branch` (else defaults to the current branch) from its remote and remove its upstream.
+///
+/// `git push REMOTE --delete BRANCH && git branch --unset-upstream BRANCH`
+///
+/// Refuses to delete the default branch, and prompts for confirmation unless `yes` is `true`.
+pub fn unpublish(branch: Option<&str>, yes: bool) -> GitResult {
+    trace!("unpublish() called with: {branch:?}, {yes}");
+
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => Git::current_branch()?,
+    };
+
+    if branch == Git::default_branch()? {
+        return Err(anyhow!(
+            "Refusing to unpublish the default branch '{}'",
+            branch
+        ));
+    }
+
+    let remote = Git::config_value(&format!("branch.{branch}.remote"))?
+        .unwrap_or_else(|| "origin".to_string());
+    let upstream = Git::config_value(&format!("branch.{branch}.merge"))?;
+    let remote_branch = match &upstream {
+        Some(merge) => merge.trim_start_matches("refs/heads/").to_string(),
+        None => branch.clone(),
+    };
+
+    if !yes && !Prompt::confirm(&format!("Delete '{remote_branch}' from '{remote}'?"))? {
+        return Ok(GitCommandResult::Error);
+    }
+
+    if let GitCommandResult::Error = GitCommand::new("push")
+        .with_default_args(&[&remote, "--delete", &remote_branch])
+        .run()?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    // there is nothing to unset if the branch was pushed without `--set-upstream`
+    if upstream.is_none() {
+        return Ok(GitCommandResult::Success);
+    }
+
+    GitCommand::new("branch")
+        .with_default_args(&["--unset-upstream", &branch])
+        .run()
+}
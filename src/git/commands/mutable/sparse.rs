@@ -0,0 +1,100 @@
+use crate::git::{GitCommand, GitResult};
+use anyhow::anyhow;
+use log::{debug, trace};
+
+/// Enable a cone-mode sparse checkout that only includes the files in the root directory.
+///
+/// `git sparse-checkout set --cone`
+pub fn init() -> GitResult {
+    trace!("init() called");
+
+    GitCommand::new("sparse-checkout")
+        .with_default_args(&["set", "--cone"])
+        .run()
+}
+
+/// Add the directories `paths` to the sparse checkout.
+///
+/// `git sparse-checkout add PATHS`
+///
+/// Fails if a sparse checkout isn't enabled, or if any of `paths` isn't a directory in `HEAD`.
+pub fn add(paths: &[String]) -> GitResult {
+    trace!("add() called with: {paths:#?}");
+
+    if !is_enabled()? {
+        return Err(anyhow!(
+            "Sparse checkout is not enabled; run `git-util sparse init` first"
+        ));
+    }
+
+    let mut directories = Vec::new();
+
+    for path in paths {
+        // cone mode patterns are directories relative to the repository root
+        let directory = path.trim_start_matches("./").trim_end_matches('/');
+
+        let object_type = GitCommand::new("cat-file")
+            .with_default_args(&["-t", &format!("HEAD:{directory}")])
+            .stdout()
+            .unwrap_or_default();
+
+        debug!("HEAD:{directory} is a '{object_type}'");
+
+        match object_type.as_str() {
+            "tree" => directories.push(directory.to_string()),
+            "" => return Err(anyhow!("'{}' doesn't exist in HEAD", path)),
+            _ => {
+                return Err(anyhow!(
+                    "'{}' is not a directory; cone mode only includes whole directories",
+                    path
+                ))
+            }
+        }
+    }
+
+    GitCommand::new("sparse-checkout")
+        .with_default_args(&["add"])
+        .with_user_args(&directories)
+        .run()
+}
+
+/// List the directories included in the sparse checkout.
+///
+/// `git sparse-checkout list`
+pub fn list() -> GitResult {
+    trace!("list() called");
+
+    GitCommand::new("sparse-checkout")
+        .with_default_args(&["list"])
+        .run()
+}
+
+/// Disable the sparse checkout and restore the full work tree.
+///
+/// `git sparse-checkout disable`
+pub fn off() -> GitResult {
+    trace!("off() called");
+
+    GitCommand::new("sparse-checkout")
+        .with_default_args(&["disable"])
+        .run()
+}
+
+/// Check whether a sparse checkout is enabled (`core.sparseCheckout`).
+///
+/// `git config --type=bool` normalizes the other spellings of true (e.g. `yes`, `on`, `1`) to `true`.
+fn is_enabled() -> anyhow::Result<bool> {
+    let output = GitCommand::new("config")
+        .with_default_args(&["--type=bool", "--get", "core.sparseCheckout"])
+        .output()?;
+
+    // `git config --get` exits with 1 if the key is not set
+    match output.status.code() {
+        Some(0) => Ok(String::from_utf8(output.stdout)?.trim_end() == "true"),
+        Some(1) => Ok(false),
+        _ => Err(anyhow!(
+            "Failed to get config value for 'core.sparseCheckout': {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
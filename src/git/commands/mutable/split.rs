@@ -0,0 +1,147 @@
+use crate::{
+    git::{
+        commands::stage_browser::StageBrowser, Git, GitCommand, GitCommandResult, GitResult,
+        DRY_RUN,
+    },
+    print::Print,
+    prompt::Prompt,
+};
+use anyhow::anyhow;
+use log::{debug, trace};
+use std::sync::atomic::Ordering;
+
+/// Split the last commit into two or more: its changes are staged again, and for each new commit, the changes that
+/// shouldn't be in it are unstaged in the staging interface (see `StageBrowser`) before it's committed.
+///
+/// The first commit starts with the original message (and author); the rest start with an empty one. If a commit fails,
+/// or I stop before every change is committed again, the split is rolled back to the original commit.
+///
+/// `git reset --soft HEAD~`, then `git commit --reedit-message=ORIGINAL` and `git commit` until the tree of `HEAD` is
+/// the original one again; `git reset --mixed ORIGINAL` to roll back.
+///
+/// Fails if there are uncommitted changes, if the last commit is a merge or the first commit, or if the current branch
+/// is protected (unless `force_protected` is `true`).
+pub fn split(force_protected: bool) -> GitResult {
+    trace!("split() called with: {force_protected}");
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+
+    // they would be mixed up with the changes of the commit
+    if let GitCommandResult::Error = Git::verify_clean_work_tree()? {
+        return Err(anyhow!(
+            "There are uncommitted changes; commit or stash them before splitting!"
+        ));
+    }
+
+    let original = GitCommand::new("rev-parse")
+        .with_default_args(&["--verify", "HEAD"])
+        .stdout()?;
+
+    if !Git::object_exists("HEAD~")? {
+        return Err(anyhow!("The first commit can't be split"));
+    }
+    if Git::object_exists("HEAD^2")? {
+        return Err(anyhow!("HEAD is a merge commit, which can't be split"));
+    }
+
+    super::backup::create("split")?;
+
+    if GitCommand::new("reset")
+        .with_default_args(&["--soft", "HEAD~"])
+        .run()?
+        == GitCommandResult::Error
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    if DRY_RUN.load(Ordering::SeqCst) {
+        return Ok(GitCommandResult::Success);
+    }
+
+    let commits = match commit_split(&original) {
+        Ok(Some(commits)) => commits,
+        Ok(None) => {
+            roll_back(&original)?;
+            Print::stderr_purple("Stopped splitting; the original commit is back");
+            return Ok(GitCommandResult::Success);
+        }
+        Err(e) => {
+            roll_back(&original)?;
+            return Err(anyhow!("{e}; the original commit is back"));
+        }
+    };
+
+    Print::info(&format!(
+        "Split {} into {} {}",
+        &original[..original.len().min(10)],
+        commits,
+        if commits == 1 { "commit" } else { "commits" }
+    ));
+
+    Ok(GitCommandResult::Success)
+}
+
+/// Commit the changes of `original` (which are staged on its parent) again in parts, until `HEAD` has its tree.
+///
+/// Returns the number of commits, or `None` if I stopped before every change was committed.
+fn commit_split(original: &str) -> anyhow::Result<Option<usize>> {
+    let mut commits = 0;
+
+    // until every change of the original commit is committed again
+    while GitCommand::new("diff")
+        .with_default_args(&["--quiet", "HEAD", original])
+        .output()?
+        .status
+        .code()
+        == Some(1)
+    {
+        Print::stderr_purple(&format!(
+            "Commit {} of the split: stage just the changes for it, then quit with q",
+            commits + 1
+        ));
+        StageBrowser::browse()?;
+
+        if let GitCommandResult::Success = Git::verify_staging_area_is_empty()? {
+            if Prompt::confirm(
+                "Nothing is staged; stop splitting, and put the original commit back?",
+            )? {
+                return Ok(None);
+            }
+            continue;
+        }
+
+        let reedit = format!("--reedit-message={original}");
+        let commit = match commits {
+            0 => GitCommand::new("commit")
+                .with_default_args(&[&reedit])
+                .run()?,
+            _ => GitCommand::new("commit").run()?,
+        };
+
+        if commit == GitCommandResult::Error {
+            return Err(anyhow!("Commit {} of the split failed", commits + 1));
+        }
+
+        commits += 1;
+        debug!("committed {commits} of the split");
+    }
+
+    Ok(Some(commits))
+}
+
+/// Put `original` back as `HEAD`, leaving its changes in the working tree, which still has all of them.
+///
+/// `git reset --mixed ORIGINAL`
+fn roll_back(original: &str) -> anyhow::Result<()> {
+    match GitCommand::new("reset")
+        .with_default_args(&["--quiet", "--mixed", original])
+        .run()?
+    {
+        GitCommandResult::Success => Ok(()),
+        GitCommandResult::Error => Err(anyhow!(
+            "Failed to roll back the split; `git-util backups restore` restores the original commit"
+        )),
+    }
+}
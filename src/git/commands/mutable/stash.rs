@@ -0,0 +1,61 @@
+use crate::{
+    commands::Commands,
+    git::{GitCommand, GitCommandResult, GitResult},
+    print::Print,
+    prompt::Prompt,
+};
+use anyhow::{Context, Result};
+use log::{debug, trace};
+use std::process::Stdio;
+
+/// `git stash ARGS`
+pub fn stash(args: &[String]) -> GitResult {
+    trace!("stash() called with: {args:#?}");
+
+    GitCommand::new("stash").with_user_args(args).run()
+}
+
+/// Pop the stash at `index` (else defaults to the most recent), but only if it applies cleanly to the work tree.
+///
+/// If it wouldn't, offer to apply it on a new branch (`git stash branch`) instead of leaving a half-applied stash.
+pub fn pop(index: Option<u16>) -> GitResult {
+    trace!("pop() called with: {index:?}");
+
+    let stash = format!("stash@{{{}}}", index.unwrap_or(0));
+
+    if applies_cleanly(&stash)? {
+        return GitCommand::new("stash")
+            .with_default_args(&["pop", &stash])
+            .run();
+    }
+
+    Print::stderr_purple(&format!("{stash} does not apply cleanly to the work tree"));
+
+    if !Prompt::confirm("Apply it to a new branch instead?")? {
+        return Ok(GitCommandResult::Error);
+    }
+
+    let branch = Prompt::input("Branch name:", &format!("stash-{}", index.unwrap_or(0)))?;
+
+    GitCommand::new("stash")
+        .with_default_args(&["branch", &branch, &stash])
+        .run()
+}
+
+/// Check whether `stash` can be applied to the work tree without conflicts.
+///
+/// `git stash show --patch STASH | git apply --check`
+fn applies_cleanly(stash: &str) -> Result<bool> {
+    let patch = Commands::pipe_from_command("git", &["stash", "show", "--patch", stash])?;
+
+    let status = Commands::status_with_timeout(
+        Commands::new_command_with_args("git", &["apply", "--check"])
+            .stdin(Stdio::from(patch))
+            .stderr(Stdio::null()),
+    )
+    .with_context(|| "Failed to execute 'git apply' command")?;
+
+    debug!("git apply --check {stash}: {status}");
+
+    Ok(status.success())
+}
@@ -0,0 +1,133 @@
+use crate::{
+    git::{GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    print::Print,
+    settings,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+/// A template file that, if present, is also set as the repository's `commit.template`.
+const COMMIT_TEMPLATE: &str = ".gitmessage";
+
+/// Copy the files of the template `name` into the work tree of the repository in the current directory, and return
+/// their paths.
+///
+/// Templates are directories under `$XDG_CONFIG_HOME/git-wrapper/templates/` (or `$HOME/.config/...`). Files that
+/// already exist in the work tree are left alone. If the template has a `.gitmessage`, it's also set as the
+/// repository's `commit.template`.
+pub fn copy(name: &str) -> Result<Vec<String>> {
+    trace!("copy() called with: {name}");
+
+    let template = directory(name)?;
+    let mut copied = Vec::new();
+
+    if DRY_RUN.load(Ordering::SeqCst) {
+        Print::stderr_purple(&format!("would copy the files in {}", template.display()));
+        return Ok(copied);
+    }
+
+    copy_dir(&template, Path::new(""), &mut copied)?;
+
+    debug!("copied from {}: {copied:#?}", template.display());
+
+    if copied.iter().any(|path| path == COMMIT_TEMPLATE) {
+        GitCommand::new("config")
+            .with_default_args(&["commit.template", COMMIT_TEMPLATE])
+            .stdout()?;
+    }
+
+    Ok(copied)
+}
+
+/// Stage `files` and commit them with `message`.
+///
+/// `git add -- FILES && git commit --no-verify --message MESSAGE`
+pub fn commit(files: &[String], message: &str) -> GitResult {
+    trace!("commit() called with: {files:#?}, {message}");
+
+    if files.is_empty() {
+        return Ok(GitCommandResult::Success);
+    }
+
+    if let GitCommandResult::Error = GitCommand::new("add")
+        .with_default_args(&["--"])
+        .with_user_args(files)
+        .run()?
+    {
+        return Ok(GitCommandResult::Error);
+    }
+
+    // the template is trusted, and the hook may not be configured for the new repository yet
+    GitCommand::new("commit")
+        .with_default_args(&["--no-verify", "--message", message])
+        .run()
+}
+
+/// The directory of the template `name`.
+///
+/// Fails with the names of the available templates if it doesn't exist.
+pub fn directory(name: &str) -> Result<PathBuf> {
+    let templates = settings::config_directory()?.join("templates");
+
+    let template = templates.join(name);
+    if template.is_dir() {
+        return Ok(template);
+    }
+
+    let mut available: Vec<String> = fs::read_dir(&templates)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    available.sort();
+
+    Err(anyhow!(
+        "No template named '{}' in {} (available: {})",
+        name,
+        templates.display(),
+        if available.is_empty() {
+            "none".to_string()
+        } else {
+            available.join(", ")
+        }
+    ))
+}
+
+/// Recursively copy the files in `source` to `destination` (relative to the current directory), adding the path of each
+/// copied file to `copied`.
+fn copy_dir(source: &Path, destination: &Path, copied: &mut Vec<String>) -> Result<()> {
+    for entry in
+        fs::read_dir(source).with_context(|| format!("Failed to read {}", source.display()))?
+    {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            fs::create_dir_all(&target)
+                .with_context(|| format!("Failed to create {}", target.display()))?;
+            copy_dir(&entry.path(), &target, copied)?;
+            continue;
+        }
+
+        let relative = target.to_string_lossy().to_string();
+        if target.exists() {
+            Print::stderr_purple(&format!("{relative} already exists; not copying it"));
+            continue;
+        }
+
+        fs::copy(entry.path(), &target)
+            .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        copied.push(relative);
+    }
+
+    Ok(())
+}
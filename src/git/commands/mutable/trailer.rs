@@ -0,0 +1,153 @@
+use crate::{
+    commands::Commands,
+    git::{GitCommand, GitCommandResult, GitResult},
+    output::OutputTarget,
+    settings::Settings,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use std::{io::Write, path::Path};
+
+/// The built-in shortcuts for trailer keys; `git-util.trailer.SHORTCUT` adds to (or replaces) them.
+const SHORTCUTS: [(&str, &str); 6] = [
+    ("ab", "Acked-by"),
+    ("cab", "Co-authored-by"),
+    ("rb", "Reviewed-by"),
+    ("ref", "Refs"),
+    ("sob", "Signed-off-by"),
+    ("tb", "Tested-by"),
+];
+
+/// Add the trailer `key: value` to the message of the last commit, or to the message in `file`.
+///
+/// `key` may be a shortcut (see `key()`). If `value` isn't given, it defaults to the committer's `NAME <EMAIL>` for
+/// keys that end in `-by`, e.g. `Reviewed-by`.
+///
+/// `git interpret-trailers --in-place --trailer=KEY:VALUE FILE`, or `git commit --amend --only --no-edit
+/// --trailer=KEY:VALUE` (which runs `git interpret-trailers` on the message), after backing up `HEAD`.
+pub fn add(
+    key: &str,
+    value: Option<&str>,
+    file: Option<&Path>,
+    force_protected: bool,
+) -> GitResult {
+    trace!("add() called with: {key}, {value:?}, {file:?}, {force_protected}");
+
+    let trailer = trailer(key, value)?;
+
+    if let Some(file) = file {
+        return GitCommand::new("interpret-trailers")
+            .with_default_args(&["--in-place", &trailer, &file.to_string_lossy()])
+            .run();
+    }
+
+    super::ensure_no_operation()?;
+    super::ensure_on_branch()?;
+    super::ensure_unprotected(force_protected)?;
+    super::backup::create("trailer")?;
+
+    GitCommand::new("commit")
+        .with_default_args(&["--amend", "--only", "--no-edit", &trailer])
+        .run()
+}
+
+/// Print the trailers of the last commit's message (or of the message in `file`), one `KEY: VALUE` per line; just
+/// the ones with `key` (which may be a shortcut), if it's given.
+///
+/// `git interpret-trailers --parse`
+pub fn show(key: Option<&str>, file: Option<&Path>) -> GitResult {
+    trace!("show() called with: {key:?}, {file:?}");
+
+    let message = match file {
+        Some(file) => std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?,
+        None => GitCommand::new("log")
+            .with_default_args(&["--max-count=1", "--format=%B", "HEAD"])
+            .stdout()?,
+    };
+
+    let output = Commands::output_with_input(
+        &mut GitCommand::new("interpret-trailers")
+            .with_default_args(&["--parse"])
+            .construct_git_command(),
+        message.as_bytes(),
+    )?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "'git interpret-trailers' failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let key = key.map(self::key);
+    let mut writer = OutputTarget::writer()?;
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let matches = match (&key, line.split_once(':')) {
+            (None, _) => true,
+            (Some(key), Some((line_key, _))) => line_key.trim().eq_ignore_ascii_case(key),
+            (Some(_), None) => false,
+        };
+
+        if matches {
+            writeln!(writer, "{line}")?;
+        }
+    }
+
+    Ok(GitCommandResult::Success)
+}
+
+/// The `--trailer` arguments for `git commit` for `trailers`, each of which is `KEY`, `KEY=VALUE`, or `KEY: VALUE`
+/// (see `add()`).
+pub fn args(trailers: &[String]) -> Result<Vec<String>> {
+    trailers
+        .iter()
+        .map(|trailer| match trailer.split_once(['=', ':']) {
+            Some((key, value)) => self::trailer(key.trim(), Some(value.trim())),
+            None => self::trailer(trailer, None),
+        })
+        .collect()
+}
+
+/// The trailer key that `key` is a shortcut for (case-insensitively), or else `key` itself.
+fn key(key: &str) -> String {
+    let shortcut = key.to_lowercase();
+
+    match Settings::get().trailers.get(&shortcut) {
+        Some(trailer) => trailer.clone(),
+        None => SHORTCUTS
+            .iter()
+            .find(|(name, _)| *name == shortcut)
+            .map_or(key, |(_, trailer)| trailer)
+            .to_string(),
+    }
+}
+
+/// `--trailer=KEY: VALUE`
+fn trailer(key: &str, value: Option<&str>) -> Result<String> {
+    let key = self::key(key);
+
+    let value = match value.filter(|value| !value.is_empty()) {
+        Some(value) => value.to_string(),
+        None if key.to_lowercase().ends_with("-by") => identity()?,
+        None => return Err(anyhow!("The trailer {} needs a value", key)),
+    };
+
+    debug!("trailer: {key}: {value}");
+
+    Ok(format!("--trailer={key}: {value}"))
+}
+
+/// The committer's `NAME <EMAIL>`.
+///
+/// `git var GIT_COMMITTER_IDENT`, which is `NAME <EMAIL> TIMESTAMP OFFSET`
+fn identity() -> Result<String> {
+    let ident = GitCommand::new("var")
+        .with_default_args(&["GIT_COMMITTER_IDENT"])
+        .stdout()?;
+
+    ident
+        .rfind('>')
+        .map(|end| ident[..=end].to_string())
+        .ok_or_else(|| anyhow!("Unexpected committer identity: \"{}\"", ident))
+}
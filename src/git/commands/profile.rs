@@ -0,0 +1,372 @@
+use crate::{
+    git::{forge::ForgeRepo, Git, GitCommand, GitCommandResult, GitResult},
+    output::OutputTarget,
+    print::Print,
+    settings::Settings,
+    table::Table,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+};
+
+/// An identity to commit with (e.g. `work` or `personal`), defined by the `git-util.profile.NAME.*` settings.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+pub struct Profile {
+    /// `git-util.profile.NAME.name`, for `user.name`
+    pub name: Option<String>,
+    /// `git-util.profile.NAME.email`, for `user.email`
+    pub email: Option<String>,
+    /// `git-util.profile.NAME.signingKey`, for `user.signingKey`
+    pub signing_key: Option<String>,
+    /// `git-util.profile.NAME.host`: the host of the remotes of the repositories that the profile is for, e.g.
+    /// `github.com` or `gitlab.example.com`
+    pub host: Option<String>,
+}
+
+/// The identity that's configured in the current repository.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+struct Identity {
+    name: Option<String>,
+    email: Option<String>,
+    signing_key: Option<String>,
+}
+
+impl Profile {
+    /// The profiles defined by the `git-util.profile.NAME.FIELD` keys of `config`, by name.
+    ///
+    /// `config` is keyed by the lowercased names that `git config --get-regexp` prints, except for `NAME`, which keeps
+    /// its case. An unknown `FIELD` is ignored with a warning, so that it can't break every command.
+    pub fn from_config(config: &HashMap<String, String>) -> BTreeMap<String, Profile> {
+        let mut profiles: BTreeMap<String, Profile> = BTreeMap::new();
+
+        for (key, value) in config {
+            let Some((name, field)) = key
+                .strip_prefix("git-util.profile.")
+                .and_then(|rest| rest.rsplit_once('.'))
+            else {
+                continue;
+            };
+
+            let value = Some(value.clone());
+
+            match field {
+                "name" => profiles.entry(name.to_string()).or_default().name = value,
+                "email" => profiles.entry(name.to_string()).or_default().email = value,
+                "signingkey" => profiles.entry(name.to_string()).or_default().signing_key = value,
+                "host" => profiles.entry(name.to_string()).or_default().host = value,
+                _ => Print::stderr_purple(&format!(
+                    "Ignoring the invalid setting \"{key}\" (a profile has a name, email, signingKey, and host)"
+                )),
+            }
+        }
+
+        profiles
+    }
+
+    /// List the profiles, marking the one that the current repository's identity matches.
+    pub fn list() -> GitResult {
+        trace!("Profile::list() called");
+
+        let profiles = &Settings::get().profiles;
+
+        if profiles.is_empty() {
+            Print::info("No profiles are defined; set git-util.profile.NAME.email to define one");
+            return Ok(GitCommandResult::Success);
+        }
+
+        let identity = Identity::current()?;
+
+        let mut table = Table::new();
+        table.add_row(["", "PROFILE", "NAME", "EMAIL", "HOST", "SIGNING KEY"]);
+        for (name, profile) in profiles {
+            table.add_row([
+                if identity.matches(profile) { "*" } else { "" },
+                name,
+                profile.name.as_deref().unwrap_or("-"),
+                profile.email.as_deref().unwrap_or("-"),
+                profile.host.as_deref().unwrap_or("-"),
+                profile.signing_key.as_deref().unwrap_or("-"),
+            ]);
+        }
+
+        write!(OutputTarget::writer()?, "{table}")?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Print the current repository's identity and the profile that it matches.
+    ///
+    /// Returns `Error` if the remote `origin` is on the host of a profile that the identity doesn't match, which is
+    /// the classic mistake of committing to a work repository with a personal email (or vice versa).
+    pub fn status() -> GitResult {
+        trace!("Profile::status() called");
+
+        let profiles = &Settings::get().profiles;
+        let identity = Identity::current()?;
+        let host = origin_host();
+
+        let matching: Vec<&String> = profiles
+            .iter()
+            .filter(|(_, profile)| identity.matches(profile))
+            .map(|(name, _)| name)
+            .collect();
+        let expected: Vec<&String> = profiles
+            .iter()
+            .filter(|(_, profile)| {
+                profile.host.is_some() && profile.host.as_deref() == host.as_deref()
+            })
+            .map(|(name, _)| name)
+            .collect();
+
+        debug!("matching: {matching:?}, expected: {expected:?}");
+
+        let mut table = Table::new();
+        table.add_row(["user.name:", identity.name.as_deref().unwrap_or("-")]);
+        table.add_row(["user.email:", identity.email.as_deref().unwrap_or("-")]);
+        table.add_row([
+            "user.signingKey:",
+            identity.signing_key.as_deref().unwrap_or("-"),
+        ]);
+        table.add_row(["origin host:", host.as_deref().unwrap_or("-")]);
+        table.add_row([
+            "profile:".to_string(),
+            match matching.is_empty() {
+                true => "none".to_string(),
+                false => join(&matching),
+            },
+        ]);
+        write!(OutputTarget::writer()?, "{table}")?;
+
+        if !expected.is_empty() && !matching.iter().any(|name| expected.contains(name)) {
+            Print::error(&format!(
+                "This repository is on {}, which is for the {} profile; use `git-util profile use {}`",
+                host.unwrap_or_default(),
+                join(&expected),
+                expected[0]
+            ));
+            return Ok(GitCommandResult::Error);
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Configure the current repository (in its local config) with the identity of the profile `name`.
+    ///
+    /// Setting a signing key also turns on `commit.gpgSign`. The `user.name`, `user.signingKey`, and `commit.gpgSign`
+    /// that the profile doesn't have are unset, so that none are left over from another profile. Warns if the profile
+    /// is for a different host than the remote `origin`'s.
+    pub fn apply(name: &str) -> GitResult {
+        trace!("Profile::apply() called with: {name}");
+
+        let profile = Profile::get(name)?;
+        let gpg_sign = profile.signing_key.as_ref().map(|_| "true".to_string());
+
+        for (key, value) in [
+            ("user.name", &profile.name),
+            ("user.email", &profile.email),
+            ("user.signingKey", &profile.signing_key),
+            ("commit.gpgSign", &gpg_sign),
+        ] {
+            let result = match value {
+                Some(value) => GitCommand::new("config")
+                    .with_default_args(&["--local", key, value])
+                    .run()?,
+                // a profile without an email keeps the current one, since there's no committing without one
+                None if key == "user.email" => continue,
+                None => unset_local(key)?,
+            };
+
+            if result == GitCommandResult::Error {
+                return Ok(GitCommandResult::Error);
+            }
+        }
+
+        if let (Some(expected), Some(host)) = (&profile.host, origin_host()) {
+            if *expected != host {
+                Print::stderr_purple(&format!(
+                    "The {name} profile is for {expected}, but this repository is on {host}"
+                ));
+            }
+        }
+
+        Print::info(&format!("Using the {name} profile in this repository"));
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Print the effective `user.name` and `user.email` and the scope (e.g. `global`) of the config they come from, or
+    /// set them in the local config if `name` or `email` is given.
+    pub fn id(name: Option<&str>, email: Option<&str>) -> GitResult {
+        trace!("Profile::id() called with: {name:?}, {email:?}");
+
+        if name.is_none() && email.is_none() {
+            let mut table = Table::new();
+            for key in ["user.name", "user.email"] {
+                match Profile::config_value_with_scope(key)? {
+                    Some((scope, value)) => table.add_row([key, &value, &format!("({scope})")]),
+                    None => table.add_row([key, "-", "(not set)"]),
+                }
+            }
+            write!(OutputTarget::writer()?, "{table}")?;
+
+            return Ok(GitCommandResult::Success);
+        }
+
+        for (key, value) in [("user.name", name), ("user.email", email)] {
+            let Some(value) = value else {
+                continue;
+            };
+
+            if GitCommand::new("config")
+                .with_default_args(&["--local", key, value])
+                .run()?
+                == GitCommandResult::Error
+            {
+                return Ok(GitCommandResult::Error);
+            }
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// The scope and value of the config setting `key`, or `None` if it is not set.
+    ///
+    /// `git config --show-scope --get KEY`
+    fn config_value_with_scope(key: &str) -> Result<Option<(String, String)>> {
+        let output = GitCommand::new("config")
+            .with_default_args(&["--show-scope", "--get", key])
+            .output()?;
+
+        // `git config --get` exits with 1 if the key is not set
+        match output.status.code() {
+            Some(0) => Ok(String::from_utf8(output.stdout)?
+                .trim_end()
+                .split_once('\t')
+                .map(|(scope, value)| (scope.to_string(), value.to_string()))),
+            Some(1) => Ok(None),
+            _ => Err(anyhow!(
+                "Failed to get config value for '{}': {}",
+                key,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        }
+    }
+
+    /// The profile `name`; fails (listing the profiles) if it isn't defined.
+    pub fn get(name: &str) -> Result<&'static Profile> {
+        let profiles = &Settings::get().profiles;
+
+        profiles.get(name).ok_or_else(|| match profiles.is_empty() {
+            true => anyhow!(
+                "No profiles are defined; set git-util.profile.{}.email to define one",
+                name
+            ),
+            false => anyhow!(
+                "Unknown profile: {} (expected one of: {})",
+                name,
+                join(&profiles.keys().collect::<Vec<_>>())
+            ),
+        })
+    }
+}
+
+impl Identity {
+    /// `git config user.name`, `user.email`, and `user.signingKey`
+    fn current() -> Result<Identity> {
+        Ok(Identity {
+            name: Git::config_value("user.name")?,
+            email: Git::config_value("user.email")?,
+            signing_key: Git::config_value("user.signingKey")?,
+        })
+    }
+
+    /// Whether the identity has the profile's email (compared case-insensitively), and its name and signing key if
+    /// it has them.
+    fn matches(&self, profile: &Profile) -> bool {
+        let same = |expected: &Option<String>, actual: &Option<String>| {
+            expected.is_none() || expected == actual
+        };
+
+        profile.email.is_some()
+            && profile.email.as_deref().map(str::to_lowercase)
+                == self.email.as_deref().map(str::to_lowercase)
+            && same(&profile.name, &self.name)
+            && same(&profile.signing_key, &self.signing_key)
+    }
+}
+
+/// Unset `key` in the local config, if it's set there.
+///
+/// `git config --local --unset KEY`
+fn unset_local(key: &str) -> GitResult {
+    let output = GitCommand::new("config")
+        .with_default_args(&["--local", "--get", key])
+        .output()?;
+
+    // `git config --get` exits with 1 if the key is not set
+    match output.status.code() {
+        Some(1) => Ok(GitCommandResult::Success),
+        _ => GitCommand::new("config")
+            .with_default_args(&["--local", "--unset", key])
+            .run(),
+    }
+}
+
+/// The host of the remote `origin`, if the repository has one with a recognized URL.
+fn origin_host() -> Option<String> {
+    match ForgeRepo::from_remote("origin") {
+        Ok(repo) => repo.map(|repo| repo.host),
+        Err(e) => {
+            debug!("no origin: {e}");
+            None
+        }
+    }
+}
+
+fn join(names: &[&String]) -> String {
+    names
+        .iter()
+        .map(|name| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_config_ignores_unknown_fields() {
+        let config = HashMap::from([
+            (
+                "git-util.profile.Work.email".to_string(),
+                "me@work.example".to_string(),
+            ),
+            (
+                "git-util.profile.Work.signingkey".to_string(),
+                "ABC123".to_string(),
+            ),
+            (
+                "git-util.profile.Work.colour".to_string(),
+                "red".to_string(),
+            ),
+            ("git-util.profile.typo.emial".to_string(), "x".to_string()),
+            ("git-util.pager".to_string(), "less".to_string()),
+        ]);
+
+        let profiles = Profile::from_config(&config);
+
+        assert_eq!(profiles.keys().collect::<Vec<_>>(), vec!["Work"]);
+        assert_eq!(
+            profiles["Work"],
+            Profile {
+                email: Some("me@work.example".to_string()),
+                signing_key: Some("ABC123".to_string()),
+                ..Profile::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use crate::{
+    commands::Commands,
+    git::{GitCommandResult, GitResult, DRY_RUN},
+    print::Print,
+    progress::Spinner,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use regex::Regex;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+/// The GitHub API endpoint for the latest release of **git-util**.
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/ccb012100/git-wrapper/releases/latest";
+
+/// A downloadable file attached to a release.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Asset {
+    name: String,
+    url: String,
+}
+
+/// The latest published release of **git-util**.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Release {
+    /// The version, without the leading `v` of the tag.
+    version: String,
+    assets: Vec<Asset>,
+}
+
+/// Updates the running `git-util` executable to the latest release.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct SelfUpdate();
+
+impl SelfUpdate {
+    /// Download the latest release for the current platform, verify its checksum, and replace the running executable
+    /// with it.
+    ///
+    /// The release's asset for the current target (e.g. `git-util-x86_64-unknown-linux-gnu.tar.gz`) can be the bare
+    /// executable, or a `.tar.gz` or `.zip` archive containing it. On Windows, where the running executable can't be
+    /// overwritten, it's renamed aside to `git-util.exe.old` first.
+    ///
+    /// If `check` is `true`, only report whether an update is available. Uses `curl` for the downloads, `sha256sum` (or
+    /// `shasum`) for the checksum, and `tar` (or `unzip`) to unpack an archive.
+    pub fn run(check: bool) -> GitResult {
+        trace!("SelfUpdate::run() called with: {check}");
+
+        let current_version = env!("CARGO_PKG_VERSION");
+        let release = SelfUpdate::latest_release()?;
+
+        debug!("latest release: {release:#?}");
+
+        if parse_version(&release.version) <= parse_version(current_version) {
+            Print::info(&format!("git-util {current_version} is up to date"));
+            return Ok(GitCommandResult::Success);
+        }
+
+        Print::info(&format!(
+            "git-util {} is available (installed: {current_version})",
+            release.version
+        ));
+
+        if check {
+            return Ok(GitCommandResult::Success);
+        }
+
+        let binary = release
+            .binary_asset()
+            .ok_or_else(|| anyhow!("Release {} has no binary for {}", release.version, target()))?;
+        let checksum = release.checksum_asset(binary).ok_or_else(|| {
+            anyhow!(
+                "Release {} has no checksum for {}",
+                release.version,
+                binary.name
+            )
+        })?;
+
+        let executable = env::current_exe()
+            .and_then(fs::canonicalize)
+            .with_context(|| "Failed to locate the running executable")?;
+
+        if DRY_RUN.load(Ordering::SeqCst) {
+            Print::stderr_purple(&format!(
+                "would replace {} with {}",
+                executable.display(),
+                binary.url
+            ));
+            return Ok(GitCommandResult::Success);
+        }
+
+        // download next to the executable, so that the final rename doesn't cross filesystems
+        let download = executable.with_file_name(format!(".{}.download", binary.name));
+
+        let spinner = Spinner::start(&format!("downloading {}", binary.name));
+        let downloaded = SelfUpdate::download(&binary.url, &download);
+        spinner.finish(downloaded.is_ok());
+        downloaded?;
+
+        if let Err(e) = SelfUpdate::verify_checksum(&download, &binary.name, &checksum.url) {
+            let _ = fs::remove_file(&download);
+            return Err(e);
+        }
+
+        let replaced = match Archive::of(&binary.name) {
+            None => SelfUpdate::replace_executable(&download, &executable),
+            Some(archive) => {
+                let unpacked = executable.with_file_name(".git-util.update");
+                let result = archive
+                    .unpack(&download, &unpacked)
+                    .and_then(|new_executable| {
+                        SelfUpdate::replace_executable(&new_executable, &executable)
+                    });
+
+                let _ = fs::remove_file(&download);
+                let _ = fs::remove_dir_all(&unpacked);
+                result
+            }
+        };
+        replaced?;
+
+        Print::info(&format!("Updated git-util to {}", release.version));
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Fetch the latest release from the GitHub API.
+    fn latest_release() -> Result<Release> {
+        let json = SelfUpdate::curl(&[
+            "--header",
+            "Accept: application/vnd.github+json",
+            LATEST_RELEASE_URL,
+        ])?;
+
+        Release::parse(&json)
+    }
+
+    /// Download `url` to the file at `destination`.
+    fn download(url: &str, destination: &Path) -> Result<()> {
+        SelfUpdate::curl(&["--output", &destination.to_string_lossy(), url]).map(|_| ())
+    }
+
+    /// Run `curl` with `args` (plus flags to fail on HTTP errors and follow redirects) and return its stdout.
+    fn curl(args: &[&str]) -> Result<String> {
+        let mut curl_args = vec!["--fail", "--silent", "--show-error", "--location"];
+        curl_args.extend_from_slice(args);
+
+        let output =
+            Commands::output_with_timeout(&mut Commands::new_command_with_args("curl", &curl_args))
+                .with_context(|| "Failed to execute curl; is it installed?")?;
+
+        if output.status.success() {
+            Ok(String::from_utf8(output.stdout)?)
+        } else {
+            Err(anyhow!(
+                "curl failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    /// Check the SHA-256 of `file` against the entry for `name` in the checksum file at `checksum_url`.
+    ///
+    /// The checksum file can either contain only the hash, or `sha256sum`-style `HASH  NAME` lines.
+    fn verify_checksum(file: &Path, name: &str, checksum_url: &str) -> Result<()> {
+        let checksums = SelfUpdate::curl(&[checksum_url])?;
+
+        let expected = checksums
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let hash = fields.next()?;
+
+                match fields.next() {
+                    None => Some(hash),
+                    // `sha256sum` marks binary files with a leading `*`
+                    Some(file_name) if file_name.trim_start_matches('*') == name => Some(hash),
+                    Some(_) => None,
+                }
+            })
+            .next()
+            .ok_or_else(|| anyhow!("No checksum for {} in {}", name, checksum_url))?
+            .to_lowercase();
+
+        let actual = sha256(file)?;
+
+        debug!("checksum of {name}: expected {expected}, actual {actual}");
+
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                name,
+                expected,
+                actual
+            ))
+        }
+    }
+
+    /// Make `download` executable and atomically rename it over `executable`.
+    ///
+    /// On Windows, the running executable can't be replaced, but it can be renamed, so it's moved aside to
+    /// `EXECUTABLE.old` first (and moved back if the rename fails); the next update removes it.
+    fn replace_executable(download: &Path, executable: &Path) -> Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            fs::set_permissions(download, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("Failed to make {} executable", download.display()))?;
+        }
+
+        let failed = || {
+            format!(
+                "Failed to replace {}; do you have permission to write to it?",
+                executable.display()
+            )
+        };
+
+        if cfg!(windows) {
+            let aside = executable.with_extension("exe.old");
+
+            // left by the last update, unless it's still running
+            let _ = fs::remove_file(&aside);
+            fs::rename(executable, &aside).with_context(failed)?;
+
+            if let Err(e) = fs::rename(download, executable) {
+                let _ = fs::rename(&aside, executable);
+                return Err(e).with_context(failed);
+            }
+
+            Ok(())
+        } else {
+            fs::rename(download, executable).with_context(failed)
+        }
+    }
+}
+
+impl Release {
+    /// Parse the `tag_name` and the assets' `browser_download_url`s from a GitHub release JSON object.
+    fn parse(json: &str) -> Result<Release> {
+        let tag = Regex::new(r#""tag_name"\s*:\s*"([^"]+)""#)?
+            .captures(json)
+            .map(|captures| captures[1].to_string())
+            .ok_or_else(|| anyhow!("Unable to find the tag of the latest release"))?;
+
+        let assets = Regex::new(r#""browser_download_url"\s*:\s*"([^"]+)""#)?
+            .captures_iter(json)
+            .map(|captures| {
+                let url = captures[1].to_string();
+                let name = url.rsplit('/').next().unwrap_or_default().to_string();
+
+                Asset { name, url }
+            })
+            .collect();
+
+        Ok(Release {
+            version: tag.trim_start_matches('v').to_string(),
+            assets,
+        })
+    }
+
+    /// The asset with the binary for the current target: `git-util-TARGET` or `git-util-VERSION-TARGET` (with `v` or
+    /// not), either bare (`.exe` on Windows) or archived as `.tar.gz` or `.zip`.
+    fn binary_asset(&self) -> Option<&Asset> {
+        let target = target();
+        let names = [
+            format!("git-util-{target}"),
+            format!("git-util-{}-{target}", self.version),
+            format!("git-util-v{}-{target}", self.version),
+        ];
+
+        self.assets.iter().find(|asset| {
+            let name = Archive::of(&asset.name)
+                .map(|archive| &asset.name[..asset.name.len() - archive.extension().len()])
+                .or_else(|| asset.name.strip_suffix(env::consts::EXE_SUFFIX))
+                .unwrap_or(&asset.name);
+
+            names.iter().any(|target_name| target_name == name)
+        })
+    }
+
+    /// The asset with the checksum for `binary`: either `BINARY.sha256`, or a combined checksums file.
+    fn checksum_asset(&self, binary: &Asset) -> Option<&Asset> {
+        let sidecar = format!("{}.sha256", binary.name);
+
+        self.assets
+            .iter()
+            .find(|asset| asset.name == sidecar)
+            .or_else(|| {
+                self.assets
+                    .iter()
+                    .find(|asset| is_checksum(&asset.name.to_lowercase()))
+            })
+    }
+}
+
+/// An archive that a release's binary can be packed in.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Archive {
+    TarGz,
+    Zip,
+}
+
+impl Archive {
+    /// The kind of archive the asset `name` is, if it's one.
+    fn of(name: &str) -> Option<Archive> {
+        if name.ends_with(".tar.gz") {
+            Some(Archive::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(Archive::Zip)
+        } else {
+            None
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Archive::TarGz => ".tar.gz",
+            Archive::Zip => ".zip",
+        }
+    }
+
+    /// Unpack `archive` into the directory `destination`, and return the path of the executable in it.
+    ///
+    /// `tar -xzf ARCHIVE -C DESTINATION`, or `unzip -q ARCHIVE -d DESTINATION` (else `tar -xf`, which unpacks zip
+    /// files on Windows)
+    fn unpack(&self, archive: &Path, destination: &Path) -> Result<PathBuf> {
+        let _ = fs::remove_dir_all(destination);
+        fs::create_dir_all(destination)
+            .with_context(|| format!("Failed to create {}", destination.display()))?;
+
+        let archive = archive.to_string_lossy();
+        let destination_dir = destination.to_string_lossy();
+
+        let output = match self {
+            Archive::TarGz => Commands::output_with_timeout(&mut Commands::new_command_with_args(
+                "tar",
+                &["-xzf", &archive, "-C", &destination_dir],
+            )),
+            Archive::Zip => Commands::output_with_timeout(&mut Commands::new_command_with_args(
+                "unzip",
+                &["-q", &archive, "-d", &destination_dir],
+            ))
+            .or_else(|_| {
+                Commands::output_with_timeout(&mut Commands::new_command_with_args(
+                    "tar",
+                    &["-xf", &archive, "-C", &destination_dir],
+                ))
+            }),
+        }
+        .with_context(|| format!("Failed to unpack {archive}; is tar (or unzip) installed?"))?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to unpack {}: {}",
+                archive,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        find_executable(destination)?
+            .ok_or_else(|| anyhow!("There's no git-util executable in {}", archive))
+    }
+}
+
+/// The path of the `git-util` executable in the directory `dir` (or a directory in it).
+fn find_executable(dir: &Path) -> Result<Option<PathBuf>> {
+    let name = format!("git-util{}", env::consts::EXE_SUFFIX);
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            if let Some(executable) = find_executable(&path)? {
+                return Ok(Some(executable));
+            }
+        } else if path
+            .file_name()
+            .is_some_and(|file_name| *file_name == *name)
+        {
+            return Ok(Some(path));
+        }
+    }
+
+    Ok(None)
+}
+
+/// The target triple of the running executable, e.g. `x86_64-unknown-linux-gnu`, which release binaries are named for.
+fn target() -> String {
+    let platform = match env::consts::OS {
+        "macos" => "apple-darwin",
+        "windows" => "pc-windows-msvc",
+        "linux" if cfg!(target_env = "musl") => "unknown-linux-musl",
+        "linux" => "unknown-linux-gnu",
+        os => os,
+    };
+
+    format!("{}-{platform}", env::consts::ARCH)
+}
+
+fn is_checksum(name: &str) -> bool {
+    name.ends_with(".sha256") || name.contains("sha256sums") || name.contains("checksums")
+}
+
+/// Parse a `MAJOR.MINOR.PATCH` version into its numeric parts, for comparison; non-numeric parts are treated as `0`.
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split(['.', '-', '+'])
+        .take(3)
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// The lowercase hex SHA-256 of `file`, using `sha256sum` (or `shasum -a 256` on macOS).
+fn sha256(file: &Path) -> Result<String> {
+    let file = file.to_string_lossy();
+
+    let output =
+        Commands::output_with_timeout(&mut Commands::new_command_with_args("sha256sum", &[&file]))
+            .or_else(|_| {
+                Commands::output_with_timeout(&mut Commands::new_command_with_args(
+                    "shasum",
+                    &["-a", "256", &file],
+                ))
+            })
+            .with_context(|| "Failed to execute sha256sum or shasum")?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to compute the checksum of {}: {}",
+            file,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    String::from_utf8(output.stdout)?
+        .split_whitespace()
+        .next()
+        .map(str::to_lowercase)
+        .ok_or_else(|| anyhow!("Failed to compute the checksum of {}", file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(names: &[&str]) -> Release {
+        Release {
+            version: "1.2.0".to_string(),
+            assets: names
+                .iter()
+                .map(|name| Asset {
+                    name: name.to_string(),
+                    url: format!("https://example.com/{name}"),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn binary_asset_matches_the_exact_target() {
+        let target = target();
+        let archive = format!("git-util-{target}.tar.gz");
+        let release = release(&[
+            &format!("git-util-{target}-debug.tar.gz"),
+            &format!("git-util-{target}.tar.gz.sha256"),
+            &format!("git-util-{}-unknown-other", env::consts::ARCH),
+            &archive,
+        ]);
+
+        assert_eq!(
+            release.binary_asset().map(|asset| asset.name.as_str()),
+            Some(archive.as_str())
+        );
+    }
+
+    #[test]
+    fn binary_asset_allows_a_version_and_a_bare_executable() {
+        let target = target();
+        let executable = format!("git-util-v1.2.0-{target}{}", env::consts::EXE_SUFFIX);
+
+        assert_eq!(
+            release(&[&executable])
+                .binary_asset()
+                .map(|asset| asset.name.as_str()),
+            Some(executable.as_str())
+        );
+        assert_eq!(release(&["git-util-v1.2.0-other.zip"]).binary_asset(), None);
+    }
+
+    #[test]
+    fn archive_of() {
+        assert_eq!(Archive::of("git-util-x.tar.gz"), Some(Archive::TarGz));
+        assert_eq!(Archive::of("git-util-x.zip"), Some(Archive::Zip));
+        assert_eq!(Archive::of("git-util-x.exe"), None);
+    }
+
+    #[test]
+    fn parse_version_compares_numerically() {
+        assert!(parse_version("1.10.0") > parse_version("1.9.3"));
+        assert_eq!(parse_version("2.0.0-rc.1"), vec![2, 0, 0]);
+    }
+}
@@ -0,0 +1,501 @@
+use crate::{
+    color::Colors,
+    git::{Git, GitCommand, GitCommandResult, GitResult, DRY_RUN},
+    print::Print,
+    tui::{self, Key, Terminal},
+};
+use anyhow::Result;
+use log::{debug, trace};
+use nu_ansi_term::{Color, Style};
+use std::{env, sync::atomic::Ordering};
+
+const HELP: &str = "tab/h/l: switch pane  j/k: select file  n/p: select hunk  space: (un)stage file  s: (un)stage hunk  q: quit";
+
+/// A full-screen interface for staging: the unstaged and the staged files in two panes, with the diff of the
+/// selected file, whose files and individual hunks can be staged and unstaged.
+#[derive(Debug)]
+pub struct StageBrowser {
+    unstaged: Vec<Entry>,
+    staged: Vec<Entry>,
+    pane: Pane,
+    /// The index of the selected file in each pane.
+    selected: [usize; 2],
+    /// The diff of the selected file, if it has been loaded.
+    diff: Option<Diff>,
+    hunk: usize,
+    /// The number of lines of the diff that are scrolled past.
+    diff_offset: usize,
+    /// A message shown in place of the help, until the next key is pressed.
+    message: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Pane {
+    Unstaged,
+    Staged,
+}
+
+/// A file with changes, from `git status --porcelain`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Entry {
+    /// The status code from the side of the `XY` code that the entry's pane shows, e.g. `M`; `?` if untracked.
+    status: char,
+    /// The path of the file, relative to the root of the repository.
+    path: String,
+}
+
+/// The diff of a single file, split into hunks so that they can be applied separately.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+struct Diff {
+    /// The lines before the first hunk, e.g. `diff --git a/x b/x` and `+++ b/x`.
+    header: Vec<String>,
+    /// The lines of each hunk, starting with its `@@ -1,2 +1,3 @@` line.
+    hunks: Vec<Vec<String>>,
+}
+
+impl Diff {
+    fn parse(diff: &str) -> Diff {
+        let mut parsed = Diff::default();
+
+        for line in diff.lines() {
+            if line.starts_with("@@") {
+                parsed.hunks.push(vec![line.to_string()]);
+            } else if let Some(hunk) = parsed.hunks.last_mut() {
+                hunk.push(line.to_string());
+            } else {
+                parsed.header.push(line.to_string());
+            }
+        }
+
+        parsed
+    }
+
+    /// A patch that only contains the hunk at `index`, for `git apply`.
+    fn patch(&self, index: usize) -> String {
+        let mut patch = self.header.join("\n");
+        patch.push('\n');
+        patch.push_str(&self.hunks[index].join("\n"));
+        patch.push('\n');
+        patch
+    }
+}
+
+impl StageBrowser {
+    /// Open the staging interface in the current repository.
+    pub fn browse() -> GitResult {
+        trace!("browse() called");
+
+        // the paths from `git status --porcelain` are relative to the root of the repository
+        env::set_current_dir(Git::repo_root()?)?;
+
+        let mut browser = StageBrowser {
+            unstaged: Vec::new(),
+            staged: Vec::new(),
+            pane: Pane::Unstaged,
+            selected: [0, 0],
+            diff: None,
+            hunk: 0,
+            diff_offset: 0,
+            message: None,
+        };
+        browser.load_status()?;
+
+        if browser.unstaged.is_empty() && browser.staged.is_empty() {
+            Print::info("No changes to stage");
+            return Ok(GitCommandResult::Success);
+        }
+
+        if browser.unstaged.is_empty() {
+            browser.pane = Pane::Staged;
+        }
+
+        browser.run(&mut Terminal::open()?)?;
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// Handle keys until the interface is closed.
+    fn run(&mut self, terminal: &mut Terminal) -> Result<()> {
+        loop {
+            let (rows, _) = terminal.size()?;
+            // each pane gets up to a quarter of the screen; the diff gets the rest
+            let pane_height = |entries: &Vec<Entry>| entries.len().clamp(1, (rows / 4).max(1));
+            let heights = [pane_height(&self.unstaged), pane_height(&self.staged)];
+            let diff_height = rows.saturating_sub(heights[0] + heights[1] + 4);
+
+            terminal.draw(&self.render(heights, diff_height)?)?;
+
+            let key = terminal.read_key()?;
+            self.message = None;
+
+            match key {
+                Key::Char('q') | Key::Escape | Key::Interrupt => return Ok(()),
+                Key::Tab | Key::Char('h') | Key::Char('l') => {
+                    self.pane = match self.pane {
+                        Pane::Unstaged => Pane::Staged,
+                        Pane::Staged => Pane::Unstaged,
+                    };
+                    self.reset_diff();
+                }
+                Key::Char('j') | Key::Down => self.select(self.selected[self.pane as usize] + 1),
+                Key::Char('k') | Key::Up => {
+                    self.select(self.selected[self.pane as usize].saturating_sub(1))
+                }
+                Key::Char('n') => self.select_hunk(self.hunk + 1),
+                Key::Char('p') => self.select_hunk(self.hunk.saturating_sub(1)),
+                Key::PageDown => {
+                    let lines = self.diff_lines().len();
+                    self.diff_offset =
+                        (self.diff_offset + diff_height).min(lines.saturating_sub(diff_height));
+                }
+                Key::PageUp => self.diff_offset = self.diff_offset.saturating_sub(diff_height),
+                Key::Char(' ') | Key::Enter => self.toggle_file()?,
+                Key::Char('s') => self.toggle_hunk()?,
+                _ => {}
+            }
+        }
+    }
+
+    /// The entries in `pane`.
+    fn entries(&self, pane: Pane) -> &Vec<Entry> {
+        match pane {
+            Pane::Unstaged => &self.unstaged,
+            Pane::Staged => &self.staged,
+        }
+    }
+
+    /// The selected entry in the focused pane, if it has any.
+    fn entry(&self) -> Option<&Entry> {
+        self.entries(self.pane)
+            .get(self.selected[self.pane as usize])
+    }
+
+    /// Select the file at `index` in the focused pane (clamped to the pane).
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.entries(self.pane).len().saturating_sub(1));
+
+        if index != self.selected[self.pane as usize] {
+            self.selected[self.pane as usize] = index;
+            self.reset_diff();
+        }
+    }
+
+    /// Select the hunk at `index` (clamped to the diff), and scroll the diff to it.
+    fn select_hunk(&mut self, index: usize) {
+        let Some(diff) = &self.diff else {
+            return;
+        };
+
+        self.hunk = index.min(diff.hunks.len().saturating_sub(1));
+        // the header is only scrolled past for the later hunks
+        self.diff_offset = match self.hunk {
+            0 => 0,
+            hunk => diff.header.len() + diff.hunks[..hunk].iter().map(Vec::len).sum::<usize>(),
+        };
+    }
+
+    /// Forget the diff of the selected file, so that it's loaded again.
+    fn reset_diff(&mut self) {
+        self.diff = None;
+        self.hunk = 0;
+        self.diff_offset = 0;
+    }
+
+    /// Load the files that have changes, and clamp the selections to them.
+    ///
+    /// `git status --porcelain -z --untracked-files=all`
+    fn load_status(&mut self) -> Result<()> {
+        let status = GitCommand::new("status")
+            .with_default_args(&["--porcelain", "-z", "--untracked-files=all"])
+            .stdout()?;
+
+        self.unstaged.clear();
+        self.staged.clear();
+        let mut entries = status.split('\0');
+
+        while let Some(entry) = entries.next() {
+            if entry.len() < 4 {
+                continue;
+            }
+
+            let (xy, path) = entry.split_at(2);
+            let path = path[1..].to_string();
+
+            // renames and copies are followed by an extra entry with the original path
+            if xy.starts_with(['R', 'C']) {
+                entries.next();
+            }
+
+            let mut codes = xy.chars();
+            let (x, y) = (codes.next().unwrap_or(' '), codes.next().unwrap_or(' '));
+
+            match xy {
+                "??" => self.unstaged.push(Entry { status: '?', path }),
+                // conflicts have to be resolved before they can be staged
+                "DD" | "AU" | "UD" | "UA" | "DU" | "AA" | "UU" => {}
+                _ => {
+                    if x != ' ' {
+                        self.staged.push(Entry {
+                            status: x,
+                            path: path.clone(),
+                        });
+                    }
+                    if y != ' ' {
+                        self.unstaged.push(Entry { status: y, path });
+                    }
+                }
+            }
+        }
+
+        debug!("unstaged: {:#?}, staged: {:#?}", self.unstaged, self.staged);
+
+        for pane in [Pane::Unstaged, Pane::Staged] {
+            let len = self.entries(pane).len();
+            let selected = &mut self.selected[pane as usize];
+            *selected = (*selected).min(len.saturating_sub(1));
+        }
+
+        Ok(())
+    }
+
+    /// Load the diff of the selected file, unless it has been already.
+    ///
+    /// `git diff [--cached] -- PATH`, or `git diff --no-index -- /dev/null PATH` for an untracked file
+    fn load_diff(&mut self) -> Result<()> {
+        if self.diff.is_some() {
+            return Ok(());
+        }
+
+        let Some(entry) = self.entry() else {
+            return Ok(());
+        };
+
+        let args = match (self.pane, entry.status) {
+            (Pane::Unstaged, '?') => vec!["--no-index", "--", "/dev/null", &entry.path],
+            (Pane::Unstaged, _) => vec!["--no-ext-diff", "--", &entry.path],
+            (Pane::Staged, _) => vec!["--no-ext-diff", "--cached", "--", &entry.path],
+        };
+
+        // `--no-index` exits with 1 when the files differ, so the status isn't checked
+        let output = GitCommand::new("diff").with_default_args(&args).output()?;
+        self.diff = Some(Diff::parse(&String::from_utf8_lossy(&output.stdout)));
+
+        Ok(())
+    }
+
+    /// The lines of the diff of the selected file, with the selected hunk marked in the gutter.
+    fn diff_lines(&self) -> Vec<String> {
+        let Some(diff) = &self.diff else {
+            return Vec::new();
+        };
+
+        let color = Colors::stdout();
+        let mut lines: Vec<String> = diff
+            .header
+            .iter()
+            .map(|line| {
+                let line = format!("  {line}");
+                Colors::paint(Color::White, &line, color).to_string()
+            })
+            .collect();
+
+        for (i, hunk) in diff.hunks.iter().enumerate() {
+            let gutter = if i == self.hunk { "▌ " } else { "  " };
+
+            lines.extend(hunk.iter().map(|line| {
+                let line_color = match line.chars().next() {
+                    Some('@') => Color::Cyan,
+                    Some('+') => Color::Green,
+                    Some('-') => Color::Red,
+                    _ => Color::Default,
+                };
+
+                format!("{gutter}{}", Colors::paint(line_color, line, color))
+            }));
+        }
+
+        lines
+    }
+
+    /// The lines of the screen: both panes, a divider, the diff, and the help (or a message).
+    fn render(&mut self, heights: [usize; 2], diff_height: usize) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        for (pane, title) in [(Pane::Unstaged, "Unstaged"), (Pane::Staged, "Staged")] {
+            let entries = self.entries(pane);
+            let title = format!("{title} ({})", entries.len());
+            lines.push(if pane == self.pane {
+                Style::new().bold().underline().paint(title).to_string()
+            } else {
+                Style::new().dimmed().paint(title).to_string()
+            });
+
+            let height = heights[pane as usize];
+            let selected = self.selected[pane as usize];
+            // keep the selected file on screen
+            let offset = (selected + 1).saturating_sub(height);
+
+            let mut rows: Vec<String> = entries
+                .iter()
+                .enumerate()
+                .skip(offset)
+                .take(height)
+                .map(|(i, entry)| {
+                    let marker = if pane == self.pane && i == selected {
+                        ">"
+                    } else {
+                        " "
+                    };
+                    let status_color = match pane {
+                        Pane::Unstaged => Color::Red,
+                        Pane::Staged => Color::Green,
+                    };
+
+                    format!(
+                        "{marker} {} {}",
+                        Colors::paint(status_color, &entry.status.to_string(), Colors::stdout()),
+                        entry.path
+                    )
+                })
+                .collect();
+            rows.resize(height, String::new());
+            lines.extend(rows);
+        }
+
+        lines.push(tui::divider());
+
+        self.load_diff()?;
+        let mut diff: Vec<String> = self
+            .diff_lines()
+            .into_iter()
+            .skip(self.diff_offset)
+            .take(diff_height)
+            .collect();
+        diff.resize(diff_height, String::new());
+        lines.extend(diff);
+
+        lines.push(tui::footer(self.message.as_deref().unwrap_or(HELP)));
+
+        Ok(lines)
+    }
+
+    /// Stage the selected file if it's unstaged, or unstage it if it's staged.
+    ///
+    /// `git add -- PATH`, or `git restore --staged -- PATH` (`git rm --cached -- PATH` before the first commit)
+    fn toggle_file(&mut self) -> Result<()> {
+        let Some(entry) = self.entry() else {
+            return Ok(());
+        };
+        let path = entry.path.clone();
+
+        match self.pane {
+            Pane::Unstaged => self.git(&["add", "--", &path], None),
+            Pane::Staged if Git::object_exists("HEAD")? => {
+                self.git(&["restore", "--staged", "--", &path], None)
+            }
+            Pane::Staged => self.git(&["rm", "--cached", "--quiet", "--", &path], None),
+        }
+    }
+
+    /// Stage the selected hunk if it's unstaged, or unstage it if it's staged.
+    ///
+    /// `git apply --cached [--reverse] -`, with a patch that only contains the hunk
+    fn toggle_hunk(&mut self) -> Result<()> {
+        let patch = match &self.diff {
+            Some(diff) if !diff.hunks.is_empty() => diff.patch(self.hunk),
+            _ => {
+                self.message = Some("The selected file has no hunks".to_string());
+                return Ok(());
+            }
+        };
+
+        match self.pane {
+            Pane::Unstaged => self.git(&["apply", "--cached", "-"], Some(&patch)),
+            Pane::Staged => self.git(&["apply", "--cached", "--reverse", "-"], Some(&patch)),
+        }
+    }
+
+    /// Run `git ARGS` with `input` on its `stdin`, then reload the files and the diff.
+    ///
+    /// The output is captured so that it doesn't disturb the screen; if the command fails, its error is shown as the
+    /// message.
+    fn git(&mut self, args: &[&str], input: Option<&str>) -> Result<()> {
+        let Some((subcommand, args)) = args.split_first() else {
+            return Ok(());
+        };
+        let git = GitCommand::new(subcommand);
+        let command = git.with_default_args(args);
+
+        if DRY_RUN.load(Ordering::SeqCst) {
+            self.message = Some(format!(
+                "would run: {}",
+                command.construct_git_command_string()
+            ));
+            return Ok(());
+        }
+
+        let output = command.output_with_input(input.unwrap_or_default().as_bytes())?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            self.message = Some(error.lines().next().unwrap_or("failed").to_string());
+        }
+
+        let hunk = self.hunk;
+        self.load_status()?;
+        self.reset_diff();
+        // stay on the same hunk, which is now the next one (if there is one)
+        self.load_diff()?;
+        self.select_hunk(hunk);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/x b/x\nindex 1..2 100644\n--- a/x\n+++ b/x\n\
+                        @@ -1,2 +1,2 @@\n-a\n+b\n c\n@@ -10 +10 @@\n-y\n\\ No newline at end of file\n+z\n";
+
+    #[test]
+    fn diff_parse_splits_the_header_and_hunks() {
+        let diff = Diff::parse(DIFF);
+
+        assert_eq!(
+            diff.header,
+            [
+                "diff --git a/x b/x",
+                "index 1..2 100644",
+                "--- a/x",
+                "+++ b/x"
+            ]
+        );
+        assert_eq!(
+            diff.hunks,
+            [
+                vec!["@@ -1,2 +1,2 @@", "-a", "+b", " c"],
+                vec!["@@ -10 +10 @@", "-y", "\\ No newline at end of file", "+z"],
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_parse_without_hunks() {
+        let diff = Diff::parse("diff --git a/x b/x\nBinary files a/x and b/x differ\n");
+
+        assert_eq!(diff.header.len(), 2);
+        assert!(diff.hunks.is_empty());
+        assert_eq!(Diff::parse(""), Diff::default());
+    }
+
+    #[test]
+    fn diff_patch_has_just_one_hunk() {
+        assert_eq!(
+            Diff::parse(DIFF).patch(1),
+            "diff --git a/x b/x\nindex 1..2 100644\n--- a/x\n+++ b/x\n\
+             @@ -10 +10 @@\n-y\n\\ No newline at end of file\n+z\n"
+        );
+    }
+}
@@ -3,8 +3,48 @@ use core::fmt;
 /// Environment variables used by the **git-util** application
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum GitUtilEnvVars {
+    /// Allows commits on protected branches, for emergencies.
+    AllowProtected,
+    /// Overrides the `git-util.cache` config setting.
+    Cache,
+    /// Overrides the `git-util.clone.forge` config setting.
+    CloneForge,
+    /// Overrides the `git-util.clone.protocol` config setting.
+    CloneProtocol,
+    /// Overrides the `git-util.clone.root` config setting.
+    CloneRoot,
+    /// Overrides the `git-util.dates` config setting.
+    Dates,
+    /// Overrides the `git-util.defaultBranch` config setting.
+    DefaultBranch,
     /// The Regex string used to match against diff changes to find changes that are not allowed in a commit.
     DisallowedStrings,
+    /// Overrides the `git-util.logCount` config setting.
+    LogCount,
+    /// Overrides the `git-util.logFile` config setting.
+    LogFile,
+    /// Overrides the `git-util.merge.defaultBranch` config setting.
+    MergeDefaultBranch,
+    /// Overrides the `git-util.merge.featureBranch` config setting.
+    MergeFeatureBranch,
+    /// Overrides the `git-util.noPager` config setting.
+    NoPager,
+    /// Overrides the `git-util.pager` config setting.
+    Pager,
+    /// Overrides the `git-util.releaseToken` config setting.
+    ReleaseToken,
+    /// Overrides the `git-util.timeout` config setting.
+    Timeout,
+    /// Overrides the `git-util.tools.column` config setting.
+    ToolsColumn,
+    /// Overrides the `git-util.tools.diff` config setting.
+    ToolsDiff,
+    /// Overrides the `git-util.tools.filter` config setting.
+    ToolsFilter,
+    /// Overrides the `git-util.tools.picker` config setting.
+    ToolsPicker,
+    /// Overrides the `git-util.tools.sed` config setting.
+    ToolsSed,
     /// The email address that is used for commits.
     UserEmail,
 }
@@ -21,6 +61,7 @@ pub enum GitEnvVars {
     ExecPath,
     #[allow(dead_code)]
     IndexFile,
+    Pager,
     #[allow(dead_code)]
     Prefix,
 }
@@ -33,6 +74,7 @@ impl fmt::Display for GitEnvVars {
             GitEnvVars::AuthorName => write!(f, "GIT_AUTHOR_NAME"),
             GitEnvVars::ExecPath => write!(f, "GIT_EXEC_PATH"),
             GitEnvVars::IndexFile => write!(f, "GIT_INDEX_FILE"),
+            GitEnvVars::Pager => write!(f, "GIT_PAGER"),
             GitEnvVars::Prefix => write!(f, "GIT_PREFIX"),
         }
     }
@@ -47,7 +89,27 @@ impl From<GitEnvVars> for String {
 impl fmt::Display for GitUtilEnvVars {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            GitUtilEnvVars::AllowProtected => write!(f, "GIT_UTIL_ALLOW_PROTECTED"),
+            GitUtilEnvVars::Cache => write!(f, "GIT_UTIL_CACHE"),
+            GitUtilEnvVars::CloneForge => write!(f, "GIT_UTIL_CLONE_FORGE"),
+            GitUtilEnvVars::CloneProtocol => write!(f, "GIT_UTIL_CLONE_PROTOCOL"),
+            GitUtilEnvVars::CloneRoot => write!(f, "GIT_UTIL_CLONE_ROOT"),
+            GitUtilEnvVars::Dates => write!(f, "GIT_UTIL_DATES"),
+            GitUtilEnvVars::DefaultBranch => write!(f, "GIT_UTIL_DEFAULT_BRANCH"),
             GitUtilEnvVars::DisallowedStrings => write!(f, "GIT_UTIL_DISALLOWED_STRINGS"),
+            GitUtilEnvVars::LogCount => write!(f, "GIT_UTIL_LOG_COUNT"),
+            GitUtilEnvVars::LogFile => write!(f, "GIT_UTIL_LOG_FILE"),
+            GitUtilEnvVars::MergeDefaultBranch => write!(f, "GIT_UTIL_MERGE_DEFAULT_BRANCH"),
+            GitUtilEnvVars::MergeFeatureBranch => write!(f, "GIT_UTIL_MERGE_FEATURE_BRANCH"),
+            GitUtilEnvVars::NoPager => write!(f, "GIT_UTIL_NO_PAGER"),
+            GitUtilEnvVars::Pager => write!(f, "GIT_UTIL_PAGER"),
+            GitUtilEnvVars::ReleaseToken => write!(f, "GIT_UTIL_RELEASE_TOKEN"),
+            GitUtilEnvVars::Timeout => write!(f, "GIT_UTIL_TIMEOUT"),
+            GitUtilEnvVars::ToolsColumn => write!(f, "GIT_UTIL_TOOLS_COLUMN"),
+            GitUtilEnvVars::ToolsDiff => write!(f, "GIT_UTIL_TOOLS_DIFF"),
+            GitUtilEnvVars::ToolsFilter => write!(f, "GIT_UTIL_TOOLS_FILTER"),
+            GitUtilEnvVars::ToolsPicker => write!(f, "GIT_UTIL_TOOLS_PICKER"),
+            GitUtilEnvVars::ToolsSed => write!(f, "GIT_UTIL_TOOLS_SED"),
             GitUtilEnvVars::UserEmail => write!(f, "GIT_UTIL_USER_EMAIL"),
         }
     }
@@ -0,0 +1,27 @@
+use core::fmt;
+use std::time::Duration;
+
+/// Errors raised by **git-util** itself (as opposed to errors from `git` or the OS).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum GitUtilError {
+    /// An interactive picker was closed without choosing anything.
+    Cancelled,
+    /// A spawned process ran longer than the configured timeout and was killed.
+    Timeout { command: String, timeout: Duration },
+}
+
+impl fmt::Display for GitUtilError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitUtilError::Cancelled => write!(f, "Cancelled"),
+            GitUtilError::Timeout { command, timeout } => write!(
+                f,
+                "`{}` timed out after {}s and was killed",
+                command,
+                timeout.as_secs_f32()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GitUtilError {}
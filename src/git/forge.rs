@@ -0,0 +1,194 @@
+use crate::git::GitCommand;
+use anyhow::Result;
+use log::{debug, trace};
+
+/// The hosting services that `git-util` knows how to build web URLs for.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Forge {
+    Bitbucket,
+    GitHub,
+    GitLab,
+    /// A host that `git-util` doesn't recognize.
+    Unknown,
+}
+
+/// A repository hosted on a forge, parsed from a remote URL.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct ForgeRepo {
+    pub forge: Forge,
+    pub host: String,
+    /// The owner (user, organization, or group path) of the repository.
+    pub owner: String,
+    pub name: String,
+}
+
+impl ForgeRepo {
+    /// Parse the URL of `remote` (e.g. `origin`).
+    ///
+    /// Returns `None` if the remote URL is not in a recognized format.
+    pub fn from_remote(remote: &str) -> Result<Option<ForgeRepo>> {
+        trace!("from_remote() called with: {remote}");
+
+        let url = GitCommand::new("remote")
+            .with_default_args(&["get-url", remote])
+            .stdout()?;
+
+        debug!("{remote} url: {url}");
+
+        Ok(ForgeRepo::parse(&url))
+    }
+
+    /// Parse a remote URL in any of the forms:
+    ///
+    /// - `git@HOST:OWNER/NAME.git`
+    /// - `ssh://git@HOST[:PORT]/OWNER/NAME.git`
+    /// - `https://HOST/OWNER/NAME[.git]`
+    pub fn parse(url: &str) -> Option<ForgeRepo> {
+        let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+            let (authority, path) = rest.split_once('/')?;
+            // strip user and port
+            let host = authority.rsplit('@').next()?;
+            (host.split(':').next()?, path)
+        } else {
+            // scp-like syntax
+            let (authority, path) = url.split_once(':')?;
+            (authority.rsplit('@').next()?, path)
+        };
+
+        let path = path.trim_matches('/').trim_end_matches(".git");
+        let (owner, name) = path.rsplit_once('/')?;
+
+        if host.is_empty() || owner.is_empty() || name.is_empty() {
+            return None;
+        }
+
+        let forge = match host {
+            "github.com" => Forge::GitHub,
+            "bitbucket.org" => Forge::Bitbucket,
+            host if host.contains("gitlab") => Forge::GitLab,
+            _ => Forge::Unknown,
+        };
+
+        Some(ForgeRepo {
+            forge,
+            host: host.to_string(),
+            owner: owner.to_string(),
+            name: name.to_string(),
+        })
+    }
+
+    /// The URL of the repository's home page.
+    pub fn web_url(&self) -> String {
+        format!("https://{}/{}/{}", self.host, self.owner, self.name)
+    }
+
+    /// The URL of the web page for the commit `hash`, if the forge is known.
+    pub fn commit_url(&self, hash: &str) -> Option<String> {
+        let web_url = self.web_url();
+
+        match self.forge {
+            Forge::GitHub => Some(format!("{web_url}/commit/{hash}")),
+            Forge::GitLab => Some(format!("{web_url}/-/commit/{hash}")),
+            Forge::Bitbucket => Some(format!("{web_url}/commits/{hash}")),
+            Forge::Unknown => None,
+        }
+    }
+
+    /// The URL for opening a pull/merge request from `branch`, if the forge is known.
+    pub fn compare_url(&self, branch: &str) -> Option<String> {
+        let web_url = self.web_url();
+
+        match self.forge {
+            Forge::GitHub => Some(format!("{web_url}/compare/{branch}?expand=1")),
+            Forge::GitLab => Some(format!(
+                "{web_url}/-/merge_requests/new?merge_request[source_branch]={branch}"
+            )),
+            Forge::Bitbucket => Some(format!("{web_url}/pull-requests/new?source={branch}")),
+            Forge::Unknown => None,
+        }
+    }
+
+    /// The URL for opening a pull/merge request from `branch` into `base`, if the forge is known.
+    pub fn pull_request_url(&self, branch: &str, base: &str) -> Option<String> {
+        let web_url = self.web_url();
+
+        match self.forge {
+            Forge::GitHub => Some(format!("{web_url}/compare/{base}...{branch}?expand=1")),
+            Forge::GitLab => Some(format!(
+                "{web_url}/-/merge_requests/new?merge_request[source_branch]={branch}&merge_request[target_branch]={base}"
+            )),
+            Forge::Bitbucket => Some(format!(
+                "{web_url}/pull-requests/new?source={branch}&dest={base}"
+            )),
+            Forge::Unknown => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(forge: Forge, host: &str, owner: &str, name: &str) -> Option<ForgeRepo> {
+        Some(ForgeRepo {
+            forge,
+            host: host.to_string(),
+            owner: owner.to_string(),
+            name: name.to_string(),
+        })
+    }
+
+    #[test]
+    fn parse_scp_like_urls() {
+        assert_eq!(
+            ForgeRepo::parse("git@github.com:owner/name.git"),
+            repo(Forge::GitHub, "github.com", "owner", "name")
+        );
+        assert_eq!(
+            ForgeRepo::parse("bitbucket.org:owner/name"),
+            repo(Forge::Bitbucket, "bitbucket.org", "owner", "name")
+        );
+    }
+
+    #[test]
+    fn parse_ssh_and_https_urls() {
+        assert_eq!(
+            ForgeRepo::parse("ssh://git@gitlab.example.com:2222/group/sub/name.git"),
+            repo(Forge::GitLab, "gitlab.example.com", "group/sub", "name")
+        );
+        assert_eq!(
+            ForgeRepo::parse("https://user@git.example.com/owner/name/"),
+            repo(Forge::Unknown, "git.example.com", "owner", "name")
+        );
+    }
+
+    #[test]
+    fn parse_rejects_other_urls() {
+        for url in [
+            "/path/to/repo",
+            "https://github.com/name",
+            "https://github.com",
+            "git@github.com:name.git",
+            ":owner/name",
+        ] {
+            assert_eq!(ForgeRepo::parse(url), None, "parsing {url:?}");
+        }
+    }
+
+    #[test]
+    fn urls_for_known_forges() {
+        let github = repo(Forge::GitHub, "github.com", "owner", "name").unwrap();
+        assert_eq!(
+            github.commit_url("abc").as_deref(),
+            Some("https://github.com/owner/name/commit/abc")
+        );
+        assert_eq!(
+            github.pull_request_url("topic", "main").as_deref(),
+            Some("https://github.com/owner/name/compare/main...topic?expand=1")
+        );
+
+        let unknown = repo(Forge::Unknown, "example.com", "owner", "name").unwrap();
+        assert_eq!(unknown.commit_url("abc"), None);
+        assert_eq!(unknown.web_url(), "https://example.com/owner/name");
+    }
+}
@@ -1 +1,5 @@
+pub mod config;
+pub mod install;
+pub mod post_commit;
 pub mod pre_commit;
+pub mod report;
@@ -0,0 +1,500 @@
+use crate::{
+    git::Git,
+    toml::{Document, Value},
+};
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use regex::Regex;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The pre-commit hook configuration, relative to the root of the work tree.
+pub const PRE_COMMIT_CONFIG: &str = ".git-wrapper/pre-commit.toml";
+
+/// A repository-wide configuration file, relative to the root of the work tree, whose `[pre-commit]` table has the
+/// same keys as `PRE_COMMIT_CONFIG`.
+const REPO_CONFIG: &str = "git-wrapper.toml";
+
+/// The names of the built-in checks, as they're given in `[skip] checks`.
+pub const CHECK_NAMES: [&str; 4] = [
+    "user_email",
+    "disallowed_strings",
+    "lfs_blobs",
+    "protected_branch",
+];
+
+/// The hook policy that's committed to the repository, so that every clone with the hooks installed enforces it.
+///
+/// It's read from `PRE_COMMIT_CONFIG` if it exists, else from the `[pre-commit]` table of `REPO_CONFIG`. The
+/// `$GIT_UTIL_*` environment variables override it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+pub struct HookConfig {
+    /// `user_email`: the only author email that commits may have.
+    pub user_email: Option<String>,
+    /// `disallowed_strings`: a case-insensitive regex that added lines may not match.
+    pub disallowed_strings: Option<String>,
+    /// `protected_branches`: patterns of the branches that commits may not be made on directly (besides the default
+    /// branch), where `*` matches anything, e.g. `release/*`.
+    ///
+    /// Nothing is protected unless this is set, so `[]` protects just the default branch.
+    pub protected_branches: Option<Vec<String>>,
+    /// The `[skip]` table (`[pre-commit.skip]` in `REPO_CONFIG`).
+    pub skip: SkipRules,
+    /// The `[checks.NAME]` tables (`[pre-commit.checks.NAME]` in `REPO_CONFIG`), in order of name.
+    pub checks: Vec<ExternalCheck>,
+}
+
+/// A check that runs an external command, e.g. a linter, and fails if it exits with a non-zero status.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+pub struct ExternalCheck {
+    /// The `NAME` of the table, which `[skip] checks` refers to it by.
+    pub name: String,
+    /// `command`: the program and its arguments.
+    ///
+    /// `{root}` is replaced with the root of the work tree, and `{staged_files}` with the paths (relative to the root)
+    /// of the added, copied, modified, and renamed files that are staged; as an argument by itself, it becomes one
+    /// argument per file. A command with `{staged_files}` doesn't run if there are none.
+    pub command: Vec<String>,
+    /// `working_directory`: where the command runs, relative to the root of the work tree; defaults to the root.
+    pub working_directory: Option<String>,
+    /// The `[checks.NAME.env]` table: environment variables to run the command with, which may use `{root}`.
+    pub env: BTreeMap<String, String>,
+    /// `output = "json"`: the command prints its problems to `stdout` as JSON, which is rendered as
+    /// `FILE:LINE: MESSAGE` (the default is `"text"`, which is printed as is).
+    pub json_output: bool,
+}
+
+/// The commits that the checks are skipped (or softened) for, so that they don't need `--no-verify`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+pub struct SkipRules {
+    /// `subjects`: prefixes of the subjects of commits to skip, e.g. `WIP:` and `fixup!`.
+    ///
+    /// The subject is only known to the `commit-msg` hook, so the checks move there when this is set.
+    pub subjects: Vec<String>,
+    /// `merges`: skip merge commits.
+    pub merges: bool,
+    /// `branches`: patterns of the branches to skip commits on, where `*` matches anything, e.g. `wip/*`.
+    pub branches: Vec<String>,
+    /// `checks`: the names of the checks to skip; defaults to all of them.
+    pub checks: Vec<String>,
+    /// `warn`: run the checks anyway, but report their failures as warnings instead of failing the commit.
+    pub warn: bool,
+}
+
+impl HookConfig {
+    /// The configuration at the root of the current repository; the default (which checks nothing) if there's none.
+    pub fn load() -> Result<HookConfig> {
+        let root = Git::repo_root()?;
+
+        for (file, table) in [(PRE_COMMIT_CONFIG, None), (REPO_CONFIG, Some("pre-commit"))] {
+            let path = root.join(file);
+            if !path.is_file() {
+                continue;
+            }
+
+            debug!("reading the hook configuration from {}", path.display());
+
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let document = Document::parse(&contents)
+                .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+
+            let skip_table = match table {
+                Some(table) => format!("{table}.skip"),
+                None => "skip".to_string(),
+            };
+
+            let mut config = match document.table(table.unwrap_or_default()) {
+                Some(settings) => HookConfig::from_table(settings, &path)?,
+                None => HookConfig::default(),
+            };
+            if let Some(skip) = document.table(&skip_table) {
+                config.skip = SkipRules::from_table(skip, &path)?;
+            }
+            config.checks = ExternalCheck::from_document(
+                &document,
+                &match table {
+                    Some(table) => format!("{table}.checks."),
+                    None => "checks.".to_string(),
+                },
+                &path,
+            )?;
+
+            if let Some(unknown) = config.skip.checks.iter().find(|check| {
+                !CHECK_NAMES.contains(&check.as_str())
+                    && !config
+                        .checks
+                        .iter()
+                        .any(|external| external.name == **check)
+            }) {
+                return Err(anyhow!(
+                    "Unknown check '{}' in {} (expected one of: {})",
+                    unknown,
+                    path.display(),
+                    CHECK_NAMES
+                        .iter()
+                        .copied()
+                        .chain(config.checks.iter().map(|check| check.name.as_str()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+
+            return Ok(config);
+        }
+
+        debug!("no hook configuration in {}", root.display());
+        Ok(HookConfig::default())
+    }
+
+    /// Fails on unknown keys and values of the wrong type, so that a typo doesn't silently turn a check off.
+    fn from_table(settings: &BTreeMap<String, Value>, path: &Path) -> Result<HookConfig> {
+        let mut config = HookConfig::default();
+
+        for (key, value) in settings {
+            match key.as_str() {
+                "user_email" => config.user_email = Some(string(key, value, path)?),
+                "disallowed_strings" => config.disallowed_strings = Some(string(key, value, path)?),
+                "protected_branches" => {
+                    config.protected_branches = Some(strings(key, value, path)?)
+                }
+                _ => return Err(anyhow!("Unknown key '{}' in {}", key, path.display())),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Why `branch` is protected, if it is: it's the default branch, or it matches one of the `protected_branches`.
+    pub fn protection(&self, branch: &str) -> Result<Option<String>> {
+        let Some(patterns) = &self.protected_branches else {
+            return Ok(None);
+        };
+
+        // a new repository doesn't have a default branch until its first commit
+        if Git::default_branch().is_ok_and(|default_branch| default_branch == branch) {
+            return Ok(Some("it's the default branch".to_string()));
+        }
+
+        for pattern in patterns {
+            if glob_matches(pattern, branch)? {
+                return Ok(Some(format!("it matches \"{pattern}\"")));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl SkipRules {
+    fn from_table(settings: &BTreeMap<String, Value>, path: &Path) -> Result<SkipRules> {
+        let mut rules = SkipRules::default();
+
+        for (key, value) in settings {
+            match key.as_str() {
+                "subjects" => rules.subjects = strings(key, value, path)?,
+                "merges" => rules.merges = boolean(key, value, path)?,
+                "branches" => rules.branches = strings(key, value, path)?,
+                // the names are checked once the external checks are known
+                "checks" => rules.checks = strings(key, value, path)?,
+                "warn" => rules.warn = boolean(key, value, path)?,
+                _ => return Err(anyhow!("Unknown key 'skip.{}' in {}", key, path.display())),
+            }
+        }
+
+        Ok(rules)
+    }
+
+    /// Whether the check `name` is skipped (or softened) when a rule matches.
+    pub fn applies_to(&self, name: &str) -> bool {
+        self.checks.is_empty() || self.checks.iter().any(|check| check == name)
+    }
+}
+
+impl ExternalCheck {
+    /// The checks defined by the tables named `PREFIX.NAME` (and their `PREFIX.NAME.env` tables) in `document`.
+    fn from_document(document: &Document, prefix: &str, path: &Path) -> Result<Vec<ExternalCheck>> {
+        let mut checks: BTreeMap<&str, ExternalCheck> = BTreeMap::new();
+        let mut envs = Vec::new();
+
+        for (table, settings) in document.tables() {
+            let Some(name) = table.strip_prefix(prefix) else {
+                continue;
+            };
+
+            if let Some(name) = name.strip_suffix(".env") {
+                envs.push((name, settings));
+                continue;
+            }
+
+            if name.contains('.') || CHECK_NAMES.contains(&name) {
+                return Err(anyhow!(
+                    "Invalid check name '{}' in {} (it may not contain '.' or be the name of a built-in check)",
+                    name,
+                    path.display()
+                ));
+            }
+
+            checks.insert(name, ExternalCheck::from_table(name, settings, path)?);
+        }
+
+        for (name, settings) in envs {
+            let check = checks.get_mut(name).ok_or_else(|| {
+                anyhow!(
+                    "[{}{}.env] in {} is for a check that isn't defined",
+                    prefix,
+                    name,
+                    path.display()
+                )
+            })?;
+
+            for (key, value) in settings {
+                check.env.insert(
+                    key.clone(),
+                    string(&format!("{name}.env.{key}"), value, path)?,
+                );
+            }
+        }
+
+        Ok(checks.into_values().collect())
+    }
+
+    fn from_table(
+        name: &str,
+        settings: &BTreeMap<String, Value>,
+        path: &Path,
+    ) -> Result<ExternalCheck> {
+        let mut check = ExternalCheck {
+            name: name.to_string(),
+            ..ExternalCheck::default()
+        };
+
+        for (key, value) in settings {
+            match key.as_str() {
+                "command" => check.command = strings(key, value, path)?,
+                "working_directory" => check.working_directory = Some(string(key, value, path)?),
+                "output" => {
+                    check.json_output = match string(key, value, path)?.as_str() {
+                        "json" => true,
+                        "text" => false,
+                        other => {
+                            return Err(anyhow!(
+                                "Invalid output '{}' for the check {} in {} (expected \"text\" or \"json\")",
+                                other,
+                                name,
+                                path.display()
+                            ))
+                        }
+                    }
+                }
+                _ => {
+                    return Err(anyhow!(
+                        "Unknown key '{}' for the check {} in {}",
+                        key,
+                        name,
+                        path.display()
+                    ))
+                }
+            }
+        }
+
+        if check.command.is_empty() {
+            return Err(anyhow!(
+                "The check {} in {} has no command",
+                name,
+                path.display()
+            ));
+        }
+
+        Ok(check)
+    }
+}
+
+/// Whether `text` matches `pattern`, in which `*` matches anything (including `/`).
+pub fn glob_matches(pattern: &str, text: &str) -> Result<bool> {
+    let regex = format!(
+        "^{}$",
+        pattern
+            .split('*')
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join(".*")
+    );
+
+    Ok(Regex::new(&regex)?.is_match(text))
+}
+
+fn string(key: &str, value: &Value, path: &Path) -> Result<String> {
+    value
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| wrong_type(key, value, path, "a string"))
+}
+
+fn strings(key: &str, value: &Value, path: &Path) -> Result<Vec<String>> {
+    value
+        .as_array()
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|value| value.as_str().map(String::from))
+                .collect()
+        })
+        .ok_or_else(|| wrong_type(key, value, path, "an array of strings"))
+}
+
+fn boolean(key: &str, value: &Value, path: &Path) -> Result<bool> {
+    value
+        .as_bool()
+        .ok_or_else(|| wrong_type(key, value, path, "a boolean"))
+}
+
+fn wrong_type(key: &str, value: &Value, path: &Path, expected: &str) -> anyhow::Error {
+    anyhow!(
+        "'{}' in {} must be {}, but it's {}",
+        key,
+        path.display(),
+        expected,
+        value.type_name()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "pre-commit.toml";
+
+    fn document(text: &str) -> Document {
+        Document::parse(text).unwrap()
+    }
+
+    #[test]
+    fn from_table() {
+        let document = document("user_email = \"me@example.com\"\n");
+        let config = HookConfig::from_table(document.table("").unwrap(), Path::new(PATH)).unwrap();
+
+        assert_eq!(config.user_email.as_deref(), Some("me@example.com"));
+        assert_eq!(config.disallowed_strings, None);
+    }
+
+    #[test]
+    fn from_table_rejects_typos_and_wrong_types() {
+        for (text, error) in [
+            (
+                "user_emial = \"x\"",
+                "Unknown key 'user_emial' in pre-commit.toml",
+            ),
+            (
+                "user_email = 1",
+                "'user_email' in pre-commit.toml must be a string, but it's an integer",
+            ),
+        ] {
+            assert_eq!(
+                HookConfig::from_table(document(text).table("").unwrap(), Path::new(PATH))
+                    .unwrap_err()
+                    .to_string(),
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn skip_rules_from_table() {
+        let document = document("subjects = [\"WIP:\"]\nmerges = true\nchecks = [\"lfs_blobs\"]\n");
+        let rules = SkipRules::from_table(document.table("").unwrap(), Path::new(PATH)).unwrap();
+
+        assert_eq!(rules.subjects, ["WIP:"]);
+        assert!(rules.merges);
+        assert!(rules.applies_to("lfs_blobs"));
+        assert!(!rules.applies_to("user_email"));
+        assert!(SkipRules::default().applies_to("user_email"));
+    }
+
+    #[test]
+    fn external_checks_from_document() {
+        let document = document(
+            "[pre-commit.checks.lint]\ncommand = [\"lint\", \"{staged_files}\"]\noutput = \"json\"\n\n\
+             [pre-commit.checks.lint.env]\nMODE = \"ci\"\n\n[pre-commit.checks.build]\ncommand = [\"make\"]\n",
+        );
+        let checks =
+            ExternalCheck::from_document(&document, "pre-commit.checks.", Path::new(PATH)).unwrap();
+
+        assert_eq!(
+            checks
+                .iter()
+                .map(|check| check.name.as_str())
+                .collect::<Vec<_>>(),
+            ["build", "lint"]
+        );
+        assert!(checks[1].json_output);
+        assert_eq!(checks[1].env.get("MODE").map(String::as_str), Some("ci"));
+    }
+
+    #[test]
+    fn external_checks_from_document_rejects_invalid_checks() {
+        for (text, error) in [
+            (
+                "[checks.user_email]\ncommand = [\"x\"]",
+                "Invalid check name 'user_email' in pre-commit.toml (it may not contain '.' or be the name of a built-in check)",
+            ),
+            ("[checks.lint]\n", "The check lint in pre-commit.toml has no command"),
+            (
+                "[checks.lint.env]\nA = \"b\"",
+                "[checks.lint.env] in pre-commit.toml is for a check that isn't defined",
+            ),
+        ] {
+            assert_eq!(
+                ExternalCheck::from_document(&document(text), "checks.", Path::new(PATH))
+                    .unwrap_err()
+                    .to_string(),
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(glob_matches("release/*", "release/1.0").unwrap());
+        assert!(glob_matches("release/*", "release/1.0/hotfix").unwrap());
+        assert!(glob_matches("*-wip", "feature-wip").unwrap());
+        assert!(glob_matches("main", "main").unwrap());
+        assert!(!glob_matches("main", "main2").unwrap());
+        assert!(!glob_matches("release/*", "pre-release/1.0").unwrap());
+    }
+
+    #[test]
+    fn glob_matches_other_characters_literally() {
+        assert!(glob_matches("v1.0", "v1.0").unwrap());
+        assert!(!glob_matches("v1.0", "v1x0").unwrap());
+        assert!(glob_matches("fix/[a]+(b)?", "fix/[a]+(b)?").unwrap());
+    }
+
+    #[test]
+    fn from_table_protected_branches() {
+        let protected = document("protected_branches = [\"release/*\"]\n");
+        let config = HookConfig::from_table(protected.table("").unwrap(), Path::new(PATH)).unwrap();
+
+        assert_eq!(
+            config.protected_branches,
+            Some(vec!["release/*".to_string()])
+        );
+
+        for (text, error) in [
+            (
+                "protected_branches = \"main\"",
+                "'protected_branches' in pre-commit.toml must be an array of strings, but it's a string",
+            ),
+            (
+                "protected_branches = [\"main\", 1]",
+                "'protected_branches' in pre-commit.toml must be an array of strings, but it's an array",
+            ),
+        ] {
+            assert_eq!(
+                HookConfig::from_table(document(text).table("").unwrap(), Path::new(PATH))
+                    .unwrap_err()
+                    .to_string(),
+                error
+            );
+        }
+    }
+}
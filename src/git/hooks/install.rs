@@ -0,0 +1,85 @@
+use crate::{
+    commands::Commands,
+    git::{hooks::config::HookConfig, Git, GitCommandResult, GitResult, DRY_RUN},
+    print::Print,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use std::{fs, sync::atomic::Ordering};
+
+/// Install a shim for each hook that **git-util** implements and that the repository needs, which calls `git-util hook
+/// HOOK`: `pre-commit`; `commit-msg`, if the hook configuration has `[skip] subjects` (see `SkipRules`); and
+/// `post-commit`, which journals each commit, if `post_commit` is `true`.
+///
+/// Existing hooks that already call **git-util** are left alone. Any other existing hook is only replaced if `force` is
+/// `true`; otherwise nothing is installed, so that the hooks aren't left half-installed.
+pub fn install(force: bool, post_commit: bool) -> GitResult {
+    trace!("install() called with: {force}, {post_commit}");
+
+    let mut hooks = vec!["pre-commit"];
+    if !HookConfig::load()?.skip.subjects.is_empty() {
+        hooks.push("commit-msg");
+    }
+    if post_commit {
+        hooks.push("post-commit");
+    }
+
+    let mut missing = Vec::new();
+
+    // check every hook before writing any of them
+    for hook in hooks {
+        // `--git-path` respects `core.hooksPath`
+        let path = Git::git_path(&format!("hooks/{hook}"))?;
+
+        if path.exists() {
+            if is_installed(hook)? {
+                debug!("{} already calls git-util", path.display());
+                continue;
+            }
+
+            if !force {
+                return Err(anyhow!(
+                    "{} already exists; use --force to replace it",
+                    path.display()
+                ));
+            }
+        }
+
+        missing.push((hook, path));
+    }
+
+    for (hook, path) in missing {
+        let shim = format!("#!/bin/sh\nexec git-util hook {hook} \"$@\"\n");
+
+        if DRY_RUN.load(Ordering::SeqCst) {
+            Print::stderr_purple(&format!("would install {}", path.display()));
+            continue;
+        }
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)
+                .with_context(|| format!("Failed to create {}", directory.display()))?;
+        }
+        fs::write(&path, shim).with_context(|| format!("Failed to write {}", path.display()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("Failed to make {} executable", path.display()))?;
+        }
+
+        Print::info(&format!("Installed {}", path.display()));
+    }
+
+    Ok(GitCommandResult::Success)
+}
+
+/// Whether the repository's `hook` (e.g. `commit-msg`) is an executable that calls `git-util hook HOOK`.
+pub fn is_installed(hook: &str) -> Result<bool> {
+    let path = Git::git_path(&format!("hooks/{hook}"))?;
+
+    Ok(fs::read_to_string(&path)
+        .is_ok_and(|existing| existing.contains(&format!("git-util hook {hook}")))
+        && Commands::is_executable(&path))
+}
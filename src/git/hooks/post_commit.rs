@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+
+use crate::{
+    git::{Git, GitCommand, GitCommandResult, GitResult},
+    journal::{Journal, JournalEntry},
+    print::Print,
+};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct PostCommitHook {}
+
+impl PostCommitHook {
+    /// Run the post-commit hook: append the commit that was just made to the `Journal`.
+    ///
+    /// The commit has already been made, so a failure is only reported as a warning.
+    pub fn run() -> GitResult {
+        info!("Running post-commit hook");
+
+        if let Err(e) = PostCommitHook::journal_entry().and_then(|entry| Journal::append(&entry)) {
+            Print::stderr_purple(&format!("Warning: the commit wasn't journaled: {e}"));
+        }
+
+        Ok(GitCommandResult::Success)
+    }
+
+    /// The entry for the commit at `HEAD`.
+    fn journal_entry() -> Result<JournalEntry> {
+        let log = GitCommand::new("log")
+            .with_default_args(&["--max-count=1", "--format=%H%x00%ct%x00%s", "HEAD"])
+            .stdout()?;
+
+        let mut fields = log.splitn(3, '\0');
+        let (Some(sha), Some(timestamp), Some(subject)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(anyhow!("Unexpected output from 'git log': {}", log));
+        };
+
+        // compared to the first parent for a merge commit, and to nothing for a root commit
+        let files_changed = GitCommand::new("diff-tree")
+            .with_default_args(&[
+                "-r",
+                "--root",
+                "--no-commit-id",
+                "--name-only",
+                "-z",
+                "--diff-merges=first-parent",
+                "HEAD",
+            ])
+            .stdout()?
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .count();
+
+        let branch = Git::current_branch()
+            .map_err(|e| debug!("not on a branch: {e}"))
+            .ok();
+
+        Ok(JournalEntry {
+            sha: sha.to_string(),
+            repository: Git::repo_root()?,
+            branch,
+            subject: subject.to_string(),
+            files_changed,
+            timestamp: timestamp.parse()?,
+        })
+    }
+}
@@ -2,109 +2,996 @@ use anyhow::{anyhow, Context};
 use log::{debug, info};
 use regex::Regex;
 use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet},
     env::{self, VarError},
     fmt::Display,
+    fs,
+    hash::{Hash, Hasher},
     io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
 };
 
 use crate::{
+    cache::Cache,
+    commands::{tasks::Tasks, Commands},
     git::{
+        commands::lfs::Lfs,
         env_vars::{GitEnvVars, GitUtilEnvVars},
-        GitCommand, GitCommandResult, GitResult,
+        hooks::{
+            config::{glob_matches, ExternalCheck, HookConfig, SkipRules, CHECK_NAMES},
+            install,
+            report::{CheckRun, HookRun, Status},
+        },
+        Git, GitCommand, GitCommandResult, GitResult,
     },
+    json,
     print::Print,
+    settings,
 };
 
+/// The maximum number of checks to run at the same time.
+const MAX_PARALLEL_CHECKS: usize = 4;
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub struct PreCommitHook {}
 
+/// The placeholder in an external check's command for the root of the work tree (or of the `IndexSnapshot`).
+const ROOT_PLACEHOLDER: &str = "{root}";
+
+/// The placeholder in an external check's command for the staged files.
+const STAGED_FILES_PLACEHOLDER: &str = "{staged_files}";
+
+/// A check that the pre-commit hook runs.
+///
+/// Each check only reads its own input (an environment variable, the staged diff, or the staged files), so they can
+/// run concurrently.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Check<'a> {
+    ProtectedBranch,
+    UserEmail,
+    DisallowedStrings,
+    LfsBlobs,
+    External(&'a ExternalCheck),
+}
+
+/// The buffered output of a check, and whether it passed; checks don't print anything themselves, so that their output
+/// isn't interleaved.
+#[derive(Debug, Default)]
+struct Report {
+    /// Messages to print to `stderr` (in purple), e.g. the line that failed the check or a warning.
+    messages: Vec<String>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    /// Why the check failed, if it did.
+    failure: Option<anyhow::Error>,
+    /// Why the check didn't run, if it didn't.
+    skipped: Option<String>,
+    /// Whether the check didn't run because it passed for the same inputs before.
+    cached: bool,
+    duration: Duration,
+}
+
+impl Report {
+    /// Whether the check ran and passed without printing anything.
+    fn is_silent_pass(&self) -> bool {
+        self.failure.is_none()
+            && self.skipped.is_none()
+            && self.messages.is_empty()
+            && self.stdout.is_empty()
+            && self.stderr.is_empty()
+    }
+}
+
 impl PreCommitHook {
     /// Run the pre-commit hook.
     ///
-    /// 1. Commit email is checked against the env value `$GIT_UTIL_USER_EMAIL`.
-    ///     - Fails if the commit email does not match.
-    /// 2. If env value `$GIT_UTIL_DISALLOWED_STRINGS` is set, the diff changes are checked for matches to the disallowed strings.
+    /// 1. If `protected_branches` is configured, the current branch is checked against them and the default branch.
+    ///     - Fails if the branch is protected, unless `$GIT_UTIL_ALLOW_PROTECTED` is set.
+    /// 2. The commit email is checked against `user_email`, unless it's `"*"`.
+    ///     - Fails if the commit email does not match, or if `user_email` isn't configured.
+    /// 3. If `disallowed_strings` is configured, the diff changes are checked for matches to the disallowed strings.
     ///     - Fails if any added changes in the diff contain a match for any of the disallowed strings.
-    pub fn run() -> GitResult {
+    /// 4. Staged files that match a Git LFS pattern are checked for having been staged as regular blobs.
+    ///     - Only warns; doesn't fail.
+    /// 5. The external checks (`[checks.NAME]`) run their commands.
+    ///     - Fails if a command exits with a non-zero status.
+    ///
+    /// The settings are read from the repository's hook configuration (see `HookConfig`), and the env values
+    /// `$GIT_UTIL_USER_EMAIL` and `$GIT_UTIL_DISALLOWED_STRINGS` override them.
+    ///
+    /// The checks run concurrently, and their output is printed in the order above once all of them have finished.
+    /// A check that passed is skipped until what's staged (or the configuration) changes; see `cache_signature()`.
+    ///
+    /// The checks are skipped (or softened) for the commits that the configured `[skip]` rules match. If there are
+    /// rules for subjects, which aren't known yet, the checks run in the `commit-msg` hook instead.
+    ///
+    /// With `dry_run`, the checks run outside of a commit to preview whether one would pass, without any side effects:
+    /// nothing is cached or saved for `git-util hook report`, and the `[skip]` rules for subjects don't apply.
+    pub fn run(dry_run: bool) -> GitResult {
         info!("Running pre-commit hook");
 
-        match env::var(String::from(GitUtilEnvVars::UserEmail)) {
-            Ok(allowed_email) => match env::var(String::from(GitEnvVars::AuthorEmail)) {
-                Ok(commit_email) => {
-                    if commit_email != allowed_email {
-                        return Err(anyhow!(
-                            "Invalid commit email; {} value is \"{}\". Expected: \"{}\"",
-                            GitEnvVars::AuthorEmail,
-                            commit_email,
-                            allowed_email
-                        ));
+        let config = HookConfig::load()?;
+        debug!("{config:#?}");
+
+        if !config.skip.subjects.is_empty() && !dry_run {
+            if install::is_installed("commit-msg")? {
+                debug!("deferring the checks to the commit-msg hook");
+                return Ok(GitCommandResult::Success);
+            }
+
+            Print::stderr_purple(
+                "Warning: the commit-msg hook isn't installed, so the [skip] subjects can't be checked; run `git-util hook install`",
+            );
+        }
+
+        PreCommitHook::run_checks("pre-commit", &config, None, None, dry_run)
+    }
+
+    /// Run the pre-commit checks against the files under `paths` (or every tracked file if there are none), rather than
+    /// the staged changes, e.g. to fix up a repository before installing the hooks.
+    ///
+    /// The files under `paths` are the tracked ones and the untracked ones that aren't ignored. `disallowed_strings`
+    /// checks every line of each file, and `user_email` and the `[skip]` rules don't apply, since there's no commit.
+    ///
+    /// With `dry_run`, the run isn't saved for `git-util hook report`.
+    pub fn run_on_files(paths: &[PathBuf], dry_run: bool) -> GitResult {
+        info!("Running pre-commit checks on files");
+
+        let config = HookConfig::load()?;
+        debug!("{config:#?}");
+
+        let files = files(paths)?;
+        debug!("files: {files:#?}");
+        if files.is_empty() {
+            return Err(anyhow!("There are no files to check"));
+        }
+
+        PreCommitHook::run_checks("pre-commit --files", &config, None, Some(&files), dry_run)
+    }
+
+    /// Run the `commit-msg` hook: the checks that `run()` deferred because there are `[skip]` rules for subjects.
+    ///
+    /// `message_file` is the file with the commit message, as passed to the hook by **Git**.
+    pub fn run_commit_msg(message_file: &Path) -> GitResult {
+        info!("Running commit-msg hook");
+
+        let config = HookConfig::load()?;
+
+        if config.skip.subjects.is_empty() {
+            debug!("the checks ran in the pre-commit hook");
+            return Ok(GitCommandResult::Success);
+        }
+
+        let message = fs::read_to_string(message_file)
+            .with_context(|| format!("Failed to read {}", message_file.display()))?;
+        // the first line that isn't blank or a comment, like `git commit --cleanup=strip`
+        let subject = message
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'));
+
+        PreCommitHook::run_checks("commit-msg", &config, subject, None, false)
+    }
+
+    /// Run the checks that aren't skipped for the commit with `subject` (if it's known) in `hook`, and report them.
+    ///
+    /// The checks are of the staged changes, or of `files` (relative to the root of the work tree) if they're given,
+    /// in which case they aren't cached and the `[skip]` rules don't apply.
+    ///
+    /// After their output, a summary of the checks is printed, and the run is saved for `git-util hook report`.
+    ///
+    /// With `dry_run`, nothing is cached or saved, and the checks that would block a commit are listed instead of
+    /// failing with their errors.
+    fn run_checks(
+        hook: &str,
+        config: &HookConfig,
+        subject: Option<&str>,
+        files: Option<&[String]>,
+        dry_run: bool,
+    ) -> GitResult {
+        let start = Instant::now();
+        let rules = &config.skip;
+        let skip_reason = match files {
+            Some(_) => None,
+            None => skip_reason(rules, subject)?,
+        };
+
+        if let Some(reason) = &skip_reason {
+            Print::stderr_purple(&format!(
+                "{} {} because {reason}",
+                if rules.warn {
+                    "Only warning about"
+                } else {
+                    "Skipping"
+                },
+                match rules.checks.is_empty() {
+                    true => "the pre-commit checks".to_string(),
+                    false => rules.checks.join(", "),
+                }
+            ));
+        }
+
+        // a skipped check that's softened still runs, but can't fail
+        let softened = |check: &Check| skip_reason.is_some() && rules.applies_to(check.name());
+
+        // without a signature, nothing is cached
+        let signature = match files.is_some() || dry_run {
+            true => None,
+            false => cache_signature(config)
+                .map_err(|e| debug!("not caching the checks: {e}"))
+                .ok(),
+        };
+        let cache_key =
+            |check: &Check, root: &Path| format!("hook-check {} {}", root.display(), check.name());
+
+        let checks: Vec<Check> = [
+            Check::ProtectedBranch,
+            Check::UserEmail,
+            Check::DisallowedStrings,
+            Check::LfsBlobs,
+        ]
+        .into_iter()
+        .chain(config.checks.iter().map(Check::External))
+        .collect();
+        // the external checks run against what's staged, not the work tree, which may have other changes
+        let snapshot = match files.is_none() && !config.checks.is_empty() {
+            true => Some(IndexSnapshot::create()?),
+            false => None,
+        };
+        let snapshot_root = snapshot.as_ref().map(|snapshot| snapshot.path.as_path());
+
+        let reports = Tasks::run_parallel(&checks, MAX_PARALLEL_CHECKS, |check| {
+            let mut report = Report::default();
+            if !rules.warn && softened(check) {
+                report.skipped = skip_reason.clone();
+                return report;
+            }
+
+            if let Some((root, signature)) = &signature {
+                if Cache::get(&cache_key(check, root), signature).is_some() {
+                    report.cached = true;
+                    return report;
+                }
+            }
+
+            let start = Instant::now();
+            if let Err(e) = check.run(config, files, snapshot_root, &mut report) {
+                report.failure = Some(e);
+            }
+            report.duration = start.elapsed();
+
+            // only a silent pass is cached, so that replaying it doesn't lose any warnings
+            if let Some((root, signature)) = &signature {
+                if report.is_silent_pass() {
+                    if let Err(e) = Cache::put(&cache_key(check, root), signature, &[]) {
+                        debug!("failed to cache {}: {e}", check.name());
                     }
                 }
-                Err(err) => return get_env_var_error(&GitEnvVars::AuthorEmail.to_string(), &err),
-            },
-            Err(err) => return get_env_var_error(&GitUtilEnvVars::UserEmail, &err),
-        }
-
-        match env::var(String::from(GitUtilEnvVars::DisallowedStrings)) {
-            Ok(disallowed_strings) => {
-                debug!(
-                    "{}=\"{}\"",
-                    GitUtilEnvVars::DisallowedStrings,
-                    disallowed_strings
-                );
-
-                // get diff for impending commit
-                let diff_changes_output: std::process::Output = GitCommand {
-                    subcommand: "diff-index",
-                    default_args: &["--patch", "--find-renames", "--cached", "HEAD"],
-                    user_args: &[],
+            }
+
+            report
+        });
+
+        let mut failures = Vec::new();
+        let mut runs = Vec::new();
+
+        for (check, report) in checks.iter().zip(reports) {
+            debug!("{check:?} passed: {}", report.failure.is_none());
+
+            for message in &report.messages {
+                Print::stderr_purple(message);
+            }
+            io::stdout().write_all(&report.stdout)?;
+            io::stderr().write_all(&report.stderr)?;
+
+            let output = [
+                report.messages.join("\n"),
+                String::from_utf8_lossy(&report.stdout).into_owned(),
+                String::from_utf8_lossy(&report.stderr).into_owned(),
+            ]
+            .into_iter()
+            .filter(|output| !output.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+            let (status, message) = match report.failure {
+                Some(failure) if softened(check) => {
+                    Print::stderr_purple(&format!("Warning: {failure}"));
+                    (Status::Warned, Some(failure.to_string()))
+                }
+                Some(failure) => {
+                    let message = failure.to_string();
+                    failures.push(failure);
+                    (Status::Failed, Some(message))
                 }
-                .construct_git_command()
-                .output()
-                .with_context(|| "Failed to execute 'git diff-index' command")?;
+                None if report.skipped.is_some() => (Status::Skipped, report.skipped),
+                None if report.cached => (
+                    Status::Passed,
+                    Some("cached: passed for the same staged changes".to_string()),
+                ),
+                None => (Status::Passed, None),
+            };
+
+            runs.push(CheckRun {
+                name: check.name().to_string(),
+                status,
+                duration: report.duration,
+                message,
+                output,
+            });
+        }
 
-                if diff_changes_output.status.success() {
-                    let stdout = String::from_utf8(diff_changes_output.stdout)?;
-                    let stdout = stdout.lines();
+        let run = HookRun::new(hook, start.elapsed(), skip_reason, runs);
+        eprint!("{}", run.summary());
 
-                    let re = Regex::new(format!("(?i){}", disallowed_strings.as_str()).as_str())
-                        .unwrap();
+        if dry_run {
+            let blocking: Vec<&str> = run
+                .checks
+                .iter()
+                .filter(|check| check.status == Status::Failed)
+                .map(|check| check.name.as_str())
+                .collect();
 
-                    debug!("{:#?}", re);
+            if blocking.is_empty() {
+                Print::info("No check would block a commit");
+                return Ok(GitCommandResult::Success);
+            }
 
-                    // filter down to code additions only
-                    for line in stdout.filter(|line| line.starts_with('+')) {
-                        if re.is_match(line) {
-                            Print::stderr_purple(&format!("Disallowed addition:\n\n{line}"));
+            for failure in &failures {
+                Print::error(&failure.to_string());
+            }
+            Print::error(&format!(
+                "These checks would block a commit: {}",
+                blocking.join(", ")
+            ));
+            return Ok(GitCommandResult::Error);
+        }
 
-                            return Err(anyhow!("Disallowed string found in commit changes!"));
-                        }
-                    }
-                    debug!("No disallowed changes found");
-                } else {
-                    io::stdout().write_all(&diff_changes_output.stdout)?
+        // the report is only for reviewing the run later, so failing to save it mustn't fail the commit
+        if let Err(e) = run.save() {
+            Print::stderr_purple(&format!("Warning: {e}"));
+        }
+
+        match failures.len() {
+            0 => Ok(GitCommandResult::Success),
+            1 => Err(failures.remove(0)),
+            count => {
+                for failure in &failures {
+                    Print::error(&failure.to_string());
                 }
+                Err(anyhow!("{} pre-commit checks failed", count))
+            }
+        }
+    }
+}
+
+impl<'a> Check<'a> {
+    /// The name of the check in the hook configuration.
+    fn name(&self) -> &'a str {
+        match self {
+            Check::UserEmail => CHECK_NAMES[0],
+            Check::DisallowedStrings => CHECK_NAMES[1],
+            Check::LfsBlobs => CHECK_NAMES[2],
+            Check::ProtectedBranch => CHECK_NAMES[3],
+            Check::External(check) => &check.name,
+        }
+    }
 
-                io::stderr().write_all(&diff_changes_output.stderr)?;
+    /// Run the check against the staged changes, or `files` if they're given; an external check runs in `snapshot` (see
+    /// `IndexSnapshot`) if it's given.
+    fn run(
+        &self,
+        config: &HookConfig,
+        files: Option<&[String]>,
+        snapshot: Option<&Path>,
+        report: &mut Report,
+    ) -> anyhow::Result<()> {
+        match (self, files) {
+            (Check::ProtectedBranch, None) => check_protected_branch(config, report),
+            (Check::UserEmail, None) => check_user_email(config, report),
+            (Check::ProtectedBranch | Check::UserEmail, Some(_)) => {
+                report.skipped = Some("only checked for commits".to_string());
+                Ok(())
             }
-            Err(err) => {
-                if err.to_string() == "environment variable not found" {
-                    debug!(
-                        "{} not found; skipping check",
-                        GitUtilEnvVars::DisallowedStrings
-                    )
-                } else {
-                    // env var exists, but there's some other problem with it
-                    return get_env_var_error(&GitUtilEnvVars::DisallowedStrings, &err);
+            (Check::DisallowedStrings, files) => check_disallowed_strings(config, files, report),
+            (Check::LfsBlobs, files) => check_lfs_blobs(files, report),
+            (Check::External(check), files) => check_external(check, files, snapshot, report),
+        }
+    }
+}
+
+/// Fails if the commit is being made on a protected branch (if `protected_branches` is set), unless
+/// `$GIT_UTIL_ALLOW_PROTECTED` is set.
+fn check_protected_branch(config: &HookConfig, report: &mut Report) -> anyhow::Result<()> {
+    if config.protected_branches.is_none() {
+        debug!("protected_branches not configured; skipping check");
+        report.skipped = Some("not configured".to_string());
+        return Ok(());
+    }
+
+    // a detached `HEAD` isn't on any branch
+    let Ok(branch) = Git::current_branch() else {
+        return Ok(());
+    };
+    let Some(reason) = config.protection(&branch)? else {
+        return Ok(());
+    };
+
+    let allowed = match env::var(String::from(GitUtilEnvVars::AllowProtected)) {
+        Ok(value) => settings::parse_bool(&value)?,
+        Err(VarError::NotPresent) => false,
+        Err(err) => return Err(get_env_var_error(&GitUtilEnvVars::AllowProtected, &err)),
+    };
+    if allowed {
+        report.messages.push(format!(
+            "Warning: committing to the protected branch {branch}, since {} is set",
+            GitUtilEnvVars::AllowProtected
+        ));
+        return Ok(());
+    }
+
+    Err(anyhow!(
+        "{} is protected, since {}; create a branch for the commit with `git switch -c NAME` (or set {}=1 in an emergency)",
+        branch,
+        reason,
+        GitUtilEnvVars::AllowProtected
+    ))
+}
+
+/// Fails if the commit email isn't the configured `user_email`, or if there's none; `"*"` allows any email, which is how
+/// the check is turned off.
+fn check_user_email(config: &HookConfig, report: &mut Report) -> anyhow::Result<()> {
+    let allowed_email = match setting(GitUtilEnvVars::UserEmail, &config.user_email)? {
+        Some(email) if email == "*" => {
+            debug!("any commit email is allowed; skipping check");
+            report.skipped = Some("any email is allowed".to_string());
+            return Ok(());
+        }
+        Some(email) => email,
+        None => {
+            return Err(anyhow!(
+                "No commit email is configured; set {} or `user_email` in the hook configuration (\"*\" allows any email)",
+                GitUtilEnvVars::UserEmail
+            ))
+        }
+    };
+
+    let commit_email = match env::var(String::from(GitEnvVars::AuthorEmail)) {
+        Ok(commit_email) => commit_email,
+        // outside of a commit, e.g. with `--dry-run`
+        Err(VarError::NotPresent) => author_email().with_context(|| {
+            format!(
+                "{} isn't set, and the author of a commit can't be determined",
+                GitEnvVars::AuthorEmail
+            )
+        })?,
+        Err(err) => {
+            return Err(get_env_var_error(
+                &GitEnvVars::AuthorEmail.to_string(),
+                &err,
+            ))
+        }
+    };
+
+    if commit_email != allowed_email {
+        return Err(anyhow!(
+            "Invalid commit email; {} value is \"{}\". Expected: \"{}\"",
+            GitEnvVars::AuthorEmail,
+            commit_email,
+            allowed_email
+        ));
+    }
+
+    Ok(())
+}
+
+/// The email of the author that a commit would have.
+///
+/// `git var GIT_AUTHOR_IDENT`, which is `NAME <EMAIL> TIMESTAMP OFFSET`
+fn author_email() -> anyhow::Result<String> {
+    let ident = GitCommand::new("var")
+        .with_default_args(&["GIT_AUTHOR_IDENT"])
+        .stdout()?;
+
+    ident
+        .split_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(email, _)| email.to_string())
+        .ok_or_else(|| anyhow!("Unexpected author identity: {}", ident))
+}
+
+/// Fails if an added line in the staged diff (or any line of `files`) matches the configured `disallowed_strings` (if
+/// it's set).
+fn check_disallowed_strings(
+    config: &HookConfig,
+    files: Option<&[String]>,
+    report: &mut Report,
+) -> anyhow::Result<()> {
+    let Some(disallowed_strings) = setting(
+        GitUtilEnvVars::DisallowedStrings,
+        &config.disallowed_strings,
+    )?
+    else {
+        debug!(
+            "{} not configured; skipping check",
+            GitUtilEnvVars::DisallowedStrings
+        );
+        report.skipped = Some("not configured".to_string());
+        return Ok(());
+    };
+
+    debug!(
+        "{}=\"{}\"",
+        GitUtilEnvVars::DisallowedStrings,
+        disallowed_strings
+    );
+
+    if let Some(files) = files {
+        let re = Regex::new(&format!("(?i){disallowed_strings}"))
+            .with_context(|| format!("Invalid {}", GitUtilEnvVars::DisallowedStrings))?;
+        return check_files_for_disallowed_strings(&re, files, report);
+    }
+
+    // get diff for impending commit
+    let diff_changes_output: std::process::Output = Commands::output_with_timeout(
+        &mut GitCommand {
+            subcommand: "diff-index",
+            default_args: &["--patch", "--find-renames", "--cached", "HEAD"],
+            user_args: &[],
+        }
+        .construct_git_command(),
+    )
+    .with_context(|| "Failed to execute 'git diff-index' command")?;
+
+    if diff_changes_output.status.success() {
+        let stdout = String::from_utf8(diff_changes_output.stdout)?;
+        let stdout = stdout.lines();
+
+        let re = Regex::new(format!("(?i){}", disallowed_strings.as_str()).as_str()).unwrap();
+
+        debug!("{:#?}", re);
+
+        // filter down to code additions only
+        for line in stdout.filter(|line| line.starts_with('+')) {
+            if re.is_match(line) {
+                report
+                    .messages
+                    .push(format!("Disallowed addition:\n\n{line}"));
+
+                return Err(anyhow!("Disallowed string found in commit changes!"));
+            }
+        }
+        debug!("No disallowed changes found");
+    } else {
+        report.stdout = diff_changes_output.stdout;
+    }
+
+    report.stderr = diff_changes_output.stderr;
+
+    Ok(())
+}
+
+/// Fails if any line of `files` matches `re`, listing every line that does.
+///
+/// Files that aren't text aren't checked.
+fn check_files_for_disallowed_strings(
+    re: &Regex,
+    files: &[String],
+    report: &mut Report,
+) -> anyhow::Result<()> {
+    let root = Git::repo_root()?;
+    let mut matches = Vec::new();
+
+    for file in files {
+        let Ok(contents) = fs::read_to_string(root.join(file)) else {
+            debug!("not checking {file}");
+            continue;
+        };
+
+        for (i, line) in contents.lines().enumerate() {
+            if re.is_match(line) {
+                matches.push(format!("{file}:{}: {line}", i + 1));
+            }
+        }
+    }
+
+    if matches.is_empty() {
+        debug!("No disallowed strings found");
+        return Ok(());
+    }
+
+    report
+        .messages
+        .push(format!("Disallowed strings:\n\n  {}", matches.join("\n  ")));
+
+    Err(anyhow!(
+        "Disallowed string found in {} line{}!",
+        matches.len(),
+        if matches.len() == 1 { "" } else { "s" }
+    ))
+}
+
+/// Warns about staged files (or `files`) that match a Git LFS pattern but are in the index as regular blobs; never
+/// fails, even if the files can't be inspected.
+fn check_lfs_blobs(files: Option<&[String]>, report: &mut Report) -> anyhow::Result<()> {
+    let regular_blobs = match files {
+        Some(files) => Lfs::regular_blobs(&files.iter().cloned().collect()),
+        None => Lfs::staged_regular_blobs(),
+    };
+    let regular_blobs = match regular_blobs {
+        Ok(regular_blobs) => regular_blobs,
+        Err(e) => {
+            report.messages.push(format!(
+                "Warning: couldn't check the files for Git LFS patterns: {e}"
+            ));
+            return Ok(());
+        }
+    };
+    if !regular_blobs.is_empty() {
+        report.messages.push(format!(
+            "Warning: these files match a Git LFS pattern, but are {} as regular blobs (is git-lfs installed?):\n\n  {}",
+            if files.is_some() { "in the index" } else { "staged" },
+            regular_blobs.join("\n  ")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Runs the command of an external check, which fails if it exits with a non-zero status.
+///
+/// The command runs in `snapshot` (the staged files), if it's given, else in the work tree. `{staged_files}` is replaced
+/// with `files` if they're given (see `command_args()`). The command runs concurrently with the other checks, so it
+/// shouldn't modify the files.
+fn check_external(
+    check: &ExternalCheck,
+    files: Option<&[String]>,
+    snapshot: Option<&Path>,
+    report: &mut Report,
+) -> anyhow::Result<()> {
+    let root = match snapshot {
+        Some(snapshot) => snapshot.to_path_buf(),
+        None => Git::repo_root()?,
+    };
+    let root_arg = root.to_string_lossy();
+
+    let staged_files = match check
+        .command
+        .iter()
+        .any(|arg| arg.contains(STAGED_FILES_PLACEHOLDER))
+    {
+        true => {
+            let staged_files = match files {
+                Some(files) => files.to_vec(),
+                None => staged_files()?,
+            };
+            if staged_files.is_empty() {
+                report.skipped = Some("no staged files".to_string());
+                return Ok(());
+            }
+            staged_files
+        }
+        false => Vec::new(),
+    };
+
+    let args = command_args(&check.command, &root_arg, &staged_files);
+
+    let (program, args) = args
+        .split_first()
+        .ok_or_else(|| anyhow!("The check {} has no command", check.name))?;
+
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .current_dir(match &check.working_directory {
+            Some(directory) => root.join(directory),
+            None => root.clone(),
+        })
+        .envs(
+            check
+                .env
+                .iter()
+                .map(|(key, value)| (key, value.replace(ROOT_PLACEHOLDER, &root_arg))),
+        );
+    debug!("{command:?}");
+
+    let output = Commands::output_with_timeout(&mut command)
+        .with_context(|| format!("Failed to run the check {} ({})", check.name, program))?;
+    let exit_error = || anyhow!("The check {} failed ({})", check.name, output.status);
+
+    report.stderr = output.stderr.clone();
+
+    if !check.json_output {
+        report.stdout = output.stdout;
+        return match output.status.success() {
+            true => Ok(()),
+            false => Err(exit_error()),
+        };
+    }
+
+    let problems = json_problems(&output.stdout)
+        .with_context(|| format!("The check {} printed invalid JSON", check.name))?;
+    report.messages.extend(problems.iter().cloned());
+
+    match (output.status.success(), problems.first()) {
+        (true, _) => Ok(()),
+        (false, None) => Err(exit_error()),
+        (false, Some(first)) => Err(anyhow!(
+            "{}: {}{}",
+            check.name,
+            first,
+            match problems.len() {
+                1 => String::new(),
+                count => format!(" (and {} more)", count - 1),
+            }
+        )),
+    }
+}
+
+/// The arguments of an external check's `command`, with `{root}` replaced with `root`, and each argument that contains
+/// `{staged_files}` repeated for each of `staged_files`, e.g. `--file={staged_files}` becomes `--file=a --file=b`.
+///
+/// The files aren't joined into one argument, since there's no telling how the command would split it.
+fn command_args(command: &[String], root: &str, staged_files: &[String]) -> Vec<String> {
+    let mut args = Vec::new();
+
+    for arg in command {
+        let arg = arg.replace(ROOT_PLACEHOLDER, root);
+
+        if arg.contains(STAGED_FILES_PLACEHOLDER) {
+            args.extend(
+                staged_files
+                    .iter()
+                    .map(|file| arg.replace(STAGED_FILES_PLACEHOLDER, file)),
+            );
+        } else {
+            args.push(arg);
+        }
+    }
+
+    args
+}
+
+/// A copy of the files in the staging area, in a temporary directory, for the external checks to run against, so that
+/// they check what's being committed, rather than the work tree. The directory is removed when it's dropped.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct IndexSnapshot {
+    path: PathBuf,
+}
+
+impl IndexSnapshot {
+    /// `git checkout-index --all --prefix=DIRECTORY/`, from the root of the work tree
+    fn create() -> anyhow::Result<IndexSnapshot> {
+        let snapshot = IndexSnapshot {
+            path: env::temp_dir().join(format!("git-util-index-{}", std::process::id())),
+        };
+        debug!(
+            "checking out the staged files to {}",
+            snapshot.path.display()
+        );
+
+        // `--all` only checks out the files under the current directory
+        let prefix = format!("--prefix={}/", snapshot.path.to_string_lossy());
+        match Commands::output_with_timeout(
+            Commands::new_command_with_args("git", &["checkout-index", "--all", &prefix])
+                .current_dir(Git::repo_root()?),
+        )? {
+            output if output.status.success() => Ok(snapshot),
+            output => Err(anyhow!(
+                "Failed to check out the staged files for the external checks: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        }
+    }
+}
+
+impl Drop for IndexSnapshot {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.path) {
+            debug!("failed to remove {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// The problems in the JSON that an external check with `output = "json"` printed, as `FILE:LINE: MESSAGE`.
+///
+/// The JSON is an object with an array of `messages`, each of which is an object with a `message` and optionally a
+/// `file` and `line`; nothing at all means no problems.
+fn json_problems(stdout: &[u8]) -> anyhow::Result<Vec<String>> {
+    let stdout = String::from_utf8_lossy(stdout);
+    if stdout.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let json = json::Value::parse(&stdout)?;
+    let messages = json
+        .get("messages")
+        .and_then(json::Value::as_array)
+        .ok_or_else(|| anyhow!("expected an object with an array of \"messages\""))?;
+
+    messages
+        .iter()
+        .map(|problem| {
+            let message = problem
+                .get("message")
+                .and_then(json::Value::as_str)
+                .ok_or_else(|| {
+                    anyhow!("expected each of the \"messages\" to have a \"message\"")
+                })?;
+
+            let file = problem.get("file").and_then(json::Value::as_str);
+            let line = problem.get("line").and_then(json::Value::as_i64);
+
+            Ok(match (file, line) {
+                (Some(file), Some(line)) => format!("{file}:{line}: {message}"),
+                (Some(file), None) => format!("{file}: {message}"),
+                _ => message.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The paths (relative to the root of the work tree) of the added, copied, modified, and renamed files that are staged.
+///
+/// `git diff --cached --name-only --diff-filter=ACMR -z`
+fn staged_files() -> anyhow::Result<Vec<String>> {
+    Ok(GitCommand::new("diff")
+        .with_default_args(&["--cached", "--name-only", "--diff-filter=ACMR", "-z"])
+        .stdout()?
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// The paths (relative to the root of the work tree) of the files under `paths` that are tracked, or untracked but
+/// not ignored; every tracked file if `paths` is empty. Files that are missing from the work tree are left out.
+///
+/// `git ls-files --full-name -z [--cached --others --exclude-standard] -- PATHS`
+fn files(paths: &[PathBuf]) -> anyhow::Result<Vec<String>> {
+    let root = Git::repo_root()?;
+
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    let mut args = vec!["--full-name", "-z"];
+    match paths.is_empty() {
+        // the whole work tree, even from a subdirectory
+        true => args.extend(["--", ":(top)"]),
+        false => {
+            args.extend(["--cached", "--others", "--exclude-standard", "--"]);
+            args.extend(paths.iter().map(String::as_str));
+        }
+    }
+
+    let files: BTreeSet<String> = GitCommand::new("ls-files")
+        .with_default_args(&args)
+        .stdout()?
+        .split('\0')
+        .filter(|path| !path.is_empty() && root.join(path).is_file())
+        .map(String::from)
+        .collect();
+
+    Ok(files.into_iter().collect())
+}
+
+/// Why the `[skip]` rules match the commit being made, if they do.
+fn skip_reason(rules: &SkipRules, subject: Option<&str>) -> anyhow::Result<Option<String>> {
+    if let Some(prefix) = subject.and_then(|subject| {
+        rules
+            .subjects
+            .iter()
+            .find(|prefix| subject.starts_with(prefix.as_str()))
+    }) {
+        return Ok(Some(format!("the subject starts with \"{prefix}\"")));
+    }
+
+    if rules.merges && Git::git_path("MERGE_HEAD")?.exists() {
+        return Ok(Some("it's a merge commit".to_string()));
+    }
+
+    if !rules.branches.is_empty() {
+        // a detached `HEAD` isn't on any branch to match
+        if let Ok(branch) = Git::current_branch() {
+            for pattern in &rules.branches {
+                if glob_matches(pattern, &branch)? {
+                    return Ok(Some(format!("the branch {branch} matches \"{pattern}\"")));
                 }
             }
         }
+    }
+
+    Ok(None)
+}
+
+/// The root of the repository and a signature of everything that the checks read: the staged tree, `HEAD`, the current
+/// branch, the hook configuration, and the environment variables that override it or are checked.
+///
+/// A check that passed is skipped while the signature is unchanged, e.g. when retrying a commit that failed another
+/// check without changing what's staged.
+fn cache_signature(config: &HookConfig) -> anyhow::Result<(PathBuf, String)> {
+    let tree = GitCommand::new("write-tree").stdout()?;
+    // there's no `HEAD` before the first commit
+    let head = GitCommand::new("rev-parse")
+        .with_default_args(&["--verify", "--quiet", "HEAD"])
+        .stdout()
+        .unwrap_or_default();
+
+    let env_vars = [
+        String::from(GitUtilEnvVars::AllowProtected),
+        String::from(GitUtilEnvVars::UserEmail),
+        String::from(GitUtilEnvVars::DisallowedStrings),
+        String::from(GitEnvVars::AuthorEmail),
+    ]
+    .map(|env_var| env::var(env_var).ok());
 
-        Ok(GitCommandResult::Success)
+    let mut hasher = DefaultHasher::new();
+    format!("{config:?}").hash(&mut hasher);
+    env_vars.hash(&mut hasher);
+    Git::current_branch().ok().hash(&mut hasher);
+
+    Ok((
+        Git::repo_root()?,
+        format!("{tree} {head} {:016x}", hasher.finish()),
+    ))
+}
+
+/// The value of `env_var` if it's set, else `configured`.
+fn setting(env_var: GitUtilEnvVars, configured: &Option<String>) -> anyhow::Result<Option<String>> {
+    match env::var(String::from(env_var)) {
+        Ok(value) => Ok(Some(value)),
+        Err(VarError::NotPresent) => Ok(configured.clone()),
+        // env var exists, but there's some other problem with it
+        Err(err) => Err(get_env_var_error(&env_var, &err)),
     }
 }
 
 /// Add detail to `&VarError` returned from `std::env::var` call.
-fn get_env_var_error<T: Display>(env_var: &T, err: &VarError) -> GitResult {
-    Err(anyhow!("failed to get env variable {}: {}", env_var, err))
+fn get_env_var_error<T: Display>(env_var: &T, err: &VarError) -> anyhow::Error {
+    anyhow!("failed to get env variable {}: {}", env_var, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn command_args_expands_staged_files_per_file() {
+        let command = strings(&[
+            "lint",
+            "--config={root}/.lint",
+            "{staged_files}",
+            "--file={staged_files}",
+        ]);
+        let staged_files = strings(&["a b.js", "c.js"]);
+
+        assert_eq!(
+            command_args(&command, "/repo", &staged_files),
+            strings(&[
+                "lint",
+                "--config=/repo/.lint",
+                "a b.js",
+                "c.js",
+                "--file=a b.js",
+                "--file=c.js",
+            ])
+        );
+    }
+
+    #[test]
+    fn command_args_without_staged_files() {
+        let command = strings(&["make", "-C", "{root}", "check"]);
+
+        assert_eq!(
+            command_args(&command, "/repo", &[]),
+            strings(&["make", "-C", "/repo", "check"])
+        );
+    }
 }
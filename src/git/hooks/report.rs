@@ -0,0 +1,302 @@
+use crate::{
+    git::{Git, GitCommandResult, GitResult},
+    json::Value,
+    output::OutputTarget,
+    table::Table,
+};
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs,
+    io::Write,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The report of the last hook run, relative to the `.git` directory.
+const LAST_RUN: &str = "git-wrapper/last-hook-run.json";
+
+/// How a check fared in a hook run.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Status {
+    Passed,
+    Failed,
+    /// It failed, but the `[skip]` rules softened the failure to a warning.
+    Warned,
+    /// It didn't run, because the `[skip]` rules matched or it isn't configured.
+    Skipped,
+}
+
+/// A check in a hook run.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct CheckRun {
+    pub name: String,
+    pub status: Status,
+    pub duration: Duration,
+    /// Why the check failed (or was skipped).
+    pub message: Option<String>,
+    /// Everything the check printed.
+    pub output: String,
+}
+
+/// A run of the pre-commit checks, saved to `LAST_RUN` so that `git-util hook report` can show it again after the
+/// commit's output has scrolled away.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct HookRun {
+    /// The hook the checks ran in: `pre-commit` or `commit-msg`.
+    pub hook: String,
+    /// When the run finished, in seconds since the Unix epoch.
+    pub finished_at: u64,
+    pub duration: Duration,
+    /// Why the `[skip]` rules matched the commit, if they did.
+    pub skipped_because: Option<String>,
+    pub checks: Vec<CheckRun>,
+}
+
+impl Status {
+    fn from_name(name: &str) -> Option<Status> {
+        [
+            Status::Passed,
+            Status::Failed,
+            Status::Warned,
+            Status::Skipped,
+        ]
+        .into_iter()
+        .find(|status| status.to_string() == name)
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Status::Passed => "passed",
+                Status::Failed => "failed",
+                Status::Warned => "warned",
+                Status::Skipped => "skipped",
+            }
+        )
+    }
+}
+
+impl HookRun {
+    /// A run of `hook` that finished just now.
+    pub fn new(
+        hook: &str,
+        duration: Duration,
+        skipped_because: Option<String>,
+        checks: Vec<CheckRun>,
+    ) -> HookRun {
+        HookRun {
+            hook: hook.to_string(),
+            finished_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            duration,
+            skipped_because,
+            checks,
+        }
+    }
+
+    /// Whether none of the checks failed.
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != Status::Failed)
+    }
+
+    /// A row for each check, with its status, duration, and the first line of its message.
+    pub fn summary(&self) -> Table {
+        let mut table = Table::new();
+        table.add_row(["CHECK", "STATUS", "DURATION", "DETAIL"]);
+
+        for check in &self.checks {
+            table.add_row([
+                check.name.clone(),
+                check.status.to_string(),
+                match check.status {
+                    Status::Skipped => "-".to_string(),
+                    _ => format!("{}ms", check.duration.as_millis()),
+                },
+                check
+                    .message
+                    .as_deref()
+                    .and_then(|message| message.lines().next())
+                    .unwrap_or_default()
+                    .to_string(),
+            ]);
+        }
+
+        table
+    }
+
+    /// Save the run as the repository's last one.
+    pub fn save(&self) -> Result<()> {
+        let path = Git::git_path(LAST_RUN)?;
+        debug!("saving the hook run to {}", path.display());
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)
+                .with_context(|| format!("Failed to create {}", directory.display()))?;
+        }
+
+        fs::write(&path, format!("{}\n", self.to_json()))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// The repository's last run.
+    fn load() -> Result<HookRun> {
+        let path = Git::git_path(LAST_RUN)?;
+        if !path.is_file() {
+            return Err(anyhow!("The hooks haven't run in this repository yet"));
+        }
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let json = Value::parse(&contents)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+
+        HookRun::from_json(&json)
+            .ok_or_else(|| anyhow!("{} isn't a hook run report", path.display()))
+    }
+
+    /// Print the last run: the summary, then the message and output of each check that has them.
+    ///
+    /// Returns `Error` if a check failed.
+    pub fn report() -> GitResult {
+        let run = HookRun::load()?;
+        let mut writer = OutputTarget::writer()?;
+
+        let age = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            .saturating_sub(run.finished_at);
+        writeln!(
+            writer,
+            "The {} checks {} {} ago, in {}ms",
+            run.hook,
+            if run.passed() { "passed" } else { "failed" },
+            format_age(age),
+            run.duration.as_millis()
+        )?;
+        if let Some(reason) = &run.skipped_because {
+            writeln!(writer, "The [skip] rules matched because {reason}")?;
+        }
+        write!(writer, "\n{}", run.summary())?;
+
+        for check in &run.checks {
+            let details: Vec<&str> = [check.message.as_deref(), Some(check.output.as_str())]
+                .into_iter()
+                .flatten()
+                .map(str::trim_end)
+                .filter(|details| !details.is_empty())
+                .collect();
+            if details.is_empty() || check.status == Status::Skipped {
+                continue;
+            }
+
+            writeln!(writer, "\n{} ({}):", check.name, check.status)?;
+            for line in details.iter().flat_map(|details| details.lines()) {
+                match line.is_empty() {
+                    true => writeln!(writer)?,
+                    false => writeln!(writer, "  {line}")?,
+                }
+            }
+        }
+
+        Ok(match run.passed() {
+            true => GitCommandResult::Success,
+            false => GitCommandResult::Error,
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        let checks = self
+            .checks
+            .iter()
+            .map(|check| {
+                Value::Object(BTreeMap::from([
+                    ("name".to_string(), check.name.as_str().into()),
+                    (
+                        "status".to_string(),
+                        check.status.to_string().as_str().into(),
+                    ),
+                    ("duration_ms".to_string(), millis(check.duration)),
+                    ("message".to_string(), check.message.as_deref().into()),
+                    ("output".to_string(), check.output.as_str().into()),
+                ]))
+            })
+            .collect();
+
+        Value::Object(BTreeMap::from([
+            ("hook".to_string(), self.hook.as_str().into()),
+            (
+                "finished_at".to_string(),
+                Value::Integer(self.finished_at as i64),
+            ),
+            ("duration_ms".to_string(), millis(self.duration)),
+            (
+                "result".to_string(),
+                if self.passed() { "passed" } else { "failed" }.into(),
+            ),
+            (
+                "skipped_because".to_string(),
+                self.skipped_because.as_deref().into(),
+            ),
+            ("checks".to_string(), Value::Array(checks)),
+        ]))
+    }
+
+    fn from_json(json: &Value) -> Option<HookRun> {
+        let duration = |json: &Value| {
+            Some(Duration::from_millis(
+                json.get("duration_ms")?.as_i64()?.try_into().ok()?,
+            ))
+        };
+        let optional = |json: &Value, key: &str| json.get(key)?.as_str().map(String::from);
+
+        let checks = json
+            .get("checks")?
+            .as_array()?
+            .iter()
+            .map(|check| {
+                Some(CheckRun {
+                    name: check.get("name")?.as_str()?.to_string(),
+                    status: Status::from_name(check.get("status")?.as_str()?)?,
+                    duration: duration(check)?,
+                    message: optional(check, "message"),
+                    output: optional(check, "output").unwrap_or_default(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(HookRun {
+            hook: json.get("hook")?.as_str()?.to_string(),
+            finished_at: json.get("finished_at")?.as_i64()?.try_into().ok()?,
+            duration: duration(json)?,
+            skipped_because: optional(json, "skipped_because"),
+            checks,
+        })
+    }
+}
+
+fn millis(duration: Duration) -> Value {
+    Value::Integer(duration.as_millis().try_into().unwrap_or(i64::MAX))
+}
+
+/// `seconds` in its largest whole unit, e.g. `5 minutes`.
+pub fn format_age(seconds: u64) -> String {
+    let (count, unit) = match seconds {
+        0..=59 => (seconds, "second"),
+        60..=3599 => (seconds / 60, "minute"),
+        3600..=86399 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+
+    format!("{count} {unit}{}", if count == 1 { "" } else { "s" })
+}
@@ -0,0 +1,220 @@
+use crate::git::{Git, GitCommand};
+use anyhow::{anyhow, Result};
+use core::fmt;
+use log::{debug, trace};
+
+/// How a file ended up with a merge conflict, based on the `XY` code from `git status --porcelain`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum ConflictKind {
+    AddedByThem,
+    AddedByUs,
+    BothAdded,
+    BothDeleted,
+    BothModified,
+    DeletedByThem,
+    DeletedByUs,
+}
+
+/// A file with an unresolved merge conflict.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    /// The path of the file, relative to the root of the repository.
+    pub path: String,
+}
+
+impl ConflictKind {
+    fn from_status(xy: &str) -> Option<ConflictKind> {
+        match xy {
+            "DD" => Some(ConflictKind::BothDeleted),
+            "AU" => Some(ConflictKind::AddedByUs),
+            "UD" => Some(ConflictKind::DeletedByThem),
+            "UA" => Some(ConflictKind::AddedByThem),
+            "DU" => Some(ConflictKind::DeletedByUs),
+            "AA" => Some(ConflictKind::BothAdded),
+            "UU" => Some(ConflictKind::BothModified),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConflictKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictKind::AddedByThem => write!(f, "added by them"),
+            ConflictKind::AddedByUs => write!(f, "added by us"),
+            ConflictKind::BothAdded => write!(f, "both added"),
+            ConflictKind::BothDeleted => write!(f, "both deleted"),
+            ConflictKind::BothModified => write!(f, "both modified"),
+            ConflictKind::DeletedByThem => write!(f, "deleted by them"),
+            ConflictKind::DeletedByUs => write!(f, "deleted by us"),
+        }
+    }
+}
+
+impl Conflict {
+    /// List the files that have unresolved merge conflicts.
+    ///
+    /// `git status --porcelain -z`
+    pub fn list() -> Result<Vec<Conflict>> {
+        trace!("list() called");
+
+        let status = GitCommand::new("status")
+            .with_default_args(&["--porcelain", "-z"])
+            .stdout()?;
+
+        let mut conflicts: Vec<Conflict> = Vec::new();
+        let mut entries = status.split('\0');
+
+        while let Some(entry) = entries.next() {
+            if entry.len() < 4 {
+                continue;
+            }
+
+            let (xy, path) = entry.split_at(2);
+
+            // renames and copies are followed by an extra entry with the original path
+            if xy.starts_with(['R', 'C']) {
+                entries.next();
+            }
+
+            if let Some(kind) = ConflictKind::from_status(xy) {
+                conflicts.push(Conflict {
+                    kind,
+                    path: path[1..].to_string(),
+                });
+            }
+        }
+
+        debug!("conflicts: {conflicts:#?}");
+
+        Ok(conflicts)
+    }
+}
+
+/// What `HEAD` points at.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Head();
+
+impl Head {
+    /// The abbreviated commit that `HEAD` is detached at, or `None` if it's on a branch (even one without commits).
+    ///
+    /// `git symbolic-ref --quiet HEAD`, which exits with 1 if `HEAD` is detached
+    pub fn detached() -> Result<Option<String>> {
+        trace!("detached() called");
+
+        let output = GitCommand::new("symbolic-ref")
+            .with_default_args(&["--quiet", "HEAD"])
+            .output()?;
+
+        let commit = match output.status.code() {
+            Some(0) => None,
+            Some(1) => Some(
+                GitCommand::new("rev-parse")
+                    .with_default_args(&["--short", "HEAD"])
+                    .stdout()?,
+            ),
+            _ => {
+                return Err(anyhow!(
+                    "Failed to resolve HEAD: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ))
+            }
+        };
+
+        debug!("detached HEAD: {commit:?}");
+
+        Ok(commit)
+    }
+}
+
+/// A multi-step **Git** operation that can be left in progress (e.g. because of conflicts).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Operation {
+    Am,
+    Bisect,
+    CherryPick,
+    Merge,
+    Rebase,
+    Revert,
+}
+
+impl Operation {
+    /// Detect which operation, if any, is currently in progress by inspecting the state files in the `.git` directory.
+    pub fn in_progress() -> Result<Option<Operation>> {
+        trace!("in_progress() called");
+
+        let operation = if Git::git_path("rebase-merge")?.is_dir() {
+            Some(Operation::Rebase)
+        } else if Git::git_path("rebase-apply")?.is_dir() {
+            // `git am` and the "apply" rebase backend share the same directory
+            if Git::git_path("rebase-apply/applying")?.exists() {
+                Some(Operation::Am)
+            } else {
+                Some(Operation::Rebase)
+            }
+        } else if Git::git_path("MERGE_HEAD")?.exists() {
+            Some(Operation::Merge)
+        } else if Git::git_path("CHERRY_PICK_HEAD")?.exists() {
+            Some(Operation::CherryPick)
+        } else if Git::git_path("REVERT_HEAD")?.exists() {
+            Some(Operation::Revert)
+        } else if Git::git_path("BISECT_LOG")?.exists() {
+            Some(Operation::Bisect)
+        } else {
+            None
+        };
+
+        debug!("operation in progress: {operation:?}");
+
+        Ok(operation)
+    }
+
+    /// The **Git Subcommand** that drives the operation.
+    pub fn subcommand(&self) -> &'static str {
+        match self {
+            Operation::Am => "am",
+            Operation::Bisect => "bisect",
+            Operation::CherryPick => "cherry-pick",
+            Operation::Merge => "merge",
+            Operation::Rebase => "rebase",
+            Operation::Revert => "revert",
+        }
+    }
+
+    /// The **Git** command that cancels the operation and restores the pre-operation state.
+    pub fn abort_command(&self) -> GitCommand<'static> {
+        match self {
+            Operation::Bisect => GitCommand {
+                subcommand: "bisect",
+                default_args: &["reset"],
+                user_args: &[],
+            },
+            _ => GitCommand {
+                subcommand: self.subcommand(),
+                default_args: &["--abort"],
+                user_args: &[],
+            },
+        }
+    }
+
+    /// The **Git** command that resumes the operation after conflicts have been resolved.
+    ///
+    /// Returns `None` for `bisect`, which has no `--continue`.
+    pub fn continue_command(&self) -> Option<GitCommand<'static>> {
+        match self {
+            Operation::Bisect => None,
+            _ => Some(GitCommand {
+                subcommand: self.subcommand(),
+                default_args: &["--continue"],
+                user_args: &[],
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.subcommand())
+    }
+}
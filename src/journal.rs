@@ -0,0 +1,157 @@
+use crate::{json::Value, settings};
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// A record of the commits made in every repository, including the ones made without **git-util**, written as JSON
+/// lines (one per commit) to `journal.jsonl` in git-util's state directory by the `post-commit` hook.
+pub struct Journal();
+
+/// A commit in the `Journal`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct JournalEntry {
+    pub sha: String,
+    /// The root of the work tree that the commit was made in.
+    pub repository: PathBuf,
+    /// The branch that the commit was made on, unless `HEAD` was detached.
+    pub branch: Option<String>,
+    pub subject: String,
+    /// The number of files that the commit changed (compared to its first parent).
+    pub files_changed: usize,
+    /// When the commit was made, in seconds since the Unix epoch.
+    pub timestamp: i64,
+}
+
+impl Journal {
+    /// The journal file.
+    pub fn path() -> Result<PathBuf> {
+        Ok(settings::state_directory()?.join("journal.jsonl"))
+    }
+
+    /// Append `entry` to the journal, creating it if it doesn't exist.
+    pub fn append(entry: &JournalEntry) -> Result<()> {
+        let path = Journal::path()?;
+        debug!("appending to {}: {entry:?}", path.display());
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)
+                .with_context(|| format!("Failed to create {}", directory.display()))?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        writeln!(file, "{}", entry.to_json())
+            .with_context(|| format!("Failed to write to {}", path.display()))
+    }
+
+    /// The entries for the commits made at or after `since` (in seconds since the Unix epoch), in the order they were
+    /// journaled; lines that can't be parsed are skipped.
+    pub fn entries_since(since: i64) -> Result<Vec<JournalEntry>> {
+        let path = Journal::path()?;
+
+        let journal = match fs::read_to_string(&path) {
+            Ok(journal) => journal,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+
+        Ok(journal
+            .lines()
+            .filter_map(|line| {
+                JournalEntry::parse(line)
+                    .map_err(|e| debug!("skipping the journal line {line:?}: {e}"))
+                    .ok()
+            })
+            .filter(|entry| entry.timestamp >= since)
+            .collect())
+    }
+}
+
+impl JournalEntry {
+    /// Parse a line of the journal.
+    fn parse(line: &str) -> Result<JournalEntry> {
+        let json = Value::parse(line)?;
+        let string = |key: &str| {
+            json.get(key)
+                .and_then(Value::as_str)
+                .map(String::from)
+                .ok_or_else(|| anyhow!("'{}' is missing or isn't a string", key))
+        };
+        let integer = |key: &str| {
+            json.get(key)
+                .and_then(Value::as_i64)
+                .ok_or_else(|| anyhow!("'{}' is missing or isn't an integer", key))
+        };
+
+        Ok(JournalEntry {
+            sha: string("sha")?,
+            repository: PathBuf::from(string("repository")?),
+            branch: json.get("branch").and_then(Value::as_str).map(String::from),
+            subject: string("subject")?,
+            files_changed: integer("files_changed")?.try_into()?,
+            timestamp: integer("timestamp")?,
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        Value::Object(BTreeMap::from([
+            ("sha".to_string(), self.sha.as_str().into()),
+            ("repository".to_string(), path(&self.repository)),
+            ("branch".to_string(), self.branch.as_deref().into()),
+            ("subject".to_string(), self.subject.as_str().into()),
+            (
+                "files_changed".to_string(),
+                Value::Integer(self.files_changed.try_into().unwrap_or(i64::MAX)),
+            ),
+            ("timestamp".to_string(), Value::Integer(self.timestamp)),
+        ]))
+    }
+}
+
+fn path(path: &Path) -> Value {
+    path.to_string_lossy().as_ref().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_round_trips() {
+        let entry = JournalEntry {
+            sha: "0123456789abcdef".to_string(),
+            repository: PathBuf::from("/home/me/a \"repo\""),
+            branch: None,
+            subject: "fix: the\tbug".to_string(),
+            files_changed: 3,
+            timestamp: 1_700_000_000,
+        };
+
+        assert_eq!(
+            JournalEntry::parse(&entry.to_json().to_string()).unwrap(),
+            entry
+        );
+    }
+
+    #[test]
+    fn parse_rejects_incomplete_entries() {
+        assert_eq!(
+            JournalEntry::parse(
+                r#"{"sha": "abc", "repository": "/r", "subject": "s", "timestamp": 1}"#
+            )
+            .unwrap_err()
+            .to_string(),
+            "'files_changed' is missing or isn't an integer"
+        );
+        assert!(JournalEntry::parse("{\"sha\": ").is_err());
+    }
+}
@@ -0,0 +1,351 @@
+use crate::audit_log::json_string;
+use anyhow::{anyhow, Result};
+use std::{collections::BTreeMap, fmt::Display, iter::Peekable, str::Chars};
+
+/// How deeply arrays and objects may be nested, so that a malformed file can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// A JSON value.
+///
+/// Only the types that **git-util**'s files use are supported: numbers are integers.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    pub fn parse(text: &str) -> Result<Value> {
+        let mut parser = Parser {
+            chars: text.chars().peekable(),
+            line: 1,
+            depth: 0,
+        };
+
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        match parser.chars.peek().copied() {
+            None => Ok(value),
+            Some(c) => Err(parser.error(&format!("unexpected '{c}' after the value"))),
+        }
+    }
+
+    /// The value of `key`, if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+/// Compact JSON, without whitespace.
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Boolean(value) => write!(f, "{value}"),
+            Value::Integer(value) => write!(f, "{value}"),
+            Value::String(value) => write!(f, "{}", json_string(value)),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{value}", json_string(key))?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// The current line, for error messages.
+    line: usize,
+    /// The number of arrays and objects that the current value is in.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Value> {
+        self.skip_whitespace();
+
+        match self.chars.peek().copied() {
+            Some(c @ ('{' | '[')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error(&format!("nested more than {MAX_DEPTH} levels deep")));
+                }
+
+                self.depth += 1;
+                let value = if c == '{' {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some(_) => {
+                let mut word = String::new();
+                while let Some(&c) = self
+                    .chars
+                    .peek()
+                    .filter(|&&c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                {
+                    word.push(c);
+                    self.chars.next();
+                }
+
+                match word.as_str() {
+                    "null" => Ok(Value::Null),
+                    "true" => Ok(Value::Boolean(true)),
+                    "false" => Ok(Value::Boolean(false)),
+                    _ => word
+                        .parse()
+                        .map(Value::Integer)
+                        .map_err(|_| self.error(&format!("unsupported value '{word}'"))),
+                }
+            }
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    /// `{"KEY": VALUE, ...}`
+    fn object(&mut self) -> Result<Value> {
+        self.expect('{')?;
+        let mut entries = BTreeMap::new();
+
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.chars.next();
+            return Ok(Value::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            entries.insert(key, value);
+
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Object(entries)),
+                _ => return Err(self.error("expected ',' or '}' in an object")),
+            }
+        }
+    }
+
+    /// `[VALUE, ...]`
+    fn array(&mut self) -> Result<Value> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.chars.next();
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(values)),
+                _ => return Err(self.error("expected ',' or ']' in an array")),
+            }
+        }
+    }
+
+    /// `"..."`, with escapes.
+    fn string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut string = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.chars.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let digits: String = self.chars.by_ref().take(4).collect();
+
+                            // characters outside the BMP (as surrogate pairs) aren't supported
+                            u32::from_str_radix(&digits, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error(&format!("invalid escape \\u{digits}")))?
+                        }
+                        other => {
+                            return Err(
+                                self.error(&format!("invalid escape \\{}", other.unwrap_or(' ')))
+                            )
+                        }
+                    };
+                    string.push(escaped);
+                }
+                None => return Err(self.error("unterminated string")),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&c) = self.chars.peek().filter(|c| c.is_ascii_whitespace()) {
+            if c == '\n' {
+                self.line += 1;
+            }
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            None => Err(self.error(&format!("expected '{expected}'"))),
+            Some(c) => Err(self.error(&format!("expected '{expected}', but found '{c}'"))),
+        }
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        anyhow!("line {}: {}", self.line, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let value = Value::Object(BTreeMap::from([
+            ("null".to_string(), Value::Null),
+            ("flag".to_string(), Value::Boolean(true)),
+            ("count".to_string(), Value::Integer(-42)),
+            (
+                "items".to_string(),
+                Value::Array(vec![
+                    Value::from("a\"b\\c"),
+                    Value::from("line\nbreak\u{1}"),
+                ]),
+            ),
+            ("empty".to_string(), Value::Object(BTreeMap::new())),
+        ]));
+
+        assert_eq!(Value::parse(&value.to_string()).unwrap(), value);
+    }
+
+    #[test]
+    fn parse_with_whitespace() {
+        let value = Value::parse("{\n  \"a\": [1, 2 ],\n  \"b\" : null\n}\n").unwrap();
+
+        assert_eq!(
+            value.get("a").and_then(Value::as_array),
+            Some(&[Value::Integer(1), Value::Integer(2)][..])
+        );
+        assert_eq!(value.get("b"), Some(&Value::Null));
+    }
+
+    #[test]
+    fn parse_escapes() {
+        assert_eq!(
+            Value::parse(r#""\"\\\/\n\r\t\b\f\u00e9""#).unwrap(),
+            Value::from("\"\\/\n\r\t\u{8}\u{c}é")
+        );
+    }
+
+    #[test]
+    fn parse_rejects_malformed_input() {
+        for (text, error) in [
+            ("", "line 1: expected a value"),
+            ("[1, 2", "line 1: expected ',' or ']' in an array"),
+            ("{\"a\" 1}", "line 1: expected ':', but found '1'"),
+            ("{\"a\": 1,\n}", "line 2: expected '\"', but found '}'"),
+            ("\"abc", "line 1: unterminated string"),
+            (r#""\x""#, "line 1: invalid escape \\x"),
+            (r#""\u12""#, "line 1: invalid escape \\u12\""),
+            ("1.5", "line 1: unsupported value '1.5'"),
+            ("[] x", "line 1: unexpected 'x' after the value"),
+        ] {
+            assert_eq!(
+                Value::parse(text).unwrap_err().to_string(),
+                error,
+                "parsing {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_limits_the_depth() {
+        let nested = |depth| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(Value::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Value::parse(&nested(100_000)).unwrap_err().to_string(),
+            format!("line 1: nested more than {MAX_DEPTH} levels deep")
+        );
+    }
+}
@@ -1,16 +1,33 @@
-use clap::Parser;
+use audit_log::AuditLog;
+use clap::{CommandFactory, FromArgMatches};
 use cli::Cli;
 use git::GitCommandResult;
 use log::debug;
+use output::OutputTarget;
 use print::Print;
+use timings::Timings;
 
+mod audit_log;
+mod cache;
 mod cli;
+mod color;
 mod commands;
 mod git;
+mod journal;
+mod json;
+mod output;
 mod print;
+mod progress;
+mod prompt;
+mod settings;
+mod table;
+mod timings;
+mod toml;
+mod tui;
 
 fn main() -> ! {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
 
     cli.initialize_logger();
 
@@ -22,13 +39,26 @@ fn main() -> ! {
 
     debug!("parsed Cli: {:#?}", &cli);
 
-    match cli.run_subcommand() {
+    let result = cli.run_subcommand(matches.subcommand_name());
+    // write out any output that's waiting to be paged, even if the subcommand failed
+    let result = OutputTarget::finish().and(result);
+
+    Timings::report();
+
+    match result {
         Ok(git_command) => match git_command {
-            GitCommandResult::Success => std::process::exit(0),
-            GitCommandResult::Error => std::process::exit(1),
+            GitCommandResult::Success => {
+                AuditLog::exit(0, None);
+                std::process::exit(0)
+            }
+            GitCommandResult::Error => {
+                AuditLog::exit(1, None);
+                std::process::exit(1)
+            }
         },
         Err(e) => {
             Print::error(&format!("{}", e));
+            AuditLog::exit(1, Some(&format!("{}", e)));
             std::process::exit(1)
         }
     }
@@ -0,0 +1,274 @@
+use crate::{commands::Commands, git::env_vars::GitEnvVars, settings::Settings};
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use std::{
+    env,
+    fs::File,
+    io::{self, IsTerminal, Write},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::{Mutex, OnceLock},
+};
+
+/// The file that output is written to instead of `stdout`; set by `OutputTarget::redirect_to()`.
+static OUTPUT_FILE: OnceLock<File> = OnceLock::new();
+
+/// How the output of the current subcommand is paged; set by `OutputTarget::set_paging()`.
+static PAGING: OnceLock<Paging> = OnceLock::new();
+
+/// The output that's waiting to be paged, or the pager that it's being written to.
+static PAGER: Mutex<Pager> = Mutex::new(Pager {
+    buffer: Vec::new(),
+    lines: 0,
+    child: None,
+});
+
+/// Whether the output of a subcommand is shown in a pager; configured per subcommand with `git-util.paging.NAME`.
+///
+/// Subcommands without a setting leave it to `git`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Paging {
+    /// Always page the output.
+    Always,
+    /// Never page the output.
+    Never,
+    /// Only page the output if it's longer than this many lines.
+    Over(usize),
+}
+
+impl Paging {
+    /// Parse a setting `value`: `always`, `never`, or a number of lines.
+    pub fn from_setting(key: &str, value: &str) -> Result<Paging> {
+        match value.to_lowercase().as_str() {
+            "always" => Ok(Paging::Always),
+            "never" => Ok(Paging::Never),
+            lines => lines.parse().map(Paging::Over).map_err(|_| {
+                anyhow!(
+                    "Invalid value for {}: \"{}\". Expected one of: always, never, or a number of lines",
+                    key,
+                    value
+                )
+            }),
+        }
+    }
+}
+
+/// Where the formatted output of subcommands is written: `stdout`, or the file passed to `--output`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct OutputTarget();
+
+impl OutputTarget {
+    /// Write all subsequent output to the file at `path` instead of `stdout`, truncating the file if it exists.
+    pub fn redirect_to(path: &Path) -> Result<()> {
+        debug!("redirecting output to {}", path.display());
+
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create output file {}", path.display()))?;
+
+        // ignore the error if output was already redirected
+        let _ = OUTPUT_FILE.set(file);
+
+        Ok(())
+    }
+
+    /// Page the output of the current subcommand according to `paging`.
+    pub fn set_paging(paging: Paging) {
+        debug!("paging: {paging:?}");
+
+        // ignore the error if paging was already set
+        let _ = PAGING.set(paging);
+    }
+
+    /// How the output of the current subcommand is paged, if it's configured.
+    pub fn paging() -> Option<Paging> {
+        PAGING.get().copied()
+    }
+
+    /// Whether output written to the output target may go through a pager (depending on its length), in which case
+    /// the output of child processes must be written through `writer()` too.
+    pub fn is_paging() -> bool {
+        matches!(
+            OutputTarget::paging(),
+            Some(Paging::Always | Paging::Over(_))
+        ) && OutputTarget::is_terminal()
+    }
+
+    /// Write out any output that's waiting to be paged, and wait for the pager to exit.
+    ///
+    /// Must be called before exiting, or buffered output is lost.
+    pub fn finish() -> Result<()> {
+        let mut pager = PAGER
+            .lock()
+            .map_err(|_| anyhow!("Failed to lock the pager"))?;
+
+        match pager.child.take() {
+            Some(mut child) => {
+                // closing its input lets the pager know that there's no more output
+                drop(child.stdin.take());
+                child
+                    .wait()
+                    .with_context(|| "Failed to wait for the pager")?;
+            }
+            None => io::stdout().write_all(&pager.buffer)?,
+        }
+
+        pager.buffer.clear();
+
+        Ok(())
+    }
+
+    /// A writer for the output target.
+    pub fn writer() -> Result<Box<dyn Write>> {
+        if OutputTarget::is_paging() {
+            return Ok(Box::new(PagedWriter()));
+        }
+
+        match OUTPUT_FILE.get() {
+            Some(file) => Ok(Box::new(
+                file.try_clone()
+                    .with_context(|| "Failed to open output file")?,
+            )),
+            None => Ok(Box::new(io::stdout())),
+        }
+    }
+
+    /// The `Stdio` to use for the `stdout` of a child process, so that its output goes to the output target.
+    pub fn stdio() -> Result<Stdio> {
+        match OUTPUT_FILE.get() {
+            Some(file) => Ok(Stdio::from(
+                file.try_clone()
+                    .with_context(|| "Failed to open output file")?,
+            )),
+            None => Ok(Stdio::inherit()),
+        }
+    }
+
+    /// Whether the output target is a terminal (i.e. output isn't redirected and `stdout` is a terminal).
+    pub fn is_terminal() -> bool {
+        OUTPUT_FILE.get().is_none() && io::stdout().is_terminal()
+    }
+}
+
+/// The state of paging the output; output is buffered until it's known whether it needs to be paged.
+struct Pager {
+    buffer: Vec<u8>,
+    /// The number of lines in `buffer`.
+    lines: usize,
+    child: Option<Child>,
+}
+
+impl Pager {
+    /// Start the pager and write the buffered output to it.
+    ///
+    /// The pager is `git-util.pager`, else whichever pager `git` would use (`$GIT_PAGER`, `core.pager`, `$PAGER`, or
+    /// `less`). As with `git`, `less` is run with `FRX` unless `$LESS` is set.
+    fn start(&mut self) -> io::Result<()> {
+        let program = match &Settings::get().pager {
+            Some(pager) => pager.clone(),
+            None => Commands::output_with_timeout(&mut Commands::new_command_with_args(
+                "git",
+                &["var", String::from(GitEnvVars::Pager).as_str()],
+            ))
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|pager| !pager.is_empty())
+            .unwrap_or_else(|| "less".to_string()),
+        };
+
+        debug!("starting the pager: {program}");
+
+        let mut command = Command::new(if cfg!(windows) { "sh.exe" } else { "sh" });
+        command.args(["-c", &program]).stdin(Stdio::piped());
+
+        if env::var_os("LESS").is_none() {
+            command.env("LESS", "FRX");
+        }
+
+        let mut child = command.spawn()?;
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin.write_all(&self.buffer)?;
+        }
+
+        self.buffer.clear();
+        self.child = Some(child);
+
+        Ok(())
+    }
+}
+
+/// Writes to the pager, starting it once the output is long enough for the current `Paging`.
+struct PagedWriter();
+
+impl Write for PagedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut pager = PAGER
+            .lock()
+            .map_err(|_| io::Error::other("Failed to lock the pager"))?;
+
+        if pager.child.is_none() {
+            pager.buffer.extend_from_slice(buf);
+            pager.lines += buf.iter().filter(|byte| **byte == b'\n').count();
+
+            let start = match OutputTarget::paging() {
+                Some(Paging::Over(lines)) => pager.lines > lines,
+                _ => true,
+            };
+
+            if start {
+                pager.start()?;
+            }
+
+            return Ok(buf.len());
+        }
+
+        let stdin = pager.child.as_mut().and_then(|child| child.stdin.as_mut());
+
+        match stdin.map(|stdin| stdin.write_all(buf)) {
+            // the pager was closed before all of the output was written
+            Some(Err(error)) if error.kind() == io::ErrorKind::BrokenPipe => Ok(buf.len()),
+            Some(Err(error)) => Err(error),
+            _ => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut pager = PAGER
+            .lock()
+            .map_err(|_| io::Error::other("Failed to lock the pager"))?;
+
+        match pager.child.as_mut().and_then(|child| child.stdin.as_mut()) {
+            Some(stdin) => stdin.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paging_from_setting() {
+        assert_eq!(
+            Paging::from_setting("key", "Always").unwrap(),
+            Paging::Always
+        );
+        assert_eq!(Paging::from_setting("key", "never").unwrap(), Paging::Never);
+        assert_eq!(Paging::from_setting("key", "40").unwrap(), Paging::Over(40));
+    }
+
+    #[test]
+    fn paging_from_setting_rejects_other_values() {
+        for value in ["sometimes", "-1", "1.5", ""] {
+            assert_eq!(
+                Paging::from_setting("git-util.paging.l", value)
+                    .unwrap_err()
+                    .to_string(),
+                format!(
+                    "Invalid value for git-util.paging.l: \"{value}\". Expected one of: always, never, or a number of lines"
+                )
+            );
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use crate::git::PRINT_COMMANDS;
-use nu_ansi_term::{AnsiString, AnsiStrings, Color};
-use std::{
-    io::{stderr, IsTerminal},
-    process::Command,
+use crate::{
+    color::Colors,
+    git::{PRINT_COMMANDS, QUIET},
 };
+use nu_ansi_term::{AnsiString, AnsiStrings, Color};
+use std::process::Command;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub struct Print();
@@ -16,11 +16,23 @@ impl Print {
         }
     }
 
+    /// Print an informational `message` to `stdout`, unless `QUIET` has been set.
+    pub fn info(message: &str) {
+        if !QUIET.load(std::sync::atomic::Ordering::SeqCst) {
+            println!("{message}");
+        }
+    }
+
     /// Print to `stderr` in purple.
     pub fn stderr_purple(message: &str) {
         Self::stderr_color(message, Color::Purple)
     }
 
+    /// Print to `stderr` in cyan.
+    pub fn stderr_cyan(message: &str) {
+        Self::stderr_color(message, Color::Cyan)
+    }
+
     /// Print Error message to `stderr`.
     pub fn error(message: &str) {
         let message: String = "Error: ".to_owned() + message;
@@ -30,7 +42,7 @@ impl Print {
 
     /// Print `message` in `color` to `stderr`.
     fn stderr_color(message: &str, color: Color) {
-        if stderr().is_terminal() {
+        if Colors::stderr() {
             Self::stderr(color.bold().paint(message))
         } else {
             eprintln!("{}", message)
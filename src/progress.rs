@@ -0,0 +1,97 @@
+use crate::{color::Colors, git::QUIET};
+use nu_ansi_term::Color;
+use std::{
+    io::{stderr, IsTerminal, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// A spinner rendered on `stderr` with a label describing the current step, e.g. "fetching origin".
+///
+/// Nothing is rendered if `stderr` is not a terminal or `QUIET` has been set.
+#[derive(Debug)]
+pub struct Spinner {
+    label: String,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    /// Whether a spinner would be rendered.
+    pub fn is_enabled() -> bool {
+        stderr().is_terminal() && !QUIET.load(Ordering::SeqCst)
+    }
+
+    /// Start rendering a spinner labelled `label`.
+    pub fn start(label: &str) -> Spinner {
+        let running = Arc::new(AtomicBool::new(true));
+
+        let handle = if Spinner::is_enabled() {
+            let running = Arc::clone(&running);
+            let label = label.to_string();
+
+            Some(thread::spawn(move || {
+                let mut frame = 0;
+
+                while running.load(Ordering::SeqCst) {
+                    eprint!(
+                        "\r{} {label}",
+                        Color::Purple.paint(FRAMES[frame].to_string())
+                    );
+                    let _ = stderr().flush();
+
+                    frame = (frame + 1) % FRAMES.len();
+                    thread::sleep(Duration::from_millis(80));
+                }
+            }))
+        } else {
+            None
+        };
+
+        Spinner {
+            label: label.to_string(),
+            running,
+            handle,
+        }
+    }
+
+    /// Stop the spinner and replace it with a line showing whether the step succeeded.
+    pub fn finish(mut self, success: bool) {
+        if self.stop() {
+            let mark = if success {
+                Colors::paint(Color::Green, "✔", Colors::stderr())
+            } else {
+                Colors::paint(Color::Red, "✘", Colors::stderr())
+            };
+
+            eprintln!("{mark} {}", self.label);
+        }
+    }
+
+    /// Stop rendering and clear the line; returns `false` if the spinner was never rendered.
+    fn stop(&mut self) -> bool {
+        self.running.store(false, Ordering::SeqCst);
+
+        match self.handle.take() {
+            Some(handle) => {
+                let _ = handle.join();
+                // clear the spinner line
+                eprint!("\r\x1b[2K");
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Context, Result};
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Prompt();
+
+impl Prompt {
+    /// Ask `question` on `stderr` and wait for a response on `stdin`.
+    ///
+    /// Returns `true` only if the answer is `y` or `yes` (case-insensitive).
+    pub fn confirm(question: &str) -> Result<bool> {
+        eprint!("{question} [y/N] ");
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        io::stdin()
+            .read_line(&mut answer)
+            .with_context(|| "Failed to read answer from stdin")?;
+
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+    }
+
+    /// Ask `question` on `stderr` and return the (trimmed) response, or `default` if the response is empty.
+    pub fn input(question: &str, default: &str) -> Result<String> {
+        eprint!("{question} [{default}] ");
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        io::stdin()
+            .read_line(&mut answer)
+            .with_context(|| "Failed to read answer from stdin")?;
+
+        match answer.trim() {
+            "" => Ok(default.to_string()),
+            answer => Ok(answer.to_string()),
+        }
+    }
+
+    /// Print `items` as a numbered list on `stderr` and ask which of them to select.
+    ///
+    /// The answer is a space- or comma-separated list of numbers, or `all`. Returns the indices of the selected items.
+    pub fn select_many<T: Display>(items: &[T]) -> Result<Vec<usize>> {
+        for (i, item) in items.iter().enumerate() {
+            eprintln!("{:>3}) {item}", i + 1);
+        }
+
+        eprint!("Select items (e.g. `1 3`, or `all`): ");
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        io::stdin()
+            .read_line(&mut answer)
+            .with_context(|| "Failed to read answer from stdin")?;
+
+        if answer.trim().eq_ignore_ascii_case("all") {
+            return Ok((0..items.len()).collect());
+        }
+
+        let mut selected: Vec<usize> = Vec::new();
+
+        for choice in answer
+            .split([' ', ','])
+            .filter(|choice| !choice.trim().is_empty())
+        {
+            match choice.trim().parse::<usize>() {
+                Ok(n) if (1..=items.len()).contains(&n) => {
+                    if !selected.contains(&(n - 1)) {
+                        selected.push(n - 1)
+                    }
+                }
+                _ => return Err(anyhow!("Invalid selection: {:?}", choice.trim())),
+            }
+        }
+
+        Ok(selected)
+    }
+}
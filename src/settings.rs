@@ -0,0 +1,348 @@
+use crate::{
+    git::{commands::profile::Profile, env_vars::GitUtilEnvVars, DateStyle, Git},
+    output::Paging,
+    timings::Timings,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    path::PathBuf,
+    sync::OnceLock,
+    time::Duration,
+};
+
+/// The settings resolved for the current invocation; set by `Settings::init()`.
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// The settings used when nothing has been configured.
+static DEFAULT_SETTINGS: Settings = Settings {
+    cache: false,
+    clone_forge: None,
+    clone_protocol: None,
+    clone_root: None,
+    column_program: None,
+    dates: None,
+    default_branch: None,
+    diff_program: None,
+    filter_program: None,
+    formats: BTreeMap::new(),
+    log_count: 25,
+    log_file: None,
+    merge_default_branch: None,
+    merge_feature_branch: None,
+    no_pager: false,
+    pager: None,
+    paging: BTreeMap::new(),
+    picker_program: None,
+    profiles: BTreeMap::new(),
+    release_token: None,
+    sed_program: None,
+    timeout: None,
+    trailers: BTreeMap::new(),
+};
+
+/// User settings for **git-util**.
+///
+/// Each setting is read from a `git-util.*` **Git** config key, and can be overridden by a `GIT_UTIL_*` environment
+/// variable.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct Settings {
+    /// Cache the output of `alias` and `conf` until the config files change.
+    ///
+    /// `git-util.cache` / `$GIT_UTIL_CACHE`
+    pub cache: bool,
+    /// The forge that `clone OWNER/REPO` clones from: `github`, `gitlab`, `bitbucket`, or a host name; defaults to
+    /// `github`.
+    ///
+    /// `git-util.clone.forge` / `$GIT_UTIL_CLONE_FORGE`
+    pub clone_forge: Option<String>,
+    /// The protocol for the URLs that `clone` shorthands expand to: `https` or `ssh`; defaults to `https`.
+    ///
+    /// `git-util.clone.protocol` / `$GIT_UTIL_CLONE_PROTOCOL`
+    pub clone_protocol: Option<String>,
+    /// The directory that `clone` clones into, as `ROOT/HOST/OWNER/REPO`, when no directory is given.
+    ///
+    /// `git-util.clone.root` / `$GIT_UTIL_CLONE_ROOT`
+    pub clone_root: Option<PathBuf>,
+    /// The `column` program, for aligning tables.
+    ///
+    /// `git-util.tools.column` / `$GIT_UTIL_TOOLS_COLUMN`
+    pub column_program: Option<String>,
+    /// How `l` and `last` show dates (`relative`, `short`, or `iso`); `l` defaults to `short`, `last` to git's default.
+    ///
+    /// `git-util.dates` / `$GIT_UTIL_DATES`
+    pub dates: Option<DateStyle>,
+    /// The default branch, instead of detecting it.
+    ///
+    /// `git-util.defaultBranch` / `$GIT_UTIL_DEFAULT_BRANCH`
+    pub default_branch: Option<String>,
+    /// The program that `show` pipes its output through to highlight diffs; defaults to `delta`. It's only used when
+    /// it's installed and the output is a terminal; an empty value turns it off.
+    ///
+    /// `git-util.tools.diff` / `$GIT_UTIL_TOOLS_DIFF`
+    pub diff_program: Option<String>,
+    /// The program used to filter lines; defaults to `rg`, but any program that accepts the same flags works.
+    ///
+    /// `git-util.tools.filter` / `$GIT_UTIL_TOOLS_FILTER`
+    pub filter_program: Option<String>,
+    /// Named pretty formats for `l --format NAME`; `l` uses the one named `l` by default.
+    ///
+    /// `git-util.format.NAME`
+    pub formats: BTreeMap<String, String>,
+    /// The number of commits listed by `l` when no count is given.
+    ///
+    /// `git-util.logCount` / `$GIT_UTIL_LOG_COUNT`
+    pub log_count: u16,
+    /// Append a JSON lines record of each invocation to this file.
+    ///
+    /// `git-util.logFile` / `$GIT_UTIL_LOG_FILE`
+    pub log_file: Option<PathBuf>,
+    /// The fast-forward policy for merging the default branch.
+    ///
+    /// `git-util.merge.defaultBranch` / `$GIT_UTIL_MERGE_DEFAULT_BRANCH`
+    pub merge_default_branch: Option<String>,
+    /// The fast-forward policy for merging any other branch.
+    ///
+    /// `git-util.merge.featureBranch` / `$GIT_UTIL_MERGE_FEATURE_BRANCH`
+    pub merge_feature_branch: Option<String>,
+    /// Pass `--no-pager` to every `git` command.
+    ///
+    /// `git-util.noPager` / `$GIT_UTIL_NO_PAGER`
+    pub no_pager: bool,
+    /// The pager that `git` uses (passed as `$GIT_PAGER`), instead of the one it's configured with.
+    ///
+    /// `git-util.pager` / `$GIT_UTIL_PAGER`
+    pub pager: Option<String>,
+    /// Whether each subcommand's output is paged: `always`, `never`, or only if it's longer than a number of lines.
+    ///
+    /// `git-util.paging.NAME`
+    pub paging: BTreeMap<String, Paging>,
+    /// The fuzzy finder that commits (and conflicted files) are picked with, e.g. `fzf` or `sk`; defaults to the built-in
+    /// one.
+    ///
+    /// `git-util.tools.picker` / `$GIT_UTIL_TOOLS_PICKER`
+    pub picker_program: Option<String>,
+    /// Identities to commit with (e.g. `work` and `personal`), for `profile use NAME`.
+    ///
+    /// `git-util.profile.NAME.name`, `.email`, `.signingKey`, and `.host`
+    pub profiles: BTreeMap<String, Profile>,
+    /// The GitHub or GitLab API token that `release` uses to publish releases; prefer the environment variable.
+    ///
+    /// `git-util.releaseToken` / `$GIT_UTIL_RELEASE_TOKEN`
+    pub release_token: Option<String>,
+    /// The `sed` program.
+    ///
+    /// `git-util.tools.sed` / `$GIT_UTIL_TOOLS_SED`
+    pub sed_program: Option<String>,
+    /// How long (in seconds) a spawned process may run before it's killed; off by default. Processes that inherit the
+    /// terminal, e.g. an editor or pager, aren't killed when `stdin` is a terminal, since they may be waiting on me.
+    ///
+    /// `git-util.timeout` / `$GIT_UTIL_TIMEOUT`
+    pub timeout: Option<Duration>,
+    /// Shortcuts for trailer keys, e.g. `rb` for `Reviewed-by`, in addition to (or replacing) the built-in ones.
+    ///
+    /// `git-util.trailer.SHORTCUT`
+    pub trailers: BTreeMap<String, String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        DEFAULT_SETTINGS.clone()
+    }
+}
+
+impl Settings {
+    /// Resolve the settings and make them available through `Settings::get()`.
+    pub fn init() -> Result<()> {
+        trace!("Settings::init() called");
+
+        let settings = Timings::time("settings", Settings::load)?;
+        debug!("settings: {settings:#?}");
+
+        // ignore the error if the settings were already initialized
+        let _ = SETTINGS.set(settings);
+
+        Ok(())
+    }
+
+    /// The settings for the current invocation, or the defaults if `Settings::init()` hasn't been called.
+    pub fn get() -> &'static Settings {
+        // this must not initialize `SETTINGS`, since the `git` commands run while loading the settings call it
+        SETTINGS.get().unwrap_or(&DEFAULT_SETTINGS)
+    }
+
+    /// The `column` program to run.
+    pub fn column_program(&self) -> &str {
+        self.column_program.as_deref().unwrap_or("column")
+    }
+
+    /// The diff highlighter to run, if one is configured.
+    pub fn diff_program(&self) -> Option<&str> {
+        match self.diff_program.as_deref() {
+            None => Some("delta"),
+            Some("") => None,
+            Some(program) => Some(program),
+        }
+    }
+
+    /// The filter program to run.
+    pub fn filter_program(&self) -> &str {
+        self.filter_program.as_deref().unwrap_or("rg")
+    }
+
+    /// The external picker to run, if one is configured (else the built-in one is used).
+    pub fn picker_program(&self) -> Option<&str> {
+        self.picker_program
+            .as_deref()
+            .filter(|program| !program.is_empty())
+    }
+
+    /// The `sed` program to run.
+    pub fn sed_program(&self) -> &str {
+        self.sed_program.as_deref().unwrap_or("sed")
+    }
+
+    fn load() -> Result<Settings> {
+        // read all the config values at once to avoid spawning a `git` process per setting
+        let config: HashMap<String, String> = Git::config_values_matching(r"^git-util\.")?
+            .into_iter()
+            .collect();
+
+        let value = |env_var: GitUtilEnvVars, key: &str| -> Option<String> {
+            match env::var(String::from(env_var)) {
+                Ok(value) => Some(value),
+                Err(_) => config.get(&key.to_lowercase()).cloned(),
+            }
+        };
+
+        let defaults = &DEFAULT_SETTINGS;
+
+        Ok(Settings {
+            cache: match value(GitUtilEnvVars::Cache, "git-util.cache") {
+                Some(cache) => parse_bool(&cache)?,
+                None => defaults.cache,
+            },
+            clone_forge: value(GitUtilEnvVars::CloneForge, "git-util.clone.forge"),
+            clone_protocol: match value(GitUtilEnvVars::CloneProtocol, "git-util.clone.protocol") {
+                Some(protocol) if protocol == "https" || protocol == "ssh" => Some(protocol),
+                Some(protocol) => {
+                    return Err(anyhow!(
+                        "Invalid git-util.clone.protocol: \"{}\" (expected https or ssh)",
+                        protocol
+                    ))
+                }
+                None => defaults.clone_protocol.clone(),
+            },
+            clone_root: value(GitUtilEnvVars::CloneRoot, "git-util.clone.root")
+                .map(|root| expand_home(&root)),
+            column_program: value(GitUtilEnvVars::ToolsColumn, "git-util.tools.column"),
+            dates: match value(GitUtilEnvVars::Dates, "git-util.dates") {
+                Some(dates) => Some(DateStyle::from_setting("git-util.dates", &dates)?),
+                None => defaults.dates,
+            },
+            default_branch: value(GitUtilEnvVars::DefaultBranch, "git-util.defaultBranch"),
+            diff_program: value(GitUtilEnvVars::ToolsDiff, "git-util.tools.diff"),
+            filter_program: value(GitUtilEnvVars::ToolsFilter, "git-util.tools.filter"),
+            formats: config
+                .iter()
+                .filter_map(|(key, format)| {
+                    key.strip_prefix("git-util.format.")
+                        .map(|name| (name.to_lowercase(), format.clone()))
+                })
+                .collect(),
+            log_count: match value(GitUtilEnvVars::LogCount, "git-util.logCount") {
+                Some(count) => count
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid log count: \"{}\"", count))?,
+                None => defaults.log_count,
+            },
+            log_file: value(GitUtilEnvVars::LogFile, "git-util.logFile").map(PathBuf::from),
+            merge_default_branch: value(
+                GitUtilEnvVars::MergeDefaultBranch,
+                "git-util.merge.defaultBranch",
+            ),
+            merge_feature_branch: value(
+                GitUtilEnvVars::MergeFeatureBranch,
+                "git-util.merge.featureBranch",
+            ),
+            no_pager: match value(GitUtilEnvVars::NoPager, "git-util.noPager") {
+                Some(no_pager) => parse_bool(&no_pager)?,
+                None => defaults.no_pager,
+            },
+            pager: value(GitUtilEnvVars::Pager, "git-util.pager"),
+            paging: config
+                .iter()
+                .filter_map(|(key, paging)| {
+                    key.strip_prefix("git-util.paging.").map(|name| {
+                        Paging::from_setting(key, paging)
+                            .map(|paging| (name.to_lowercase(), paging))
+                    })
+                })
+                .collect::<Result<_>>()?,
+            picker_program: value(GitUtilEnvVars::ToolsPicker, "git-util.tools.picker"),
+            profiles: Profile::from_config(&config),
+            release_token: value(GitUtilEnvVars::ReleaseToken, "git-util.releaseToken"),
+            sed_program: value(GitUtilEnvVars::ToolsSed, "git-util.tools.sed"),
+            timeout: match value(GitUtilEnvVars::Timeout, "git-util.timeout") {
+                Some(seconds) => Some(
+                    seconds
+                        .parse()
+                        .ok()
+                        .filter(|seconds: &f64| *seconds > 0.0)
+                        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                        .ok_or_else(|| anyhow!("Invalid timeout: \"{}\"", seconds))?,
+                ),
+                None => defaults.timeout,
+            },
+            trailers: config
+                .iter()
+                .filter_map(|(key, trailer)| {
+                    key.strip_prefix("git-util.trailer.")
+                        .map(|shortcut| (shortcut.to_lowercase(), trailer.clone()))
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Parse a boolean the same way that **Git** does for config values.
+pub fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" | "" => Ok(false),
+        _ => Err(anyhow!("Invalid boolean value: \"{}\"", value)),
+    }
+}
+
+/// Expand a leading `~/` to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// The directory of **git-util**'s own files, e.g. templates: `$XDG_CONFIG_HOME/git-wrapper` (else
+/// `$HOME/.config/git-wrapper`).
+pub fn config_directory() -> Result<PathBuf> {
+    Ok(match env::var_os("XDG_CONFIG_HOME") {
+        Some(config_home) if !config_home.is_empty() => PathBuf::from(config_home),
+        _ => PathBuf::from(env::var_os("HOME").ok_or_else(|| anyhow!("$HOME is not set"))?)
+            .join(".config"),
+    }
+    .join("git-wrapper"))
+}
+
+/// The directory of the state that **git-util** keeps between runs, e.g. the journal of commits:
+/// `$XDG_STATE_HOME/git-wrapper` (else `$HOME/.local/state/git-wrapper`).
+pub fn state_directory() -> Result<PathBuf> {
+    Ok(match env::var_os("XDG_STATE_HOME") {
+        Some(state_home) if !state_home.is_empty() => PathBuf::from(state_home),
+        _ => PathBuf::from(env::var_os("HOME").ok_or_else(|| anyhow!("$HOME is not set"))?)
+            .join(".local")
+            .join("state"),
+    }
+    .join("git-wrapper"))
+}
@@ -0,0 +1,59 @@
+use core::fmt;
+
+/// Rows of text that are displayed with their columns aligned, similar to `column --table`.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct Table {
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new() -> Table {
+        Table { rows: Vec::new() }
+    }
+
+    pub fn add_row<I, S>(&mut self, row: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.rows.push(row.into_iter().map(Into::into).collect());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut widths: Vec<usize> = Vec::new();
+
+        for row in &self.rows {
+            for (i, cell) in row.iter().enumerate() {
+                let width = cell.chars().count();
+
+                match widths.get_mut(i) {
+                    Some(max) => *max = (*max).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+
+        for row in &self.rows {
+            let mut line = String::new();
+
+            for (i, cell) in row.iter().enumerate() {
+                if i == row.len() - 1 {
+                    // don't pad the last column, so that there's no trailing whitespace
+                    line.push_str(cell);
+                } else {
+                    line.push_str(&format!("{:<width$}  ", cell, width = widths[i]));
+                }
+            }
+
+            writeln!(f, "{line}")?;
+        }
+
+        Ok(())
+    }
+}
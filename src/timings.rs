@@ -0,0 +1,149 @@
+use crate::{audit_log::AuditLog, git::TIMINGS, print::Print};
+use std::{
+    process::{Child, Command},
+    sync::{atomic::Ordering, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// The durations recorded so far, in the order they finished.
+static RECORDED: Mutex<Vec<(String, Duration)>> = Mutex::new(Vec::new());
+
+/// The threads waiting on piped processes, which record their durations when those processes exit.
+static WAITERS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// Records how long spawned processes and internal phases take, and reports them on `stderr` when `TIMINGS` is set.
+pub struct Timings();
+
+impl Timings {
+    /// Whether timings are being recorded.
+    pub fn is_enabled() -> bool {
+        TIMINGS.load(Ordering::SeqCst)
+    }
+
+    /// Run `phase`, recording how long it took under `label`.
+    pub fn time<T, F>(label: &str, phase: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        if !Timings::is_enabled() {
+            return phase();
+        }
+
+        let start = Instant::now();
+        let result = phase();
+        Timings::record(label.to_string(), start.elapsed());
+
+        result
+    }
+
+    /// Run `command` to completion with `run`, recording how long it took.
+    pub fn time_command<T, F>(command: &mut Command, run: F) -> T
+    where
+        F: FnOnce(&mut Command) -> T,
+    {
+        if !Timings::is_enabled() {
+            return run(command);
+        }
+
+        let label = Timings::label(command);
+        let start = Instant::now();
+        let result = run(command);
+        Timings::record(label, start.elapsed());
+
+        result
+    }
+
+    /// Record how long `child` (spawned from `command`) runs, without blocking on it.
+    ///
+    /// Used for processes that are piped into another command and never waited on directly. The process is also
+    /// added to the `AuditLog`, once it exits.
+    pub fn track(command: &Command, mut child: Child) {
+        if !Timings::is_enabled() && !AuditLog::is_enabled() {
+            return;
+        }
+
+        let label = Timings::label(command);
+        let start = Instant::now();
+
+        // `Command` isn't `Send`, so the waiter gets a copy with just the program and arguments
+        let mut logged_command = Command::new(command.get_program());
+        logged_command.args(command.get_args());
+
+        let waiter = thread::spawn(move || {
+            let exit_code = child.wait().ok().and_then(|status| status.code());
+            let duration = start.elapsed();
+
+            if Timings::is_enabled() {
+                Timings::record(label, duration);
+            }
+            AuditLog::command(&logged_command, exit_code, duration);
+        });
+
+        if let Ok(mut waiters) = WAITERS.lock() {
+            waiters.push(waiter);
+        }
+    }
+
+    /// Print the recorded timings to `stderr`, e.g. `git config: 12ms, sed: 1ms, column: 8ms`.
+    ///
+    /// The tracked processes are waited for even if timings aren't enabled, so that they're added to the `AuditLog`.
+    pub fn report() {
+        // every piped process has finished (or been orphaned) by now, so this won't block for long
+        let waiters: Vec<JoinHandle<()>> = match WAITERS.lock() {
+            Ok(mut waiters) => waiters.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        for waiter in waiters {
+            let _ = waiter.join();
+        }
+
+        if !Timings::is_enabled() {
+            return;
+        }
+
+        let Ok(recorded) = RECORDED.lock() else {
+            return;
+        };
+
+        if recorded.is_empty() {
+            return;
+        }
+
+        let timings = recorded
+            .iter()
+            .map(|(label, duration)| format!("{label}: {}ms", duration.as_millis()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Print::stderr_cyan(&format!("timings: {timings}"));
+    }
+
+    fn record(label: String, duration: Duration) {
+        if let Ok(mut recorded) = RECORDED.lock() {
+            recorded.push((label, duration));
+        }
+    }
+
+    /// The program name, plus the **Git Subcommand** if the program is `git` (e.g. `git config`).
+    fn label(command: &Command) -> String {
+        let program = command.get_program().to_string_lossy().to_string();
+
+        if program != "git" {
+            return program;
+        }
+
+        let mut args = command.get_args().map(|arg| arg.to_string_lossy());
+
+        // skip global options, e.g. `--no-pager` and `-c color.ui=always`
+        while let Some(arg) = args.next() {
+            if arg == "-c" || arg == "-C" {
+                args.next();
+            } else if !arg.starts_with('-') {
+                return format!("{program} {arg}");
+            }
+        }
+
+        program
+    }
+}
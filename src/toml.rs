@@ -0,0 +1,456 @@
+use anyhow::{anyhow, Result};
+use std::{collections::BTreeMap, iter::Peekable, str::Chars};
+
+/// How deeply arrays may be nested, so that a malformed file can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// A value in a TOML document.
+///
+/// Only the types that **git-util**'s files use are supported: dates, floats, and inline tables aren't.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum Value {
+    Array(Vec<Value>),
+    Boolean(bool),
+    Integer(i64),
+    String(String),
+}
+
+/// A parsed TOML document: the keys and values of each table, by the name of the table.
+///
+/// The keys before the first table header are in the table named `""`. Dotted keys aren't split into tables, so
+/// `a.b = 1` is the key `a.b`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+pub struct Document {
+    tables: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// A name for the type of the value, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Array(_) => "an array",
+            Value::Boolean(_) => "a boolean",
+            Value::Integer(_) => "an integer",
+            Value::String(_) => "a string",
+        }
+    }
+}
+
+impl Document {
+    pub fn parse(text: &str) -> Result<Document> {
+        Parser {
+            chars: text.chars().peekable(),
+            line: 1,
+            depth: 0,
+        }
+        .document()
+    }
+
+    /// The keys and values of the table `name`, if the document has it.
+    pub fn table(&self, name: &str) -> Option<&BTreeMap<String, Value>> {
+        self.tables.get(name)
+    }
+
+    /// The names of the tables, and their keys and values, in order of name.
+    pub fn tables(&self) -> impl Iterator<Item = (&String, &BTreeMap<String, Value>)> {
+        self.tables.iter()
+    }
+}
+
+/// `text` as a TOML basic string, in double quotes.
+pub fn quote(text: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// `key` as it's written in a TOML document: bare if it can be, else quoted.
+pub fn key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(is_bare_key_char) {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+fn is_bare_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// The current line, for error messages.
+    line: usize,
+    /// The number of arrays that the current value is in.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn document(&mut self) -> Result<Document> {
+        let mut document = Document::default();
+        let mut table = String::new();
+        document.tables.insert(table.clone(), BTreeMap::new());
+
+        loop {
+            self.skip_whitespace(true);
+
+            match self.chars.peek() {
+                None => return Ok(document),
+                Some('[') => {
+                    self.chars.next();
+                    self.skip_whitespace(false);
+                    table = self.key()?;
+                    self.skip_whitespace(false);
+                    self.expect(']')?;
+
+                    if document.tables.contains_key(&table) && !table.is_empty() {
+                        return Err(self.error(&format!("table [{table}] is defined twice")));
+                    }
+                    document.tables.insert(table.clone(), BTreeMap::new());
+                }
+                Some(_) => {
+                    let key = self.key()?;
+                    self.skip_whitespace(false);
+                    self.expect('=')?;
+                    self.skip_whitespace(false);
+                    let value = self.value()?;
+
+                    let entries = document.tables.entry(table.clone()).or_default();
+                    if entries.insert(key.clone(), value).is_some() {
+                        return Err(self.error(&format!("key '{key}' is defined twice")));
+                    }
+                }
+            }
+
+            self.end_of_line()?;
+        }
+    }
+
+    /// A bare, quoted, or dotted key; the parts of a dotted key are joined with `.`.
+    fn key(&mut self) -> Result<String> {
+        let mut parts = Vec::new();
+
+        loop {
+            let part = match self.chars.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let mut part = String::new();
+                    while let Some(&c) = self.chars.peek().filter(|&&c| is_bare_key_char(c)) {
+                        part.push(c);
+                        self.chars.next();
+                    }
+
+                    if part.is_empty() {
+                        return Err(self.error("expected a key"));
+                    }
+                    part
+                }
+            };
+            parts.push(part);
+
+            self.skip_whitespace(false);
+            if self.chars.peek() != Some(&'.') {
+                return Ok(parts.join("."));
+            }
+            self.chars.next();
+            self.skip_whitespace(false);
+        }
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        match self.chars.peek() {
+            Some('"') => Ok(Value::String(self.basic_string()?)),
+            Some('\'') => Ok(Value::String(self.literal_string()?)),
+            Some('[') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error(&format!("nested more than {MAX_DEPTH} levels deep")));
+                }
+
+                self.depth += 1;
+                let value = self.array();
+                self.depth -= 1;
+                value
+            }
+            Some(_) => {
+                let mut word = String::new();
+                while let Some(&c) = self
+                    .chars
+                    .peek()
+                    .filter(|&&c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '_' | '.'))
+                {
+                    word.push(c);
+                    self.chars.next();
+                }
+
+                match word.as_str() {
+                    "true" => Ok(Value::Boolean(true)),
+                    "false" => Ok(Value::Boolean(false)),
+                    _ => word
+                        .replace('_', "")
+                        .parse()
+                        .map(Value::Integer)
+                        .map_err(|_| self.error(&format!("unsupported value '{word}'"))),
+                }
+            }
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    /// `[VALUE, ...]`, which may span several lines and have a trailing comma.
+    fn array(&mut self) -> Result<Value> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+
+        loop {
+            self.skip_whitespace(true);
+            if self.chars.peek() == Some(&']') {
+                break;
+            }
+
+            values.push(self.value()?);
+
+            self.skip_whitespace(true);
+            match self.chars.peek() {
+                Some(',') => {
+                    self.chars.next();
+                }
+                Some(']') => break,
+                _ => return Err(self.error("expected ',' or ']' in an array")),
+            }
+        }
+
+        self.expect(']')?;
+        Ok(Value::Array(values))
+    }
+
+    /// `"..."`, with escapes.
+    fn basic_string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut string = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.chars.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some(u @ ('u' | 'U')) => {
+                            let digits: String = self
+                                .chars
+                                .by_ref()
+                                .take(if u == 'u' { 4 } else { 8 })
+                                .collect();
+
+                            u32::from_str_radix(&digits, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| {
+                                    self.error(&format!("invalid escape \\{u}{digits}"))
+                                })?
+                        }
+                        other => {
+                            return Err(
+                                self.error(&format!("invalid escape \\{}", other.unwrap_or(' ')))
+                            )
+                        }
+                    };
+                    string.push(escaped);
+                }
+                Some('\n') | None => return Err(self.error("unterminated string")),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    /// `'...'`, without escapes.
+    fn literal_string(&mut self) -> Result<String> {
+        self.expect('\'')?;
+        let mut string = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('\'') => return Ok(string),
+                Some('\n') | None => return Err(self.error("unterminated string")),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    /// Skip spaces, tabs, and comments, and newlines too if `newlines` is `true`.
+    fn skip_whitespace(&mut self, newlines: bool) {
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                ' ' | '\t' | '\r' => {}
+                '\n' if newlines => self.line += 1,
+                '#' => {
+                    while self.chars.peek().is_some_and(|&c| c != '\n') {
+                        self.chars.next();
+                    }
+                    continue;
+                }
+                _ => return,
+            }
+            self.chars.next();
+        }
+    }
+
+    /// Nothing but whitespace or a comment may follow a table header or a key/value pair on the same line.
+    fn end_of_line(&mut self) -> Result<()> {
+        self.skip_whitespace(false);
+
+        match self.chars.peek().copied() {
+            None | Some('\n') => Ok(()),
+            Some(c) => Err(self.error(&format!("unexpected '{c}' at the end of the line"))),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some('\n') | None => Err(self.error(&format!("expected '{expected}'"))),
+            Some(c) => Err(self.error(&format!("expected '{expected}', but found '{c}'"))),
+        }
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        anyhow!("line {}: {}", self.line, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_round_trips() {
+        let text = "a \"quoted\" \\path\\\nwith\ttabs, \u{7f}, and é";
+        let document = Document::parse(&format!("{} = {}", key("the key"), quote(text))).unwrap();
+
+        assert_eq!(
+            document
+                .table("")
+                .and_then(|table| table.get("the key"))
+                .and_then(Value::as_str),
+            Some(text)
+        );
+    }
+
+    #[test]
+    fn key_is_bare_if_it_can_be() {
+        assert_eq!(key("a-b_c1"), "a-b_c1");
+        assert_eq!(key("a.b"), "\"a.b\"");
+        assert_eq!(key(""), "\"\"");
+    }
+
+    #[test]
+    fn parse_tables_and_values() {
+        let document = Document::parse(
+            "top = true # a comment\n\n[first]\nname = 'literal \\n'\ncount = 1_000\n\
+             list = [\n  \"a\",\n  [1, 2], # nested\n]\n\n[\"second\".part]\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            document
+                .tables()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            ["", "first", "second.part"]
+        );
+        assert_eq!(
+            document.table("").and_then(|table| table.get("top")),
+            Some(&Value::Boolean(true))
+        );
+
+        let first = document.table("first").unwrap();
+        assert_eq!(
+            first.get("name").and_then(Value::as_str),
+            Some("literal \\n")
+        );
+        assert_eq!(first.get("count"), Some(&Value::Integer(1000)));
+        assert_eq!(
+            first.get("list"),
+            Some(&Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_malformed_input() {
+        for (text, error) in [
+            ("a = 1\na = 2", "line 2: key 'a' is defined twice"),
+            ("[t]\n[t]", "line 2: table [t] is defined twice"),
+            ("a = \"abc\nb = 1", "line 1: unterminated string"),
+            ("a = 'abc", "line 1: unterminated string"),
+            ("a = \"\\q\"", "line 1: invalid escape \\q"),
+            ("a = 1.5", "line 1: unsupported value '1.5'"),
+            ("a = [1 2]", "line 1: expected ',' or ']' in an array"),
+            (
+                "a = 1 b = 2",
+                "line 1: unexpected 'b' at the end of the line",
+            ),
+            ("a", "line 1: expected '='"),
+            ("= 1", "line 1: expected a key"),
+        ] {
+            assert_eq!(
+                Document::parse(text).unwrap_err().to_string(),
+                error,
+                "parsing {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_limits_the_depth() {
+        let nested = |depth| format!("a = {}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(Document::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Document::parse(&nested(100_000)).unwrap_err().to_string(),
+            format!("line 1: nested more than {MAX_DEPTH} levels deep")
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use nu_ansi_term::Style;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, IsTerminal, Read, Write},
+    process::{Command, Stdio},
+};
+
+pub mod fuzzy;
+pub mod picker;
+
+/// A key pressed in a `Terminal`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Tab,
+    Escape,
+    Backspace,
+    /// `Ctrl-C`, which doesn't send `SIGINT` in raw mode.
+    Interrupt,
+    Char(char),
+}
+
+/// The controlling terminal (`/dev/tty`), switched to raw mode and the alternate screen for a full-screen interface.
+///
+/// The terminal is restored when this is dropped, so an error doesn't leave it unusable.
+#[derive(Debug)]
+pub struct Terminal {
+    tty: File,
+    /// The mode to restore, as printed by `stty -g`.
+    saved_mode: String,
+    /// Input that has been read but not parsed into keys yet.
+    pending: Vec<u8>,
+}
+
+impl Terminal {
+    /// Open the controlling terminal and switch it to raw mode and the alternate screen.
+    ///
+    /// Fails if `stdout` isn't a terminal, or if `stty` isn't installed, and on platforms other than Unix, which don't
+    /// have either `/dev/tty` or `stty`.
+    pub fn open() -> Result<Terminal> {
+        if !cfg!(unix) {
+            return Err(anyhow!(
+                "The interactive mode isn't supported on this platform"
+            ));
+        }
+        if !io::stdout().is_terminal() {
+            return Err(anyhow!("The interactive mode needs a terminal"));
+        }
+
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .with_context(|| "Failed to open /dev/tty")?;
+
+        let saved_mode = stty(&tty, &["-g"])?;
+        // `time 1`: a read returns 0.1s after the first byte, so escape sequences are read in one go
+        stty(&tty, &["raw", "-echo", "min", "1", "time", "1"])?;
+
+        let mut terminal = Terminal {
+            tty,
+            saved_mode,
+            pending: Vec::new(),
+        };
+        // switch to the alternate screen and hide the cursor
+        terminal.tty.write_all(b"\x1b[?1049h\x1b[?25l")?;
+
+        Ok(terminal)
+    }
+
+    /// The size of the terminal, as `(rows, columns)`.
+    pub fn size(&self) -> Result<(usize, usize)> {
+        let size = stty(&self.tty, &["size"])?;
+
+        match size.split_once(' ') {
+            // a terminal that doesn't know its size (e.g. a bare pseudo-terminal) reports `0 0`
+            Some(("0", "0")) => Ok((24, 80)),
+            Some((rows, columns)) => Ok((rows.parse()?, columns.parse()?)),
+            None => Err(anyhow!("Unexpected output from `stty size`: {}", size)),
+        }
+    }
+
+    /// Replace the screen with `lines`, each truncated to the width of the terminal.
+    ///
+    /// Lines may contain color escape sequences; tabs are expanded.
+    pub fn draw(&mut self, lines: &[String]) -> Result<()> {
+        let (_, columns) = self.size()?;
+
+        let mut screen = String::from("\x1b[H");
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                screen.push_str("\r\n");
+            }
+            screen.push_str(&truncate(line, columns));
+            // clear whatever the previous frame left on the rest of the line
+            screen.push_str("\x1b[K");
+        }
+        screen.push_str("\x1b[J");
+
+        self.tty.write_all(screen.as_bytes())?;
+        Ok(self.tty.flush()?)
+    }
+
+    /// Wait for a key to be pressed; unrecognized escape sequences are ignored.
+    pub fn read_key(&mut self) -> Result<Key> {
+        loop {
+            // a single read can return several keys, e.g. when text is pasted
+            if self.pending.is_empty() {
+                let mut buffer = [0; 64];
+                let read = self.tty.read(&mut buffer)?;
+
+                if read == 0 {
+                    return Err(anyhow!("The terminal was closed"));
+                }
+
+                self.pending.extend_from_slice(&buffer[..read]);
+            }
+
+            let (key, length) = parse_key(&self.pending);
+            let input: Vec<u8> = self.pending.drain(..length).collect();
+
+            match key {
+                Some(key) => return Ok(key),
+                None => debug!("ignoring input: {input:?}"),
+            }
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        // show the cursor and leave the alternate screen
+        let _ = self.tty.write_all(b"\x1b[?25h\x1b[?1049l");
+        let _ = self.tty.flush();
+        let _ = stty(&self.tty, &[&self.saved_mode]);
+    }
+}
+
+/// The input of the special keys that are recognized, other than `Escape`, `Enter`, `Backspace`, and `Ctrl-C`.
+const KEY_SEQUENCES: [(&[u8], Key); 15] = [
+    (b"\x1b[A", Key::Up),
+    (b"\x1bOA", Key::Up),
+    (b"\x1b[B", Key::Down),
+    (b"\x1bOB", Key::Down),
+    (b"\x1b[5~", Key::PageUp),
+    (b"\x1b[6~", Key::PageDown),
+    (b"\x1b[H", Key::Home),
+    (b"\x1bOH", Key::Home),
+    (b"\x1b[1~", Key::Home),
+    (b"\x1b[F", Key::End),
+    (b"\x1bOF", Key::End),
+    (b"\x1b[4~", Key::End),
+    // `Ctrl-N` and `Ctrl-P`, which move down and up in most pickers
+    (b"\x0e", Key::Down),
+    (b"\x10", Key::Up),
+    (b"\t", Key::Tab),
+];
+
+/// The first key in `input` (if it's one that's recognized), and the number of bytes it takes up.
+fn parse_key(input: &[u8]) -> (Option<Key>, usize) {
+    if let Some((sequence, key)) = KEY_SEQUENCES
+        .iter()
+        .find(|(sequence, _)| input.starts_with(sequence))
+    {
+        return (Some(*key), sequence.len());
+    }
+
+    match input {
+        [] => (None, 0),
+        // an unrecognized escape sequence, which ends with a byte in `@..=~`
+        [b'\x1b', b'[' | b'O', rest @ ..] => (
+            None,
+            rest.iter()
+                .position(|byte| (b'@'..=b'~').contains(byte))
+                .map_or(input.len(), |end| end + 3),
+        ),
+        [b'\x1b', ..] => (Some(Key::Escape), 1),
+        [b'\r' | b'\n', ..] => (Some(Key::Enter), 1),
+        [b'\x7f' | b'\x08', ..] => (Some(Key::Backspace), 1),
+        [b'\x03', ..] => (Some(Key::Interrupt), 1),
+        [first, ..] => {
+            let length = match first {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            }
+            .min(input.len());
+
+            let key = std::str::from_utf8(&input[..length])
+                .ok()
+                .and_then(|input| input.chars().next())
+                .filter(|c| !c.is_control())
+                .map(Key::Char);
+
+            (key, length)
+        }
+    }
+}
+
+/// A dimmed horizontal line across the screen, for separating panes.
+pub fn divider() -> String {
+    // `Terminal::draw()` cuts it off at the width of the terminal
+    Style::new().dimmed().paint("─".repeat(512)).to_string()
+}
+
+/// `text` in reverse video across the whole screen, for the help line at the bottom.
+pub fn footer(text: &str) -> String {
+    Style::new()
+        .reverse()
+        .paint(format!("{text:<512}"))
+        .to_string()
+}
+
+/// `line` cut off after `width` visible characters, with tabs expanded and escape sequences kept (but not counted).
+pub fn truncate(line: &str, width: usize) -> String {
+    let mut truncated = String::new();
+    let mut visible = 0;
+    let mut chars = line.chars();
+
+    while visible < width {
+        let Some(c) = chars.next() else {
+            break;
+        };
+
+        match c {
+            '\x1b' => {
+                // copy the whole sequence, e.g. `\x1b[1;32m`
+                truncated.push(c);
+                for c in chars.by_ref() {
+                    truncated.push(c);
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            '\t' => {
+                let spaces = (8 - visible % 8).min(width - visible);
+                truncated.push_str(&" ".repeat(spaces));
+                visible += spaces;
+            }
+            c => {
+                truncated.push(c);
+                visible += 1;
+            }
+        }
+    }
+
+    // the line may have been cut off before its color was reset
+    truncated.push_str("\x1b[0m");
+    truncated
+}
+
+/// `stty ARGS`, on `tty`; returns the trimmed output.
+fn stty(tty: &File, args: &[&str]) -> Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(tty.try_clone()?)
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| {
+            "Failed to execute stty; the interactive mode needs it to control the terminal"
+        })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    } else {
+        Err(anyhow!("`stty {}` failed", args.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_key_sequences() {
+        assert_eq!(parse_key(b"\x1b[A"), (Some(Key::Up), 3));
+        assert_eq!(parse_key(b"\x1bOB"), (Some(Key::Down), 3));
+        assert_eq!(parse_key(b"\x1b[5~x"), (Some(Key::PageUp), 4));
+        assert_eq!(parse_key(b"\x1b"), (Some(Key::Escape), 1));
+        assert_eq!(parse_key(b"\r"), (Some(Key::Enter), 1));
+        assert_eq!(parse_key(b"\x7f"), (Some(Key::Backspace), 1));
+        assert_eq!(parse_key(b"\x03"), (Some(Key::Interrupt), 1));
+        assert_eq!(parse_key(b""), (None, 0));
+    }
+
+    #[test]
+    fn parse_key_skips_unrecognized_sequences() {
+        assert_eq!(parse_key(b"\x1b[1;5Cq"), (None, 6));
+        // cut off before its final byte
+        assert_eq!(parse_key(b"\x1b[1;5"), (None, 5));
+        assert_eq!(parse_key(b"\x01"), (None, 1));
+    }
+
+    #[test]
+    fn parse_key_reads_utf8_characters() {
+        assert_eq!(parse_key(b"ab"), (Some(Key::Char('a')), 1));
+        assert_eq!(parse_key("éx".as_bytes()), (Some(Key::Char('é')), 2));
+        assert_eq!(parse_key("€".as_bytes()), (Some(Key::Char('€')), 3));
+        // a character that hasn't been read completely
+        assert_eq!(parse_key(&"€".as_bytes()[..2]), (None, 2));
+    }
+
+    #[test]
+    fn truncate_counts_visible_characters() {
+        assert_eq!(truncate("abcdef", 3), "abc\x1b[0m");
+        assert_eq!(truncate("ab", 3), "ab\x1b[0m");
+        assert_eq!(truncate("éèê", 2), "éè\x1b[0m");
+    }
+
+    #[test]
+    fn truncate_keeps_escape_sequences() {
+        assert_eq!(
+            truncate("\x1b[1;32mgreen\x1b[0m text", 3),
+            "\x1b[1;32mgre\x1b[0m"
+        );
+        assert_eq!(truncate("ab\x1b[0m", 2), "ab\x1b[0m");
+    }
+
+    #[test]
+    fn truncate_expands_tabs() {
+        assert_eq!(truncate("a\tb", 10), "a       b\x1b[0m");
+        assert_eq!(truncate("a\tb", 4), "a   \x1b[0m");
+    }
+}
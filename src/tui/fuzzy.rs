@@ -0,0 +1,230 @@
+use super::{Key, Terminal};
+use crate::color::Colors;
+use anyhow::Result;
+use log::{debug, trace};
+use nu_ansi_term::{Color, Style};
+
+const HELP: &str = "type to filter  ↑/↓: select  enter: choose  esc: cancel";
+
+/// Picks one of a list of items by typing part of it: the items that contain the typed characters in order (but not
+/// necessarily next to each other) are listed, best matches first.
+///
+/// Matching is case-insensitive, unless the query has an uppercase character.
+#[derive(Debug)]
+pub struct FuzzyFinder<'a> {
+    prompt: &'a str,
+    items: &'a [String],
+    query: String,
+    matches: Vec<Match>,
+    /// The index of the selected match.
+    selected: usize,
+}
+
+/// An item that matches the query.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Match {
+    /// Higher is better.
+    score: i64,
+    /// The index of the item.
+    index: usize,
+    /// The indices of the characters of the item that matched the query, to highlight.
+    positions: Vec<usize>,
+}
+
+impl FuzzyFinder<'_> {
+    /// Let the user pick one of `items`, in a full-screen list under `prompt`.
+    ///
+    /// Returns the index of the chosen item, or `None` if the picker was cancelled.
+    pub fn select(prompt: &str, items: &[String]) -> Result<Option<usize>> {
+        trace!("select() called with: {prompt}, {} items", items.len());
+
+        let mut finder = FuzzyFinder {
+            prompt,
+            items,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        finder.filter();
+
+        let mut terminal = Terminal::open()?;
+
+        loop {
+            let (rows, _) = terminal.size()?;
+            let height = rows.saturating_sub(2).max(1);
+
+            terminal.draw(&finder.render(height))?;
+
+            match terminal.read_key()? {
+                Key::Escape | Key::Interrupt => return Ok(None),
+                Key::Enter => {
+                    if let Some(selected) = finder.matches.get(finder.selected) {
+                        debug!("selected: {}", items[selected.index]);
+                        return Ok(Some(selected.index));
+                    }
+                }
+                Key::Up => finder.selected = finder.selected.saturating_sub(1),
+                Key::Down => {
+                    finder.selected =
+                        (finder.selected + 1).min(finder.matches.len().saturating_sub(1))
+                }
+                Key::PageUp => finder.selected = finder.selected.saturating_sub(height),
+                Key::PageDown => {
+                    finder.selected =
+                        (finder.selected + height).min(finder.matches.len().saturating_sub(1))
+                }
+                Key::Backspace => {
+                    finder.query.pop();
+                    finder.filter();
+                }
+                Key::Char(c) => {
+                    finder.query.push(c);
+                    finder.filter();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Match the items against the query, and select the best match.
+    fn filter(&mut self) {
+        let ignore_case = !self.query.chars().any(char::is_uppercase);
+
+        self.matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                score(&self.query, item, ignore_case).map(|(score, positions)| Match {
+                    score,
+                    index,
+                    positions,
+                })
+            })
+            .collect();
+        // the best matches first, keeping the order of the items for equal scores
+        self.matches
+            .sort_by(|a, b| b.score.cmp(&a.score).then(a.index.cmp(&b.index)));
+        self.selected = 0;
+    }
+
+    /// The lines of the screen: the prompt and query, the matches, and the help.
+    fn render(&self, height: usize) -> Vec<String> {
+        let color = Colors::stdout();
+        let mut lines = vec![format!(
+            "{} {}{}",
+            Style::new().bold().paint(format!("{}>", self.prompt)),
+            self.query,
+            // there's no cursor on the alternate screen
+            Style::new().reverse().paint(" ")
+        )];
+
+        // keep the selected match on screen
+        let offset = (self.selected + 1).saturating_sub(height);
+
+        for (i, matched) in self.matches.iter().enumerate().skip(offset).take(height) {
+            let marker = if i == self.selected {
+                Style::new().bold().paint(">").to_string()
+            } else {
+                " ".to_string()
+            };
+
+            let item: String = self.items[matched.index]
+                .chars()
+                .enumerate()
+                .map(|(position, c)| {
+                    if matched.positions.contains(&position) {
+                        Colors::paint(Color::Yellow, &c.to_string(), color).to_string()
+                    } else {
+                        c.to_string()
+                    }
+                })
+                .collect();
+
+            lines.push(format!("{marker} {item}"));
+        }
+
+        lines.resize(height + 1, String::new());
+        lines.push(super::footer(&format!(
+            "{}/{}  {HELP}",
+            self.matches.len(),
+            self.items.len()
+        )));
+
+        lines
+    }
+}
+
+/// How well `query` matches `text`, and the positions of the matching characters in `text`.
+///
+/// `None` if `text` doesn't contain every character of `query` in order. Consecutive matches and matches at the start
+/// of a word score higher, and an empty query matches everything equally.
+fn score(query: &str, text: &str, ignore_case: bool) -> Option<(i64, Vec<usize>)> {
+    let normalize = |c: char| {
+        if ignore_case {
+            c.to_lowercase().next().unwrap_or(c)
+        } else {
+            c
+        }
+    };
+
+    let text: Vec<char> = text.chars().collect();
+    let mut positions = Vec::new();
+    let mut score = 0;
+    let mut start = 0;
+
+    for q in query.chars().map(normalize) {
+        let position = start + text[start..].iter().position(|&c| normalize(c) == q)?;
+
+        score += 1;
+        if positions.last().is_some_and(|&last| last + 1 == position) {
+            score += 5;
+        }
+        if position == 0 || !text[position - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        positions.push(position);
+        start = position + 1;
+    }
+
+    // prefer matches that are closer to the start
+    score -= positions.first().map_or(0, |&first| first as i64 / 8);
+
+    Some((score, positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_needs_every_character_in_order() {
+        assert_eq!(
+            score("abc", "a-b-c", false).map(|(_, p)| p),
+            Some(vec![0, 2, 4])
+        );
+        assert_eq!(score("cba", "a-b-c", false), None);
+        assert_eq!(score("abcd", "abc", false), None);
+    }
+
+    #[test]
+    fn score_ignores_case_only_if_asked() {
+        assert!(score("ABC", "abc", true).is_some());
+        assert_eq!(score("ABC", "abc", false), None);
+    }
+
+    #[test]
+    fn score_empty_query_matches_everything() {
+        assert_eq!(score("", "anything", false), Some((0, vec![])));
+    }
+
+    #[test]
+    fn score_prefers_consecutive_matches_and_word_starts() {
+        let score = |query, text| score(query, text, false).unwrap().0;
+
+        assert!(score("fix", "fix bug") > score("fix", "f i x"));
+        assert!(score("b", "fix bug") > score("u", "fix bug"));
+        assert!(score("bug", "bug") > score("bug", "some long prefix bug"));
+    }
+}
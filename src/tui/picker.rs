@@ -0,0 +1,84 @@
+use super::fuzzy::FuzzyFinder;
+use crate::{commands::Commands, prompt::Prompt, settings::Settings};
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+use std::process::Command;
+
+/// Picks items (commits, and the conflicted files for `resolve --pick`) with the picker set with `git-util.tools.picker`,
+/// else with the built-in `FuzzyFinder` (or, to pick more than one, `Prompt::select_many()`).
+///
+/// An external picker (e.g. `fzf` or `sk`) is run with `sh -c`. It gets the items on `stdin`, one per line, and must
+/// print the chosen items on `stdout`, one per line; exiting with a non-zero status, or printing nothing, cancels.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Picker();
+
+impl Picker {
+    /// Let the user pick one of `items`; `prompt` says what the item is picked for.
+    ///
+    /// Returns the index of the chosen item, or `None` if the picker was cancelled.
+    pub fn select(prompt: &str, items: &[String]) -> Result<Option<usize>> {
+        trace!("select() called with: {prompt}, {} items", items.len());
+
+        match Settings::get().picker_program() {
+            // pickers that print more than one item (e.g. `fzf --multi`) only get the first one
+            Some(program) => Ok(Picker::select_external(program, items)?.first().copied()),
+            None => FuzzyFinder::select(prompt, items),
+        }
+    }
+
+    /// Let the user pick any number of `items`, e.g. with `fzf --multi`.
+    ///
+    /// Returns the indices of the chosen items, which are empty if the picker was cancelled.
+    pub fn select_many(items: &[String]) -> Result<Vec<usize>> {
+        trace!("select_many() called with: {} items", items.len());
+
+        match Settings::get().picker_program() {
+            Some(program) => Picker::select_external(program, items),
+            None => Prompt::select_many(items),
+        }
+    }
+
+    /// `ITEMS | sh -c PROGRAM`
+    fn select_external(program: &str, items: &[String]) -> Result<Vec<usize>> {
+        debug!("running the picker: {program}");
+
+        let mut command = Command::new(if cfg!(windows) { "sh.exe" } else { "sh" });
+        command.args(["-c", program]);
+
+        let output = Commands::output_with_input(
+            &mut command,
+            format!("{}\n", items.join("\n")).as_bytes(),
+        )?;
+
+        match output.status.code() {
+            Some(0) => {}
+            // `sh` couldn't find the picker
+            Some(127) => {
+                return Err(anyhow!(
+                    "Failed to run the picker `{}`: {}",
+                    program,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ))
+            }
+            status => {
+                debug!("the picker exited with {status:?}");
+                return Ok(Vec::new());
+            }
+        }
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|chosen| !chosen.is_empty())
+            .map(
+                |chosen| match items.iter().position(|item| item == chosen) {
+                    Some(index) => Ok(index),
+                    None => Err(anyhow!(
+                        "The picker `{}` printed an item that wasn't one of the choices: {}",
+                        program,
+                        chosen
+                    )),
+                },
+            )
+            .collect()
+    }
+}